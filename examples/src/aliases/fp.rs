@@ -0,0 +1,32 @@
+pub type ExprRef = Box<Expr>;
+pub type Env = Vec<(String, Int)>;
+pub type Int = i32;
+
+pub enum Expr {
+    Lit(Int),
+    Var(String),
+    Add(ExprRef, ExprRef),
+}
+
+pub fn eval(expr: &Expr, env: &Env) -> Int {
+    match expr {
+        Expr::Lit(n) => *n,
+        Expr::Var(name) => env
+            .iter()
+            .find(|(var, _)| var == name)
+            .map(|(_, value)| *value)
+            .unwrap_or(0),
+        Expr::Add(left, right) => eval(left, env) + eval(right, env),
+    }
+}
+
+pub fn increment(expr: ExprRef) -> Expr {
+    Expr::Add(expr, Box::new(Expr::Lit(1)))
+}
+
+pub fn demo() -> Int {
+    let env: Env = vec![(String::from("x"), 4)];
+    let sum: ExprRef = Box::new(Expr::Add(Box::new(Expr::Lit(3)), Box::new(Expr::Var(String::from("x")))));
+    let result = eval(&increment(sum), &env);
+    result
+}
@@ -0,0 +1,51 @@
+pub type ShapeRef = Box<dyn Shape>;
+pub type Pair<T> = (T, T);
+pub type Length = f64;
+
+pub trait Shape {
+    fn area(&self) -> Length;
+    fn scale(&self, factor: Length) -> ShapeRef;
+}
+
+pub struct Square {
+    pub side: Length,
+}
+
+impl Shape for Square {
+    fn area(&self) -> Length {
+        self.side * self.side
+    }
+
+    fn scale(&self, factor: Length) -> ShapeRef {
+        Box::new(Square { side: self.side * factor })
+    }
+}
+
+pub struct Stack {
+    pub shapes: Pair<ShapeRef>,
+}
+
+impl Shape for Stack {
+    fn area(&self) -> Length {
+        self.shapes.0.area() + self.shapes.1.area()
+    }
+
+    fn scale(&self, factor: Length) -> ShapeRef {
+        Box::new(Stack {
+            shapes: (self.shapes.0.scale(factor), self.shapes.1.scale(factor)),
+        })
+    }
+}
+
+pub fn total_area(shapes: &Vec<ShapeRef>) -> Length {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+pub fn demo() -> Length {
+    let square: ShapeRef = Box::new(Square { side: 2.0 });
+    let stack: ShapeRef = Box::new(Stack {
+        shapes: (Box::new(Square { side: 1.0 }), square.scale(0.5)),
+    });
+    let result = total_area(&vec![square, stack.scale(2.0)]);
+    result
+}
@@ -0,0 +1,60 @@
+pub trait Bundle {
+    fn map<F: Fn(i32) -> i32>(&self, f: F) -> Self;
+    fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: Fn(A, i32) -> A;
+    fn total(&self) -> i32;
+}
+
+pub struct One {
+    pub value: i32,
+}
+
+pub struct Two {
+    pub first: i32,
+    pub second: i32,
+}
+
+impl Bundle for One {
+    fn map<F: Fn(i32) -> i32>(&self, f: F) -> Self {
+        One { value: f(self.value) }
+    }
+
+    fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: Fn(A, i32) -> A,
+    {
+        f(init, self.value)
+    }
+
+    fn total(&self) -> i32 {
+        self.value
+    }
+}
+
+impl Bundle for Two {
+    fn map<G: Fn(i32) -> i32>(&self, f: G) -> Self {
+        Two { first: f(self.first), second: f(self.second) }
+    }
+
+    fn fold<B, F>(&self, init: B, f: F) -> B
+    where
+        F: Fn(B, i32) -> B,
+    {
+        let acc: B = f(init, self.first);
+        f(acc, self.second)
+    }
+
+    fn total(&self) -> i32 {
+        self.first + self.second
+    }
+}
+
+pub fn demo() -> i32 {
+    let two = Two { first: 1, second: 2 };
+    let doubled = two.map(|x| x * 2);
+    let one = One { value: 5 };
+    let sum = doubled.fold::<i32, _>(0, |acc, x| acc + x);
+    let count = one.fold(0, |count, _| count + 1);
+    sum * 100 + one.map(|x| x + 1).total() * 10 + count
+}
@@ -0,0 +1,40 @@
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Circle { radius } => 3.0 * radius * radius,
+        Shape::Square { side } => side * side,
+    }
+}
+
+pub fn total_area(shapes: &Vec<Shape>) -> f64 {
+    let mut total = 0.0;
+    for shape in shapes {
+        total = total + area(shape);
+    }
+    total
+}
+
+pub fn make_shapes() -> Vec<Shape> {
+    vec![Shape::Circle { radius: 1.0 }, Shape::Square { side: 2.0 }]
+}
+
+pub fn with_area(side: f64) -> (Shape, f64) {
+    (Shape::Square { side }, side * side)
+}
+
+pub fn demo() -> f64 {
+    let mut shapes = make_shapes();
+    shapes.push(Shape::Square { side: 1.0 });
+    let (square, _) = with_area(2.0);
+    shapes.push(square);
+    let spare: Option<Shape> = Some(Shape::Circle { radius: 2.0 });
+    match spare {
+        Some(shape) => shapes.push(shape),
+        None => (),
+    }
+    total_area(&shapes)
+}
@@ -0,0 +1,47 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+pub struct Square {
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+pub struct Group {
+    pub shapes: Vec<Box<dyn Shape>>,
+    pub border: Option<Box<dyn Shape>>,
+}
+
+impl Shape for Group {
+    fn area(&self) -> f64 {
+        let mut total = 0.0;
+        for shape in self.shapes.iter() {
+            total = total + shape.area();
+        }
+        match &self.border {
+            Some(border) => total + border.area(),
+            None => total,
+        }
+    }
+}
+
+pub fn squares(sides: Vec<f64>) -> Vec<Box<dyn Shape>> {
+    let mut shapes: Vec<Box<dyn Shape>> = Vec::new();
+    for side in sides {
+        shapes.push(Box::new(Square { side }));
+    }
+    shapes
+}
+
+pub fn demo() -> f64 {
+    let group = Group {
+        shapes: squares(vec![1.0, 2.0]),
+        border: Some(Box::new(Square { side: 3.0 })),
+    };
+    group.area()
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum Figure {
+    Square { side: u32 },
+    Rectangle { width: u32, height: u32 },
+}
+
+impl fmt::Display for Figure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Figure::Square { side } => write!(f, "{}x{}", side, side),
+            Figure::Rectangle { width, height } => write!(f, "{}x{}", width, height),
+        }
+    }
+}
+
+pub fn name(figure: &Figure) -> String {
+    match figure {
+        Figure::Square { .. } => "square".to_string(),
+        Figure::Rectangle { .. } => "rectangle".to_string(),
+    }
+}
+
+pub fn area(figure: &Figure) -> u32 {
+    match figure {
+        Figure::Square { side } => side * side,
+        Figure::Rectangle { width, height } => width * height,
+    }
+}
+
+pub fn describe(figure: &Figure) -> String {
+    format!("{} with area {}", name(figure), area(figure))
+}
+
+pub fn label(figure: &Figure) -> String {
+    name(figure).to_uppercase()
+}
+
+pub fn demo() -> String {
+    let square = Figure::Square { side: 3 };
+    let rectangle = Figure::Rectangle { width: 2, height: 5 };
+    let copy = rectangle.clone();
+    format!("{}; {} is {}; {:?}", describe(&square), label(&copy), rectangle, square)
+}
@@ -0,0 +1,2 @@
+pub mod oop;
+pub mod fp;
@@ -0,0 +1,71 @@
+use std::fmt;
+
+pub trait Named {
+    fn name(&self) -> String;
+}
+
+pub trait Figure: Named + fmt::Display + fmt::Debug + Clone {
+    fn area(&self) -> u32;
+
+    fn describe(&self) -> String {
+        format!("{} with area {}", self.name(), self.area())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Square {
+    pub side: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rectangle {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Named for Square {
+    fn name(&self) -> String {
+        "square".to_string()
+    }
+}
+
+impl Named for Rectangle {
+    fn name(&self) -> String {
+        "rectangle".to_string()
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.side, self.side)
+    }
+}
+
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl Figure for Square {
+    fn area(&self) -> u32 {
+        self.side * self.side
+    }
+}
+
+impl Figure for Rectangle {
+    fn area(&self) -> u32 {
+        self.width * self.height
+    }
+}
+
+pub fn label<N: Named>(item: &N) -> String {
+    item.name().to_uppercase()
+}
+
+pub fn demo() -> String {
+    let square = Square { side: 3 };
+    let rectangle = Rectangle { width: 2, height: 5 };
+    let copy = rectangle.clone();
+    format!("{}; {} is {}; {:?}", square.describe(), label(&copy), rectangle, square)
+}
@@ -0,0 +1,56 @@
+pub struct Frame {
+    pub width: f64,
+}
+
+impl Frame {
+    pub fn new(width: f64) -> Frame {
+        Frame { width }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width * self.width
+    }
+
+    pub fn scale(&self, factor: u32) -> Frame {
+        Frame { width: self.width * factor as f64 }
+    }
+}
+
+impl Shape {
+    pub fn new(side: f64) -> Shape {
+        Shape::Square { side }
+    }
+
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Square { side } => side * side,
+            Shape::Circle { radius } => 3.0 * radius * radius,
+        }
+    }
+
+    pub fn scale(&self, factor: f64) -> Shape {
+        match self {
+            Shape::Square { side } => Shape::Square { side: side * factor },
+            Shape::Circle { radius } => Shape::Circle { radius: radius * factor },
+        }
+    }
+}
+
+pub enum Shape {
+    Square { side: f64 },
+    Circle { radius: f64 },
+}
+
+pub fn framed(shape: &Shape) -> Frame {
+    Frame::new(shape.area())
+}
+
+pub fn transformed_area(shape: &Shape, transforms: &[fn(f64) -> f64]) -> f64 {
+    transforms[0](shape.area())
+}
+
+pub fn demo() -> f64 {
+    let shape = Shape::new(2.0);
+    let frame = framed(&shape.scale(2.0)).scale(2);
+    frame.area() + shape.area()
+}
@@ -0,0 +1,23 @@
+pub mod exprs {
+    pub enum Expr {
+        Lit(i32),
+        Add(Box<Expr>, Box<Expr>),
+    }
+
+    pub fn eval(expr: &Expr) -> i32 {
+        match expr {
+            Expr::Lit(n) => *n,
+            Expr::Add(left, right) => eval(left) + eval(right),
+        }
+    }
+}
+
+pub mod app {
+    use super::exprs::{eval as evaluate, Expr as E};
+    use super::exprs::Expr::Lit;
+
+    pub fn demo() -> i32 {
+        let expr = E::Add(Box::new(Lit(1)), Box::new(super::exprs::Expr::Lit(2)));
+        evaluate(&expr)
+    }
+}
@@ -0,0 +1,54 @@
+pub mod shapes {
+    pub trait Shape {
+        fn area(&self) -> f64;
+        fn scale(&self, factor: f64) -> Box<dyn Shape>;
+    }
+
+    pub struct Circle {
+        pub radius: f64,
+    }
+
+    impl Shape for Circle {
+        fn area(&self) -> f64 {
+            3.0 * self.radius * self.radius
+        }
+
+        fn scale(&self, factor: f64) -> Box<dyn Shape> {
+            Box::new(Circle { radius: self.radius * factor })
+        }
+    }
+
+    pub mod square {
+        use super::Shape as ShapeTrait;
+
+        pub struct Square {
+            pub side: f64,
+        }
+
+        impl ShapeTrait for Square {
+            fn area(&self) -> f64 {
+                self.side * self.side
+            }
+
+            fn scale(&self, factor: f64) -> Box<dyn ShapeTrait> {
+                Box::new(Square { side: self.side * factor })
+            }
+        }
+    }
+}
+
+pub mod app {
+    use super::shapes::{self, Circle as C, Shape as S};
+    use super::shapes::square::Square;
+
+    pub fn total_area(shapes: &Vec<Box<dyn S>>) -> f64 {
+        shapes.iter().map(|shape| shape.area()).sum()
+    }
+
+    pub fn demo() -> f64 {
+        let circle: Box<dyn S> = Box::new(C { radius: 1.0 });
+        let square = Square { side: 2.0 };
+        let other = shapes::Circle { radius: 2.0 };
+        total_area(&vec![circle, square.scale(0.5), Box::new(other)])
+    }
+}
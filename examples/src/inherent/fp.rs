@@ -0,0 +1,32 @@
+pub enum Shape {
+    Circle { radius: f64 },
+    Rectangle { width: f64, height: f64 },
+}
+
+impl Shape {
+    pub fn unit() -> Self {
+        Shape::Circle { radius: 1.0 }
+    }
+
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Circle { radius } => 3.0 * radius * radius,
+            Shape::Rectangle { width, height } => width * height,
+        }
+    }
+
+    pub fn scale(&self, factor: f64) -> Shape {
+        match self {
+            Shape::Circle { radius } => Shape::Circle { radius: radius * factor },
+            Shape::Rectangle { width, height } => Shape::Rectangle {
+                width: width * factor,
+                height: height * factor,
+            },
+        }
+    }
+}
+
+pub fn demo() {
+    let shape = Shape::unit();
+    println!("Area: {}", shape.scale(2.0).area());
+}
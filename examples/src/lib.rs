@@ -1,8 +1,19 @@
 pub mod set;
 pub mod exp;
 pub mod exp2;
+pub mod containers;
 pub mod generics;
 pub mod multi_interface;
+pub mod pointers;
+pub mod shared;
+pub mod aliases;
+pub mod imports;
+pub mod library;
+pub mod parsers;
+pub mod polygons;
+pub mod figures;
+pub mod units;
+pub mod bundles;
 pub mod mutable;
 pub mod report;
 pub mod shape;
@@ -10,5 +21,11 @@ pub mod shape2;
 pub mod shape3;
 pub mod list;
 pub mod sql;
+pub mod trait_types;
 pub mod bool;
 pub mod countdown;
+pub mod inherent;
+pub mod frames;
+pub mod literals;
+pub mod modules;
+pub mod multi_file;
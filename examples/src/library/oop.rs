@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+pub trait Item {
+    fn title(&self) -> String;
+    fn pages(&self) -> usize;
+    fn index(&self, entries: &mut HashMap<String, usize>);
+}
+
+pub struct Book {
+    pub name: String,
+    pub chapters: Vec<usize>,
+}
+
+impl Item for Book {
+    fn title(&self) -> String {
+        self.name.trim().to_string()
+    }
+
+    fn pages(&self) -> usize {
+        self.chapters.iter().sum()
+    }
+
+    fn index(&self, entries: &mut HashMap<String, usize>) {
+        entries.insert(self.title(), self.pages());
+    }
+}
+
+pub struct Collection {
+    pub name: String,
+    pub items: Vec<Box<dyn Item>>,
+    pub editor: Option<String>,
+}
+
+impl Item for Collection {
+    fn title(&self) -> String {
+        let mut title = self.name.to_uppercase();
+        match self.editor.as_ref() {
+            Some(editor_name) => {
+                title.push_str(", ed. ");
+                title.push_str(editor_name);
+            }
+            None => (),
+        }
+        title
+    }
+
+    fn pages(&self) -> usize {
+        let total: usize = self.items.iter().map(|child| child.pages()).sum();
+        total.max(1)
+    }
+
+    fn index(&self, entries: &mut HashMap<String, usize>) {
+        for item in self.items.iter() {
+            item.index(entries);
+        }
+        entries.insert(self.title(), self.items.len());
+    }
+}
+
+pub fn demo() -> usize {
+    let mut items: Vec<Box<dyn Item>> = Vec::new();
+    items.push(Box::new(Book { name: " Dune ".to_string(), chapters: vec![120, 200] }));
+    items.push(Box::new(Collection { name: "empty".to_string(), items: Vec::new(), editor: None }));
+    let shelf = Collection { name: "classics".to_string(), items, editor: Some("Ann".to_string()) };
+
+    let mut entries = HashMap::new();
+    shelf.index(&mut entries);
+    entries.get("Dune").copied().unwrap_or(0) + entries.len() + shelf.pages()
+}
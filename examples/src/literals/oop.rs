@@ -0,0 +1,57 @@
+pub trait Counter {
+    fn count(&self) -> u64;
+    fn step(&self, by: u8) -> Box<dyn Counter>;
+    fn describe(&self, label: &str) -> String;
+}
+
+pub struct Clicks {
+    pub clicks: u64,
+}
+
+impl Counter for Clicks {
+    fn count(&self) -> u64 {
+        self.clicks
+    }
+
+    fn step(&self, by: u8) -> Box<dyn Counter> {
+        Box::new(Clicks { clicks: self.clicks + by as u64 })
+    }
+
+    fn describe(&self, label: &str) -> String {
+        format!("{}: {}", label, self.clicks + 1)
+    }
+}
+
+pub struct Level {
+    pub level: i8,
+}
+
+impl Counter for Level {
+    fn count(&self) -> u64 {
+        if self.level > 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn step(&self, by: u8) -> Box<dyn Counter> {
+        if by > 1u8 {
+            Box::new(Level { level: self.level + 2 })
+        } else {
+            Box::new(Level { level: self.level + 1 })
+        }
+    }
+
+    fn describe(&self, label: &str) -> String {
+        format!("{}: {}", label, self.level)
+    }
+}
+
+pub fn demo() -> String {
+    let clicks = Clicks { clicks: 0 };
+    let clicks = clicks.step(2u8).step(1);
+    let level = Level { level: -1 };
+    let level = level.step(2);
+    clicks.describe("clicks") + ", " + &level.describe("level") + &format!(" ({})", level.count())
+}
@@ -0,0 +1,25 @@
+pub mod exprs {
+    pub enum Expr {
+        Lit { value: i32 },
+        Add { left: Box<Expr>, right: Box<Expr> },
+    }
+
+    pub fn eval(expr: &Expr) -> i32 {
+        match expr {
+            Expr::Lit { value } => *value,
+            Expr::Add { left, right } => eval(left) + eval(right),
+        }
+    }
+}
+
+pub mod app {
+    use super::exprs::Expr;
+
+    pub fn demo() -> i32 {
+        let expr = Expr::Add {
+            left: Box::new(Expr::Lit { value: 1 }),
+            right: Box::new(super::exprs::Expr::Lit { value: 2 }),
+        };
+        super::exprs::eval(&expr)
+    }
+}
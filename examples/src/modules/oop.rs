@@ -0,0 +1,55 @@
+pub mod shapes {
+    pub trait Shape {
+        fn area(&self) -> f64;
+        fn scale(&self, factor: f64) -> Box<dyn Shape>;
+    }
+
+    pub struct Circle {
+        pub radius: f64,
+    }
+
+    impl Shape for Circle {
+        fn area(&self) -> f64 {
+            3.0 * self.radius * self.radius
+        }
+
+        fn scale(&self, factor: f64) -> Box<dyn Shape> {
+            Box::new(Circle {
+                radius: self.radius * factor,
+            })
+        }
+    }
+}
+
+pub mod rectangle {
+    pub struct Rectangle {
+        pub width: f64,
+        pub height: f64,
+    }
+
+    impl super::shapes::Shape for Rectangle {
+        fn area(&self) -> f64 {
+            self.width * self.height
+        }
+
+        fn scale(&self, factor: f64) -> Box<dyn super::shapes::Shape> {
+            Box::new(Rectangle {
+                width: self.width * factor,
+                height: self.height * factor,
+            })
+        }
+    }
+}
+
+pub mod app {
+    use super::shapes::Shape;
+
+    pub fn demo() -> f64 {
+        let circle = super::shapes::Circle { radius: 1.0 };
+        let rectangle = super::rectangle::Rectangle {
+            width: 2.0,
+            height: 3.0,
+        };
+        circle.area() + rectangle.area()
+    }
+}
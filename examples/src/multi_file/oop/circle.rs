@@ -0,0 +1,17 @@
+use super::shape::Shape;
+
+pub struct Circle {
+    pub radius: f64,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.radius * self.radius
+    }
+
+    fn scale(&self, factor: f64) -> Box<dyn Shape> {
+        Box::new(Circle {
+            radius: self.radius * factor,
+        })
+    }
+}
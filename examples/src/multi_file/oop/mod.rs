@@ -0,0 +1,12 @@
+pub mod shape;
+pub mod circle;
+#[path = "square_shape.rs"]
+pub mod square;
+
+use shape::Shape;
+
+pub fn total_area() -> f64 {
+    let circle = circle::Circle { radius: 1.0 };
+    let square = square::Square { side: 2.0 };
+    circle.area() + square.area()
+}
@@ -0,0 +1,4 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn scale(&self, factor: f64) -> Box<dyn Shape>;
+}
@@ -0,0 +1,17 @@
+use super::shape::Shape;
+
+pub struct Square {
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    fn scale(&self, factor: f64) -> Box<dyn Shape> {
+        Box::new(Square {
+            side: self.side * factor,
+        })
+    }
+}
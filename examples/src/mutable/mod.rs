@@ -1,2 +1,3 @@
 pub mod oop;
 pub mod fp;
+pub mod oop_tuple;
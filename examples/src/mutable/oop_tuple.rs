@@ -0,0 +1,37 @@
+pub trait Light {
+    fn increase_brightness(&mut self);
+    fn get_brightness(&self) -> i8;
+}
+
+pub struct Dimmer(pub i8);
+
+pub struct RGB(pub i8, pub i8, pub i8);
+
+impl Light for Dimmer {
+    fn increase_brightness(&mut self) {
+        self.0 += 1;
+    }
+
+    fn get_brightness(&self) -> i8 {
+        return self.0;
+    }
+}
+
+impl Light for RGB {
+    fn increase_brightness(&mut self) {
+        self.0 += 1;
+        self.1 += 1;
+        self.2 += 1;
+    }
+
+    fn get_brightness(&self) -> i8 {
+        return (self.0 + self.1 + self.2) / 3;
+    }
+}
+
+pub fn demo() {
+    let mut light = Dimmer(0);
+    light.increase_brightness();
+
+    println!("Brightness: {}", light.get_brightness());
+}
@@ -0,0 +1,51 @@
+pub enum Parser<T> {
+    Literal { text: String, value: T },
+    Either { first: Box<Parser<T>>, second: Box<Parser<T>> },
+}
+
+pub fn parse<T: Clone>(parser: &Parser<T>, input: &str) -> Result<T, String> {
+    match parser {
+        Parser::Literal { text, value } => {
+            if input == text {
+                Ok(value.clone())
+            } else {
+                Err("expected ".to_string() + text)
+            }
+        }
+        Parser::Either { first, second } => match parse(first, input) {
+            Ok(output) => Ok(output),
+            Err(_) => parse(second, input),
+        },
+    }
+}
+
+pub fn describe<T>(parser: &Parser<T>) -> String {
+    match parser {
+        Parser::Literal { text, .. } => text.clone(),
+        Parser::Either { first, second } => describe(first) + " or " + &describe(second),
+    }
+}
+
+pub fn score(inputs: Vec<&str>, parser: &Parser<usize>) -> usize {
+    let mut total = 0;
+    for input in inputs {
+        let points = match parse(parser, input) {
+            Ok(points) => points,
+            Err(_) => 0,
+        };
+        total += points;
+    }
+    total
+}
+
+pub fn demo() -> usize {
+    let yes = Parser::Literal { text: "yes".to_string(), value: 10 };
+    let no = Parser::Literal { text: "no".to_string(), value: 1 };
+    let answer = Parser::Either { first: Box::new(yes), second: Box::new(no) };
+
+    let greeting = Parser::Literal { text: "hi".to_string(), value: "hello".to_string() };
+    let greeted: String = parse(&greeting, "hi").unwrap();
+
+    let description = describe(&answer);
+    score(vec!["yes", "maybe", "no", "yes"], &answer) * 10 + description.len() + greeted.len()
+}
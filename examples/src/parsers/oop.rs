@@ -0,0 +1,71 @@
+pub trait Parser {
+    type Output;
+    type Error;
+    fn parse(&self, input: &str) -> Result<Self::Output, Self::Error>;
+    fn describe(&self) -> String;
+}
+
+pub struct Literal<T> {
+    pub text: String,
+    pub value: T,
+}
+
+impl<T: Clone> Parser for Literal<T> {
+    type Output = T;
+    type Error = String;
+    fn parse(&self, input: &str) -> Result<T, String> {
+        if input == self.text {
+            Ok(self.value.clone())
+        } else {
+            Err("expected ".to_string() + &self.text)
+        }
+    }
+
+    fn describe(&self) -> String {
+        self.text.clone()
+    }
+}
+
+pub struct Either<T> {
+    pub first: Box<dyn Parser<Output = T, Error = String>>,
+    pub second: Box<dyn Parser<Output = T, Error = String>>,
+}
+
+impl<T> Parser for Either<T> {
+    type Output = T;
+    type Error = String;
+    fn parse(&self, input: &str) -> Result<Self::Output, Self::Error> {
+        match self.first.parse(input) {
+            Ok(output) => Ok(output),
+            Err(_) => self.second.parse(input),
+        }
+    }
+
+    fn describe(&self) -> String {
+        self.first.describe() + " or " + &self.second.describe()
+    }
+}
+
+pub fn score(inputs: Vec<&str>, parser: &Box<dyn Parser<Output = usize, Error = String>>) -> usize {
+    let mut total = 0;
+    for input in inputs {
+        let points = match parser.parse(input) {
+            Ok(points) => points,
+            Err(_) => 0,
+        };
+        total += points;
+    }
+    total
+}
+
+pub fn demo() -> usize {
+    let yes: Box<dyn Parser<Output = usize, Error = String>> = Box::new(Literal { text: "yes".to_string(), value: 10 });
+    let no: Box<dyn Parser<Output = usize, Error = String>> = Box::new(Literal { text: "no".to_string(), value: 1 });
+    let answer: Box<dyn Parser<Output = usize, Error = String>> = Box::new(Either { first: yes, second: no });
+
+    let greeting = Literal { text: "hi".to_string(), value: "hello".to_string() };
+    let greeted: String = greeting.parse("hi").unwrap();
+
+    let description = answer.describe();
+    score(vec!["yes", "maybe", "no", "yes"], &answer) * 10 + description.len() + greeted.len()
+}
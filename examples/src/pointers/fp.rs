@@ -0,0 +1,36 @@
+pub enum Shape {
+    Square { side: f64 },
+    Scaled { inner: Box<Shape>, by: f64 },
+}
+
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Square { side } => side * side,
+        Shape::Scaled { inner, by } => by * area(inner),
+    }
+}
+
+pub fn boxed_area(shape: &Box<Shape>) -> f64 {
+    area(shape)
+}
+
+pub fn owned_area(shape: Box<Shape>) -> f64 {
+    area(&shape)
+}
+
+pub fn mut_area(shape: &mut Shape) -> f64 {
+    area(shape)
+}
+
+pub fn ref_ref_area(shape: &&Shape) -> f64 {
+    area(shape)
+}
+
+pub fn demo() -> f64 {
+    let mut square = Shape::Square { side: 2.0 };
+    let boxed = Box::new(Shape::Scaled { inner: Box::new(Shape::Square { side: 1.0 }), by: 3.0 });
+    let borrowed = &square;
+    let first = boxed_area(&boxed) + ref_ref_area(&borrowed);
+    let mutable = &mut square;
+    first + mut_area(mutable) + owned_area(boxed)
+}
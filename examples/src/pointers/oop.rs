@@ -0,0 +1,48 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+pub struct Square {
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+pub fn describe(shape: &dyn Shape) -> f64 {
+    shape.area()
+}
+
+pub fn first_area(shape: &Box<dyn Shape>) -> f64 {
+    describe(&**shape)
+}
+
+pub fn owned_area(shape: Box<dyn Shape>) -> f64 {
+    describe(&*shape) + shape.area()
+}
+
+pub fn mut_area(shape: &mut Box<dyn Shape>) -> f64 {
+    first_area(shape) + describe(&**shape)
+}
+
+pub fn ref_ref_area(shape: &&dyn Shape) -> f64 {
+    describe(*shape) + shape.area()
+}
+
+pub fn total(shapes: &Vec<Box<dyn Shape>>) -> f64 {
+    let mut total = 0.0;
+    for shape in shapes.iter() {
+        total = total + describe(&**shape);
+    }
+    total
+}
+
+pub fn demo() -> f64 {
+    let mut shape: Box<dyn Shape> = Box::new(Square { side: 2.0 });
+    let square = Square { side: 1.0 };
+    let borrowed: &dyn Shape = &square;
+    mut_area(&mut shape) + ref_ref_area(&borrowed) + owned_area(shape) + total(&vec![Box::new(Square { side: 3.0 })])
+}
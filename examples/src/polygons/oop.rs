@@ -0,0 +1,50 @@
+pub trait Polygon {
+    const SIDES: usize;
+    const NAME: &'static str = "polygon";
+    fn perimeter(&self) -> usize;
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn is_regular(&self) -> bool {
+        self.perimeter() % Self::SIDES == 0
+    }
+}
+
+pub struct Square {
+    pub side: usize,
+}
+
+impl Polygon for Square {
+    const SIDES: usize = 4;
+    const NAME: &'static str = "square";
+    fn perimeter(&self) -> usize {
+        Self::SIDES * self.side
+    }
+}
+
+pub struct Triangle {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+
+impl Polygon for Triangle {
+    const SIDES: usize = 3;
+    fn perimeter(&self) -> usize {
+        self.a + self.b + self.c
+    }
+}
+
+pub fn corners<P: Polygon>(polygon: &P) -> usize {
+    P::SIDES
+}
+
+pub fn demo() -> usize {
+    let square = Square { side: 5 };
+    let triangle = Triangle { a: 3, b: 4, c: 5 };
+    let regular = if triangle.is_regular() { 1000 } else { 0 };
+    let name_length = square.name().len() + triangle.name().len();
+    corners(&square) * 100 + Triangle::SIDES * 10 + square.perimeter() + triangle.perimeter() + regular + name_length
+}
@@ -0,0 +1,33 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub enum Expr {
+    Lit(i32),
+    Add(Rc<Expr>, Rc<Expr>),
+    /// Counts how many times it has been evaluated, the count is shared with the caller
+    Counter(Rc<RefCell<i32>>),
+}
+
+pub fn eval(expr: &Expr) -> i32 {
+    match expr {
+        Expr::Lit(n) => *n,
+        Expr::Add(left, right) => eval(left) + eval(right),
+        Expr::Counter(count) => {
+            let evaluations = *count.borrow() + 1;
+            *count.borrow_mut() = evaluations;
+            evaluations
+        }
+    }
+}
+
+pub fn double(expr: Rc<Expr>) -> Expr {
+    Expr::Add(expr.clone(), expr)
+}
+
+pub fn demo() -> i32 {
+    let shared = Rc::new(Expr::Lit(3));
+    let count = Rc::new(RefCell::new(0));
+    let sum = Expr::Add(shared.clone(), Rc::new(Expr::Counter(count.clone())));
+    let result = eval(&double(shared)) + eval(&sum) + eval(&sum) + *count.borrow();
+    result
+}
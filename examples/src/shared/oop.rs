@@ -0,0 +1,57 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn scale(&self, factor: f64) -> Rc<dyn Shape>;
+}
+
+pub struct Square {
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    fn scale(&self, factor: f64) -> Rc<dyn Shape> {
+        Rc::new(Square { side: self.side * factor })
+    }
+}
+
+/// A group shares its shapes with the rest of the program
+pub struct Group {
+    pub first: Rc<dyn Shape>,
+    pub second: Arc<dyn Shape>,
+}
+
+impl Shape for Group {
+    fn area(&self) -> f64 {
+        self.first.area() + self.second.area()
+    }
+
+    fn scale(&self, factor: f64) -> Rc<dyn Shape> {
+        Rc::new(Group {
+            first: self.first.scale(factor),
+            second: Arc::new(Square { side: factor }),
+        })
+    }
+}
+
+pub fn total_area(shapes: &Vec<Rc<dyn Shape>>) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+pub fn demo() -> f64 {
+    let square: Rc<dyn Shape> = Rc::new(Square { side: 2.0 });
+    let shared = Rc::clone(&square);
+    let largest = RefCell::new(0.0);
+    *largest.borrow_mut() = square.area();
+
+    let group = Group { first: square, second: Arc::new(Square { side: 1.0 }) };
+    let scaled = group.scale(2.0);
+    let result = total_area(&vec![shared, scaled]) + *largest.borrow();
+    result
+}
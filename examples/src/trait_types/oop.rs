@@ -0,0 +1,66 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+pub struct Square {
+    pub side: f64,
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+
+pub struct Canvas {
+    pub background: Box<dyn Shape>,
+    pub scale: f64,
+}
+
+impl Canvas {
+    pub fn area(&self) -> f64 {
+        self.scale * self.background.area()
+    }
+}
+
+pub struct Holder<T: Shape> {
+    pub shape: T,
+}
+
+impl<T: Shape> Holder<T> {
+    pub fn doubled(&self) -> f64 {
+        double(&self.shape)
+    }
+}
+
+pub const UNIT: &dyn Shape = &Square { side: 1.0 };
+
+pub fn describe(shape: &dyn Shape) -> f64 {
+    shape.area()
+}
+
+pub fn sum(a: impl Shape, b: &impl Shape) -> f64 {
+    a.area() + b.area()
+}
+
+pub fn double<T: Shape>(shape: &T) -> f64 {
+    2.0 * shape.area()
+}
+
+pub fn triple<T>(shape: T) -> f64 where T: Shape {
+    3.0 * shape.area()
+}
+
+pub fn make(side: f64) -> impl Shape {
+    Square { side }
+}
+
+pub fn boxed(side: f64) -> Box<dyn Shape> {
+    Box::new(Square { side })
+}
+
+pub fn demo() -> f64 {
+    let canvas = Canvas { background: boxed(2.0), scale: 2.0 };
+    let holder: Holder<Square> = Holder { shape: Square { side: 3.0 } };
+    canvas.area() + describe(UNIT) + sum(make(1.0), &make(2.0)) + double::<Square>(&Square { side: 1.0 }) + triple(make(1.0)) + holder.doubled()
+}
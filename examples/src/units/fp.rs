@@ -0,0 +1,48 @@
+pub enum Length {
+    Metres { metres: u32 },
+    Centimetres { centimetres: u32 },
+}
+
+pub fn metres_from_millimetres(millimetres: u32) -> Length {
+    Length::Metres { metres: millimetres / 1000 }
+}
+
+pub fn centimetres_from_millimetres(millimetres: u32) -> Length {
+    Length::Centimetres { centimetres: millimetres / 10 }
+}
+
+pub fn metres_unit() -> &'static str {
+    "m"
+}
+
+pub fn centimetres_unit() -> &'static str {
+    "cm"
+}
+
+pub fn precision() -> u32 {
+    10
+}
+
+pub fn millimetres(length: &Length) -> u32 {
+    match length {
+        Length::Metres { metres } => metres * 1000,
+        Length::Centimetres { centimetres } => centimetres * 10,
+    }
+}
+
+pub fn describe(length: &Length) -> String {
+    match length {
+        Length::Centimetres { centimetres } => format!("{}{}", centimetres, centimetres_unit()),
+        _ => format!("{}mm", millimetres(length)),
+    }
+}
+
+pub fn rounded(length: &Length) -> u32 {
+    millimetres(length) / precision()
+}
+
+pub fn demo() -> String {
+    let metres = metres_from_millimetres(2500);
+    let centimetres = centimetres_from_millimetres(2500);
+    format!("{} in {}, {} rounds to {}", describe(&metres), metres_unit(), describe(&centimetres), rounded(&centimetres))
+}
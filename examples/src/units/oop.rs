@@ -0,0 +1,64 @@
+pub trait Length {
+    fn from_millimetres(millimetres: u32) -> Self where Self: Sized;
+    fn unit() -> &'static str where Self: Sized;
+
+    fn precision() -> u32 where Self: Sized {
+        10
+    }
+
+    fn millimetres(&self) -> u32;
+
+    fn describe(&self) -> String {
+        format!("{}mm", self.millimetres())
+    }
+}
+
+pub struct Metres {
+    pub metres: u32,
+}
+
+pub struct Centimetres {
+    pub centimetres: u32,
+}
+
+impl Length for Metres {
+    fn from_millimetres(millimetres: u32) -> Self {
+        Metres { metres: millimetres / 1000 }
+    }
+
+    fn unit() -> &'static str {
+        "m"
+    }
+
+    fn millimetres(&self) -> u32 {
+        self.metres * 1000
+    }
+}
+
+impl Length for Centimetres {
+    fn from_millimetres(millimetres: u32) -> Self {
+        Self { centimetres: millimetres / 10 }
+    }
+
+    fn unit() -> &'static str {
+        "cm"
+    }
+
+    fn millimetres(&self) -> u32 {
+        self.centimetres * 10
+    }
+
+    fn describe(&self) -> String {
+        format!("{}{}", self.centimetres, Self::unit())
+    }
+}
+
+pub fn rounded<L: Length>(length: &L) -> u32 {
+    length.millimetres() / L::precision()
+}
+
+pub fn demo() -> String {
+    let metres = Metres::from_millimetres(2500);
+    let centimetres = <Centimetres as Length>::from_millimetres(2500);
+    format!("{} in {}, {} rounds to {}", metres.describe(), Metres::unit(), centimetres.describe(), rounded(&centimetres))
+}
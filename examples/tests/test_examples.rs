@@ -73,3 +73,210 @@ fn test_mutable_oop() {
     assert!(light.get_brightness() == 0);
 }
 
+
+#[test]
+fn test_mutable_oop_tuple() {
+    use examples::mutable::oop_tuple::*;
+
+    let mut light = RGB(10, 20, 30);
+    assert!(light.get_brightness() == 20);
+
+    light.increase_brightness();
+    assert!(light.get_brightness() == 21);
+}
+
+#[test]
+fn test_modules_oop() {
+    use examples::modules::oop::*;
+
+    assert_eq!(app::demo(), 9.0);
+}
+
+#[test]
+fn test_modules_fp() {
+    use examples::modules::fp::*;
+
+    assert_eq!(app::demo(), 3);
+}
+
+#[test]
+fn test_multi_file_oop() {
+    use examples::multi_file::oop::*;
+
+    assert_eq!(total_area(), 7.0);
+}
+
+#[test]
+fn test_containers_fp() {
+    use examples::containers::fp::*;
+
+    assert_eq!(demo(), 24.0);
+}
+
+#[test]
+fn test_containers_oop() {
+    use examples::containers::oop::*;
+
+    assert_eq!(demo(), 14.0);
+}
+
+#[test]
+fn test_trait_types_oop() {
+    use examples::trait_types::oop::*;
+
+    assert_eq!(demo(), 37.0);
+}
+
+#[test]
+fn test_pointers_oop() {
+    use examples::pointers::oop::*;
+
+    assert_eq!(demo(), 27.0);
+}
+
+#[test]
+fn test_pointers_fp() {
+    use examples::pointers::fp::*;
+
+    assert_eq!(demo(), 14.0);
+}
+
+#[test]
+fn test_shared_oop() {
+    use examples::shared::oop::*;
+
+    assert_eq!(demo(), 28.0);
+}
+
+#[test]
+fn test_shared_fp() {
+    use examples::shared::fp::*;
+
+    assert_eq!(demo(), 17);
+}
+
+#[test]
+fn test_aliases_oop() {
+    use examples::aliases::oop::*;
+
+    assert_eq!(demo(), 12.0);
+}
+
+#[test]
+fn test_aliases_fp() {
+    use examples::aliases::fp::*;
+
+    assert_eq!(demo(), 8);
+}
+
+#[test]
+fn test_imports_oop() {
+    use examples::imports::oop::app::*;
+
+    assert_eq!(demo(), 16.0);
+}
+
+#[test]
+fn test_imports_fp() {
+    use examples::imports::fp::app::*;
+
+    assert_eq!(demo(), 3);
+}
+
+#[test]
+fn test_library_oop() {
+    use examples::library::oop::*;
+
+    assert_eq!(demo(), 644);
+}
+
+#[test]
+fn test_library_fp() {
+    use examples::library::fp::*;
+
+    assert_eq!(demo(), 644);
+}
+
+#[test]
+fn test_parsers_oop() {
+    use examples::parsers::oop::*;
+
+    assert_eq!(demo(), 224);
+}
+
+#[test]
+fn test_parsers_fp() {
+    use examples::parsers::fp::*;
+
+    assert_eq!(demo(), 224);
+}
+
+#[test]
+fn test_polygons_oop() {
+    use examples::polygons::oop::*;
+
+    assert_eq!(demo(), 1475);
+}
+
+#[test]
+fn test_polygons_fp() {
+    use examples::polygons::fp::*;
+
+    assert_eq!(demo(), 1475);
+}
+
+#[test]
+fn test_figures_oop() {
+    use examples::figures::oop::*;
+
+    assert_eq!(demo(), "square with area 9; RECTANGLE is 2x5; Square { side: 3 }");
+}
+
+#[test]
+fn test_figures_fp() {
+    use examples::figures::fp::*;
+
+    assert_eq!(demo(), "square with area 9; RECTANGLE is 2x5; Square { side: 3 }");
+}
+
+#[test]
+fn test_units_oop() {
+    use examples::units::oop::*;
+
+    assert_eq!(demo(), "2000mm in m, 250cm rounds to 250");
+}
+
+#[test]
+fn test_units_fp() {
+    use examples::units::fp::*;
+
+    assert_eq!(demo(), "2000mm in m, 250cm rounds to 250");
+}
+
+#[test]
+fn test_bundles_oop() {
+    use examples::bundles::oop::*;
+
+    assert_eq!(demo(), 661);
+}
+
+#[test]
+fn test_bundles_fp() {
+    use examples::bundles::fp::*;
+
+    assert_eq!(demo(), 661);
+}
+
+#[test]
+fn test_frames_fp() {
+    use examples::frames::fp::*;
+
+    assert_eq!(demo(), 1028.0);
+}
+
+#[test]
+fn test_literals_oop() {
+    use examples::literals::oop::*;
+
+    assert_eq!(demo(), "clicks: 4, level: 1 (1)");
+}
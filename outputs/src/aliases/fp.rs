@@ -0,0 +1,43 @@
+pub trait Expr {
+    fn increment(self: Box<Self>) -> Box<dyn Expr>;
+    fn eval(&self, env: &Env) -> Int;
+}
+pub struct Lit(pub Int);
+impl Expr for Lit {
+    fn increment(self: Box<Self>) -> Box<dyn Expr> {
+        Box::new(Add(self, Box::new(Lit(1))))
+    }
+    fn eval(&self, env: &Env) -> Int {
+        self.0
+    }
+}
+pub struct Var(pub String);
+impl Expr for Var {
+    fn increment(self: Box<Self>) -> Box<dyn Expr> {
+        Box::new(Add(self, Box::new(Lit(1))))
+    }
+    fn eval(&self, env: &Env) -> Int {
+        env.iter()
+            .find(|(var, _)| var == &self.0)
+            .map(|(_, value)| *value)
+            .unwrap_or(0)
+    }
+}
+pub struct Add(pub ExprRef, pub ExprRef);
+impl Expr for Add {
+    fn increment(self: Box<Self>) -> Box<dyn Expr> {
+        Box::new(Add(self, Box::new(Lit(1))))
+    }
+    fn eval(&self, env: &Env) -> Int {
+        self.0.eval(env) + self.1.eval(env)
+    }
+}
+pub type ExprRef = Box<dyn Expr>;
+pub type Env = Vec<(String, Int)>;
+pub type Int = i32;
+pub fn demo() -> Int {
+    let env: Env = vec![(String::from("x"), 4)];
+    let sum: ExprRef = Box::new(Add(Box::new(Lit(3)), Box::new(Var(String::from("x")))));
+    let result = sum.increment().eval(&env);
+    result
+}
@@ -0,0 +1,34 @@
+pub enum Shape {
+    Square { side: Length },
+    Stack { shapes: Pair<ShapeRef> },
+}
+pub fn area(shape: &Shape) -> Length {
+    match shape {
+        Shape::Square { side } => side * side,
+        Shape::Stack { shapes } => area(&*shapes.0) + area(&*shapes.1),
+    }
+}
+pub fn scale(shape: &Shape, factor: Length) -> ShapeRef {
+    match shape {
+        Shape::Square { side } => Box::new(Shape::Square {
+            side: side * factor,
+        }),
+        Shape::Stack { shapes } => Box::new(Shape::Stack {
+            shapes: (scale(&*shapes.0, factor), scale(&*shapes.1, factor)),
+        }),
+    }
+}
+pub type ShapeRef = Box<Shape>;
+pub type Pair<T> = (T, T);
+pub type Length = f64;
+pub fn total_area(shapes: &Vec<ShapeRef>) -> Length {
+    shapes.iter().map(|shape| area(&**shape)).sum()
+}
+pub fn demo() -> Length {
+    let square: ShapeRef = Box::new(Shape::Square { side: 2.0 });
+    let stack: ShapeRef = Box::new(Shape::Stack {
+        shapes: (Box::new(Shape::Square { side: 1.0 }), scale(&*square, 0.5)),
+    });
+    let result = total_area(&vec![square, scale(&*stack, 2.0)]);
+    result
+}
@@ -0,0 +1,69 @@
+pub trait Bundle {
+    fn total(&self) -> i32;
+    fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: Fn(A, i32) -> A,
+        Self: Sized;
+    fn map<F: Fn(i32) -> i32>(&self, f: F) -> Box<dyn Bundle>
+    where
+        Self: Sized;
+}
+pub struct One {
+    pub value: i32,
+}
+impl Bundle for One {
+    fn total(&self) -> i32 {
+        self.value
+    }
+    fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: Fn(A, i32) -> A,
+        Self: Sized,
+    {
+        f(init, self.value)
+    }
+    fn map<F: Fn(i32) -> i32>(&self, f: F) -> Box<dyn Bundle>
+    where
+        Self: Sized,
+    {
+        Box::new(One {
+            value: f(self.value),
+        })
+    }
+}
+pub struct Two {
+    pub first: i32,
+    pub second: i32,
+}
+impl Bundle for Two {
+    fn total(&self) -> i32 {
+        self.first + self.second
+    }
+    fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: Fn(A, i32) -> A,
+        Self: Sized,
+    {
+        let acc: A = f(init, self.first);
+        f(acc, self.second)
+    }
+    fn map<F: Fn(i32) -> i32>(&self, f: F) -> Box<dyn Bundle>
+    where
+        Self: Sized,
+    {
+        Box::new(Two {
+            first: f(self.first),
+            second: f(self.second),
+        })
+    }
+}
+pub fn demo() -> i32 {
+    let two = Two {
+        first: 1,
+        second: 2,
+    };
+    let one = One { value: 5 };
+    let sum = two.fold::<i32, _>(0, |acc, x| acc + x * 2);
+    let count = one.fold(0, |count, _| count + 1);
+    sum * 100 + one.map(|x| x + 1).total() * 10 + count
+}
@@ -0,0 +1,42 @@
+pub enum Bundle {
+    One { value: i32 },
+    Two { first: i32, second: i32 },
+}
+pub fn map<F: Fn(i32) -> i32>(bundle: &Bundle, f: F) -> Bundle {
+    match bundle {
+        Bundle::One { value } => Bundle::One { value: f(*value) },
+        Bundle::Two { first, second } => Bundle::Two {
+            first: f(*first),
+            second: f(*second),
+        },
+    }
+}
+pub fn fold<A, F>(bundle: &Bundle, init: A, f: F) -> A
+where
+    F: Fn(A, i32) -> A,
+{
+    match bundle {
+        Bundle::One { value } => f(init, *value),
+        Bundle::Two { first, second } => {
+            let acc: A = f(init, *first);
+            f(acc, *second)
+        }
+    }
+}
+pub fn total(bundle: &Bundle) -> i32 {
+    match bundle {
+        Bundle::One { value } => *value,
+        Bundle::Two { first, second } => first + second,
+    }
+}
+pub fn demo() -> i32 {
+    let two = Bundle::Two {
+        first: 1,
+        second: 2,
+    };
+    let doubled = map(&two, |x| x * 2);
+    let one = Bundle::One { value: 5 };
+    let sum = fold::<i32, _>(&doubled, 0, |acc, x| acc + x);
+    let count = fold(&one, 0, |count, _| count + 1);
+    sum * 100 + total(&map(&one, |x| x + 1)) * 10 + count
+}
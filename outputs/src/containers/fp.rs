@@ -0,0 +1,47 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+pub struct Circle {
+    pub radius: f64,
+}
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.radius * self.radius
+    }
+}
+pub struct Square {
+    pub side: f64,
+}
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+pub fn total_area(shapes: &Vec<Box<dyn Shape>>) -> f64 {
+    let mut total = 0.0;
+    for shape in shapes {
+        total = total + shape.area();
+    }
+    total
+}
+pub fn make_shapes() -> Vec<Box<dyn Shape>> {
+    vec![
+        Box::new(Circle { radius: 1.0 }),
+        Box::new(Square { side: 2.0 }),
+    ]
+}
+pub fn with_area(side: f64) -> (Box<dyn Shape>, f64) {
+    (Box::new(Square { side }), side * side)
+}
+pub fn demo() -> f64 {
+    let mut shapes = make_shapes();
+    shapes.push(Box::new(Square { side: 1.0 }));
+    let (square, _) = with_area(2.0);
+    shapes.push(square);
+    let spare: Option<Box<dyn Shape>> = Some(Box::new(Circle { radius: 2.0 }));
+    match spare {
+        Some(shape) => shapes.push(shape),
+        None => (),
+    }
+    total_area(&shapes)
+}
@@ -0,0 +1,38 @@
+pub enum Shape {
+    Square {
+        side: f64,
+    },
+    Group {
+        shapes: Vec<Shape>,
+        border: Option<Box<Shape>>,
+    },
+}
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Square { side } => side * side,
+        Shape::Group { shapes, border } => {
+            let mut total = 0.0;
+            for shape in shapes.iter() {
+                total = total + area(shape);
+            }
+            match &border {
+                Some(border) => total + area(border),
+                None => total,
+            }
+        }
+    }
+}
+pub fn squares(sides: Vec<f64>) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+    for side in sides {
+        shapes.push(Shape::Square { side });
+    }
+    shapes
+}
+pub fn demo() -> f64 {
+    let group = Shape::Group {
+        shapes: squares(vec![1.0, 2.0]),
+        border: Some(Box::new(Shape::Square { side: 3.0 })),
+    };
+    area(&group)
+}
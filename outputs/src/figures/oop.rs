@@ -0,0 +1,55 @@
+#[derive(Debug, Clone)]
+pub enum Figure {
+    Square { side: u32 },
+    Rectangle { width: u32, height: u32 },
+}
+impl fmt::Display for Figure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Figure::Square { side } => {
+                write!(f, "{}x{}", side, side)
+            }
+            Figure::Rectangle { width, height } => {
+                write!(f, "{}x{}", width, height)
+            }
+        }
+    }
+}
+pub fn area(figure: &Figure) -> u32 {
+    match figure {
+        Figure::Square { side } => side * side,
+        Figure::Rectangle { width, height } => width * height,
+    }
+}
+pub fn describe(figure: &Figure) -> String {
+    match figure {
+        _ => {
+            format!("{} with area {}", name(figure), area(figure))
+        }
+    }
+}
+pub fn name(figure: &Figure) -> String {
+    match figure {
+        Figure::Square { side } => "square".to_string(),
+        Figure::Rectangle { width, height } => "rectangle".to_string(),
+    }
+}
+use std::fmt;
+pub fn label(item: &Figure) -> String {
+    name(item).to_uppercase()
+}
+pub fn demo() -> String {
+    let square = Figure::Square { side: 3 };
+    let rectangle = Figure::Rectangle {
+        width: 2,
+        height: 5,
+    };
+    let copy = rectangle.clone();
+    format!(
+        "{}; {} is {}; {:?}",
+        describe(&square),
+        label(&copy),
+        rectangle,
+        square
+    )
+}
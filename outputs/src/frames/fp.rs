@@ -0,0 +1,62 @@
+pub trait Shape {
+    fn scale(&self, factor: f64) -> Box<dyn Shape>;
+    fn transformed_area(&self, transforms: &[fn(f64) -> f64]) -> f64 {
+        transforms[0](self.area())
+    }
+    fn framed(&self) -> Frame {
+        Frame::new(self.area())
+    }
+    fn area(&self) -> f64;
+}
+pub struct Square {
+    pub side: f64,
+}
+impl Shape for Square {
+    fn scale(&self, factor: f64) -> Box<dyn Shape> {
+        Box::new(Square {
+            side: self.side * factor,
+        })
+    }
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+pub struct Circle {
+    pub radius: f64,
+}
+impl Shape for Circle {
+    fn scale(&self, factor: f64) -> Box<dyn Shape> {
+        Box::new(Circle {
+            radius: self.radius * factor,
+        })
+    }
+    fn area(&self) -> f64 {
+        3.0 * self.radius * self.radius
+    }
+}
+pub struct Frame {
+    pub width: f64,
+}
+impl Frame {
+    pub fn new(width: f64) -> Frame {
+        Frame { width }
+    }
+    pub fn area(&self) -> f64 {
+        self.width * self.width
+    }
+    pub fn scale(&self, factor: u32) -> Frame {
+        Frame {
+            width: self.width * factor as f64,
+        }
+    }
+}
+impl dyn Shape {
+    pub fn new(side: f64) -> Box<dyn Shape> {
+        Box::new(Square { side })
+    }
+}
+pub fn demo() -> f64 {
+    let shape = <dyn Shape>::new(2.0);
+    let frame = shape.scale(2.0).framed().scale(2);
+    frame.area() + shape.area()
+}
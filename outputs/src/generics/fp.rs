@@ -0,0 +1,15 @@
+pub trait Container {
+    type T;
+    fn get_item(self: Box<Self>) -> Self::T;
+}
+pub struct LoggingContainer<T>(pub T);
+impl<T> Container for LoggingContainer<T> {
+    type T = T;
+    fn get_item(self: Box<Self>) -> T {
+        self.0
+    }
+}
+pub fn demo() {
+    let container: Box<dyn Container<T = String>> = Box::new(LoggingContainer("hello".to_string()));
+    println!("{}", container.get_item());
+}
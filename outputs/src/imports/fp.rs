@@ -0,0 +1,27 @@
+pub mod exprs {
+    pub trait Expr {
+        fn eval(&self) -> i32;
+    }
+    pub struct Lit(pub i32);
+    impl Expr for Lit {
+        fn eval(&self) -> i32 {
+            self.0
+        }
+    }
+    pub struct Add(pub Box<dyn Expr>, pub Box<dyn Expr>);
+    impl Expr for Add {
+        fn eval(&self) -> i32 {
+            self.0.eval() + self.1.eval()
+        }
+    }
+}
+pub mod app {
+    use super::exprs::Expr;
+    pub fn demo() -> i32 {
+        let expr = super::exprs::Add(
+            Box::new(super::exprs::Lit(1)),
+            Box::new(super::exprs::Lit(2)),
+        );
+        expr.eval()
+    }
+}
@@ -0,0 +1,35 @@
+pub mod shapes {
+    pub enum Shape {
+        Circle { radius: f64 },
+        Square { side: f64 },
+    }
+    pub fn area(shape: &Shape) -> f64 {
+        match shape {
+            Shape::Circle { radius } => 3.0 * radius * radius,
+            Shape::Square { side } => side * side,
+        }
+    }
+    pub fn scale(shape: &Shape, factor: f64) -> Shape {
+        match shape {
+            Shape::Circle { radius } => Shape::Circle {
+                radius: radius * factor,
+            },
+            Shape::Square { side } => Shape::Square {
+                side: side * factor,
+            },
+        }
+    }
+    pub mod square {}
+}
+pub mod app {
+    use super::shapes::{self, Shape};
+    pub fn total_area(shapes: &Vec<Shape>) -> f64 {
+        shapes.iter().map(|shape| super::shapes::area(shape)).sum()
+    }
+    pub fn demo() -> f64 {
+        let circle: Box<Shape> = Box::new(Shape::Circle { radius: 1.0 });
+        let square = Shape::Square { side: 2.0 };
+        let other = Shape::Circle { radius: 2.0 };
+        total_area(&vec![*circle, super::shapes::scale(&square, 0.5), other])
+    }
+}
@@ -0,0 +1,41 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn scale(&self, factor: f64) -> Box<dyn Shape>;
+}
+pub struct Circle {
+    pub radius: f64,
+}
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.radius * self.radius
+    }
+    fn scale(&self, factor: f64) -> Box<dyn Shape> {
+        Box::new(Circle {
+            radius: self.radius * factor,
+        })
+    }
+}
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+    fn scale(&self, factor: f64) -> Box<dyn Shape> {
+        Box::new(Rectangle {
+            width: self.width * factor,
+            height: self.height * factor,
+        })
+    }
+}
+impl dyn Shape {
+    pub fn unit() -> Box<dyn Shape> {
+        Box::new(Circle { radius: 1.0 })
+    }
+}
+pub fn demo() {
+    let shape = <dyn Shape>::unit();
+    println!("Area: {}", shape.scale(2.0).area());
+}
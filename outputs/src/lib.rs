@@ -4,4 +4,22 @@ pub mod shape2;
 pub mod bool;
 pub mod countdown;
 pub mod mutable;
+pub mod pointers;
+pub mod shared;
+pub mod aliases;
+pub mod imports;
+pub mod library;
+pub mod parsers;
+pub mod polygons;
+pub mod figures;
+pub mod units;
+pub mod bundles;
+pub mod containers;
 pub mod generics;
+pub mod inherent;
+pub mod frames;
+pub mod literals;
+pub mod modules;
+pub mod multi_file;
+pub mod sql;
+pub mod trait_types;
@@ -0,0 +1,82 @@
+pub enum Item {
+    Book {
+        name: String,
+        chapters: Vec<usize>,
+    },
+    Collection {
+        name: String,
+        items: Vec<Item>,
+        editor: Option<String>,
+    },
+}
+pub fn title(item: &Item) -> String {
+    match item {
+        Item::Book { name, chapters } => name.trim().to_string(),
+        Item::Collection {
+            name,
+            items,
+            editor,
+        } => {
+            let mut title = name.to_uppercase();
+            match editor.as_ref() {
+                Some(editor_name) => {
+                    title.push_str(", ed. ");
+                    title.push_str(editor_name);
+                }
+                None => (),
+            }
+            title
+        }
+    }
+}
+pub fn pages(item: &Item) -> usize {
+    match item {
+        Item::Book { name, chapters } => chapters.iter().sum(),
+        Item::Collection {
+            name,
+            items,
+            editor,
+        } => {
+            let total: usize = items.iter().map(|child| pages(child)).sum();
+            total.max(1)
+        }
+    }
+}
+pub fn index(item: &Item, entries: &mut HashMap<String, usize>) {
+    match item {
+        Item::Book { name, chapters } => {
+            entries.insert(title(item), pages(item));
+        }
+        Item::Collection {
+            name,
+            items,
+            editor,
+        } => {
+            for item in items.iter() {
+                index(item, entries);
+            }
+            entries.insert(title(item), items.len());
+        }
+    }
+}
+use std::collections::HashMap;
+pub fn demo() -> usize {
+    let mut items: Vec<Item> = Vec::new();
+    items.push(Item::Book {
+        name: " Dune ".to_string(),
+        chapters: vec![120, 200],
+    });
+    items.push(Item::Collection {
+        name: "empty".to_string(),
+        items: Vec::new(),
+        editor: None,
+    });
+    let shelf = Item::Collection {
+        name: "classics".to_string(),
+        items,
+        editor: Some("Ann".to_string()),
+    };
+    let mut entries = HashMap::new();
+    index(&shelf, &mut entries);
+    entries.get("Dune").copied().unwrap_or(0) + entries.len() + pages(&shelf)
+}
@@ -0,0 +1,50 @@
+pub enum Counter {
+    Clicks { clicks: u64 },
+    Level { level: i8 },
+}
+pub fn count(counter: &Counter) -> u64 {
+    match counter {
+        Counter::Clicks { clicks } => *clicks,
+        Counter::Level { level } => {
+            if level > &0 {
+                1
+            } else {
+                0
+            }
+        }
+    }
+}
+pub fn step(counter: &Counter, by: u8) -> Counter {
+    match counter {
+        Counter::Clicks { clicks } => Counter::Clicks {
+            clicks: clicks + by as u64,
+        },
+        Counter::Level { level } => {
+            if by > 1u8 {
+                Counter::Level { level: level + 2 }
+            } else {
+                Counter::Level { level: level + 1 }
+            }
+        }
+    }
+}
+pub fn describe(counter: &Counter, label: &str) -> String {
+    match counter {
+        Counter::Clicks { clicks } => {
+            format!("{}: {}", label, clicks + 1)
+        }
+        Counter::Level { level } => {
+            format!("{}: {}", label, level)
+        }
+    }
+}
+pub fn demo() -> String {
+    let clicks = Counter::Clicks { clicks: 0 };
+    let clicks = step(&step(&clicks, 2u8), 1);
+    let level = Counter::Level { level: -1 };
+    let level = step(&level, 2);
+    describe(&clicks, "clicks")
+        + ", "
+        + &describe(&level, "level")
+        + &format!(" ({})", count(&level))
+}
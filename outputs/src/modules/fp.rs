@@ -0,0 +1,32 @@
+pub mod exprs {
+    pub trait Expr {
+        fn eval(&self) -> i32;
+    }
+    pub struct Lit {
+        pub value: i32,
+    }
+    impl Expr for Lit {
+        fn eval(&self) -> i32 {
+            self.value
+        }
+    }
+    pub struct Add {
+        pub left: Box<dyn Expr>,
+        pub right: Box<dyn Expr>,
+    }
+    impl Expr for Add {
+        fn eval(&self) -> i32 {
+            self.left.eval() + self.right.eval()
+        }
+    }
+}
+pub mod app {
+    use super::exprs::Expr;
+    pub fn demo() -> i32 {
+        let expr = super::exprs::Add {
+            left: Box::new(super::exprs::Lit { value: 1 }),
+            right: Box::new(super::exprs::Lit { value: 2 }),
+        };
+        expr.eval()
+    }
+}
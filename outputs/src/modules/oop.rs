@@ -0,0 +1,35 @@
+pub mod shapes {
+    pub enum Shape {
+        Circle { radius: f64 },
+        Rectangle { width: f64, height: f64 },
+    }
+    pub fn area(shape: &Shape) -> f64 {
+        match shape {
+            Shape::Circle { radius } => 3.0 * radius * radius,
+            Shape::Rectangle { width, height } => width * height,
+        }
+    }
+    pub fn scale(shape: &Shape, factor: f64) -> Shape {
+        match shape {
+            Shape::Circle { radius } => Shape::Circle {
+                radius: radius * factor,
+            },
+            Shape::Rectangle { width, height } => Shape::Rectangle {
+                width: width * factor,
+                height: height * factor,
+            },
+        }
+    }
+}
+pub mod rectangle {}
+pub mod app {
+    use super::shapes::Shape;
+    pub fn demo() -> f64 {
+        let circle = Shape::Circle { radius: 1.0 };
+        let rectangle = Shape::Rectangle {
+            width: 2.0,
+            height: 3.0,
+        };
+        super::shapes::area(&circle) + super::shapes::area(&rectangle)
+    }
+}
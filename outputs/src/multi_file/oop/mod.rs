@@ -0,0 +1,10 @@
+pub mod circle;
+pub mod shape;
+#[path = "square_shape.rs"]
+pub mod square;
+use shape::Shape;
+pub fn total_area() -> f64 {
+    let circle = Shape::Circle { radius: 1.0 };
+    let square = Shape::Square { side: 2.0 };
+    shape::area(&circle) + shape::area(&square)
+}
@@ -0,0 +1,20 @@
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Circle { radius } => 3.0 * radius * radius,
+        Shape::Square { side } => side * side,
+    }
+}
+pub fn scale(shape: &Shape, factor: f64) -> Shape {
+    match shape {
+        Shape::Circle { radius } => Shape::Circle {
+            radius: radius * factor,
+        },
+        Shape::Square { side } => Shape::Square {
+            side: side * factor,
+        },
+    }
+}
@@ -0,0 +1,34 @@
+pub trait DimmableLight {
+    fn increase_brightness(self: Box<Self>) -> Box<dyn DimmableLight>;
+    fn decrease_brightness(self: Box<Self>) -> Box<dyn DimmableLight>;
+    fn get_brightness(self: Box<Self>) -> i8;
+}
+pub struct Dimmer(pub i8);
+impl DimmableLight for Dimmer {
+    fn increase_brightness(self: Box<Self>) -> Box<dyn DimmableLight> {
+        Box::new(Dimmer(self.0 + 1))
+    }
+    fn decrease_brightness(self: Box<Self>) -> Box<dyn DimmableLight> {
+        Box::new(Dimmer(self.0 - 1))
+    }
+    fn get_brightness(self: Box<Self>) -> i8 {
+        self.0
+    }
+}
+pub struct RGB(pub i8, pub i8, pub i8);
+impl DimmableLight for RGB {
+    fn increase_brightness(self: Box<Self>) -> Box<dyn DimmableLight> {
+        Box::new(RGB(self.0 + 1, self.1 + 1, self.2 + 1))
+    }
+    fn decrease_brightness(self: Box<Self>) -> Box<dyn DimmableLight> {
+        Box::new(RGB(self.0 - 1, self.1 - 1, self.2 - 1))
+    }
+    fn get_brightness(self: Box<Self>) -> i8 {
+        (self.0 + self.1 + self.2) / 3
+    }
+}
+pub fn dimmer() {
+    let mut dimmer: Box<dyn DimmableLight> = Box::new(Dimmer(10));
+    dimmer = dimmer.increase_brightness();
+    println!("Brightness: {}", dimmer.get_brightness());
+}
@@ -1,2 +1,4 @@
 pub mod oop_basic;
 pub mod oop;
+pub mod oop_tuple;
+pub mod fp;
@@ -57,12 +57,12 @@ pub fn turn_off(light: Light) -> Light {
     }
 }
 pub fn get_brightness(light: &Light) -> i32 {
-    match &light {
+    match light {
         Light::Dimmer { brightness } => {
             return *brightness;
         }
         Light::RGB { r, g, b } => {
-            return (r + g + *b) / 3;
+            return (r + g + b) / 3;
         }
     }
 }
@@ -0,0 +1,33 @@
+pub enum Light {
+    Dimmer(i8),
+    RGB(i8, i8, i8),
+}
+pub fn increase_brightness(light: Light) -> Light {
+    match &light {
+        Light::Dimmer(mut field_0) => {
+            field_0 += 1;
+            Light::Dimmer(field_0)
+        }
+        Light::RGB(mut field_0, mut field_1, mut field_2) => {
+            field_0 += 1;
+            field_1 += 1;
+            field_2 += 1;
+            Light::RGB(field_0, field_1, field_2)
+        }
+    }
+}
+pub fn get_brightness(light: &Light) -> i8 {
+    match light {
+        Light::Dimmer(field_0) => {
+            return *field_0;
+        }
+        Light::RGB(field_0, field_1, field_2) => {
+            return (field_0 + field_1 + field_2) / 3;
+        }
+    }
+}
+pub fn demo() {
+    let mut light = Light::Dimmer(0);
+    light = increase_brightness(light);
+    println!("Brightness: {}", get_brightness(&light));
+}
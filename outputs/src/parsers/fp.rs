@@ -0,0 +1,70 @@
+pub trait Parser {
+    type T: Clone;
+    fn parse(&self, input: &str) -> Result<Self::T, String>;
+    fn describe(&self) -> String;
+}
+pub struct Literal<T> {
+    pub text: String,
+    pub value: T,
+}
+impl<T: Clone> Parser for Literal<T> {
+    type T = T;
+    fn parse(&self, input: &str) -> Result<T, String> {
+        if input == self.text {
+            Ok(self.value.clone())
+        } else {
+            Err("expected ".to_string() + &self.text)
+        }
+    }
+    fn describe(&self) -> String {
+        self.text.clone()
+    }
+}
+pub struct Either<T> {
+    pub first: Box<dyn Parser<T = T>>,
+    pub second: Box<dyn Parser<T = T>>,
+}
+impl<T: Clone> Parser for Either<T> {
+    type T = T;
+    fn parse(&self, input: &str) -> Result<T, String> {
+        match self.first.parse(input) {
+            Ok(output) => Ok(output),
+            Err(_) => self.second.parse(input),
+        }
+    }
+    fn describe(&self) -> String {
+        self.first.describe() + " or " + &self.second.describe()
+    }
+}
+pub fn score(inputs: Vec<&str>, parser: &dyn Parser<T = usize>) -> usize {
+    let mut total = 0;
+    for input in inputs {
+        let points = match parser.parse(input) {
+            Ok(points) => points,
+            Err(_) => 0,
+        };
+        total += points;
+    }
+    total
+}
+pub fn demo() -> usize {
+    let yes = Literal {
+        text: "yes".to_string(),
+        value: 10,
+    };
+    let no = Literal {
+        text: "no".to_string(),
+        value: 1,
+    };
+    let answer = Either {
+        first: Box::new(yes),
+        second: Box::new(no),
+    };
+    let greeting = Literal {
+        text: "hi".to_string(),
+        value: "hello".to_string(),
+    };
+    let greeted: String = greeting.parse("hi").unwrap();
+    let description = answer.describe();
+    score(vec!["yes", "maybe", "no", "yes"], &answer) * 10 + description.len() + greeted.len()
+}
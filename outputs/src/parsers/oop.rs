@@ -0,0 +1,63 @@
+pub enum Parser<Output> {
+    Literal {
+        text: String,
+        value: Output,
+    },
+    Either {
+        first: Box<Parser<Output>>,
+        second: Box<Parser<Output>>,
+    },
+}
+pub fn parse<Output: Clone>(parser: &Parser<Output>, input: &str) -> Result<Output, String> {
+    match parser {
+        Parser::Literal { text, value } => {
+            if input == text {
+                Ok(value.clone())
+            } else {
+                Err("expected ".to_string() + &text)
+            }
+        }
+        Parser::Either { first, second } => match parse(&*first, input) {
+            Ok(output) => Ok(output),
+            Err(_) => parse(&*second, input),
+        },
+    }
+}
+pub fn describe<Output: Clone>(parser: &Parser<Output>) -> String {
+    match parser {
+        Parser::Literal { text, value } => text.clone(),
+        Parser::Either { first, second } => describe(&*first) + " or " + &describe(&*second),
+    }
+}
+pub fn score(inputs: Vec<&str>, parser: &Box<Parser<usize>>) -> usize {
+    let mut total = 0;
+    for input in inputs {
+        let points = match parse(&**parser, input) {
+            Ok(points) => points,
+            Err(_) => 0,
+        };
+        total += points;
+    }
+    total
+}
+pub fn demo() -> usize {
+    let yes: Box<Parser<usize>> = Box::new(Parser::Literal {
+        text: "yes".to_string(),
+        value: 10,
+    });
+    let no: Box<Parser<usize>> = Box::new(Parser::Literal {
+        text: "no".to_string(),
+        value: 1,
+    });
+    let answer: Box<Parser<usize>> = Box::new(Parser::Either {
+        first: yes,
+        second: no,
+    });
+    let greeting = Parser::Literal {
+        text: "hi".to_string(),
+        value: "hello".to_string(),
+    };
+    let greeted: String = parse(&greeting, "hi").unwrap();
+    let description = describe(&*answer);
+    score(vec!["yes", "maybe", "no", "yes"], &answer) * 10 + description.len() + greeted.len()
+}
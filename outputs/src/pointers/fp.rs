@@ -0,0 +1,43 @@
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn boxed_area(&self) -> f64 {
+        self.area()
+    }
+    fn ref_ref_area(&self) -> f64 {
+        self.area()
+    }
+    fn owned_area(self: Box<Self>) -> f64 {
+        self.area()
+    }
+    fn mut_area(&mut self) -> f64 {
+        self.area()
+    }
+}
+pub struct Square {
+    pub side: f64,
+}
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+}
+pub struct Scaled {
+    pub inner: Box<dyn Shape>,
+    pub by: f64,
+}
+impl Shape for Scaled {
+    fn area(&self) -> f64 {
+        self.by * self.inner.area()
+    }
+}
+pub fn demo() -> f64 {
+    let mut square: Box<dyn Shape> = Box::new(Square { side: 2.0 });
+    let boxed = Box::new(Scaled {
+        inner: Box::new(Square { side: 1.0 }),
+        by: 3.0,
+    });
+    let borrowed = &square;
+    let first = boxed.boxed_area() + borrowed.ref_ref_area();
+    let mutable = &mut square;
+    first + mutable.mut_area() + boxed.owned_area()
+}
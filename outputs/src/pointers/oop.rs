@@ -0,0 +1,39 @@
+pub enum Shape {
+    Square { side: f64 },
+}
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Square { side } => side * side,
+    }
+}
+pub fn describe(shape: &Shape) -> f64 {
+    area(shape)
+}
+pub fn first_area(shape: &Box<Shape>) -> f64 {
+    describe(&**shape)
+}
+pub fn owned_area(shape: Box<Shape>) -> f64 {
+    describe(&*shape) + area(&*shape)
+}
+pub fn mut_area(shape: &mut Box<Shape>) -> f64 {
+    first_area(shape) + describe(&**shape)
+}
+pub fn ref_ref_area(shape: &&Shape) -> f64 {
+    describe(*shape) + area(*shape)
+}
+pub fn total(shapes: &Vec<Shape>) -> f64 {
+    let mut total = 0.0;
+    for shape in shapes.iter() {
+        total = total + describe(shape);
+    }
+    total
+}
+pub fn demo() -> f64 {
+    let mut shape: Box<Shape> = Box::new(Shape::Square { side: 2.0 });
+    let square = Shape::Square { side: 1.0 };
+    let borrowed: &Shape = &square;
+    mut_area(&mut shape)
+        + ref_ref_area(&borrowed)
+        + owned_area(shape)
+        + total(&vec![Shape::Square { side: 3.0 }])
+}
@@ -0,0 +1,48 @@
+pub trait Polygon {
+    const SIDES: usize;
+    const NAME: &'static str;
+    fn sides(&self) -> usize {
+        Self::SIDES
+    }
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+    fn perimeter(&self) -> usize;
+    fn is_regular(&self) -> bool {
+        self.perimeter() % self.sides() == 0
+    }
+}
+pub struct Square {
+    pub side: usize,
+}
+impl Polygon for Square {
+    const SIDES: usize = 4;
+    const NAME: &'static str = "square";
+    fn perimeter(&self) -> usize {
+        self.sides() * self.side
+    }
+}
+pub struct Triangle {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+impl Polygon for Triangle {
+    const SIDES: usize = 3;
+    const NAME: &'static str = "polygon";
+    fn perimeter(&self) -> usize {
+        self.a + self.b + self.c
+    }
+}
+pub fn demo() -> usize {
+    let square = Square { side: 5 };
+    let triangle = Triangle { a: 3, b: 4, c: 5 };
+    let regular = if triangle.is_regular() { 1000 } else { 0 };
+    let name_length = square.name().len() + triangle.name().len();
+    square.sides() * 100
+        + triangle.sides() * 10
+        + square.perimeter()
+        + triangle.perimeter()
+        + regular
+        + name_length
+}
@@ -0,0 +1,42 @@
+pub enum Polygon {
+    Square { side: usize },
+    Triangle { a: usize, b: usize, c: usize },
+}
+pub fn sides(polygon: &Polygon) -> usize {
+    match polygon {
+        Polygon::Square { side } => 4,
+        Polygon::Triangle { a, b, c } => 3,
+    }
+}
+pub fn name(polygon: &Polygon) -> &'static str {
+    match polygon {
+        Polygon::Square { side } => "square",
+        _ => "polygon",
+    }
+}
+pub fn perimeter(polygon: &Polygon) -> usize {
+    match polygon {
+        Polygon::Square { side } => sides(polygon) * side,
+        Polygon::Triangle { a, b, c } => a + b + c,
+    }
+}
+pub fn is_regular(polygon: &Polygon) -> bool {
+    match polygon {
+        _ => perimeter(polygon) % sides(polygon) == 0,
+    }
+}
+pub fn corners(polygon: &Polygon) -> usize {
+    sides(polygon)
+}
+pub fn demo() -> usize {
+    let square = Polygon::Square { side: 5 };
+    let triangle = Polygon::Triangle { a: 3, b: 4, c: 5 };
+    let regular = if is_regular(&triangle) { 1000 } else { 0 };
+    let name_length = name(&square).len() + name(&triangle).len();
+    corners(&square) * 100
+        + 3 * 10
+        + perimeter(&square)
+        + perimeter(&triangle)
+        + regular
+        + name_length
+}
@@ -1,7 +1,7 @@
 pub trait Set {
     fn is_empty(&self) -> bool;
-    fn contains(&self, target: i32) -> bool;
     fn insert(self: Box<Self>, value: i32) -> Box<dyn Set>;
+    fn contains(&self, target: i32) -> bool;
     fn union(self: Box<Self>, right: Box<dyn Set>) -> Box<dyn Set>;
 }
 pub struct Empty {}
@@ -9,15 +9,15 @@ impl Set for Empty {
     fn is_empty(&self) -> bool {
         true
     }
-    fn contains(&self, target: i32) -> bool {
-        false
-    }
     fn insert(self: Box<Self>, value: i32) -> Box<dyn Set> {
         if self.contains(value) {
             return self;
         }
         return Box::new(Insert { s1: self, value });
     }
+    fn contains(&self, target: i32) -> bool {
+        false
+    }
     fn union(self: Box<Self>, right: Box<dyn Set>) -> Box<dyn Set> {
         right
     }
@@ -30,15 +30,15 @@ impl Set for Insert {
     fn is_empty(&self) -> bool {
         false
     }
-    fn contains(&self, target: i32) -> bool {
-        self.value == target || self.s1.contains(target)
-    }
     fn insert(self: Box<Self>, value: i32) -> Box<dyn Set> {
-        if self.contains(self.value) {
+        if self.contains(value) {
             return self;
         }
         return Box::new(Insert { s1: self, value });
     }
+    fn contains(&self, target: i32) -> bool {
+        self.value == target || self.s1.contains(target)
+    }
     fn union(self: Box<Self>, right: Box<dyn Set>) -> Box<dyn Set> {
         Box::new(Union {
             s1: self,
@@ -54,15 +54,15 @@ impl Set for Union {
     fn is_empty(&self) -> bool {
         self.s1.is_empty() && self.s2.is_empty()
     }
-    fn contains(&self, target: i32) -> bool {
-        self.s1.contains(target) || self.s2.contains(target)
-    }
     fn insert(self: Box<Self>, value: i32) -> Box<dyn Set> {
         if self.contains(value) {
             return self;
         }
         return Box::new(Insert { s1: self, value });
     }
+    fn contains(&self, target: i32) -> bool {
+        self.s1.contains(target) || self.s2.contains(target)
+    }
     fn union(self: Box<Self>, right: Box<dyn Set>) -> Box<dyn Set> {
         Box::new(Union {
             s1: self,
@@ -4,7 +4,7 @@ pub enum Set {
     Union { set1: Box<Set>, set2: Box<Set> },
 }
 pub fn is_empty(set: &Set) -> bool {
-    match &set {
+    match set {
         Set::Empty {} => {
             return true;
         }
@@ -17,7 +17,7 @@ pub fn is_empty(set: &Set) -> bool {
     }
 }
 pub fn contains(set: &Set, i: i32) -> bool {
-    match &set {
+    match set {
         Set::Empty {} => {
             return false;
         }
@@ -80,7 +80,7 @@ pub fn union(set: Set, s: Set) -> Set {
     }
 }
 pub fn type_id(set: &Set) -> i32 {
-    match &set {
+    match set {
         Set::Empty {} => {
             return 10;
         }
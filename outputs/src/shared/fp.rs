@@ -0,0 +1,42 @@
+pub trait Expr {
+    fn eval(&self) -> i32;
+    fn double(self: Rc<Self>) -> Rc<dyn Expr>;
+}
+pub struct Lit(pub i32);
+impl Expr for Lit {
+    fn eval(&self) -> i32 {
+        self.0
+    }
+    fn double(self: Rc<Self>) -> Rc<dyn Expr> {
+        Rc::new(Add(self.clone(), self))
+    }
+}
+pub struct Add(pub Rc<dyn Expr>, pub Rc<dyn Expr>);
+impl Expr for Add {
+    fn eval(&self) -> i32 {
+        self.0.eval() + self.1.eval()
+    }
+    fn double(self: Rc<Self>) -> Rc<dyn Expr> {
+        Rc::new(Add(self.clone(), self))
+    }
+}
+pub struct Counter(pub Rc<RefCell<i32>>);
+impl Expr for Counter {
+    fn eval(&self) -> i32 {
+        let evaluations = *self.0.borrow() + 1;
+        *self.0.borrow_mut() = evaluations;
+        evaluations
+    }
+    fn double(self: Rc<Self>) -> Rc<dyn Expr> {
+        Rc::new(Add(self.clone(), self))
+    }
+}
+use std::cell::RefCell;
+use std::rc::Rc;
+pub fn demo() -> i32 {
+    let shared = Rc::new(Lit(3));
+    let count = Rc::new(RefCell::new(0));
+    let sum = Add(shared.clone(), Rc::new(Counter(count.clone())));
+    let result = shared.double().eval() + sum.eval() + sum.eval() + *count.borrow();
+    result
+}
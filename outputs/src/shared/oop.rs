@@ -0,0 +1,45 @@
+pub enum Shape {
+    Square {
+        side: f64,
+    },
+    Group {
+        first: Rc<Shape>,
+        second: Arc<Shape>,
+    },
+}
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Square { side } => side * side,
+        Shape::Group { first, second } => area(&*first) + area(&*second),
+    }
+}
+pub fn scale(shape: &Shape, factor: f64) -> Rc<Shape> {
+    match shape {
+        Shape::Square { side } => Rc::new(Shape::Square {
+            side: side * factor,
+        }),
+        Shape::Group { first, second } => Rc::new(Shape::Group {
+            first: scale(&*first, factor),
+            second: Arc::new(Shape::Square { side: factor }),
+        }),
+    }
+}
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+pub fn total_area(shapes: &Vec<Rc<Shape>>) -> f64 {
+    shapes.iter().map(|shape| area(&**shape)).sum()
+}
+pub fn demo() -> f64 {
+    let square: Rc<Shape> = Rc::new(Shape::Square { side: 2.0 });
+    let shared = Rc::clone(&square);
+    let largest = RefCell::new(0.0);
+    *largest.borrow_mut() = area(&*square);
+    let group = Shape::Group {
+        first: square,
+        second: Arc::new(Shape::Square { side: 1.0 }),
+    };
+    let scaled = scale(&group, 2.0);
+    let result = total_area(&vec![shared, scaled]) + *largest.borrow();
+    result
+}
@@ -0,0 +1 @@
+pub mod fp_predicate;
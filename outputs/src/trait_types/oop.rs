@@ -0,0 +1,59 @@
+pub enum Shape {
+    Square { side: f64 },
+}
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Square { side } => side * side,
+    }
+}
+pub struct Canvas {
+    pub background: Box<Shape>,
+    pub scale: f64,
+}
+impl Canvas {
+    pub fn area(&self) -> f64 {
+        self.scale * area(&*self.background)
+    }
+}
+pub struct Holder {
+    pub shape: Shape,
+}
+impl Holder {
+    pub fn doubled(&self) -> f64 {
+        double(&self.shape)
+    }
+}
+pub const UNIT: &Shape = &Shape::Square { side: 1.0 };
+pub fn describe(shape: &Shape) -> f64 {
+    area(shape)
+}
+pub fn sum(a: Shape, b: &Shape) -> f64 {
+    area(&a) + area(b)
+}
+pub fn double(shape: &Shape) -> f64 {
+    2.0 * area(shape)
+}
+pub fn triple(shape: Shape) -> f64 {
+    3.0 * area(&shape)
+}
+pub fn make(side: f64) -> Shape {
+    Shape::Square { side }
+}
+pub fn boxed(side: f64) -> Box<Shape> {
+    Box::new(Shape::Square { side })
+}
+pub fn demo() -> f64 {
+    let canvas = Canvas {
+        background: boxed(2.0),
+        scale: 2.0,
+    };
+    let holder: Holder = Holder {
+        shape: Shape::Square { side: 3.0 },
+    };
+    canvas.area()
+        + describe(UNIT)
+        + sum(make(1.0), &make(2.0))
+        + double(&Shape::Square { side: 1.0 })
+        + triple(make(1.0))
+        + holder.doubled()
+}
@@ -65,3 +65,280 @@ fn test_output_mutable_opp() {
     light = increase_brightness(light);
     assert_eq!(get_brightness(&light), 21);
 }
+
+#[test]
+fn test_output_mutable_oop_tuple() {
+    use outputs::mutable::oop_tuple::*;
+
+    let mut light = Light::RGB(10, 20, 30);
+    assert_eq!(get_brightness(&light), 20);
+
+    light = increase_brightness(light);
+    assert_eq!(get_brightness(&light), 21);
+}
+
+#[test]
+fn test_output_mutable_fp() {
+    use outputs::mutable::fp::*;
+
+    let light: Box<dyn DimmableLight> = Box::new(RGB(10, 20, 30));
+    assert_eq!(light.increase_brightness().get_brightness(), 21);
+    assert_eq!(Box::new(Dimmer(10)).decrease_brightness().get_brightness(), 9);
+    dimmer();
+}
+
+#[test]
+fn test_output_inherent_fp() {
+    use outputs::inherent::fp::*;
+
+    let shape = <dyn Shape>::unit();
+    assert_eq!(shape.area(), 3.0);
+    assert_eq!(shape.scale(2.0).area(), 12.0);
+
+    let rectangle = Rectangle { width: 2.0, height: 3.0 };
+    assert_eq!(rectangle.area(), 6.0);
+}
+
+#[test]
+fn test_output_frames_fp() {
+    use outputs::frames::fp::*;
+
+    assert_eq!(demo(), 1028.0);
+    assert_eq!(<dyn Shape>::new(2.0).framed().scale(3).area(), 144.0);
+    assert_eq!(<dyn Shape>::new(2.0).transformed_area(&[|area| area * 2.0]), 8.0);
+}
+
+#[test]
+fn test_output_literals_oop() {
+    use outputs::literals::oop::*;
+
+    assert_eq!(demo(), "clicks: 4, level: 1 (1)");
+}
+
+#[test]
+fn test_output_modules_oop() {
+    use outputs::modules::oop::*;
+
+    assert_eq!(app::demo(), 9.0);
+
+    let circle = shapes::Shape::Circle { radius: 1.0 };
+    assert_eq!(shapes::area(&shapes::scale(&circle, 2.0)), 12.0);
+}
+
+#[test]
+fn test_output_modules_fp() {
+    use outputs::modules::fp::*;
+    use outputs::modules::fp::exprs::Expr;
+
+    assert_eq!(app::demo(), 3);
+
+    let lit = exprs::Lit { value: 4 };
+    assert_eq!(lit.eval(), 4);
+}
+
+#[test]
+fn test_output_multi_file_oop() {
+    use outputs::multi_file::oop::*;
+
+    assert_eq!(total_area(), 7.0);
+
+    let square = shape::Shape::Square { side: 1.0 };
+    assert_eq!(shape::area(&shape::scale(&square, 3.0)), 9.0);
+}
+
+#[test]
+fn test_output_containers_fp() {
+    use outputs::containers::fp::*;
+
+    assert_eq!(demo(), 24.0);
+
+    let (square, area) = with_area(3.0);
+    assert_eq!(square.area(), area);
+}
+
+#[test]
+fn test_output_containers_oop() {
+    use outputs::containers::oop::*;
+
+    assert_eq!(demo(), 14.0);
+    assert_eq!(squares(vec![1.0, 2.0]).iter().map(area).sum::<f64>(), 5.0);
+}
+
+#[test]
+fn test_output_trait_types_oop() {
+    use outputs::trait_types::oop::*;
+
+    assert_eq!(demo(), 37.0);
+    assert_eq!(double(UNIT), 2.0);
+    assert_eq!(sum(make(2.0), &Shape::Square { side: 1.0 }), 5.0);
+    assert_eq!(Holder { shape: Shape::Square { side: 2.0 } }.doubled(), 8.0);
+}
+
+#[test]
+fn test_output_pointers_oop() {
+    use outputs::pointers::oop::*;
+
+    assert_eq!(demo(), 27.0);
+
+    let mut square: Box<Shape> = Box::new(Shape::Square { side: 2.0 });
+    assert_eq!(mut_area(&mut square), 8.0);
+    assert_eq!(ref_ref_area(&&*square), 8.0);
+}
+
+#[test]
+fn test_output_pointers_fp() {
+    use outputs::pointers::fp::*;
+
+    assert_eq!(demo(), 14.0);
+
+    let boxed: Box<dyn Shape> = Box::new(Square { side: 2.0 });
+    assert_eq!(boxed.boxed_area(), 4.0);
+    assert_eq!(boxed.owned_area(), 4.0);
+}
+
+#[test]
+fn test_output_shared_oop() {
+    use outputs::shared::oop::*;
+    use std::rc::Rc;
+
+    assert_eq!(demo(), 28.0);
+
+    let square = Rc::new(Shape::Square { side: 1.0 });
+    assert_eq!(total_area(&vec![square.clone(), scale(&square, 3.0)]), 10.0);
+}
+
+#[test]
+fn test_output_shared_fp() {
+    use outputs::shared::fp::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    assert_eq!(demo(), 17);
+
+    let count = Rc::new(RefCell::new(0));
+    let counter: Rc<dyn Expr> = Rc::new(Counter(count.clone()));
+    assert_eq!(counter.double().eval(), 3);
+    assert_eq!(*count.borrow(), 2);
+}
+
+#[test]
+fn test_output_aliases_oop() {
+    use outputs::aliases::oop::*;
+
+    assert_eq!(demo(), 12.0);
+
+    let stack: ShapeRef = Box::new(Shape::Stack {
+        shapes: (Box::new(Shape::Square { side: 1.0 }), Box::new(Shape::Square { side: 2.0 })),
+    });
+    assert_eq!(area(&scale(&stack, 2.0)), 20.0);
+}
+
+#[test]
+fn test_output_aliases_fp() {
+    use outputs::aliases::fp::*;
+
+    assert_eq!(demo(), 8);
+
+    let env: Env = vec![(String::from("y"), 2)];
+    let expr: ExprRef = Box::new(Var(String::from("y")));
+    assert_eq!(expr.increment().eval(&env), 3);
+}
+
+#[test]
+fn test_output_imports_oop() {
+    use outputs::imports::oop::app::*;
+    use outputs::imports::oop::shapes::*;
+
+    assert_eq!(demo(), 16.0);
+    assert_eq!(total_area(&vec![Shape::Square { side: 3.0 }]), 9.0);
+}
+
+#[test]
+fn test_output_imports_fp() {
+    use outputs::imports::fp::app::*;
+    use outputs::imports::fp::exprs::*;
+
+    assert_eq!(demo(), 3);
+    assert_eq!(Add(Box::new(Lit(4)), Box::new(Lit(5))).eval(), 9);
+}
+
+#[test]
+fn test_output_library_oop() {
+    use outputs::library::oop::*;
+
+    assert_eq!(demo(), 644);
+    assert_eq!(pages(&Item::Book { name: "Emma".to_string(), chapters: vec![5, 6] }), 11);
+}
+
+#[test]
+fn test_output_library_fp() {
+    use outputs::library::fp::*;
+
+    assert_eq!(demo(), 644);
+    assert_eq!(Book { name: "Emma".to_string(), chapters: vec![5, 6] }.pages(), 11);
+}
+
+#[test]
+fn test_output_parsers_oop() {
+    use outputs::parsers::oop::*;
+
+    assert_eq!(demo(), 224);
+    assert_eq!(parse(&Parser::Literal { text: "a".to_string(), value: 'x' }, "a"), Ok('x'));
+}
+
+#[test]
+fn test_output_parsers_fp() {
+    use outputs::parsers::fp::*;
+
+    assert_eq!(demo(), 224);
+    assert_eq!(Literal { text: "a".to_string(), value: 'x' }.parse("b"), Err("expected a".to_string()));
+}
+
+#[test]
+fn test_output_polygons_oop() {
+    use outputs::polygons::oop::*;
+
+    assert_eq!(demo(), 1475);
+    assert_eq!(sides(&Polygon::Triangle { a: 1, b: 1, c: 1 }), 3);
+}
+
+#[test]
+fn test_output_polygons_fp() {
+    use outputs::polygons::fp::*;
+
+    assert_eq!(demo(), 1475);
+    assert_eq!(Square::SIDES, 4);
+    assert_eq!(<Triangle as Polygon>::NAME, "polygon");
+}
+
+#[test]
+fn test_output_figures_oop() {
+    use outputs::figures::oop::*;
+
+    assert_eq!(demo(), "square with area 9; RECTANGLE is 2x5; Square { side: 3 }");
+    assert_eq!(format!("{}", Figure::Rectangle { width: 4, height: 1 }), "4x1");
+}
+
+#[test]
+fn test_output_units_oop() {
+    use outputs::units::oop::*;
+
+    assert_eq!(demo(), "2000mm in m, 250cm rounds to 250");
+    assert_eq!(millimetres(&metres_from_millimetres(4200)), 4000);
+}
+
+#[test]
+fn test_output_bundles_oop() {
+    use outputs::bundles::oop::*;
+
+    assert_eq!(demo(), 661);
+    assert_eq!(fold::<String, _>(&Bundle::Two { first: 1, second: 2 }, String::new(), |acc, x| acc + &x.to_string()), "12");
+}
+
+#[test]
+fn test_output_bundles_fp() {
+    use outputs::bundles::fp::*;
+
+    assert_eq!(demo(), 661);
+    assert_eq!(Two { first: 1, second: 2 }.map(|x| x * 3).total(), 9);
+}
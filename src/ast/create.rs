@@ -2,8 +2,10 @@ use syn::*;
 use syn::punctuated::Punctuated;
 use syn::__private::Span;
 use syn::token::{Comma, Colon};
+use quote::ToTokens;
 
-use crate::context::delta::{GetDeltaType, RefType, get_ident_from_path};
+use crate::context::delta::{GetDeltaType, Pointer, RefType, get_ident_from_path};
+use crate::context::errors::{ErrorCode, RfoodError, RfoodResult};
 use crate::context::gamma::Gamma;
 
 pub fn create_enum(name: &Ident, variants: Vec<syn::Variant>, generics: &syn::Generics, vis: Visibility) -> ItemEnum {
@@ -43,70 +45,71 @@ pub fn create_trait(name: &Ident, items: &Vec<TraitItem>, vis: Visibility) -> It
     }
 }
 
-pub fn create_enum_variant(name: &Ident, mut fields: syn::Fields) -> syn::Variant {
+/// Remove pub and dyn from a field of a generator so it can be used in an enum variant
+fn create_enum_variant_field(field: &syn::Field) -> syn::Field {
     // Remove pub from fields
-    if let syn::Fields::Named(enum_fields) = &fields {
-    
-        let new_named = &syn::punctuated::Punctuated::from_iter(
-            // Remove pub from fields
-            enum_fields.named.iter().map(|field| {
-                match field.vis {
-                    syn::Visibility::Public(_) => {
-                        syn::Field{
-                            vis: syn::Visibility::Inherited,
-                            ..field.clone()
-                        }
-                    }
-                    _ => field.clone(),
-                }
-            // Remove dyn from fields
-            }).map(|mut field| {
-                // Match on any box type
-                match &mut field {
-                    syn::Field{ty: syn::Type::Path(syn::TypePath{path: syn::Path{ref mut segments, ..}, ..}), ..} => { 
-                        if let Some(segment) = segments.iter_mut().next() {
-                            if segment.ident.to_string() == "Box" {
-                                if let syn::PathArguments::AngleBracketed(angle_bracket_args) = &segment.arguments {
-                                    let new_args: syn::punctuated::Punctuated<syn::GenericArgument, syn::token::Comma> = syn::punctuated::Punctuated::from_iter(angle_bracket_args.args.iter().map(|arg| {
-                                        match arg {
-                                            syn::GenericArgument::Type(
-                                                syn::Type::TraitObject(
-                                                    syn::TypeTraitObject{
-                                                        dyn_token: Some(_),
-                                                        bounds, 
-                                                        ..
-                                                    }
-                                                )
-                                            ) => {
-                                                if let Some(syn::TypeParamBound::Trait(
-                                                    syn::TraitBound{path, ..}
-                                                )) = bounds.iter().next() {
-                                                    return syn::GenericArgument::Type(
-                                                        syn::Type::Path(
-                                                            syn::TypePath{
-                                                                qself: None,
-                                                                path: path.clone(),
-                                                            }
-                                                        )
-                                                    )
-                                                }
-                                                panic!("Unsupported type trait bound");
-                                            },
-                                            _ => panic!("Unsupported type trait bound"),
-                                        }
-                                    }));
-                                    segment.arguments = syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments{args: new_args, ..*angle_bracket_args});
+    let mut field = match field.vis {
+        syn::Visibility::Public(_) => {
+            syn::Field{
+                vis: syn::Visibility::Inherited,
+                ..field.clone()
+            }
+        }
+        _ => field.clone(),
+    };
+
+    // Remove dyn from fields
+    // Match on any box type
+    if let syn::Field{ty: syn::Type::Path(syn::TypePath{path: syn::Path{ref mut segments, ..}, ..}), ..} = &mut field { 
+        if let Some(segment) = segments.iter_mut().next() {
+            if segment.ident.to_string() == "Box" {
+                if let syn::PathArguments::AngleBracketed(angle_bracket_args) = &segment.arguments {
+                    let new_args: syn::punctuated::Punctuated<syn::GenericArgument, syn::token::Comma> = syn::punctuated::Punctuated::from_iter(angle_bracket_args.args.iter().map(|arg| {
+                        match arg {
+                            syn::GenericArgument::Type(
+                                syn::Type::TraitObject(
+                                    syn::TypeTraitObject{
+                                        dyn_token: Some(_),
+                                        bounds, 
+                                        ..
+                                    }
+                                )
+                            ) => {
+                                if let Some(syn::TypeParamBound::Trait(
+                                    syn::TraitBound{path, ..}
+                                )) = bounds.iter().next() {
+                                    return syn::GenericArgument::Type(
+                                        syn::Type::Path(
+                                            syn::TypePath{
+                                                qself: None,
+                                                path: path.clone(),
+                                            }
+                                        )
+                                    )
                                 }
-                            }
+                                arg.clone()
+                            },
+                            // Any other box is left as it is, e.g. `Box<i32>`
+                            _ => arg.clone(),
                         }
-                        field
-                    }
-                    _ => field
+                    }));
+                    segment.arguments = syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments{args: new_args, ..*angle_bracket_args});
                 }
-            })
-        );
+            }
+        }
+    }
+
+    field
+}
 
-        fields = syn::Fields::Named(syn::FieldsNamed{named: new_named.clone(), ..*enum_fields});
+pub fn create_enum_variant(name: &Ident, mut fields: syn::Fields) -> syn::Variant {
+    match &mut fields {
+        syn::Fields::Named(syn::FieldsNamed{named: enum_fields, ..}) | syn::Fields::Unnamed(syn::FieldsUnnamed{unnamed: enum_fields, ..}) => {
+            *enum_fields = syn::punctuated::Punctuated::from_iter(
+                enum_fields.iter().map(create_enum_variant_field)
+            );
+        },
+        syn::Fields::Unit => (),
     }
 
     syn::Variant{
@@ -118,12 +121,17 @@ pub fn create_enum_variant(name: &Ident, mut fields: syn::Fields) -> syn::Varian
 }
 
 pub fn create_dyn_box_of_path(path: &Path) -> Path {
+    create_dyn_pointer_of_path(path, Pointer::Box)
+}
+
+/// Create a pointer to a trait object of a path, e.g. Shape -> Rc<dyn Shape>
+pub fn create_dyn_pointer_of_path(path: &Path, pointer: Pointer) -> Path {
     Path{
         leading_colon: None,
         segments: Punctuated::from_iter(
             vec![
                 PathSegment{
-                    ident: Ident::new("Box", Span::call_site()),
+                    ident: pointer.ident(),
                     arguments: syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments{
                         args: Punctuated::from_iter(
                             vec![
@@ -158,51 +166,122 @@ pub fn create_dyn_box_of_path(path: &Path) -> Path {
     }
 }
 
-pub fn create_dyn_box_of_type(type_: &Type) -> Type {
+/// Create the import of a pointer, e.g. `use std::rc::Rc;`
+pub fn create_pointer_import(pointer: Pointer) -> ItemUse {
+    match pointer {
+        Pointer::Box => parse_quote!(use std::boxed::Box;),
+        Pointer::Rc => parse_quote!(use std::rc::Rc;),
+        Pointer::Arc => parse_quote!(use std::sync::Arc;),
+    }
+}
+
+/// Create an import of the item at a path, e.g. `use super::shapes::Shape;`
+pub fn create_use_of_path(path: &Path) -> ItemUse {
+    parse_quote!(use #path;)
+}
+
+/// Create a trait object of a type, e.g. Shape -> dyn Shape
+pub fn create_dyn_of_type(type_: &Type) -> RfoodResult<Type> {
+    match type_ {
+        Type::Path(type_path) => Ok(create_dyn_of_path(&type_path.path)),
+        _ => Err(unsupported_trait_object_type(type_)),
+    }
+}
+
+/// Create a trait object of the trait at a path, e.g. Shape -> dyn Shape
+pub fn create_dyn_of_path(path: &Path) -> Type {
+    Type::TraitObject(TypeTraitObject{
+        dyn_token: Some(syn::Token![dyn](Span::call_site())),
+        bounds: Punctuated::from_iter(
+            vec![
+                TypeParamBound::Trait(
+                    TraitBound{
+                        lifetimes: None,
+                        path: path.clone(),
+                        modifier: TraitBoundModifier::None,
+                        paren_token: None,
+                    }
+                )
+            ]
+        ),
+    })
+}
+
+fn unsupported_trait_object_type(type_: &Type) -> RfoodError {
+    RfoodError::new(
+        ErrorCode::UnsupportedType,
+        format!("Only a path to a trait can be made a trait object, found `{}`", type_.to_token_stream()),
+    ).with_span(type_)
+}
+
+/// Create a path to an associated function of a trait object, e.g. <dyn Shape>::new
+pub fn create_dyn_trait_fn_path(trait_path: &Path, fn_ident: &Ident) -> ExprPath {
+    ExprPath {
+        attrs: Vec::new(),
+        qself: Some(QSelf {
+            lt_token: token::Lt::default(),
+            ty: Box::new(create_dyn_of_path(trait_path)),
+            position: 0,
+            as_token: None,
+            gt_token: token::Gt::default(),
+        }),
+        path: Path {
+            leading_colon: Some(token::Colon2::default()),
+            segments: Punctuated::from_iter(vec![
+                PathSegment{
+                    ident: fn_ident.clone(),
+                    arguments: PathArguments::None,
+                }
+            ]),
+        },
+    }
+}
+
+pub fn create_dyn_box_of_type(type_: &Type) -> RfoodResult<Type> {
+    create_dyn_pointer_of_type(type_, Pointer::Box)
+}
+
+pub fn create_dyn_pointer_of_type(type_: &Type, pointer: Pointer) -> RfoodResult<Type> {
     match type_ {
         Type::Path(type_path) => {
-            Type::Path(TypePath{
-                path: create_dyn_box_of_path(&type_path.path),
+            Ok(Type::Path(TypePath{
+                path: create_dyn_pointer_of_path(&type_path.path, pointer),
                 ..type_path.clone()
-            })
+            }))
         },
-        _ => panic!("Unsupported type"),
+        _ => Err(unsupported_trait_object_type(type_)),
     }
 }
 
-pub fn create_dyn_box_arg(fn_arg: &FnArg) -> FnArg {
+pub fn create_dyn_box_arg(fn_arg: &FnArg) -> RfoodResult<FnArg> {
     match fn_arg {
         FnArg::Typed(typed) => {
-            FnArg::Typed(PatType{
-                ty: Box::new(create_dyn_box_of_type(&*typed.ty)),
+            Ok(FnArg::Typed(PatType{
+                ty: Box::new(create_dyn_box_of_type(&*typed.ty)?),
                 ..typed.clone()
-            })
+            }))
         },
-        _ => panic!("Unsupported fn arg type for creating dyn box")
+        FnArg::Receiver(receiver) => Err(RfoodError::unsupported_syntax("A `self` argument cannot be boxed").with_span(receiver)),
     }
 }
 
 pub fn create_struct(ident: &Ident, trait_ident: &Ident, mut fields: Fields, vis: Visibility) -> ItemStruct {
     // TODO remove the mutability here?
-    let new_fields = match &mut fields {
+    match &mut fields {
         Fields::Named(FieldsNamed { named: fields, .. }) | Fields::Unnamed(FieldsUnnamed { unnamed: fields, .. }) => {
-            Fields::Named(FieldsNamed{
-               brace_token: token::Brace::default(),
-               named: Punctuated::from_iter(fields.iter_mut().map(|field| {
-                   // Set the field to public 
-                   field.vis = Visibility::Public(VisPublic{pub_token: token::Pub::default()});
-                   field.clone()
-               }))
-           })
+            for field in fields.iter_mut() {
+                // Set the field to public 
+                field.vis = Visibility::Public(VisPublic{pub_token: token::Pub::default()});
+            }
         },
-        Fields::Unit => fields
+        Fields::Unit => ()
     };
 
     ItemStruct {
         attrs: Vec::new(),
         vis,
         struct_token: token::Struct::default(),
-        fields: new_fields,
+        fields,
         ident: ident.clone(),
         generics: Generics::default(),
         semi_token: Some(token::Semi::default()),
@@ -265,7 +344,7 @@ pub fn create_match_statement(match_ident: &syn::Ident, arms: Vec<syn::Arm>) ->
                     qself: None,
                     path: match_ident.clone().into(),
                 }
-            ))),
+            ), false)),
             arms,
             brace_token: syn::token::Brace{span: syn::__private::Span::call_site()},
         },
@@ -384,6 +463,34 @@ pub fn create_match_arm(match_path: syn::Path, elems: Vec<syn::Ident>, body: syn
   } 
 }
 
+/// Create a match arm for a tuple variant, binding each positional field to an ident
+pub fn create_tuple_match_arm(match_path: syn::Path, elems: Vec<syn::Ident>, body: syn::Expr, mutable: bool) -> syn::Arm {
+  syn::Arm {
+    attrs: Vec::new() as Vec<syn::Attribute>,
+    pat: syn::Pat::TupleStruct(syn::PatTupleStruct{
+        attrs: Vec::new() as Vec<syn::Attribute>,
+        path: match_path,
+        pat: syn::PatTuple {
+            attrs: Vec::new() as Vec<syn::Attribute>,
+            paren_token: syn::token::Paren{span: syn::__private::Span::call_site()},
+            elems: Punctuated::from_iter(elems.iter().map(|item| {
+                Pat::Ident(syn::PatIdent{
+                    attrs: Vec::new() as Vec<syn::Attribute>,
+                    by_ref: None,
+                    mutability: if mutable {Some(token::Mut::default())} else {None},
+                    ident: item.clone(),
+                    subpat: None,
+                })
+            })),
+        },
+    }),
+    guard: None,
+    fat_arrow_token: syn::token::FatArrow{spans: [syn::__private::Span::call_site(), syn::__private::Span::call_site()]},
+    body: Box::new(body),
+    comma: None,
+  } 
+}
+
 pub fn create_reference_of_type(type_: Type) -> Type {
     Type::Reference(
         TypeReference{
@@ -423,12 +530,13 @@ pub fn remove_reference_of_expr(expr: &Expr) -> Expr {
     }
 }
 
-pub fn create_reference_of_expr(expr: &Expr) -> Expr {
+/// Borrow an expression, `&expr` or `&mut expr`
+pub fn create_reference_of_expr(expr: &Expr, mutable: bool) -> Expr {
     Expr::Reference(
         ExprReference{
             attrs: Vec::new(),
             and_token: token::And { spans: [Span::call_site()] },
-            mutability: None,
+            mutability: if mutable { Some(token::Mut::default()) } else { None },
             raw: syn::reserved::Reserved::default(),
             expr: Box::new(expr.clone()),
         }
@@ -436,6 +544,11 @@ pub fn create_reference_of_expr(expr: &Expr) -> Expr {
 }
 
 pub fn create_box_of_expr(expr: &Expr) -> Expr {
+    create_wrapper_of_expr(&Ident::new("Box", Span::call_site()), expr)
+}
+
+/// Create a new value of a wrapper type around an expression, e.g. `Rc::new(expr)`
+pub fn create_wrapper_of_expr(wrapper: &Ident, expr: &Expr) -> Expr {
     Expr::Call(
         ExprCall {
             attrs: Vec::new(),
@@ -453,7 +566,7 @@ pub fn create_box_of_expr(expr: &Expr) -> Expr {
                             [
                                 syn::PathSegment{
                                     arguments: syn::PathArguments::None,
-                                    ident: Ident::new("Box", Span::call_site()),
+                                    ident: wrapper.clone(),
                                 },
                                 syn::PathSegment{
                                     arguments: syn::PathArguments::None,
@@ -468,28 +581,39 @@ pub fn create_box_of_expr(expr: &Expr) -> Expr {
     )
 }
 
+/// The argument which passes a generic parameter on, e.g. `T` for `T: Clone` and `'a` for `'a`
 pub fn generic_argumnet_from_generic_parameter(generic_param: GenericParam) -> GenericArgument {
-    if let GenericParam::Type(type_param) = generic_param {
-        return GenericArgument::Type(Type::Path(TypePath{
+    match generic_param {
+        GenericParam::Type(type_param) => GenericArgument::Type(Type::Path(TypePath{
+            qself: None,
+            path: type_param.ident.into(),
+        })),
+        GenericParam::Lifetime(lifetime_def) => GenericArgument::Lifetime(lifetime_def.lifetime),
+        GenericParam::Const(const_param) => GenericArgument::Const(Expr::Path(ExprPath {
+            attrs: Vec::new(),
             qself: None,
-            path: type_param.ident.clone().into(),
-        }));
+            path: const_param.ident.into(),
+        })),
     }
-    panic!("Unsupported generic parameter, currently only type parameters are supported");
 }
 
-pub fn generic_parameter_from_generic_argument(generic_argument: &GenericArgument) -> GenericParam {
-    if let GenericArgument::Type(Type::Path(TypePath {path, ..})) = generic_argument {
-        return GenericParam::Type(TypeParam{
-            attrs: Vec::new(),
-            ident: path.segments.last().unwrap().ident.clone(),
-            colon_token: None,
-            bounds: Punctuated::new(),
-            eq_token: None,
-            default: None,
-        });
+pub fn generic_parameter_from_generic_argument(generic_argument: &GenericArgument) -> RfoodResult<GenericParam> {
+    match generic_argument {
+        GenericArgument::Type(Type::Path(TypePath { qself: None, path })) if path.get_ident().is_some() => {
+            Ok(GenericParam::Type(TypeParam{
+                attrs: Vec::new(),
+                ident: path.segments[0].ident.clone(),
+                colon_token: None,
+                bounds: Punctuated::new(),
+                eq_token: None,
+                default: None,
+            }))
+        }
+        _ => Err(RfoodError::new(
+            ErrorCode::UnsupportedType,
+            format!("Only type parameters are supported as generic parameters, found `{}`", generic_argument.to_token_stream()),
+        ).with_span(generic_argument)),
     }
-    panic!("Unsupported generic parameter, currently only type parameters are supported");
 }
 
 pub fn add_generics_to_path_segment(segmenet: PathSegment, generics: &syn::Generics) -> PathSegment {
@@ -541,7 +665,7 @@ pub fn create_consumer_signature_arg(enum_name: &Ident, enum_instance_name: &Ide
 }
 
 pub fn create_self_fn_arg(reference_type: RefType) -> FnArg {
-    if matches!(reference_type, RefType::Box(_)) {
+    if let Some(pointer) = reference_type.get_pointer() {
         FnArg::Typed(
             syn::PatType{
                 attrs: Vec::new(),
@@ -564,7 +688,7 @@ pub fn create_self_fn_arg(reference_type: RefType) -> FnArg {
                                 segments: syn::punctuated::Punctuated::from_iter(
                                     [
                                         syn::PathSegment{
-                                            ident: Ident::new("Box", Span::call_site()),
+                                            ident: pointer.ident(),
                                             arguments: PathArguments::AngleBracketed(AngleBracketedGenericArguments{
                                                 colon2_token: None,
                                                 lt_token: token::Lt::default(),
@@ -604,10 +728,13 @@ pub fn create_self_fn_arg(reference_type: RefType) -> FnArg {
             Receiver{
                 attrs: Vec::new(),
                 reference: match reference_type {
-                    RefType::Ref(_) => Some((token::And::default(), None)),
+                    RefType::Ref(_) | RefType::MutRef(_) => Some((token::And::default(), None)),
                     _ => None, 
                 },
-                mutability: None,
+                mutability: match reference_type {
+                    RefType::MutRef(_) => Some(token::Mut::default()),
+                    _ => None,
+                },
                 self_token: token::SelfValue::default(),
             }
         )
@@ -663,32 +790,60 @@ pub fn create_self_field_call(field_name: &Ident) -> Expr {
     create_field_call(&Ident::new("self", Span::call_site()), field_name)
 }
 
+/// Access a member of self, this can be a named field (self.foo) or a positional field (self.0)
+pub fn create_self_member_call(member: &Member) -> Expr {
+    match member {
+        Member::Named(field_name) => create_self_field_call(field_name),
+        Member::Unnamed(_) => Expr::Field(ExprField{
+            attrs: Vec::new() as Vec<syn::Attribute>,
+            base: Box::new(create_self_expr()),
+            member: member.clone(),
+            dot_token: token::Dot::default(),
+        }),
+    }
+}
+
 pub fn create_function_call(method: &Ident, args: Punctuated<Expr, Comma>) -> Expr {
-    let method_path = Expr::Path(ExprPath{attrs: Vec::new(), qself: None, path: Path{
-        leading_colon: None,
-        segments: Punctuated::from_iter(
-            vec![PathSegment{ident: method.clone(), arguments: PathArguments::None}]
-        ),
-    }});
+    create_path_call(&create_path_from_ident(method), args)
+}
 
+/// Create a call to the function at a path, e.g. super::shapes::area(shape)
+pub fn create_path_call(path: &Path, args: Punctuated<Expr, Comma>) -> Expr {
     Expr::Call(ExprCall{
         attrs: Vec::new(),
         paren_token: token::Paren { span: Span::call_site() },
-        func: Box::new(method_path),
+        func: Box::new(Expr::Path(create_expr_path_from_path(path.clone()))),
         args: args.clone(),
     })
 }
 
+/// Create a `todo!()` left in place of an expression which could not be transformed, the message
+/// holds the error and the original expression
+pub fn create_todo_expr(message: &str) -> Expr {
+    Expr::Macro(ExprMacro {
+        attrs: Vec::new(),
+        mac: Macro {
+            path: create_path_from_ident(&Ident::new("todo", Span::call_site())),
+            bang_token: token::Bang::default(),
+            delimiter: MacroDelimiter::Paren(token::Paren::default()),
+            tokens: LitStr::new(message, Span::call_site()).to_token_stream(),
+        },
+    })
+}
+
 pub fn create_self_method_call(method: &Ident, args: Punctuated<Expr, Comma>) -> Expr {
     create_method_call(method, &create_self_expr(), &args)
 }
 
-pub fn add_self_to_path(exp: &Expr) -> Expr {
+pub fn add_self_to_path(exp: &Expr) -> RfoodResult<Expr> {
     match exp {
         Expr::Path(expr_path) | Expr::Reference(ExprReference { expr: box Expr::Path(expr_path), .. }) => {
-            create_self_field_call(&get_ident_from_path(&expr_path.path))
+            Ok(create_self_field_call(&get_ident_from_path(&expr_path.path)))
         },
-        _ => panic!("Unsupported expression type for adding self, {:?}", exp),
+        _ => Err(RfoodError::unsupported_syntax(format!(
+            "Only a variable can become a field of self, found `{}`",
+            exp.to_token_stream(),
+        )).with_span(exp)),
     }
 }
 
@@ -748,6 +903,18 @@ pub fn create_expression_block(stmts: Vec<syn::Stmt>) -> Expr {
     })
 }
 
+pub fn create_pat_ident(ident: &Ident) -> Pat {
+    Pat::Ident(
+        PatIdent{
+            attrs: Vec::new(),
+            ident: ident.clone(),
+            mutability: None,
+            subpat: None,
+            by_ref : None,
+        }
+    )
+}
+
 pub fn create_let_stmt(name: &Ident, expr: &Expr, mutable: bool) -> Local {
     Local{
         attrs: Vec::new(),
@@ -1,2 +1,3 @@
 pub mod create;
 pub mod print;
+pub mod modules;
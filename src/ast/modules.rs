@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::*;
+
+use crate::context::errors::*;
+
+/// A source file of a crate
+#[derive(Debug, Clone)]
+pub struct ModuleFile {
+    /// The path of the file
+    pub path: PathBuf,
+    /// The module the file is loaded as, empty for the root file
+    pub module_path: Vec<Ident>,
+    /// The inner attributes of the file, these are kept out of the inlined module
+    pub attrs: Vec<Attribute>,
+}
+
+/// Check if the file owns its directory, i.e. modules declared in it are found next to it
+fn is_mod_rs_file(path: &Path) -> bool {
+    matches!(
+        path.file_name().and_then(|name| name.to_str()),
+        Some("lib.rs" | "main.rs" | "mod.rs")
+    )
+}
+
+/// Get the value of a `#[path = "..."]` attribute
+fn get_path_attribute(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match attr.parse_meta() {
+        Ok(Meta::NameValue(MetaNameValue { path, lit: Lit::Str(lit_str), .. })) if path.is_ident("path") => {
+            Some(lit_str.value())
+        }
+        _ => None,
+    })
+}
+
+fn parse_source_file(path: &Path) -> RfoodResult<syn::File> {
+    let src = fs::read_to_string(path).map_err(|error| {
+        RfoodError::new(ErrorCode::Io, format!("Unable to read module file {:?}: {}", path, error))
+    })?;
+    syn::parse_file(&src).map_err(|error| RfoodError::from(error).in_file(path))
+}
+
+/// Find the file of an out of line module
+///
+/// `file_dir` is the directory of the source file the module is declared in and `module_dir` is
+/// the directory its child modules are found in (including any inline modules the declaration is
+/// nested in).
+fn find_module_file(
+    item_mod: &ItemMod,
+    file_dir: &Path,
+    module_dir: &Path,
+    is_inline: bool,
+) -> RfoodResult<PathBuf> {
+    // Path attributes are relative to the file unless they are inside an inline module
+    if let Some(path) = get_path_attribute(&item_mod.attrs) {
+        return Ok(if is_inline { module_dir.join(path) } else { file_dir.join(path) });
+    }
+
+    let name = item_mod.ident.to_string();
+    let candidates = [
+        module_dir.join(format!("{}.rs", name)),
+        module_dir.join(&name).join("mod.rs"),
+    ];
+    candidates
+        .iter()
+        .find(|candidate| candidate.is_file())
+        .cloned()
+        .ok_or_else(|| {
+            RfoodError::not_found(&name, "file of module").with_span(&item_mod.ident)
+        })
+}
+
+/// Get the file a module is declared in, this is the file of its closest ancestor loaded from a
+/// file
+pub fn get_file_of_module<'a>(module_path: &[Ident], files: &'a [ModuleFile]) -> Option<&'a ModuleFile> {
+    files
+        .iter()
+        .filter(|file| module_path.starts_with(&file.module_path))
+        .max_by_key(|file| file.module_path.len())
+}
+
+/// Replace all the out of line modules in some items with inline modules containing the items
+/// of their file
+fn inline_modules(
+    items: &mut Vec<Item>,
+    module_path: &[Ident],
+    file_dir: &Path,
+    module_dir: &Path,
+    is_inline: bool,
+    files: &mut Vec<ModuleFile>,
+) -> RfoodResult<()> {
+    for item in items.iter_mut() {
+        if let Item::Mod(item_mod) = item {
+            let mut child_path = module_path.to_vec();
+            child_path.push(item_mod.ident.clone());
+
+            match &mut item_mod.content {
+                Some((_, child_items)) => {
+                    let child_dir = module_dir.join(item_mod.ident.to_string());
+                    inline_modules(child_items, &child_path, file_dir, &child_dir, true, files)?;
+                }
+                None => {
+                    let path = find_module_file(item_mod, file_dir, module_dir, is_inline)
+                        .map_err(|error| error.in_file(&get_file_of_module(module_path, files).unwrap().path))?;
+                    let mut child_items = load_module_file(&path, &child_path, files)?;
+                    let child_dir = if is_mod_rs_file(&path) || get_path_attribute(&item_mod.attrs).is_some() {
+                        path.parent().unwrap().to_path_buf()
+                    } else {
+                        path.with_extension("")
+                    };
+                    inline_modules(&mut child_items, &child_path, path.parent().unwrap(), &child_dir, false, files)?;
+
+                    item_mod.content = Some((token::Brace::default(), child_items));
+                    item_mod.semi = None;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a module file and record it, returning its items
+fn load_module_file(path: &Path, module_path: &[Ident], files: &mut Vec<ModuleFile>) -> RfoodResult<Vec<Item>> {
+    let syntax = parse_source_file(path)?;
+    files.push(ModuleFile {
+        path: path.to_path_buf(),
+        module_path: module_path.to_vec(),
+        attrs: syntax.attrs,
+    });
+    Ok(syntax.items)
+}
+
+/// Load a crate from its root file (e.g. `lib.rs` or `main.rs`) following all the `mod foo;`
+/// declarations. The modules of each file are inlined so the crate can be transformed as a single
+/// file, along with this the files that make up the crate are returned so the crate can be split
+/// back up with `split_crate`.
+pub fn load_crate(root_path: &Path) -> RfoodResult<(syn::File, Vec<ModuleFile>)> {
+    let mut syntax = parse_source_file(root_path)?;
+    let mut files = vec![ModuleFile {
+        path: root_path.to_path_buf(),
+        module_path: Vec::new(),
+        attrs: syntax.attrs.clone(),
+    }];
+
+    let root_dir = root_path.parent().unwrap();
+    inline_modules(&mut syntax.items, &Vec::new(), root_dir, root_dir, false, &mut files)?;
+
+    Ok((syntax, files))
+}
+
+/// Move the items of the modules loaded from files out of a list of items, putting back the out
+/// of line module declarations
+fn outline_modules(
+    items: &mut Vec<Item>,
+    module_path: &[Ident],
+    files: &[ModuleFile],
+    file_items: &mut HashMap<Vec<Ident>, Vec<Item>>,
+) {
+    for item in items.iter_mut() {
+        if let Item::Mod(item_mod) = item {
+            if let Some((_, child_items)) = &mut item_mod.content {
+                let mut child_path = module_path.to_vec();
+                child_path.push(item_mod.ident.clone());
+                outline_modules(child_items, &child_path, files, file_items);
+
+                if files.iter().any(|file| file.module_path == child_path) {
+                    file_items.insert(child_path, std::mem::take(child_items));
+                    item_mod.content = None;
+                    item_mod.semi = Some(token::Semi::default());
+                }
+            }
+        }
+    }
+}
+
+/// Split a crate loaded with `load_crate` back into its files
+pub fn split_crate(syntax: &syn::File, files: &[ModuleFile]) -> Vec<(ModuleFile, syn::File)> {
+    let mut root_items = syntax.items.clone();
+    let mut file_items = HashMap::new();
+    outline_modules(&mut root_items, &Vec::new(), files, &mut file_items);
+    file_items.insert(Vec::new(), root_items);
+
+    files
+        .iter()
+        .map(|file| {
+            (
+                file.clone(),
+                syn::File {
+                    shebang: if file.module_path.is_empty() { syntax.shebang.clone() } else { None },
+                    attrs: file.attrs.clone(),
+                    items: file_items.remove(&file.module_path).unwrap_or_default(),
+                },
+            )
+        })
+        .collect()
+}
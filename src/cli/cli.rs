@@ -1,7 +1,8 @@
-use clap::Parser;
+use clap::{Args, Parser};
 use std::path::PathBuf;
 
-use crate::transform::transformer::TransformType;
+use crate::context::delta::Pointer;
+use crate::transform::transformer::{TransformMode, TransformType};
 
 #[derive(Parser)]
 #[clap(name = "git")]
@@ -9,6 +10,38 @@ use crate::transform::transformer::TransformType;
 pub struct Cli {
     #[clap(subcommand)]
     pub command: Commands,
+    /// Log the steps of the transformation to stderr, -vv and -vvv log more detail
+    #[clap(short, long, parse(from_occurrences), global = true)]
+    pub verbose: u64,
+    /// Log everything as JSON lines to stderr, including the trait/enum/consumer being transformed
+    #[clap(long, global = true)]
+    pub trace_json: bool,
+}
+
+/// The options of the commands which plan a transformation
+#[derive(Args)]
+pub struct PlanArgs {
+    #[clap(arg_enum, default_value_t = TransformType::OOPToFP)]
+    pub transform_type: TransformType,
+    /// Only transform these traits/enums, e.g. shapes::Shape
+    #[clap(long)]
+    pub include: Vec<String>,
+    /// Do not transform these traits/enums
+    #[clap(long)]
+    pub exclude: Vec<String>,
+    /// Transform these traits to enums, used with --to-oop instead of the transform type
+    #[clap(long, conflicts_with_all = &["include", "exclude"])]
+    pub to_fp: Vec<String>,
+    /// Transform these enums to traits, used with --to-fp instead of the transform type
+    #[clap(long, conflicts_with_all = &["include", "exclude"])]
+    pub to_oop: Vec<String>,
+    /// The pointer the trait objects created when transforming enums to traits are held in
+    #[clap(long, arg_enum, default_value_t = Pointer::Box)]
+    pub pointer: Pointer,
+    /// A file of impls with the signatures of methods of types which are not in the code, e.g.
+    /// those of a dependency, in addition to the standard library
+    #[clap(long, parse(from_os_str))]
+    pub signatures: Vec<PathBuf>,
 }
 
 #[derive(Parser)]
@@ -22,9 +55,38 @@ pub enum Commands {
         /// The path of the file to transform
         #[clap(required = true, parse(from_os_str))]
         path: PathBuf,
-        #[clap(arg_enum, default_value_t = TransformType::OOPToFP)]
-        transform_type: TransformType,
+        #[clap(flatten)]
+        plan: PlanArgs,
         #[clap(default_value = "outputs/output.rs", parse(from_os_str))]
         output_path: PathBuf,
+        /// Fail on the first error (strict) or leave what cannot be transformed and report it
+        /// (best-effort)
+        #[clap(long, arg_enum, default_value_t = TransformMode::Strict)]
+        mode: TransformMode,
+    },
+    /// Check which traits/enums can be transformed, without transforming them
+    #[clap(arg_required_else_help = true)]
+    Check{
+        /// The path of the file to check
+        #[clap(required = true, parse(from_os_str))]
+        path: PathBuf,
+        #[clap(flatten)]
+        plan: PlanArgs,
+    },
+    /// Transform a crate, following the modules declared in other files
+    #[clap(arg_required_else_help = true)]
+    TransformCrate{
+        /// The root file of the crate, e.g. src/lib.rs
+        #[clap(required = true, parse(from_os_str))]
+        path: PathBuf,
+        #[clap(flatten)]
+        plan: PlanArgs,
+        /// The directory to write the transformed files to
+        #[clap(default_value = "outputs/crate", parse(from_os_str))]
+        output_path: PathBuf,
+        /// Fail on the first error (strict) or leave what cannot be transformed and report it
+        /// (best-effort)
+        #[clap(long, arg_enum, default_value_t = TransformMode::Strict)]
+        mode: TransformMode,
     },
 }
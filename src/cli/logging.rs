@@ -0,0 +1,30 @@
+use std::io::IsTerminal;
+use tracing::Level;
+use tracing_subscriber::fmt;
+
+/// Set up the logging of the transformation, logs are written to stderr so the output of a
+/// command can still be piped
+///
+/// Only warnings are logged by default, each `-v` logs more: the steps of the transformation, then
+/// each call and method call transformed, then every expression. `--trace-json` logs everything
+/// as JSON lines along with the spans of the trait/enum/consumer being transformed.
+pub fn init_logging(verbose: u64, trace_json: bool) {
+    let level = match verbose {
+        _ if trace_json => Level::TRACE,
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let subscriber = fmt().with_max_level(level).with_writer(std::io::stderr);
+
+    if trace_json {
+        subscriber.json().with_current_span(true).with_span_list(true).init();
+    } else {
+        subscriber
+            .with_target(false)
+            .without_time()
+            .with_ansi(std::io::stderr().is_terminal())
+            .init();
+    }
+}
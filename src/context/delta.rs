@@ -3,19 +3,142 @@
 // https://rustc-dev-guide.rust-lang.org/the-parser.html 
 
 use std::collections::HashMap;
+use std::rc::Rc;
 use syn::*;
+use syn::visit::{visit_pat_ident, Visit};
 use syn::__private::Span;
+use quote::ToTokens;
 use crate::context::*;
 use crate::ast::create::{remove_deference_of_expr, remove_reference_of_expr};
-use gamma::Gamma;
+use gamma::{Gamma, get_pattern_bindings};
 use errors::*;
+use inference::{InferredTypes, TypeInference};
 
+/// The smart pointers the trait objects created from FP to OOP can be held in
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pointer {
+    #[default]
+    Box,
+    Rc,
+    Arc,
+}
+
+impl Pointer {
+    pub fn ident(&self) -> Ident {
+        let name = match self {
+            Pointer::Box => "Box",
+            Pointer::Rc => "Rc",
+            Pointer::Arc => "Arc",
+        };
+        Ident::new(name, Span::call_site())
+    }
+
+    /// This pointer around the given pointers, e.g. `Rc` around `None` is `Rc<T>`
+    pub fn wrap(&self, inner: RefType) -> RefType {
+        match self {
+            Pointer::Box => RefType::Box(Box::new(inner)),
+            Pointer::Rc => RefType::Rc(Box::new(inner)),
+            Pointer::Arc => RefType::Arc(Box::new(inner)),
+        }
+    }
+}
+
+/// The boxes, smart pointers, cells and references around a type, from the outside in, e.g.
+/// `&Box<T>` is `Ref(Box(None))` and `Rc<RefCell<T>>` is `Rc(RefCell(None))`
 #[derive(Debug, Clone, PartialEq)]
 pub enum RefType {
     Box(Box<RefType>),
+    Rc(Box<RefType>),
+    Arc(Box<RefType>),
+    RefCell(Box<RefType>),
     Ref(Box<RefType>),
+    MutRef(Box<RefType>),
     None,
 }
+impl RefType {
+    /// The wrapper type with the given name around the given pointers, `None` if the name is not
+    /// a wrapper, e.g. `Rc` around `None` is `Rc<T>`
+    pub fn from_wrapper(ident: &Ident, inner: RefType) -> Option<Self> {
+        let inner = Box::new(inner);
+        match ident.to_string().as_str() {
+            "Box" => Some(RefType::Box(inner)),
+            "Rc" => Some(RefType::Rc(inner)),
+            "Arc" => Some(RefType::Arc(inner)),
+            "RefCell" => Some(RefType::RefCell(inner)),
+            _ => None,
+        }
+    }
+
+    /// The name of the outer wrapper type, e.g. `Rc` for `Rc<RefCell<T>>`
+    pub fn get_wrapper_ident(&self) -> Option<Ident> {
+        let name = match self {
+            RefType::Box(_) => "Box",
+            RefType::Rc(_) => "Rc",
+            RefType::Arc(_) => "Arc",
+            RefType::RefCell(_) => "RefCell",
+            _ => return None,
+        };
+        Some(Ident::new(name, Span::call_site()))
+    }
+
+    /// The outer smart pointer, e.g. `Rc` for `Rc<RefCell<T>>`
+    pub fn get_pointer(&self) -> Option<Pointer> {
+        match self {
+            RefType::Box(_) => Some(Pointer::Box),
+            RefType::Rc(_) => Some(Pointer::Rc),
+            RefType::Arc(_) => Some(Pointer::Arc),
+            _ => None,
+        }
+    }
+
+    /// A shared (or mutable) borrow of a value, `&T` (or `&mut T`)
+    pub fn borrow(mutable: bool) -> Self {
+        if mutable {
+            RefType::MutRef(Box::new(RefType::None))
+        } else {
+            RefType::Ref(Box::new(RefType::None))
+        }
+    }
+
+    /// Either a shared or a mutable borrow
+    pub fn is_ref(&self) -> bool {
+        matches!(self, RefType::Ref(_) | RefType::MutRef(_))
+    }
+
+    /// The pointers inside the outer pointer, `None` if this is not a pointer
+    pub fn inner(&self) -> Option<&RefType> {
+        match self {
+            RefType::Box(box inner)
+            | RefType::Rc(box inner)
+            | RefType::Arc(box inner)
+            | RefType::RefCell(box inner)
+            | RefType::Ref(box inner)
+            | RefType::MutRef(box inner) => Some(inner),
+            RefType::None => None,
+        }
+    }
+
+    /// The number of pointers which have to be dereferenced to reach the given inner pointers,
+    /// `None` if they are not inside of these pointers
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use rfood::context::delta::GetRefType;
+    ///
+    /// let ref_type = parse_str::<Type>("&Box<&mut T>").unwrap().get_ref_type();
+    /// let inner = parse_str::<Type>("&mut T").unwrap().get_ref_type();
+    /// assert_eq!(ref_type.get_depth_of(&inner), Some(2));
+    /// assert_eq!(inner.get_depth_of(&ref_type), None);
+    /// ```
+    pub fn get_depth_of(&self, inner: &RefType) -> Option<usize> {
+        if self == inner {
+            return Some(0);
+        }
+        self.inner().and_then(|ref_type| ref_type.get_depth_of(inner)).map(|depth| depth + 1)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum EType {
@@ -25,30 +148,272 @@ pub enum EType {
     Any,
 }
 
+/// The structure of a type once any boxes and references around it are removed
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    /// A named type, with the modules before its name and its generic arguments, e.g.
+    /// `shapes::Shape` or `Vec<Shape>`
+    Path(Vec<Ident>, Vec<DeltaType>),
+    /// A trait object, e.g. the `dyn Shape` of `Box<dyn Shape>`
+    TraitObject(Vec<Ident>, Vec<DeltaType>),
+    Tuple(Vec<DeltaType>),
+    /// An array with its length, or a slice if there is no length
+    Array(Box<DeltaType>, Option<Box<Expr>>),
+    /// A function pointer (named `fn`) or `Fn`/`FnMut`/`FnOnce` trait object, with the types of
+    /// its arguments and its return type
+    Fn(Vec<DeltaType>, Box<DeltaType>),
+    /// A type rfood does not look inside of, e.g. `impl Iterator<Item = i32>` or `_`
+    Other(Box<Type>),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DeltaType {
+    /// The last segment of the path of a named type, `_` for types without a name (e.g. tuples)
     pub name: Ident,
     pub ref_type: RefType,
+    pub kind: TypeKind,
 }
 impl DeltaType {
     pub fn new(name: &str, ref_type: RefType) -> Self {
+        DeltaType::from_ident(&Ident::new(name, Span::call_site()), ref_type)
+    }
+
+    /// Create a named type without generic arguments
+    pub fn from_ident(ident: &Ident, ref_type: RefType) -> Self {
+        DeltaType {
+            name: ident.clone(),
+            ref_type,
+            kind: TypeKind::Path(Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Create a type without a name
+    pub fn unnamed(kind: TypeKind) -> Self {
+        DeltaType {
+            name: Ident::new("_", Span::call_site()),
+            ref_type: RefType::None,
+            kind,
+        }
+    }
+
+    pub fn tuple(elems: Vec<DeltaType>) -> Self {
+        DeltaType::unnamed(TypeKind::Tuple(elems))
+    }
+
+    pub fn unit() -> Self {
+        DeltaType::tuple(Vec::new())
+    }
+
+    /// A type which is not known, written as `_`
+    pub fn unknown() -> Self {
+        DeltaType::unnamed(TypeKind::Other(Box::new(parse_quote!(_))))
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(&self.kind, TypeKind::Other(box Type::Infer(_)))
+    }
+
+    /// Check if the type is a primitive integer or float, ignoring any references
+    pub fn is_number(&self) -> bool {
+        matches!(&self.kind, TypeKind::Path(modules, args) if modules.is_empty() && args.is_empty())
+            && (inference::is_integer_name(&self.name) || inference::is_float_name(&self.name))
+    }
+
+    /// The same type behind different boxes and references
+    pub fn with_ref_type(&self, ref_type: RefType) -> Self {
         DeltaType {
-            name: Ident::new(name, Span::call_site()),
-            ref_type
+            ref_type,
+            ..self.clone()
+        }
+    }
+
+    /// Get the types directly inside this one, e.g. the generic arguments or elements of a tuple
+    pub fn inner_types(&self) -> Vec<&DeltaType> {
+        match &self.kind {
+            TypeKind::Path(_, args) | TypeKind::TraitObject(_, args) | TypeKind::Tuple(args) => args.iter().collect(),
+            TypeKind::Array(elem, _) => vec![&**elem],
+            TypeKind::Fn(inputs, output) => inputs.iter().chain(std::iter::once(&**output)).collect(),
+            TypeKind::Other(_) => Vec::new(),
         }
     }
+
+    /// Check if this type, or any type inside of it, is a named type with the given name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use rfood::context::delta::GetDeltaType;
+    ///
+    /// let delta_type = parse_str::<Type>("Vec<(i32, Box<dyn Shape>)>").unwrap().get_delta_type();
+    /// assert!(delta_type.contains_name(&parse_str::<Ident>("Shape").unwrap()));
+    /// assert!(!delta_type.contains_name(&parse_str::<Ident>("Circle").unwrap()));
+    /// ```
+    pub fn contains_name(&self, name: &Ident) -> bool {
+        (matches!(self.kind, TypeKind::Path(..) | TypeKind::TraitObject(..)) && &self.name == name)
+            || self.inner_types().into_iter().any(|inner| inner.contains_name(name))
+    }
+
     pub fn is_equaivalent(&self, other: &Self, gamma: &Gamma) -> bool {
-        self == other || (self.ref_type == other.ref_type && gamma.is_subtype_of(&self.name, &other.name))
+        if self == other {
+            return true;
+        }
+        if self.ref_type != other.ref_type {
+            return false;
+        }
+        let inner_equivalent = |left: Vec<&DeltaType>, right: Vec<&DeltaType>| {
+            left.len() == right.len() && left.iter().zip(right).all(|(left, right)| {
+                left.is_unknown() || right.is_unknown() || left.is_equaivalent(right, gamma)
+            })
+        };
+        match (&self.kind, &other.kind) {
+            // The modules a type is referred to through do not change the type
+            (TypeKind::Path(_, args) | TypeKind::TraitObject(_, args), TypeKind::Path(_, other_args) | TypeKind::TraitObject(_, other_args)) => {
+                (self.name == other.name || gamma.is_subtype_of(&self.name, &other.name))
+                    && inner_equivalent(args.iter().collect(), other_args.iter().collect())
+            }
+            (TypeKind::Tuple(_), TypeKind::Tuple(_)) | (TypeKind::Array(..), TypeKind::Array(..)) | (TypeKind::Fn(..), TypeKind::Fn(..)) => {
+                self.name == other.name && inner_equivalent(self.inner_types(), other.inner_types())
+            }
+            _ => false,
+        }
+    }
+
+    /// Replace each of the types directly inside this one
+    pub fn map_inner_types(&self, f: &dyn Fn(&DeltaType) -> DeltaType) -> DeltaType {
+        let map_all = |types: &Vec<DeltaType>| -> Vec<DeltaType> { types.iter().map(f).collect() };
+        let kind = match &self.kind {
+            TypeKind::Path(modules, args) => TypeKind::Path(modules.clone(), map_all(args)),
+            TypeKind::TraitObject(modules, args) => TypeKind::TraitObject(modules.clone(), map_all(args)),
+            TypeKind::Tuple(elems) => TypeKind::Tuple(map_all(elems)),
+            TypeKind::Array(elem, len) => TypeKind::Array(Box::new(f(elem)), len.clone()),
+            TypeKind::Fn(inputs, output) => TypeKind::Fn(map_all(inputs), Box::new(f(output))),
+            TypeKind::Other(type_) => TypeKind::Other(type_.clone()),
+        };
+        DeltaType {
+            kind,
+            ..self.clone()
+        }
+    }
+
+    /// Replace `Self`, including inside of generic arguments, with the self type, fails if the
+    /// type uses `Self` outside of an impl or trait
+    pub fn replace_self(&self, self_type: Option<Ident>) -> RfoodResult<DeltaType> {
+        match self_type {
+            Some(self_type) => Ok(self.with_self_type(&self_type)),
+            None if self.contains_name(&Ident::new("Self", Span::call_site())) => Err(RfoodError::new(
+                ErrorCode::UnsupportedSyntax,
+                "`Self` can only be used in the methods of impls and traits",
+            )),
+            None => Ok(self.clone()),
+        }
     }
 
-    pub fn replace_self(&self, self_type: Option<Ident>) -> DeltaType {
-        if self.name == "Self" {
+    fn with_self_type(&self, self_type: &Ident) -> DeltaType {
+        let delta_type = self.map_inner_types(&|inner| inner.with_self_type(self_type));
+        if self.name == "Self" && matches!(self.kind, TypeKind::Path(..)) {
             return DeltaType {
-                name: self_type.unwrap_or_else(|| panic!("Self type not provided")),
-                ..self.clone()
+                name: self_type.clone(),
+                ..delta_type
+            }
+        }
+        delta_type
+    }
+
+    /// Turn the type back into syntax
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use quote::ToTokens;
+    /// use rfood::context::delta::GetDeltaType;
+    ///
+    /// for written in ["&Box<dyn shapes::Shape>", "Vec<(i32, [Shape; 2])>", "Box<dyn Fn(&Shape) -> bool>", "fn(i32)"] {
+    ///     let delta_type = parse_str::<Type>(written).unwrap().get_delta_type();
+    ///     assert_eq!(
+    ///         delta_type.to_type().to_token_stream().to_string(),
+    ///         parse_str::<Type>(written).unwrap().to_token_stream().to_string(),
+    ///     );
+    /// }
+    /// ```
+    pub fn to_type(&self) -> Type {
+        fn to_path(modules: &[Ident], name: &Ident, args: &[DeltaType]) -> Path {
+            let segments = modules.iter().map(|module| PathSegment::from(module.clone()));
+            let mut path = Path {
+                leading_colon: None,
+                segments: segments.chain(std::iter::once(PathSegment::from(name.clone()))).collect(),
+            };
+            if !args.is_empty() {
+                let args = args.iter().map(|arg| arg.to_type());
+                path.segments.last_mut().unwrap().arguments = PathArguments::AngleBracketed(parse_quote!(<#(#args),*>));
+            }
+            path
+        }
+
+        let type_: Type = match &self.kind {
+            TypeKind::Path(modules, args) => Type::Path(TypePath { qself: None, path: to_path(modules, &self.name, args) }),
+            TypeKind::TraitObject(modules, args) => {
+                let path = to_path(modules, &self.name, args);
+                parse_quote!(dyn #path)
+            }
+            TypeKind::Tuple(elems) if elems.len() == 1 => {
+                let elem = elems[0].to_type();
+                parse_quote!((#elem,))
+            }
+            TypeKind::Tuple(elems) => {
+                let elems = elems.iter().map(|elem| elem.to_type());
+                parse_quote!((#(#elems),*))
+            }
+            TypeKind::Array(elem, Some(len)) => {
+                let elem = elem.to_type();
+                parse_quote!([#elem; #len])
+            }
+            TypeKind::Array(elem, None) => {
+                let elem = elem.to_type();
+                parse_quote!([#elem])
+            }
+            TypeKind::Fn(inputs, output) => {
+                let inputs = inputs.iter().map(|input| input.to_type());
+                let output = match &output.kind {
+                    TypeKind::Tuple(elems) if elems.is_empty() => ReturnType::Default,
+                    _ => ReturnType::Type(Default::default(), Box::new(output.to_type())),
+                };
+                let name = &self.name;
+                if name == "fn" {
+                    parse_quote!(fn(#(#inputs),*) #output)
+                } else {
+                    parse_quote!(dyn #name(#(#inputs),*) #output)
+                }
+            }
+            TypeKind::Other(type_) => *type_.clone(),
+        };
+
+        fn wrap(type_: Type, ref_type: &RefType) -> Type {
+            match ref_type {
+                RefType::Box(box inner) | RefType::Rc(box inner) | RefType::Arc(box inner) | RefType::RefCell(box inner) => {
+                    let wrapper = ref_type.get_wrapper_ident().unwrap();
+                    let inner = wrap(type_, inner);
+                    parse_quote!(#wrapper<#inner>)
+                }
+                RefType::Ref(box inner) => {
+                    let inner = wrap(type_, inner);
+                    parse_quote!(&#inner)
+                }
+                RefType::MutRef(box inner) => {
+                    let inner = wrap(type_, inner);
+                    parse_quote!(&mut #inner)
+                }
+                RefType::None => type_,
             }
         }
-        return self.clone();
+        wrap(type_, &self.ref_type)
+    }
+}
+
+impl std::fmt::Display for DeltaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_type().to_token_stream())
     }
 }
 
@@ -56,45 +421,50 @@ impl DeltaType {
 pub struct Delta {
     pub self_ty: Option<Ident>,
     pub types: HashMap<Ident, DeltaType>,
+    /// The module the code being transformed is in, empty for the root of the file
+    pub module_path: Vec<Ident>,
+    /// The types inferred for the body of the function being transformed
+    pub inferred: Rc<InferredTypes>,
 }
 
 pub fn get_struct_attrs(struct_: &ItemStruct) -> Vec<Ident> {
     Vec::from_iter(fields_to_delta_types(&struct_.fields, false).iter().map(|(field, _)| field.clone()))
 }
 
-pub fn get_type_from_box(segment: &PathSegment) -> std::result::Result<Ident, NotABoxType> {
+/// Check if a type holds a single value behind a pointer or in a cell, e.g. `Box`, `Rc` or `RefCell`
+pub fn is_wrapper_ident(ident: &Ident) -> bool {
+    RefType::from_wrapper(ident, RefType::None).is_some()
+}
+
+/// Get the type held by a wrapper type, e.g. the `Shape` of `Rc<dyn Shape>`
+pub fn get_type_from_box(segment: &PathSegment) -> RfoodResult<Ident> {
     // If the thing has args
     if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, ..}) = &segment.arguments {
-        let arg = args.first().unwrap();
-
         // If it is a dyn thing
-        if let GenericArgument::Type(Type::TraitObject(TypeTraitObject { bounds, .. })) = arg {
-            let bound = bounds.first().unwrap();
-            if let TypeParamBound::Trait(TraitBound { path, .. }) = bound {
-                return Ok(get_ident_from_path(&path));
+        if let Some(GenericArgument::Type(Type::TraitObject(TypeTraitObject { bounds, .. }))) = args.first() {
+            if let Some(TypeParamBound::Trait(TraitBound { path, .. })) = bounds.first() {
+                return Ok(get_ident_from_path(path));
             }
         }
 
         // If not a dyn thing
-        if let GenericArgument::Type(Type::Path(type_path)) = arg{
+        if let Some(GenericArgument::Type(Type::Path(type_path))) = args.first() {
             return Ok(get_ident_from_path(&type_path.path));
         }
     }
 
-    Err(NotABoxType{segment: segment.clone()})
+    Err(RfoodError::invalid_type(format!("Failed to get type from box `{}`", segment.ident)).with_span(segment))
 }
 
 pub trait GetOptionalDeltaTypeFn {
-    fn get_delta_type(&self, self_type: Option<Ident>) -> Option<DeltaType>;
+    fn get_delta_type(&self, self_type: Option<Ident>) -> RfoodResult<Option<DeltaType>>;
 }
 
 impl GetOptionalDeltaTypeFn for ReturnType {
-    fn get_delta_type(&self, self_type: Option<Ident>) -> Option<DeltaType> {
+    fn get_delta_type(&self, self_type: Option<Ident>) -> RfoodResult<Option<DeltaType>> {
         match self {
-            ReturnType::Default => None,
-            ReturnType::Type(_, ty) => {
-                return Some(ty.get_delta_type().replace_self(self_type));
-            }
+            ReturnType::Default => Ok(None),
+            ReturnType::Type(_, ty) => ty.get_delta_type().replace_self(self_type).map(Some).map_err(|error| error.with_span(ty)),
         }
     }
 }
@@ -106,40 +476,101 @@ pub trait GetDeltaType {
 impl GetDeltaType for Type {
     fn get_delta_type(&self) -> DeltaType {
         match self {
-            Type::Path(type_path) => DeltaType{name: get_ident_from_path(&type_path.path), ref_type: self.get_ref_type()},
-            Type::Reference(TypeReference { elem, .. }) => {
-                elem.get_delta_type()
+            Type::Path(TypePath { qself: None, path }) => path.get_delta_type(),
+            Type::Reference(TypeReference { elem, mutability, .. }) => {
+                let delta_type = elem.get_delta_type();
+                let inner = Box::new(delta_type.ref_type.clone());
+                delta_type.with_ref_type(if mutability.is_some() { RefType::MutRef(inner) } else { RefType::Ref(inner) })
+            }
+            Type::TraitObject(TypeTraitObject { bounds, .. }) => match bounds.iter().find_map(|bound| match bound {
+                TypeParamBound::Trait(TraitBound { path, .. }) => Some(path),
+                _ => None,
+            }) {
+                Some(path) => match path.get_delta_type() {
+                    DeltaType { kind: TypeKind::Path(modules, args), name, ref_type } => DeltaType {
+                        name,
+                        ref_type,
+                        kind: TypeKind::TraitObject(modules, args),
+                    },
+                    delta_type => delta_type,
+                },
+                None => DeltaType::unnamed(TypeKind::Other(Box::new(self.clone()))),
+            }
+            Type::Tuple(TypeTuple { elems, .. }) => {
+                DeltaType::tuple(elems.iter().map(|elem| elem.get_delta_type()).collect())
+            }
+            Type::Array(TypeArray { elem, len, .. }) => {
+                DeltaType::unnamed(TypeKind::Array(Box::new(elem.get_delta_type()), Some(Box::new(len.clone()))))
             }
-            _ => panic!("Other types not supported, {:?}", self)
+            Type::Slice(TypeSlice { elem, .. }) => {
+                DeltaType::unnamed(TypeKind::Array(Box::new(elem.get_delta_type()), None))
+            }
+            Type::BareFn(TypeBareFn { inputs, output, .. }) => DeltaType {
+                name: Ident::new("fn", Span::call_site()),
+                ref_type: RefType::None,
+                kind: get_fn_type_kind(inputs.iter().map(|input| &input.ty), output),
+            },
+            Type::Paren(TypeParen { elem, .. }) | Type::Group(TypeGroup { elem, .. }) => elem.get_delta_type(),
+            _ => DeltaType::unnamed(TypeKind::Other(Box::new(self.clone()))),
         }
     }
 }
 
 impl GetDeltaType for Path {
     fn get_delta_type(&self) -> DeltaType {
-        let segment = self.segments.first().unwrap();
-    
-        if segment.ident == "Box" {
-            return DeltaType{name: get_type_from_box(segment).unwrap(), ref_type: RefType::Box(Box::new(RefType::None))};
-        }
-    
-        return DeltaType{name: segment.ident.clone(), ref_type: RefType::None};
+        let segment = self.segments.last().unwrap();
+        let modules = self.segments.iter().take(self.segments.len() - 1).map(|segment| segment.ident.clone()).collect();
+
+        let kind = match &segment.arguments {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => {
+                let args: Vec<DeltaType> = args.iter().filter_map(|arg| match arg {
+                    GenericArgument::Type(type_) => Some(type_.get_delta_type()),
+                    _ => None,
+                }).collect();
+
+                if args.len() == 1 {
+                    if let Some(ref_type) = RefType::from_wrapper(&segment.ident, args[0].ref_type.clone()) {
+                        return args[0].with_ref_type(ref_type);
+                    }
+                }
+                TypeKind::Path(modules, args)
+            }
+            PathArguments::Parenthesized(ParenthesizedGenericArguments { inputs, output, .. }) => {
+                get_fn_type_kind(inputs.iter(), output)
+            }
+            PathArguments::None => TypeKind::Path(modules, Vec::new()),
+        };
+
+        DeltaType { name: segment.ident.clone(), ref_type: RefType::None, kind }
     }
 }
 
+fn get_fn_type_kind<'a>(inputs: impl Iterator<Item = &'a Type>, output: &ReturnType) -> TypeKind {
+    TypeKind::Fn(
+        inputs.map(|input| input.get_delta_type()).collect(),
+        Box::new(match output {
+            ReturnType::Default => DeltaType::unit(),
+            ReturnType::Type(_, type_) => type_.get_delta_type(),
+        }),
+    )
+}
+
 pub trait GetDeltaTypeFn {
-    fn get_delta_type(&self, self_type: Option<Ident>) -> DeltaType;
+    fn get_delta_type(&self, self_type: Option<Ident>) -> RfoodResult<DeltaType>;
 }
 
 impl GetDeltaTypeFn for FnArg {
-    fn get_delta_type(&self, self_type: Option<Ident>) -> DeltaType {
-        DeltaType {
-            name: match self {
-                FnArg::Typed(typed) => typed.ty.get_delta_type().name,
-                FnArg::Receiver(_) => self_type.clone().unwrap(),
-            },
-            ref_type: self.get_ref_type(),
-        }.replace_self(self_type)
+    fn get_delta_type(&self, self_type: Option<Ident>) -> RfoodResult<DeltaType> {
+        match (self, &self_type) {
+            (FnArg::Typed(typed), _) => typed.ty.get_delta_type(),
+            (FnArg::Receiver(_), Some(self_type)) => DeltaType::from_ident(self_type, self.get_ref_type()),
+            (FnArg::Receiver(receiver), None) => {
+                return Err(RfoodError::new(
+                    ErrorCode::UnsupportedSyntax,
+                    "A `self` argument can only be used in the methods of impls and traits",
+                ).with_span(receiver))
+            }
+        }.replace_self(self_type).map_err(|error| error.with_span(self))
     }
 }
 
@@ -149,19 +580,7 @@ pub trait GetRefType {
 
 impl GetRefType for Type {
     fn get_ref_type(&self) -> RefType {
-        // TODO add in reference types
-        return match &self {
-            Type::Path(type_path) => {
-                let segment = type_path.path.segments.first().unwrap();
-                if segment.ident == "Box" {
-                    RefType::Box(Box::new(RefType::None))
-                } else {
-                    RefType::None
-                }
-            },
-            Type::Reference(_) => RefType::Ref(Box::new(RefType::None)),
-            _ => RefType::None,
-        }
+        self.get_delta_type().ref_type
     }
 }
 
@@ -171,9 +590,9 @@ impl GetRefType for FnArg {
             FnArg::Typed(type_pat) => {
                 type_pat.ty.get_ref_type()
             }
-            FnArg::Receiver(Receiver { reference, .. }) => {
+            FnArg::Receiver(Receiver { reference, mutability, .. }) => {
                 match reference {
-                    Some(_) => RefType::Ref(Box::new(RefType::None)),
+                    Some(_) => RefType::borrow(mutability.is_some()),
                     None => RefType::None,
                 }
             },
@@ -212,7 +631,7 @@ impl GetRefType for FnArg {
 /// let result = new_box_call_expr(&expr);
 /// assert!(matches!(result, std::result::Result::Err(_)));
 /// ```
-pub fn new_box_call_expr(expr: &Expr) -> std::result::Result<Expr, InvalidType> {
+pub fn new_box_call_expr(expr: &Expr) -> RfoodResult<Expr> {
     if let Expr::Call(ExprCall{
         func,
         args,
@@ -231,7 +650,34 @@ pub fn new_box_call_expr(expr: &Expr) -> std::result::Result<Expr, InvalidType>
         }
     }
 
-    return Err(InvalidType{message: "Could not find type".to_string()});
+    return Err(RfoodError::invalid_type("Expression is not a Box::new call").with_span(expr));
+}
+
+/// Get the wrapper type and the expr inside a new wrapper expression, e.g. `Rc::new(expr)`
+///
+/// # Examples
+///
+/// ```
+/// use syn::*;
+/// use quote::ToTokens;
+/// use rfood::context::delta::new_wrapper_call_expr;
+///
+/// let expr = parse_str::<Expr>("std::rc::Rc::new(RefCell::new(1))").unwrap();
+/// let (wrapper, inner) = new_wrapper_call_expr(&expr).unwrap();
+/// assert_eq!(wrapper.to_string(), "Rc");
+/// assert_eq!(inner.to_token_stream().to_string(), "RefCell :: new (1)");
+/// assert!(new_wrapper_call_expr(&parse_str::<Expr>("Vec::new()").unwrap()).is_none());
+/// ```
+pub fn new_wrapper_call_expr(expr: &Expr) -> Option<(Ident, Expr)> {
+    if let Expr::Call(ExprCall { func: box Expr::Path(ExprPath { path, .. }), args, .. }) = expr {
+        let idents: Vec<&Ident> = path.segments.iter().map(|segment| &segment.ident).collect();
+        if let [.., wrapper, new] = idents.as_slice() {
+            if *new == "new" && is_wrapper_ident(wrapper) && args.len() == 1 {
+                return Some(((*wrapper).clone(), args.first().unwrap().clone()));
+            }
+        }
+    }
+    None
 }
 
 pub fn is_dyn_box_generator_return(signature: &Signature, gamma: &Gamma) -> bool {
@@ -241,7 +687,11 @@ pub fn is_dyn_box_generator_return(signature: &Signature, gamma: &Gamma) -> bool
         if let Type::Path(
             TypePath { path: Path{ segments, .. } , .. }
         ) = &**type_ {
-            if let Ok(ident) = get_type_from_box(segments.first().unwrap()) {
+            let segment = segments.last().unwrap();
+            if segment.ident != "Box" {
+                return false;
+            }
+            if let Ok(ident) = get_type_from_box(segment) {
                 return gamma.is_trait(&ident);
             }
         }
@@ -249,70 +699,143 @@ pub fn is_dyn_box_generator_return(signature: &Signature, gamma: &Gamma) -> bool
     return false;
 }
 
+/// Get the name of the type a path refers to, the module the type is in is ignored
+///
+/// # Examples
+///
+/// ```
+/// use syn::*;
+/// use rfood::context::delta::get_ident_from_path;
+///
+/// let path = parse_str::<Path>("Box<dyn super::shapes::Shape>").unwrap();
+/// assert_eq!(get_ident_from_path(&path).to_string(), "Shape");
+///
+/// // A wrapper of a type which is not named is the wrapper itself
+/// let path = parse_str::<Path>("Rc<[i32]>").unwrap();
+/// assert_eq!(get_ident_from_path(&path).to_string(), "Rc");
+/// ```
 pub fn get_ident_from_path(Path { segments, .. }: &Path) -> Ident {
-    let segment = segments.first().unwrap();
+    let segment = segments.last().unwrap();
 
-    if segment.ident == "Box" {
-        return get_type_from_box(segment).unwrap();
+    if is_wrapper_ident(&segment.ident) {
+        return get_type_from_box(segment).unwrap_or_else(|_| segment.ident.clone());
     }
 
     return segment.ident.clone();
 }
 
-pub fn get_type_from_function_arg(arg: &FnArg, self_type: Option<&Ident>) -> DeltaType{
-    // TODO add in reference types
-    let delta_type = match &arg {
-        FnArg::Typed(PatType{ty: box Type::Path(type_path), ..}) => {
-            type_path.path.get_delta_type()
-        }
-        FnArg::Typed(PatType{ty: box Type::Reference(TypeReference{ elem: box Type::Path(type_path), .. }), ..}) => {
-            type_path.path.get_delta_type()
-        }
-        FnArg::Receiver(_) => {
-            if self_type.is_none() {
-                panic!("Receiver not supported when self type is None");
-            }
-            DeltaType{name: self_type.unwrap().clone(), ref_type: RefType::None}
-        }
-        _ => panic!("Could not get type from function argument, {:?}", arg),
-    };
+pub fn get_type_from_function_arg(arg: &FnArg, self_type: Option<&Ident>) -> RfoodResult<DeltaType> {
+    arg.get_delta_type(self_type.cloned())
+}
 
-    delta_type.replace_self(self_type.cloned())
+/// Get the closure trait bound of each type parameter which has one, e.g. `Fn(i32) -> i32` of
+/// `F: Fn(i32) -> i32`, from the parameters and the where clause
+pub fn get_fn_bounds(generics: &Generics) -> Vec<(Ident, Path)> {
+    let where_bounds = generics.where_clause.iter().flat_map(|where_clause| {
+        where_clause.predicates.iter().filter_map(|predicate| match predicate {
+            WherePredicate::Type(PredicateType { bounded_ty: Type::Path(TypePath { qself: None, path }), bounds, .. }) => {
+                path.get_ident().map(|ident| (ident.clone(), bounds))
+            }
+            _ => None,
+        })
+    });
+    generics
+        .type_params()
+        .map(|type_param| (type_param.ident.clone(), &type_param.bounds))
+        .chain(where_bounds)
+        .filter_map(|(ident, bounds)| {
+            bounds.iter().find_map(|bound| match bound {
+                TypeParamBound::Trait(TraitBound { path, .. })
+                    if matches!(path.segments.last().unwrap().arguments, PathArguments::Parenthesized(_)) =>
+                {
+                    Some((ident.clone(), path.clone()))
+                }
+                _ => None,
+            })
+        })
+        .collect()
 }
 
+pub fn get_attribute_ident_from_function_arg(arg: &FnArg) -> RfoodResult<Ident> {
+    match arg {
+        FnArg::Typed(PatType { pat: box Pat::Ident(pat_ident), .. }) => Ok(pat_ident.ident.clone()),
+        FnArg::Receiver(_) => Ok(Ident::new("self", Span::call_site())),
+        FnArg::Typed(PatType { pat, .. }) => Err(RfoodError::unsupported_syntax(format!(
+            "Only arguments bound to a name are supported, found `{}`",
+            pat.to_token_stream(),
+        )).with_span(pat)),
+    }
+}
 
-pub fn get_attribute_ident_from_function_arg(arg: &FnArg) -> Ident {
-    if let FnArg::Typed(PatType { pat, .. }) = arg {
-        if let Pat::Ident(pat_ident) = &**pat {
-            return pat_ident.ident.clone();
+/// Get the variables bound by a pattern
+pub fn get_pattern_idents(pat: &Pat) -> Vec<Ident> {
+    struct FindPatIdents(Vec<Ident>);
+    impl<'ast> Visit<'ast> for FindPatIdents {
+        fn visit_pat_ident(&mut self, pat_ident: &'ast PatIdent) {
+            self.0.push(pat_ident.ident.clone());
+            visit_pat_ident(self, pat_ident);
         }
     }
+    let mut find_pat_idents = FindPatIdents(Vec::new());
+    find_pat_idents.visit_pat(pat);
+    find_pat_idents.0
+}
+
+/// Get the name of the variable used to bind the positional field at index of a tuple
+/// struct/variant
+///
+/// # Examples
+///
+/// ```
+/// use rfood::context::delta::get_positional_field_ident;
+///
+/// assert_eq!(get_positional_field_ident(1).to_string(), "field_1");
+/// ```
+pub fn get_positional_field_ident(index: usize) -> Ident {
+    Ident::new(&format!("field_{}", index), Span::call_site())
+}
 
-    if let FnArg::Receiver(_) = arg {
-        return Ident::new("self", Span::call_site());
+/// Get the name of the variable a struct member is bound to when destructured
+pub fn get_member_ident(member: &Member) -> Ident {
+    match member {
+        Member::Named(ident) => ident.clone(),
+        Member::Unnamed(Index { index, .. }) => get_positional_field_ident(*index as usize),
     }
+}
 
-    // TODO This will panic for all self types
-    panic!("Could not get attribute name from function argument");
+/// Get the members of some fields, in order
+pub fn get_field_members(fields: &Fields) -> Vec<Member> {
+    fields.iter().enumerate().map(|(index, field)| {
+        match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        }
+    }).collect()
 }
 
 fn fields_to_delta_types(fields: &Fields, is_ref: bool) -> Vec<(Ident, DeltaType)> {
-    match fields {
-        Fields::Named(fields_named) => {
-            fields_named.named.iter().map(|field| {
-                let dt = field.ty.get_delta_type();
-                (field.ident.clone().unwrap(), DeltaType{
-                    name: dt.name,
-                    ref_type: if is_ref && dt.ref_type == RefType::None {
-                        RefType::Ref(Box::new(dt.ref_type))
-                    } else {
-                        dt.ref_type 
-                    }})
-            }).into_iter().collect()
-        },
-        Fields::Unit => vec![],
-        _ => panic!("Unanmed structs/enums are not supported")
-    }
+    fields.iter().zip(get_field_members(fields)).map(|(field, member)| {
+        let dt = field.ty.get_delta_type();
+        (get_member_ident(&member), if is_ref && dt.ref_type == RefType::None {
+            dt.with_ref_type(RefType::Ref(Box::new(RefType::None)))
+        } else {
+            dt
+        })
+    }).collect()
+}
+
+/// Get the type of a member of some fields (named or positional)
+pub fn get_type_of_fields_member(fields: &Fields, member: &Member, is_ref: bool) -> Option<DeltaType> {
+    let member_ident = get_member_ident(member);
+    fields_to_delta_types(fields, is_ref)
+        .into_iter()
+        .find_map(|(ident, delta_type)| if ident == member_ident { Some(delta_type) } else { None })
+}
+
+/// Get the types of fields in order, this is used to type the arguments of a tuple struct/variant
+/// instantiation
+pub fn get_field_types(fields: &Fields) -> Vec<DeltaType> {
+    fields_to_delta_types(fields, false).into_iter().map(|(_, delta_type)| delta_type).collect()
 }
 
 pub fn get_return_type_from_signature(signature: &Signature) -> EType {
@@ -324,14 +847,16 @@ pub fn get_return_type_from_signature(signature: &Signature) -> EType {
     }
 }
 
-pub fn get_function_call_name(expr_call: &ExprCall) -> Ident{
-    get_expr_call_name(&*expr_call.func)
+/// Get the name of the function being called, `None` if the function is not a path (e.g.
+/// `(self.f)(x)` or `handlers[0](x)`)
+pub fn get_function_call_name(expr_call: &ExprCall) -> Option<Ident> {
+    get_expr_call_name(&expr_call.func)
 }
 
-pub fn get_expr_call_name(expr: &Expr) -> Ident {
+pub fn get_expr_call_name(expr: &Expr) -> Option<Ident> {
     match &expr {
-        Expr::Path(ExprPath{ path, .. }) => get_path_call_name(path),
-        _ => panic!("Could not get function name from call")
+        Expr::Path(ExprPath{ path, .. }) => Some(get_path_call_name(path)),
+        _ => None,
     }
 }
 
@@ -348,26 +873,51 @@ pub fn get_path_call_name(path: &Path) -> Ident {
 impl Delta {
     pub fn new() -> Self {
         return Delta {
-            self_ty: None, types: HashMap::new(),
+            self_ty: None, types: HashMap::new(), module_path: Vec::new(), inferred: Rc::default(),
         }
     }
 
-    pub fn get_type(&self, ident: &Ident) -> DeltaType {
-        self.types.get(&ident).unwrap_or_else(|| panic!("Type {:?} not in delta. {:?}", ident, self.types)).clone()
+    /// Create the delta for the items of a submodule. Constants of the parent are kept so they
+    /// can still be typed when referred to through `super::`
+    pub fn for_module(&self, module_ident: &Ident) -> Self {
+        let mut module_path = self.module_path.clone();
+        module_path.push(module_ident.clone());
+        Delta {
+            module_path,
+            ..self.clone()
+        }
+    }
+
+    /// Get the type instantiated by a struct or tuple struct/variant path, this is the first item
+    /// after any modules in the path. E.g. `Shape` for `shapes::Shape::Circle`
+    pub(crate) fn get_type_of_instantiation_path(&self, path: &Path, gamma: &Gamma) -> Ident {
+        match gamma.resolve_path(&self.module_path, path) {
+            Some((_, item_idents)) => item_idents.first().unwrap().clone(),
+            None => get_path_call_name(path),
+        }
+    }
+
+    /// Check if a variable is declared, its type may not be known
+    pub fn is_variable(&self, ident: &Ident) -> bool {
+        self.types.contains_key(ident) || self.inferred.has_variable(ident)
+    }
+
+    pub fn get_type(&self, ident: &Ident) -> RfoodResult<DeltaType> {
+        self.types.get(&ident).cloned().ok_or_else(|| RfoodError::unknown_variable(ident))
     }
 
-    pub fn get_type_of_member(self, member: &Member) -> DeltaType {
+    pub fn get_type_of_member(self, member: &Member) -> RfoodResult<DeltaType> {
         match member {
             Member::Named(member_named) => {
                 self.get_type(member_named)
             },
-            _ => panic!("Unanmed structs/enums are not supported")
+            _ => Err(RfoodError::unsupported_syntax("Unnamed members of structs/enums are not supported").with_span(member))
         }
     }
 
     pub fn collect_for_struct(&mut self, struct_: &ItemStruct, struct_ref_type: RefType) {
         let mut field_type = fields_to_delta_types(&struct_.fields, false);
-        if matches!(struct_ref_type, RefType::Ref(_)) {
+        if struct_ref_type.is_ref() {
             field_type.iter_mut().for_each(|(_, delta_type)| {
                 delta_type.ref_type = RefType::Ref(Box::new(RefType::None));
             });
@@ -383,170 +933,154 @@ impl Delta {
         );
     }
     
-    pub fn collect_new_for_destructor_impl(&mut self, new_sig: &Signature, generator: &ItemStruct) {
-        self.collect_for_sig(&new_sig, None);
+    pub fn collect_new_for_destructor_impl(&mut self, new_sig: &Signature, generator: &ItemStruct) -> RfoodResult<()> {
+        self.collect_for_sig(&new_sig, None)?;
         // TODO Catch any overwritting and rename as required
         self.collect_for_struct(&generator, RefType::Ref(Box::new(RefType::None)));
+        Ok(())
     }
 
-    pub fn collect_old_for_destructor_impl(&mut self, old_sig: &Signature, generator: &ItemStruct) {
+    pub fn collect_old_for_destructor_impl(&mut self, old_sig: &Signature, generator: &ItemStruct) -> RfoodResult<()> {
         self.self_ty = Some(generator.ident.clone());
-        self.collect_for_sig(old_sig, Some(&generator.ident));
+        self.collect_for_sig(old_sig, Some(&generator.ident))
     }
 
-    pub fn collect_for_sig(&mut self, signature: &Signature, self_type: Option<&Ident>) {
-        let types: HashMap<Ident, DeltaType> = signature.inputs.iter().map(|arg| {
-            (get_attribute_ident_from_function_arg(arg), get_type_from_function_arg(arg, self_type))
-        }).into_iter().collect();
+    pub fn collect_for_sig(&mut self, signature: &Signature, self_type: Option<&Ident>) -> RfoodResult<()> {
+        let types: HashMap<Ident, DeltaType> = signature.inputs.iter().filter(|arg| {
+            // Arguments bound by other patterns (e.g. tuples) are collected from the inferred types
+            matches!(arg, FnArg::Receiver(_) | FnArg::Typed(PatType{pat: box Pat::Ident(_), ..}))
+        }).map(|arg| {
+            Ok((get_attribute_ident_from_function_arg(arg)?, get_type_from_function_arg(arg, self_type)?))
+        }).collect::<RfoodResult<_>>()?;
         self.types.extend(types);
+
+        // An argument of a type parameter bound by `Fn` is a closure of the type of the bound
+        for (ident, bound) in get_fn_bounds(&signature.generics) {
+            for delta_type in self.types.values_mut() {
+                if delta_type.name == ident && matches!(&delta_type.kind, TypeKind::Path(modules, args) if modules.is_empty() && args.is_empty()) {
+                    *delta_type = bound.get_delta_type().with_ref_type(delta_type.ref_type.clone());
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Collect delta info from 
-    pub fn collect_for_arm(&mut self, arm: &Arm, gamma: &Gamma) {
+    pub fn collect_for_arm(&mut self, arm: &Arm, matched: &Expr, gamma: &Gamma) -> RfoodResult<()> {
         if let Pat::Struct(PatStruct{
             path,
-            fields,
+            ..
+        }) | Pat::TupleStruct(PatTupleStruct{
+            path,
             ..
         }) = &arm.pat {
             // Get the type of the thing being matched
             let enum_name = get_path_call_name(&path);
-            let variant = gamma.get_constructor(&enum_name);
+            if !gamma.is_constructor_path(path) {
+                // Variants of enums which are not in gamma (e.g. `Some`) are typed by inference
+                self.collect_for_matched_pattern(&arm.pat, matched, gamma);
+                return Ok(());
+            }
+            let variant = gamma.get_constructor(&enum_name).map_err(|error| error.with_span(path))?;
 
-            // Get the type of the fields
-            self.collect_for_enum_variant(&variant.unwrap(), true);
+            // Get the type of the variables bound to each field, mutable bindings are copied out of
+            // the matched value so are not references
+            for (pat_ident, member) in get_pattern_bindings(&arm.pat) {
+                let is_ref = pat_ident.mutability.is_none();
+                if let Some(delta_type) = get_type_of_fields_member(&gamma.expand_fields_type_aliases(&variant.fields), &member, is_ref) {
+                    self.types.insert(pat_ident.ident, delta_type);
+                }
+            }
         }
+        Ok(())
     }
 
-    pub fn collect_for_local(&mut self, local: &Local, gamma: &Gamma) {
+    pub fn collect_for_local(&mut self, local: &Local, gamma: &Gamma) -> RfoodResult<()> {
         // If the type is specified, use that
         if let Local { pat: Pat::Type(PatType{pat, ty, ..}), .. } = local {
             if let Pat::Ident(PatIdent{ident, ..}) = &**pat {
-                self.types.insert(ident.clone(), ty.get_delta_type());
+                self.types.insert(ident.clone(), gamma.expand_type_aliases(ty).get_delta_type());
             }
         }
         
         // Otherwise infer the type
         else if let Local {pat: Pat::Ident(PatIdent{ident, ..}), init, ..} = local {
-            if init.is_none() {
-                panic!("A let expression must have a body or a type");
+            let (_, expr) = init.as_ref().ok_or_else(|| {
+                RfoodError::unsupported_syntax("A let statement must have a value or a type").with_span(local)
+            })?;
+            match self.get_type_of_expr(expr, gamma).or_else(|error| {
+                self.inferred.get_binding(ident, &self.module_path).ok_or(error)
+            }) {
+                Ok(delta_type) => {
+                    self.types.insert(ident.clone(), delta_type);
+                }
+                // A variable which cannot be typed is left out of delta
+                Err(RfoodError { code: ErrorCode::TypeInferenceFailed, .. }) => {
+                    self.types.remove(ident);
+                }
+                Err(error) => return Err(error),
             }
-            let expr = &init.as_ref().unwrap().1;
-            self.types.insert(ident.clone(), self.get_type_of_expr(expr, gamma).unwrap());
         }
+
+        // Variables bound by other patterns (e.g. tuples) can only be typed by inference
+        else {
+            self.collect_for_inferred_pattern(&local.pat);
+        }
+        Ok(())
     }
 
-    pub fn collect_for_const(&mut self, const_: &ItemConst) {
-        self.types.insert(const_.ident.clone(), const_.ty.get_delta_type());
+    /// Collect the types of the variables bound by matching a pattern against an expression which
+    /// has already been transformed, variables which cannot be typed this way use their inferred
+    /// types
+    pub fn collect_for_matched_pattern(&mut self, pat: &Pat, matched: &Expr, gamma: &Gamma) {
+        for (ident, delta_type) in TypeInference::new(self, gamma).get_pattern_types(pat, matched) {
+            match delta_type.or_else(|| self.inferred.get_binding(&ident, &self.module_path)) {
+                Some(delta_type) => self.types.insert(ident, delta_type),
+                None => self.types.remove(&ident),
+            };
+        }
     }
 
-    pub fn get_type_of_expr(&self, expr: &Expr, gamma: &Gamma) -> std::result::Result<DeltaType, TypeInferenceFailed> {
-        match expr {
-            // TODO Match self.thing here so we can do in any order
-            // TODO add handling reference
-            Expr::Unary(ExprUnary { expr, op, .. }) => {
-                let type_ = self.get_type_of_expr(expr, gamma);
-                match (op, &type_) {
-                    (UnOp::Deref(_), Ok(DeltaType{
-                        name,
-                        ref_type: RefType::Box(inner_ref_type) | RefType::Ref(inner_ref_type),
-                    })) => Ok(DeltaType{name: name.clone(), ref_type: *inner_ref_type.clone()}),
-                    _ => type_
-                }
-            },
-            Expr::Reference(ExprReference{ expr, .. }) => {
-                let inner_expr_type = self.get_type_of_expr(expr, gamma);
-                if inner_expr_type.is_err() {
-                    return inner_expr_type;
-                }
+    /// Collect the inferred types of the variables bound by a pattern, variables without a type
+    /// are removed so they do not refer to any variable they shadow
+    pub fn collect_for_inferred_pattern(&mut self, pat: &Pat) {
+        for ident in get_pattern_idents(pat) {
+            match self.inferred.get_binding(&ident, &self.module_path) {
+                Some(delta_type) => self.types.insert(ident, delta_type),
+                None => self.types.remove(&ident),
+            };
+        }
+    }
 
-                let inner_expr_type = inner_expr_type.unwrap();
-                Ok(DeltaType{
-                    name: inner_expr_type.clone().name,
-                    ref_type: RefType::Ref(Box::new(inner_expr_type.clone().ref_type))
-                })
-            },
-            Expr::Path(ExprPath { path, .. }) => {
-                Ok(self.get_type(&get_ident_from_path(path)))
-            },
-            Expr::Call(ExprCall {..}) if new_box_call_expr(expr).is_ok() => {
-                let inner_expr_type = self.get_type_of_expr(&new_box_call_expr(expr).unwrap(), gamma);
-                if inner_expr_type.is_err() {
-                    return inner_expr_type;
-                }
-                let inner_expr_type = inner_expr_type.unwrap();
-                Ok(DeltaType{name: inner_expr_type.name, ref_type: RefType::Box(Box::new(inner_expr_type.ref_type))})
-            },
-            // TODO this is wrong if the call is not being transformed, this should be checked for
-            // both the call and method call. Might be worth taking in a "transformed" boolean
-            Expr::Call(expr_call) => {
-                let func_name = get_function_call_name(&expr_call);
-                // NOTE I just changed this I think itll cause chaos
-                // let sig = gamma.get_transformed_consumer_signature(&func_name);
-                let sig = gamma.get_signature(&func_name).unwrap();
-
-                match get_return_type_from_signature(&sig) {
-                    EType::DeltaType(ty) => Ok(ty),
-                    _ => panic!("Function {:?} not found", sig)
-                }
-            }
-            Expr::Struct(ExprStruct {path, .. }) => Ok(DeltaType{
-                name: path.segments.first().unwrap().ident.clone(),
-                ref_type: RefType::None
-            }),
-            Expr::MethodCall(ExprMethodCall { receiver, method, ..}) => {
-                let receiver_type = self.get_type_of_expr(&receiver, gamma);
-                if receiver_type.is_err() {
-                    return receiver_type;
-                }
-                let receiver_type = receiver_type.unwrap();
+    /// Infer the types of the expressions and variables of a function body before it is
+    /// transformed, the arguments of the function must already be collected
+    pub fn collect_for_body(&mut self, signature: &Signature, block: &Block, self_type: Option<&Ident>, gamma: &Gamma) {
+        self.inferred = Rc::default();
+        self.inferred = Rc::new(TypeInference::new(self, gamma).infer_body(signature, block, self_type));
+    }
 
-                if method == "clone" {
-                    return Ok(receiver_type);
-                }
+    pub fn collect_for_const(&mut self, const_: &ItemConst) {
+        self.types.insert(const_.ident.clone(), const_.ty.get_delta_type());
+    }
 
-                let method_sig = gamma.get_destructor_signature(&receiver_type.name, &method).unwrap();
+    pub fn collect_for_static(&mut self, static_: &ItemStatic) {
+        self.types.insert(static_.ident.clone(), static_.ty.get_delta_type());
+    }
 
-                match get_return_type_from_signature(&method_sig) {
-                    EType::DeltaType(ty) => Ok(ty),
-                    _ => panic!("Method {:?} not found", method_sig)
-                }
-            },
-            Expr::Field(ExprField {base, member, .. }) => {
-                let base_type = self.get_type_of_expr(&base, gamma);
-                if base_type.is_err() {
-                    return base_type;
-                }
-                match member {
-                    Member::Named(member_ident) => Ok(gamma.get_type_of_field(&base_type.unwrap().name, &member_ident)),
-                    _ => panic!("Unanmed structs/enums are not supported")
-                }
-            },
-            Expr::Lit(ExprLit{lit, ..}) => {
-                match lit {
-                    Lit::Int(_) => Ok(DeltaType{name: Ident::new("i32", Span::call_site()), ref_type: RefType::None}),
-                    Lit::Float(_) => Ok(DeltaType{name: Ident::new("f32", Span::call_site()), ref_type: RefType::None}),
-                    Lit::Bool(_) => Ok(DeltaType{name: Ident::new("bool", Span::call_site()), ref_type: RefType::None}),
-                    Lit::Str(_) => Ok(DeltaType{name: Ident::new("str", Span::call_site()), ref_type: RefType::None}),
-                    _ => panic!("Unsupported literal {:?}", lit)
-                }
-            },
-            Expr::Binary(ExprBinary { left, right, op, .. }) => {
-                match op {
-                    BinOp::Eq(_) | BinOp::Ne(_) | BinOp::Lt(_) | BinOp::Le(_) | BinOp::Gt(_) | BinOp::Ge(_)  => Ok(DeltaType{name: Ident::new("bool", Span::call_site()), ref_type: RefType::None}),
-                    BinOp::Add(_) | BinOp::Div(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Rem(_) => {
-                        let left_ty = self.get_type_of_expr(left, gamma);
-                        if let Ok(left_ty) = left_ty {
-                            Ok(DeltaType{name: left_ty.name, ref_type: RefType::None})
-                        } else {
-                            left_ty
-                        }
-                    },
-                    BinOp::And(_) | BinOp::Or(_) => Ok(DeltaType{name: Ident::new("bool", Span::call_site()), ref_type: RefType::None}),
-                    _ => panic!("Unsupported op {:?}", op)
-                }
-            },
-            Expr::Paren(ExprParen { expr, .. }) => self.get_type_of_expr(expr, gamma),
-            _ => Err(TypeInferenceFailed{expr: expr.clone()}),
-        }
+    /// Get the type of an expression, see [`TypeInference`]
+    pub fn get_type_of_expr(&self, expr: &Expr, gamma: &Gamma) -> RfoodResult<DeltaType> {
+        TypeInference::new(self, gamma).get_type_of_expr(expr)
+    }
+
+    /// Get the types expected of the arguments of a call of the standard library, see
+    /// [`TypeInference::get_expected_arg_types`]
+    pub fn get_expected_arg_types(
+        &self,
+        expr: &Expr,
+        receiver_type: Option<&DeltaType>,
+        required_type: Option<&DeltaType>,
+        gamma: &Gamma,
+    ) -> Option<Vec<Option<DeltaType>>> {
+        TypeInference::new(self, gamma).get_expected_arg_types(expr, receiver_type, required_type)
     }
 }
@@ -1,43 +1,174 @@
 use syn::*;
+use syn::spanned::Spanned;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone)]
-pub struct NotABoxType {
-    pub segment: PathSegment,
+/// The kind of an error, each kind has a code which never changes so it can be searched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    Internal,
+    ParseFailed,
+    Io,
+    NotFound,
+    InvalidType,
+    TypeInferenceFailed,
+    UnknownVariable,
+    UnsupportedType,
+    UnsupportedSyntax,
 }
-impl fmt::Display for NotABoxType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Failed to get type from box: {:?}", self.segment)
+impl ErrorCode {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCode::Internal => "RF0000",
+            ErrorCode::ParseFailed => "RF0001",
+            ErrorCode::Io => "RF0002",
+            ErrorCode::NotFound => "RF0003",
+            ErrorCode::InvalidType => "RF0004",
+            ErrorCode::TypeInferenceFailed => "RF0005",
+            ErrorCode::UnknownVariable => "RF0006",
+            ErrorCode::UnsupportedType => "RF0007",
+            ErrorCode::UnsupportedSyntax => "RF0008",
+        }
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct NotFound {
-    pub item_name: String,
-    pub type_name: String,
-}
-impl fmt::Display for NotFound {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Failed to find {:?} of type {:?}", self.item_name, self.type_name)
+    /// Which restriction of the transformation was hit
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            ErrorCode::Internal => "rfood hit a case it does not handle, this is a bug in rfood",
+            ErrorCode::ParseFailed => "the input must be valid Rust syntax",
+            ErrorCode::Io => "a file could not be read or written",
+            ErrorCode::NotFound => "only functions, methods and types declared in the transformed code (or crate), common methods of the standard library and the method signatures given with --signatures are known to rfood",
+            ErrorCode::InvalidType => "the expression cannot be converted to the type required where it is used, only boxes and references are added or removed",
+            ErrorCode::TypeInferenceFailed => "the type of the expression must be known to transform it, it is inferred from the signatures, fields and expressions it is used with",
+            ErrorCode::UnknownVariable => "a variable which is not declared in the transformed code (e.g. a constant imported from another crate) can only be used where its type can be inferred",
+            ErrorCode::UnsupportedType => "only paths, references, boxes and trait objects are supported as types",
+            ErrorCode::UnsupportedSyntax => "this syntax is not supported by the transformation",
+        }
     }
 }
 
+/// Where in the source an error was found, lines start at 1 and columns at 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An error found transforming some code
 #[derive(Debug, Clone)]
-pub struct InvalidType {
+pub struct RfoodError {
+    pub code: ErrorCode,
     pub message: String,
+    pub location: Option<SourceLocation>,
+    /// The module the error was found in, used to find the file of the error in a crate
+    pub module_path: Option<Vec<Ident>>,
+}
+
+pub type RfoodResult<T> = std::result::Result<T, RfoodError>;
+
+impl RfoodError {
+    pub fn new<S: Into<String>>(code: ErrorCode, message: S) -> Self {
+        RfoodError {
+            code,
+            message: message.into(),
+            location: None,
+            module_path: None,
+        }
+    }
+
+    pub fn not_found(item_name: &str, type_name: &str) -> Self {
+        RfoodError::new(ErrorCode::NotFound, format!("Failed to find {} `{}`", type_name, item_name))
+    }
+
+    pub fn invalid_type<S: Into<String>>(message: S) -> Self {
+        RfoodError::new(ErrorCode::InvalidType, message)
+    }
+
+    pub fn type_inference_failed(expr: &Expr) -> Self {
+        RfoodError::new(ErrorCode::TypeInferenceFailed, "Type inference failed for expression").with_span(expr)
+    }
+
+    pub fn unknown_variable(ident: &Ident) -> Self {
+        RfoodError::new(ErrorCode::UnknownVariable, format!("Variable `{}` is not in scope", ident)).with_span(ident)
+    }
+
+    pub fn unsupported_syntax<S: Into<String>>(message: S) -> Self {
+        RfoodError::new(ErrorCode::UnsupportedSyntax, message)
+    }
+
+    /// Set the location of the error from a syntax node, unless the error already has one. Nodes
+    /// created by the transformation have no location so they are ignored
+    pub fn with_span<T: Spanned>(self, node: &T) -> Self {
+        self.at_span(node.span())
+    }
+
+    fn at_span(mut self, span: proc_macro2::Span) -> Self {
+        if self.location.is_none() {
+            let (start, end) = (span.start(), span.end());
+            if start != end {
+                self.location = Some(SourceLocation {
+                    file: None,
+                    line: start.line,
+                    column: start.column,
+                });
+            }
+        }
+        self
+    }
+
+    /// Set the module the error was found in, unless the error already has one
+    pub fn in_module(mut self, module_path: &[Ident]) -> Self {
+        if self.module_path.is_none() {
+            self.module_path = Some(module_path.to_vec());
+        }
+        self
+    }
+
+    /// Set the file the error was found in
+    pub fn in_file(mut self, path: &Path) -> Self {
+        if let Some(location) = &mut self.location {
+            location.file.get_or_insert_with(|| path.to_path_buf());
+        }
+        self
+    }
 }
-impl fmt::Display for InvalidType {
+
+impl fmt::Display for RfoodError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.message)
+        writeln!(f, "error[{}]: {}", self.code.code(), self.message)?;
+        if let Some(SourceLocation { file, line, column }) = &self.location {
+            let file = file.as_ref().map_or("<input>".to_string(), |file| file.display().to_string());
+            writeln!(f, "  --> {}:{}:{}", file, line, column + 1)?;
+        }
+        write!(f, "  = note: {}", self.code.explanation())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct TypeInferenceFailed {
-    pub expr: Expr,
+impl std::error::Error for RfoodError {}
+
+impl From<syn::Error> for RfoodError {
+    fn from(error: syn::Error) -> Self {
+        RfoodError::new(ErrorCode::ParseFailed, error.to_string()).at_span(error.span())
+    }
 }
-impl fmt::Display for TypeInferenceFailed {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Type inference failed for type {:?}", self.expr)
+
+impl From<std::io::Error> for RfoodError {
+    fn from(error: std::io::Error) -> Self {
+        RfoodError::new(ErrorCode::Io, error.to_string())
     }
 }
+
+/// Run the function, converting any panic into an internal error
+///
+/// Unsupported input is reported with an error result, this is only a last resort for bugs in the
+/// transformation. Panics abort on wasm32, so the wasm binding must not rely on it.
+pub fn catch_internal_errors<T>(f: impl FnOnce() -> RfoodResult<T>) -> RfoodResult<T> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = match panic.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => panic.downcast_ref::<&str>().map_or("Unknown panic".to_string(), |message| message.to_string()),
+        };
+        Err(RfoodError::new(ErrorCode::Internal, message))
+    })
+}
@@ -1,61 +1,125 @@
 extern crate proc_macro;
 
 use crate::ast::create::generic_parameter_from_generic_argument;
-use crate::context::delta::{GetDeltaType, DeltaType};
+use crate::context::delta::{GetDeltaType, DeltaType, Pointer, get_type_of_fields_member, get_path_call_name};
+use crate::context::signatures::MethodSignatures;
 use crate::context::*;
-use crate::transform::transformer::TransformType;
+use crate::transform::transformer::{TransformMode, TransformType, transform_inherent_method_to_consumer};
+use crate::transform::visitors::{ReplaceTypeAliases, get_generic_type_args};
 use errors::*;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use syn::visit::{visit_item_enum, visit_item_impl, visit_item_struct, visit_item_trait, Visit};
+use syn::visit_mut::VisitMut;
+use syn::visit::{visit_file, visit_item, visit_item_enum, visit_item_impl, visit_item_mod, visit_item_struct, visit_item_trait, Visit};
 use syn::*;
-
-pub fn get_generics_from_type(type_: &Type) -> Generics {
-    if let Type::Path(TypePath {
-        path: Path { segments, .. },
-        ..
-    }) = type_
-    {
-        return get_generics_from_path_segment(segments.first().unwrap());
+use syn::__private::Span;
+use syn::punctuated::Punctuated;
+use quote::ToTokens;
+use tracing::trace;
+
+pub fn get_generics_from_type(type_: &Type) -> RfoodResult<Generics> {
+    if let Type::Path(TypePath { path: Path { segments, .. }, .. }) = type_ {
+        if let Some(segment) = segments.first() {
+            return get_generics_from_path_segment(segment);
+        }
     }
 
-    panic!("Not implemented. Cannot get generics from type.");
+    Err(RfoodError::new(
+        ErrorCode::UnsupportedType,
+        format!("Cannot get the generics of `{}`", type_.to_token_stream()),
+    ).with_span(type_))
 }
 
-pub fn get_fn_arg_name(fn_arg: &FnArg) -> Ident {
-    if let FnArg::Typed(PatType { box pat, .. }) = fn_arg {
-        if let Pat::Ident(PatIdent { ident, .. }) = pat {
-            return ident.clone();
-        }
+pub fn get_fn_arg_name(fn_arg: &FnArg) -> RfoodResult<Ident> {
+    if let FnArg::Typed(PatType { pat: box Pat::Ident(PatIdent { ident, .. }), .. }) = fn_arg {
+        return Ok(ident.clone());
     }
 
-    panic!("Not implemented. Cannot get fn arg name. {:?}", fn_arg);
+    Err(RfoodError::unsupported_syntax(format!(
+        "Only arguments bound to a name are supported, found `{}`",
+        fn_arg.to_token_stream(),
+    )).with_span(fn_arg))
 }
 
-pub fn create_generics_from_args(args: &AngleBracketedGenericArguments) -> Generics {
+pub fn create_generics_from_args(args: &AngleBracketedGenericArguments) -> RfoodResult<Generics> {
     let mut generics = Generics::default();
     for arg in &args.args {
         generics
             .params
-            .push(generic_parameter_from_generic_argument(arg));
+            .push(generic_parameter_from_generic_argument(arg)?);
+    }
+    Ok(generics)
+}
+
+pub fn get_generics_from_path_segment(segment: &PathSegment) -> RfoodResult<Generics> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => create_generics_from_args(args),
+        PathArguments::None => Ok(Generics::default()),
+        PathArguments::Parenthesized(_) => Err(RfoodError::new(
+            ErrorCode::UnsupportedType,
+            format!("Cannot get the generics of `{}`", segment.to_token_stream()),
+        ).with_span(segment)),
     }
-    return generics;
 }
 
-pub fn get_generics_from_path_segment(segment: &PathSegment) -> Generics {
-    if let PathArguments::AngleBracketed(args) = &segment.arguments {
-        return create_generics_from_args(args);
+/// The type arguments given to an enum in a type, through any references or pointers, e.g. `[i32]`
+/// for `&Box<Parser<i32>>`
+pub fn get_enum_type_args(type_: &Type, enum_ident: &Ident) -> Vec<Type> {
+    match type_ {
+        Type::Reference(TypeReference { elem, .. }) | Type::Paren(TypeParen { elem, .. }) => get_enum_type_args(elem, enum_ident),
+        Type::Path(TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+            let args = get_generic_type_args(path);
+            match args.first() {
+                _ if segment.ident == *enum_ident => args,
+                Some(inner) if args.len() == 1 => get_enum_type_args(inner, enum_ident),
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
     }
-    if let PathArguments::None = &segment.arguments {
-        return Generics::default();
+}
+
+/// The path to an item in a module from another module, ignoring any imports, e.g.
+/// `super::shapes::Shape` for `Shape` in `shapes` used in `app`
+pub fn get_relative_module_path(from_module: &[Ident], item_module: &[Ident], ident: &Ident) -> Path {
+    let common_length = from_module
+        .iter()
+        .zip(item_module.iter())
+        .take_while(|(from, to)| from == to)
+        .count();
+
+    let segments = std::iter::repeat(Ident::new("super", Span::call_site()))
+        .take(from_module.len() - common_length)
+        .chain(item_module[common_length..].iter().cloned())
+        .chain(std::iter::once(ident.clone()))
+        .map(|ident| PathSegment { ident, arguments: PathArguments::None });
+
+    Path {
+        leading_colon: None,
+        segments: Punctuated::from_iter(segments),
     }
+}
 
-    panic!(
-        "Cannot get generics from unsupported path segment, {:?}",
-        segment
-    );
+/// Check if a type gives an enum the type parameters of a function, e.g. `&Parser<T>` in
+/// `fn parse<T>`. A function taking an enum with other type arguments (e.g. `&Parser<i32>`) does
+/// not handle every value of the enum so is not a consumer of it
+fn is_enum_given_type_params(type_: &Type, enum_: &ItemEnum, generics: &Generics) -> bool {
+    let args = get_enum_type_args(type_, &enum_.ident);
+    let mut params = Vec::new();
+    for arg in args.iter() {
+        match arg {
+            Type::Path(TypePath { qself: None, path }) => match path.get_ident() {
+                Some(ident) if generics.type_params().any(|param| param.ident == *ident) && !params.contains(&ident) => params.push(ident),
+                _ => return false,
+            },
+            _ => return false,
+        }
+    }
+    params.len() == enum_.generics.type_params().count()
 }
 
-pub fn get_consumer_match_statement(consumer: &ItemFn) -> std::result::Result<ExprMatch, NotFound> {
+pub fn get_consumer_match_statement(consumer: &ItemFn) -> RfoodResult<ExprMatch> {
     let last_stmt = consumer.block.stmts.last();
     if let Some(stmt) = last_stmt {
         match stmt {
@@ -76,33 +140,29 @@ pub fn get_consumer_match_statement(consumer: &ItemFn) -> std::result::Result<Ex
             _ => (),
         }
     }
-    return Err(NotFound {
-        item_name: "Consumer match statement".to_string(),
-        type_name: "ExprMatch".to_string(),
-    });
+    return Err(RfoodError::not_found(&consumer.sig.ident.to_string(), "match statement of consumer"));
 }
 
-pub fn get_match_expr_for_enum(
+pub fn get_match_arm_for_enum(
     consumer: &ItemFn,
     enum_variant_ident: &Ident,
-) -> std::result::Result<Expr, NotFound> {
+) -> RfoodResult<Arm> {
 
-    println!("Getting match expression for {:?} in {:?}", enum_variant_ident, consumer.sig.ident);
-    let match_expr = get_consumer_match_statement(consumer);
-    if match_expr.is_err() {
-        return Err(match_expr.err().unwrap());
-    }
+    trace!(variant = %enum_variant_ident, consumer = %consumer.sig.ident, "Getting match arm");
+    let match_expr = get_consumer_match_statement(consumer)?;
 
-    let enum_expr = match_expr.clone().unwrap().arms.iter().find_map(|arm| {
+    let enum_arm = match_expr.arms.iter().find_map(|arm| {
         // If the arm pat is the enum
         if let Pat::Struct(PatStruct {
             path, ..
+        }) | Pat::TupleStruct(PatTupleStruct {
+            path, ..
         }) | Pat::Path(PatPath{
             path, ..
         }) = &arm.pat
         {
-            if path.segments.last().unwrap().ident == *enum_variant_ident {
-                return Some(*arm.body.clone());
+            if path.segments.last().map_or(false, |segment| segment.ident == *enum_variant_ident) {
+                return Some(arm.clone());
             }
         }
 
@@ -110,22 +170,63 @@ pub fn get_match_expr_for_enum(
     });
 
     // If there is an arm for the enum, return that
-    if enum_expr.is_some() {
-        return Ok(enum_expr.unwrap());
+    if let Some(enum_arm) = enum_arm {
+        return Ok(enum_arm);
     }
 
     // Otherwise get the wildcard arm
-    Ok(match_expr
-        .unwrap()
+    match match_expr
         .arms
         .iter()
-        .find_map(|arm| {
-            if let Pat::Wild(_) = &arm.pat {
-                return Some(*arm.body.clone());
-            }
-            return None;
-        })
-        .unwrap())
+        .find(|arm| matches!(arm.pat, Pat::Wild(_))) {
+        Some(arm) => Ok(arm.clone()),
+        None => Err(RfoodError::not_found(&enum_variant_ident.to_string(), "consumer match arm")),
+    }
+}
+
+pub fn get_match_expr_for_enum(
+    consumer: &ItemFn,
+    enum_variant_ident: &Ident,
+) -> RfoodResult<Expr> {
+    get_match_arm_for_enum(consumer, enum_variant_ident).map(|arm| *arm.body)
+}
+
+/// Get the variables bound by a pattern on an enum variant along with the member of the variant
+/// each variable is bound to
+///
+/// # Examples
+///
+/// ```
+/// use syn::*;
+/// use rfood::context::gamma::get_pattern_bindings;
+///
+/// let arm = parse_str::<Arm>("Light::RGB(r, _, b) => r + b").unwrap();
+/// let bindings = get_pattern_bindings(&arm.pat);
+/// assert_eq!(bindings.len(), 2);
+/// assert_eq!(bindings[1].0.ident.to_string(), "b");
+/// assert_eq!(bindings[1].1, Member::Unnamed(Index::from(2)));
+/// ```
+pub fn get_pattern_bindings(pat: &Pat) -> Vec<(PatIdent, Member)> {
+    match pat {
+        Pat::Struct(PatStruct { fields, .. }) => fields
+            .iter()
+            .filter_map(|field_pat| match &*field_pat.pat {
+                Pat::Ident(pat_ident) => Some((pat_ident.clone(), field_pat.member.clone())),
+                _ => None,
+            })
+            .collect(),
+        Pat::TupleStruct(PatTupleStruct { pat: PatTuple { elems, .. }, .. }) => elems
+            .iter()
+            // Positions after a rest pattern cannot be known without the variant
+            .take_while(|elem| !matches!(elem, Pat::Rest(_)))
+            .enumerate()
+            .filter_map(|(index, elem)| match elem {
+                Pat::Ident(pat_ident) => Some((pat_ident.clone(), Member::Unnamed(Index::from(index)))),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
 }
 
 pub fn is_mutable_self(sig: &Signature) -> bool {
@@ -141,6 +242,110 @@ pub fn is_mutable_self(sig: &Signature) -> bool {
     }
 }
 
+/// Check if the first argument of a signature is self (self, &self, &mut self or self: Box<Self>)
+pub fn has_self_receiver(sig: &Signature) -> bool {
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(_)) => true,
+        Some(FnArg::Typed(PatType { pat: box Pat::Ident(PatIdent { ident, .. }), .. })) => ident == "self",
+        _ => false,
+    }
+}
+
+/// Get the name an item is declared with, if it has one
+pub fn get_item_ident(item: &Item) -> Option<Ident> {
+    match item {
+        Item::Const(ItemConst { ident, .. })
+        | Item::Enum(ItemEnum { ident, .. })
+        | Item::Fn(ItemFn { sig: Signature { ident, .. }, .. })
+        | Item::Mod(ItemMod { ident, .. })
+        | Item::Static(ItemStatic { ident, .. })
+        | Item::Struct(ItemStruct { ident, .. })
+        | Item::Trait(ItemTrait { ident, .. })
+        | Item::TraitAlias(ItemTraitAlias { ident, .. })
+        | Item::Type(ItemType { ident, .. })
+        | Item::Union(ItemUnion { ident, .. }) => Some(ident.clone()),
+        _ => None,
+    }
+}
+
+/// Get the names imported by a use tree along with the path each is imported from, glob imports
+/// do not import any specific name so are skipped
+///
+/// # Examples
+///
+/// ```
+/// use syn::*;
+/// use rfood::context::gamma::get_use_tree_imports;
+///
+/// let item_use = parse_str::<ItemUse>("use super::shapes::{self, Shape as S, circle::*};").unwrap();
+/// let imports: Vec<(String, String)> = get_use_tree_imports(&item_use.tree, &[])
+///     .into_iter()
+///     .map(|(name, path)| (name.to_string(), path.iter().map(|ident| ident.to_string()).collect::<Vec<_>>().join("::")))
+///     .collect();
+/// assert_eq!(imports, vec![
+///     ("shapes".to_string(), "super::shapes".to_string()),
+///     ("S".to_string(), "super::shapes::Shape".to_string()),
+/// ]);
+/// ```
+pub fn get_use_tree_imports(tree: &UseTree, prefix: &[Ident]) -> Vec<(Ident, Vec<Ident>)> {
+    let with_prefix = |ident: &Ident| -> Vec<Ident> {
+        prefix.iter().chain(std::iter::once(ident)).cloned().collect()
+    };
+    match tree {
+        UseTree::Path(UsePath { ident, tree, .. }) => get_use_tree_imports(tree, &with_prefix(ident)),
+        // `use shapes::{self}` imports the module itself
+        UseTree::Name(UseName { ident }) if ident == "self" => match prefix.last() {
+            Some(module) => vec![(module.clone(), prefix.to_vec())],
+            None => Vec::new(),
+        },
+        UseTree::Name(UseName { ident }) => vec![(ident.clone(), with_prefix(ident))],
+        UseTree::Rename(UseRename { ident, rename, .. }) if ident == "self" => match prefix.last() {
+            Some(_) => vec![(rename.clone(), prefix.to_vec())],
+            None => Vec::new(),
+        },
+        UseTree::Rename(UseRename { ident, rename, .. }) => vec![(rename.clone(), with_prefix(ident))],
+        UseTree::Glob(_) => Vec::new(),
+        UseTree::Group(UseGroup { items, .. }) => items
+            .iter()
+            .flat_map(|tree| get_use_tree_imports(tree, prefix))
+            .collect(),
+    }
+}
+
+/// A module of the file being transformed
+#[derive(Debug, Clone, Default)]
+pub struct ModuleScope {
+    /// The names of all the items declared directly in the module
+    pub items: HashSet<Ident>,
+    /// The modules declared in the module
+    pub modules: HashMap<Ident, ModuleScope>,
+    /// The items imported into the module by `use` statements, from the name they are imported
+    /// as to the path they are imported from (relative to the module)
+    pub imports: HashMap<Ident, Vec<Ident>>,
+}
+
+impl ModuleScope {
+    /// Get a module from its path relative to this module
+    pub fn get_module(&self, module_path: &[Ident]) -> Option<&ModuleScope> {
+        match module_path.split_first() {
+            Some((ident, rest)) => self.modules.get(ident)?.get_module(rest),
+            None => Some(self),
+        }
+    }
+
+    /// Get a module from its path relative to this module, creating any missing modules
+    fn get_module_mut(&mut self, module_path: &[Ident]) -> &mut ModuleScope {
+        match module_path.split_first() {
+            Some((ident, rest)) => self
+                .modules
+                .entry(ident.clone())
+                .or_default()
+                .get_module_mut(rest),
+            None => self,
+        }
+    }
+}
+
 /// Global context
 #[derive(Debug, Clone)]
 pub struct Gamma {
@@ -164,12 +369,42 @@ pub struct Gamma {
     /// Set of mutable consumers
     pub mutable_consumers: HashSet<Ident>,
 
+    /// How errors found transforming the code are handled
+    pub mode: TransformMode,
+    /// The pointer the trait objects created from FP to OOP are held in
+    pub pointer: Pointer,
+    /// The signatures of the methods of types which are not in the code, e.g. `Vec::push`
+    pub method_signatures: MethodSignatures,
+    /// The errors skipped in best effort mode, gamma is shared by the whole transformation so they
+    /// are collected here
+    skipped: RefCell<Vec<RfoodError>>,
+
     /// The signatures of all the destructors and consumers
     pub signatures: HashMap<Ident, Signature>,
+    /// The signatures of the methods of inherent impls by the type they are of and their name,
+    /// these are kept apart so methods of different types can share a name
+    pub inherent_signatures: HashMap<(Ident, Ident), Signature>,
+
+    /// The module tree of the file, the root scope is the file itself
+    pub module_scope: ModuleScope,
+    /// The module each datatype, interface, generator and consumer is declared in. Items are
+    /// identified by name so must have unique names across modules
+    pub item_modules: HashMap<Ident, Vec<Ident>>,
+    /// The type aliases declared in the file, these are expanded when reasoning about types
+    pub type_aliases: HashMap<Ident, ItemType>,
 
     // Helpers
     /// All structs found in the ast -> Note these may not be inscope!
     _structs: Vec<ItemStruct>,
+    /// The module currently being visited
+    current_module: Vec<Ident>,
+    /// The errors found while visiting, the visitor cannot return them
+    errors: Vec<RfoodError>,
+    /// The inherent impls found while visiting, these are collected once every enum is known
+    inherent_impls: Vec<ItemImpl>,
+    /// The errors of the impls of each trait for types which cannot be generators (e.g.
+    /// `impl Trait for i32`), these are only reported if the trait is transformed
+    unsupported_impls: HashMap<Ident, Vec<RfoodError>>,
 }
 
 impl Gamma {
@@ -181,25 +416,299 @@ impl Gamma {
             destructors: HashMap::new(),
             enum_consumers: HashMap::new(),
             signatures: HashMap::new(),
+            inherent_signatures: HashMap::new(),
 
             mutable_consumers: HashSet::new(),
+            mode: TransformMode::default(),
+            pointer: Pointer::default(),
+            method_signatures: MethodSignatures::default(),
+            skipped: RefCell::new(Vec::new()),
+            module_scope: ModuleScope::default(),
+            item_modules: HashMap::new(),
+            type_aliases: HashMap::new(),
             _structs: Vec::new(),
+            current_module: Vec::new(),
+            errors: Vec::new(),
+            inherent_impls: Vec::new(),
+            unsupported_impls: HashMap::new(),
         };
     }
 
-    fn from_file(syntax: &syn::File) -> Self {
+    fn from_file(syntax: &syn::File) -> RfoodResult<Self> {
         let mut gamma = Gamma::empty();
         gamma.visit_file(syntax);
-        gamma
+        gamma.check_errors()?;
+        Ok(gamma)
+    }
+
+    /// Return the first error found while collecting gamma
+    pub fn check_errors(&self) -> RfoodResult<()> {
+        match self.errors.first() {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Return the first error of the impls of a trait for types which cannot be generators
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use rfood::context::errors::ErrorCode;
+    /// use rfood::context::gamma::generate_gamma;
+    ///
+    /// let gamma = generate_gamma(&parse_str("
+    ///     trait Shape { fn area(&self) -> i32; }
+    ///     impl Shape for i32 { fn area(&self) -> i32 { *self } }
+    /// ").unwrap()).unwrap();
+    ///
+    /// let error = gamma.check_trait_impls(&parse_str("Shape").unwrap()).unwrap_err();
+    /// assert_eq!(error.code, ErrorCode::NotFound);
+    /// ```
+    pub fn check_trait_impls(&self, trait_ident: &Ident) -> RfoodResult<()> {
+        match self.unsupported_impls.get(trait_ident).and_then(|errors| errors.first()) {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
     }
     
-    pub fn get_signature(&self, ident: &Ident) -> std::result::Result<Signature, NotFound> {
+    /// Handle an error found transforming some code. In strict mode the error is returned, in
+    /// best effort mode it is recorded so the code can be left as it is
+    pub fn skip_error(&self, error: RfoodError) -> RfoodResult<()> {
+        match self.mode {
+            TransformMode::Strict => Err(error),
+            TransformMode::BestEffort => {
+                self.skipped.borrow_mut().push(error);
+                Ok(())
+            }
+        }
+    }
+
+    /// Take the errors skipped so far
+    pub fn take_skipped(&self) -> Vec<RfoodError> {
+        self.skipped.take()
+    }
+
+    /// Collect items declared in a module, used to add the items created by a transformation to
+    /// the module of the trait/enum they were created from
+    pub fn visit_module_items(&mut self, module_path: &[Ident], items: &[Item]) {
+        let current_module = std::mem::replace(&mut self.current_module, module_path.to_vec());
+        self.module_scope.get_module_mut(module_path);
+        for item in items {
+            self.visit_item(item);
+        }
+        self.add_inherent_impls();
+        self.current_module = current_module;
+    }
+
+    /// Get the module an item is declared in, items which are not known are assumed to be in the
+    /// root module
+    pub fn get_item_module(&self, ident: &Ident) -> Vec<Ident> {
+        self.item_modules.get(ident).cloned().unwrap_or_default()
+    }
+
+    /// Get the path which refers to an item from inside a module
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use quote::quote;
+    /// use rfood::context::gamma::generate_gamma;
+    ///
+    /// let file = parse_str::<File>(
+    ///     "mod shapes { pub enum Shape { Circle { radius: f32 } } } mod app { }"
+    /// ).unwrap();
+    /// let gamma = generate_gamma(&file).unwrap();
+    /// let from_module = vec![parse_str::<Ident>("app").unwrap()];
+    /// let path = gamma.get_relative_item_path(&from_module, &parse_str::<Ident>("Shape").unwrap());
+    /// assert_eq!(quote!(#path).to_string(), "super :: shapes :: Shape");
+    /// ```
+    ///
+    /// An item imported into the module is referred to by the name it is imported as
+    ///
+    /// ```
+    /// use syn::*;
+    /// use quote::quote;
+    /// use rfood::context::gamma::generate_gamma;
+    ///
+    /// let file = parse_str::<File>(
+    ///     "mod shapes { pub enum Shape { Circle { radius: f32 } } } mod app { use super::shapes::Shape as S; }"
+    /// ).unwrap();
+    /// let gamma = generate_gamma(&file).unwrap();
+    /// let from_module = vec![parse_str::<Ident>("app").unwrap()];
+    /// let path = gamma.get_relative_item_path(&from_module, &parse_str::<Ident>("Shape").unwrap());
+    /// assert_eq!(quote!(#path).to_string(), "S");
+    /// ```
+    pub fn get_relative_item_path(&self, from_module: &[Ident], ident: &Ident) -> Path {
+        let item_module = self.get_item_module(ident);
+        if let Some(name) = self.get_imported_name(from_module, &item_module, ident) {
+            return Path::from(name);
+        }
+        get_relative_module_path(from_module, &item_module, ident)
+    }
+
+    /// Resolve the modules at the start of a path used inside a module. Returns the module the
+    /// path refers to and the remaining segments, e.g. `super::shapes::Shape::Circle` used in
+    /// `app` is `Shape::Circle` in `shapes`. A path starting with a name imported by a `use`
+    /// continues from the path it is imported from, so with `use super::shapes::Shape as S`
+    /// `S::Circle` is also `Shape::Circle` in `shapes`.
+    ///
+    /// Returns None if the path cannot be in the file, i.e. it goes above the root module or is
+    /// an absolute path to another crate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use rfood::context::gamma::generate_gamma;
+    ///
+    /// let file = parse_str::<File>(
+    ///     "mod shapes { pub enum Shape { Circle } } mod app { use super::shapes::Shape as S; }"
+    /// ).unwrap();
+    /// let gamma = generate_gamma(&file).unwrap();
+    /// let app = vec![parse_str::<Ident>("app").unwrap()];
+    /// let (module, idents) = gamma.resolve_path(&app, &parse_str::<Path>("S::Circle").unwrap()).unwrap();
+    /// assert_eq!(module, vec![parse_str::<Ident>("shapes").unwrap()]);
+    /// assert_eq!(idents, vec![parse_str::<Ident>("Shape").unwrap(), parse_str::<Ident>("Circle").unwrap()]);
+    /// ```
+    pub fn resolve_path(&self, from_module: &[Ident], path: &Path) -> Option<(Vec<Ident>, Vec<Ident>)> {
+        if path.leading_colon.is_some() {
+            return None;
+        }
+
+        let mut idents: Vec<Ident> = path.segments.iter().map(|segment| segment.ident.clone()).collect();
+        // Imports may be of other imports, the number of steps is limited in case of a cycle
+        for _ in 0..8 {
+            match self
+                .module_scope
+                .get_module(from_module)
+                .and_then(|scope| scope.imports.get(&idents[0]))
+            {
+                // A module declared with the same name takes precedence
+                Some(import) if import.len() > 1 && !self.is_child_module(from_module, &idents[0]) => {
+                    idents.splice(0..1, import.iter().cloned());
+                }
+                _ => break,
+            }
+        }
+        let mut module = from_module.to_vec();
+        let mut index = 0;
+
+        // The last segment is always an item
+        while index + 1 < idents.len() {
+            let ident = &idents[index];
+            if index == 0 && ident == "crate" {
+                module.clear();
+            } else if index == 0 && ident == "self" {
+            } else if ident == "super" {
+                module.pop()?;
+            } else if self.is_child_module(&module, ident) {
+                module.push(ident.clone());
+            } else {
+                break;
+            }
+            index += 1;
+        }
+
+        Some((module, idents[index..].to_vec()))
+    }
+
+    /// Get the name an item is imported as in a module, if it is imported
+    fn get_imported_name(&self, from_module: &[Ident], item_module: &[Ident], ident: &Ident) -> Option<Ident> {
+        let scope = self.module_scope.get_module(from_module)?;
+        let mut imports: Vec<(&Ident, &Vec<Ident>)> = scope.imports.iter().collect();
+        // The imports are in a hashmap, sort them so the name used does not change between runs
+        imports.sort_by_key(|(name, _)| name.to_string());
+        imports.into_iter().find_map(|(name, import)| {
+            let path = Path {
+                leading_colon: None,
+                segments: Punctuated::from_iter(import.iter().map(|ident| PathSegment::from(ident.clone()))),
+            };
+            match self.resolve_path(from_module, &path) {
+                Some((module, idents)) if module == item_module && idents == [ident.clone()] => Some(name.clone()),
+                _ => None,
+            }
+        })
+    }
+
+    fn is_child_module(&self, module: &[Ident], ident: &Ident) -> bool {
+        self.module_scope
+            .get_module(module)
+            .map_or(false, |scope| scope.modules.contains_key(ident))
+    }
+
+    /// Check if a path used inside a module can refer to the item with the same name collected
+    /// in gamma.
+    ///
+    /// This is only false if the path resolves to a module which declares a different item with
+    /// that name, paths to items brought into scope by a glob import are assumed to be correct.
+    pub fn is_path_to_item(&self, from_module: &[Ident], path: &Path) -> bool {
+        match self.resolve_path(from_module, path) {
+            Some((module, item_idents)) if !item_idents.is_empty() => {
+                let ident = &item_idents[0];
+                let is_declared_in_module = self
+                    .module_scope
+                    .get_module(&module)
+                    .map_or(false, |scope| scope.items.contains(ident));
+
+                !is_declared_in_module
+                    || self.item_modules.get(ident).map_or(true, |item_module| *item_module == module)
+            }
+            _ => false,
+        }
+    }
+
+    /// Replace the type aliases in a type with the types they stand for, wherever they are in
+    /// the type. The arguments of generic aliases are substituted into the aliased type
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syn::*;
+    /// use quote::quote;
+    /// use rfood::context::gamma::generate_gamma;
+    ///
+    /// let file = parse_str::<File>(
+    ///     "type ShapeRef = Box<dyn Shape>; type Pair<T> = (T, T);"
+    /// ).unwrap();
+    /// let gamma = generate_gamma(&file).unwrap();
+    /// let type_ = gamma.expand_type_aliases(&parse_str::<Type>("Vec<Pair<ShapeRef>>").unwrap());
+    /// assert_eq!(
+    ///     quote!(#type_).to_string(),
+    ///     "Vec < (Box < dyn Shape > , Box < dyn Shape >) >"
+    /// );
+    /// ```
+    pub fn expand_type_aliases(&self, type_: &Type) -> Type {
+        let mut type_ = type_.clone();
+        ReplaceTypeAliases::new(&self.type_aliases).visit_type_mut(&mut type_);
+        type_
+    }
+
+    /// Replace the type aliases in the arguments and output of a signature
+    pub fn expand_signature_type_aliases(&self, signature: &Signature) -> Signature {
+        let mut signature = signature.clone();
+        if !self.type_aliases.is_empty() {
+            ReplaceTypeAliases::new(&self.type_aliases).visit_signature_mut(&mut signature);
+        }
+        signature
+    }
+
+    /// Replace the type aliases in the types of fields of a struct or variant
+    pub fn expand_fields_type_aliases(&self, fields: &Fields) -> Fields {
+        let mut fields = fields.clone();
+        if !self.type_aliases.is_empty() {
+            ReplaceTypeAliases::new(&self.type_aliases).visit_fields_mut(&mut fields);
+        }
+        fields
+    }
+
+    /// Get the signature of a destructor, consumer or function with its type aliases expanded
+    pub fn get_signature(&self, ident: &Ident) -> RfoodResult<Signature> {
         match self.signatures.get(ident) {
-            Some(sig) => Ok(sig.clone()),
-            None => Err(NotFound{
-                item_name: ident.to_string(),
-                type_name: "Signature".to_string(),
-            })
+            Some(sig) => Ok(self.expand_signature_type_aliases(sig)),
+            None => Err(RfoodError::not_found(&ident.to_string(), "signature of"))
         }
     }
 
@@ -207,6 +716,30 @@ impl Gamma {
         self.signatures.insert(ident.clone(), sig.clone());
     }
 
+    /// Get the signature of a method of an inherent impl of a type with its type aliases expanded
+    pub fn get_inherent_signature(&self, type_ident: &Ident, method: &Ident) -> RfoodResult<Signature> {
+        match self.inherent_signatures.get(&(type_ident.clone(), method.clone())) {
+            Some(sig) => Ok(self.expand_signature_type_aliases(sig)),
+            None => Err(RfoodError::not_found(&format!("{}::{}", type_ident, method), "signature of")),
+        }
+    }
+
+    /// Get the signature of the function a path refers to, the methods of inherent impls are
+    /// found by their type, e.g. `Frame::new` or `<dyn Shape>::unit`
+    pub fn get_path_signature(&self, qself: Option<&QSelf>, path: &Path) -> RfoodResult<Signature> {
+        let ident = get_path_call_name(path);
+        let type_ident = match qself {
+            Some(QSelf { ty, position: 0, .. }) => Some(ty.get_delta_type().name),
+            _ => path.segments.iter().rev().nth(1).map(|segment| segment.ident.clone()),
+        };
+        match type_ident {
+            Some(type_ident) => self
+                .get_inherent_signature(&type_ident, &ident)
+                .or_else(|_| self.get_signature(&ident)),
+            None => self.get_signature(&ident),
+        }
+    }
+
     pub fn is_trait(&self, ident: &Ident) -> bool {
         return self.get_trait(ident).is_ok();
     }
@@ -223,80 +756,64 @@ impl Gamma {
             || self.is_enum(ident);
     }
     
-    pub fn get_trait(&self, ident: &Ident) -> std::result::Result<ItemTrait, NotFound> {
+    pub fn get_trait(&self, ident: &Ident) -> RfoodResult<ItemTrait> {
         match self.traits.iter().find(|t| t.ident == ident.clone()) {
             Some(t) => Ok(t.clone()),
-            None => Err(NotFound {
-                item_name: ident.to_string(),
-                type_name: "trait".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&ident.to_string(), "trait")),
         }
     }
 
-    pub fn get_enum(&self, ident: &Ident) -> std::result::Result<ItemEnum, NotFound> {
+    pub fn get_enum(&self, ident: &Ident) -> RfoodResult<ItemEnum> {
         match self.enums.iter().find(|e| e.ident == ident.clone()) {
             Some(e) => Ok(e.clone()),
-            None => Err(NotFound {
-                item_name: ident.to_string(),
-                type_name: "enum".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&ident.to_string(), "enum")),
         }
     }
 
-    pub fn get_enum_variant(&self, enum_ident: &Ident, enum_variant_ident: &Ident) -> std::result::Result<Variant, NotFound> {
+    pub fn get_enum_variant(&self, enum_ident: &Ident, enum_variant_ident: &Ident) -> RfoodResult<Variant> {
         let enum_ = self
-            .get_enum(&self.get_base_type_name_from_type_name(enum_ident));
+            .get_enum(&self.get_base_type_name_from_type_name(enum_ident))?;
 
-        if enum_.is_err() {
-            return Err(enum_.err().unwrap());
-        }
-
-        match enum_.unwrap()
+        match enum_
             .variants
             .clone()
             .iter()
             .find(|v| v.ident == enum_variant_ident.clone()) {
             Some(v) => Ok(v.clone()),
-            None => Err(NotFound{
-                item_name: enum_variant_ident.to_string(),
-                type_name: "enum variant".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&enum_variant_ident.to_string(), "enum variant")),
         }
     }
 
-    pub fn get_constructor(&self, ident: &Ident) -> std::result::Result<Variant, NotFound> {
+    pub fn get_constructor(&self, ident: &Ident) -> RfoodResult<Variant> {
         match self.enums.iter().find_map(|e| {
             self.get_enum_variant(&e.ident, &ident).ok()
         }) {
             Some(v) => Ok(v),
-            None => Err(NotFound{
-                item_name: ident.to_string(),
-                type_name: "constructor".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&ident.to_string(), "constructor")),
         }
     }
 
-    pub fn get_enum_variant_enum(&self, enum_variant_ident: &Ident) -> std::result::Result<ItemEnum, NotFound> {
+    pub fn get_enum_variant_enum(&self, enum_variant_ident: &Ident) -> RfoodResult<ItemEnum> {
         match self.enums.iter().find(|e| {
             e.variants.iter().any(|v| v.ident == enum_variant_ident.clone())
         }) {
             Some(e) => Ok(e.clone()),
-            None => Err(NotFound{
-                item_name: enum_variant_ident.to_string(),
-                type_name: "enum for enum variant".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&enum_variant_ident.to_string(), "enum for enum variant")),
         }
     }
 
-    pub fn get_generators(&self, trait_ident: &Ident) -> Vec<(ItemStruct, ItemImpl)> {
-        self.generators
-            .get(&trait_ident)
-            .unwrap_or_else(|| panic!("Trait {:?} not found in gamma generators", trait_ident))
-            .clone()
+    pub fn get_generators(&self, trait_ident: &Ident) -> RfoodResult<Vec<(ItemStruct, ItemImpl)>> {
+        match self.generators.get(trait_ident) {
+            Some(generators) => Ok(generators.clone()),
+            // A trait which is not implemented by any struct has no generators
+            None if self.is_trait(trait_ident) => Ok(Vec::new()),
+            None => Err(RfoodError::not_found(&trait_ident.to_string(), "trait")),
+        }
     }
 
     pub fn is_generator_of_trait(&self, trait_ident: &Ident) -> bool {
         self.get_generators(&trait_ident)
+            .unwrap_or_default()
             .iter()
             .any(|(struct_, _)| struct_.ident == *trait_ident)
     }
@@ -316,6 +833,13 @@ impl Gamma {
             || self.get_trait(type_ident).is_ok()
     }
 
+    /// Check if a path is the name of a tuple struct generator or enum variant constructor, i.e. if
+    /// calling it instantiates a datatype or interface
+    pub fn is_constructor_path(&self, path: &Path) -> bool {
+        let ident = &path.segments.last().unwrap().ident;
+        self.get_constructor(ident).is_ok() || self.get_all_generators().iter().any(|(struct_, _)| struct_.ident == *ident)
+    }
+
     pub fn is_consumer(&self, fn_ident: &Ident) -> bool {
         self.enum_consumers
             .iter()
@@ -330,7 +854,7 @@ impl Gamma {
 
     pub fn is_destructor_of_trait(&self, trait_ident: &Ident, fn_ident: &Ident) -> bool {
         let trait_ident = self.get_base_type_name_from_type_name(&trait_ident);
-        self.destructors.get(&trait_ident).unwrap().iter().any(|item_fn| item_fn.sig.ident == *fn_ident)
+        self.destructors.get(&trait_ident).map_or(false, |destructors| destructors.iter().any(|item_fn| item_fn.sig.ident == *fn_ident))
     }
 
     /// For a given generator (struct) find the trait that it implements
@@ -342,18 +866,19 @@ impl Gamma {
             .iter()
             .find(|t| {
                 self.get_generators(&t.ident)
+                    .unwrap_or_default()
                     .iter()
                     .any(|(struct_, _)| struct_.ident == *generator_ident)
             })
             .cloned()
     }
 
-    pub fn get_struct_by_name(&self, ident: &Ident) -> ItemStruct {
+    pub fn get_struct_by_name(&self, ident: &Ident) -> RfoodResult<ItemStruct> {
         self._structs
             .iter()
             .find(|s| return s.ident == *ident)
-            .unwrap_or_else(|| panic!("Struct {:?} not found in gamma", ident))
-            .clone()
+            .cloned()
+            .ok_or_else(|| RfoodError::not_found(&ident.to_string(), "struct"))
     }
 
     pub fn get_destructors(&self, trait_ident: &Ident) -> Vec<TraitItemMethod> {
@@ -363,23 +888,16 @@ impl Gamma {
         }
     }
 
-    pub fn get_generator(&self, generator_ident: &Ident) -> ItemTrait {
-        self.generators
-            .iter()
-            .find_map(|(trait_ident, generators)| {
-                return generators.iter().find_map(|(generator_struct, _)| {
-                    generator_struct.ident == *generator_ident;
-                    Some(self.get_trait(trait_ident).unwrap().clone())
-                });
-            })
-            .unwrap()
+    pub fn get_generator(&self, generator_ident: &Ident) -> RfoodResult<ItemTrait> {
+        self.get_generator_trait(generator_ident)
+            .ok_or_else(|| RfoodError::not_found(&generator_ident.to_string(), "trait of generator"))
     }
 
     pub fn get_destructor_signature(
         &self,
         generator_ident: &Ident,
         destructor_ident: &Ident,
-    ) -> std::result::Result<Signature, NotFound> {
+    ) -> RfoodResult<Signature> {
         let traits = self.get_traits_for_generator(&generator_ident);
         match self.traits
             .iter()
@@ -394,16 +912,27 @@ impl Gamma {
                     })
             }) {
             Some(sig) => Ok(sig),
-            None => Err(NotFound{item_name: destructor_ident.to_string(), type_name: "destructor".to_string()}),
+            None => Err(RfoodError::not_found(&destructor_ident.to_string(), "destructor")),
         }
     }
 
     pub fn is_destructor_method_call(&self, expr_method_call: &ExprMethodCall, delta: &delta::Delta) -> bool {
-        let expr_type = delta.get_type_of_expr(&expr_method_call.receiver, self).unwrap().name;
-        self.is_generator_type(&expr_type) && self.is_destructor_of_trait(
-            &expr_type,
-            &expr_method_call.method
-        ) 
+        match delta.get_type_of_expr(&expr_method_call.receiver, self) {
+            Ok(expr_type) => self.is_generator_type(&expr_type.name) && self.is_destructor_of_trait(
+                &expr_type.name,
+                &expr_method_call.method
+            ),
+            Err(_) => false,
+        }
+    }
+
+    /// A method call on an enum (or a value which cannot be typed) to a consumer of the enum, a
+    /// method of another type with the same name as a consumer is not a consumer call
+    pub fn is_consumer_method_call(&self, expr_method_call: &ExprMethodCall, delta: &delta::Delta) -> bool {
+        self.is_consumer(&expr_method_call.method) && match delta.get_type_of_expr(&expr_method_call.receiver, self) {
+            Ok(expr_type) => self.is_enum(&expr_type.name),
+            Err(_) => true,
+        }
     }
 
     pub fn is_mutable_self_destructor(&self, generator_ident: &Ident, destructor_ident: &Ident) -> bool {
@@ -430,16 +959,16 @@ impl Gamma {
     }
 
     pub fn is_mutable_self_method_call(&self, expr_method_call: &ExprMethodCall, delta: &delta::Delta) -> bool {
-        self.is_mutable_self_destructor(
-            &delta.get_type_of_expr(&expr_method_call.receiver, self).unwrap().name, 
-            &expr_method_call.method
-        )
+        match delta.get_type_of_expr(&expr_method_call.receiver, self) {
+            Ok(receiver_type) => self.is_mutable_self_destructor(&receiver_type.name, &expr_method_call.method),
+            Err(_) => false,
+        }
     }
 
     pub fn get_destructor_impl_for_generator(
         generator_impl: &ItemImpl,
         destructor_ident: &Ident,
-    ) -> std::result::Result<ImplItemMethod, NotFound> {
+    ) -> RfoodResult<ImplItemMethod> {
         // Filter all methods in the impl to find the one that matches the destructor
         match generator_impl.items.iter().find_map(|item| {
             return match &*item {
@@ -452,17 +981,14 @@ impl Gamma {
             };
         }) {
             Some(impl_item_method) => Ok(impl_item_method),
-            None => Err(NotFound {
-                item_name: destructor_ident.to_string(),
-                type_name: "destructor".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&destructor_ident.to_string(), "destructor")),
         }
     }
 
     pub fn get_destructor_impl_for_trait(
         trait_: &ItemTrait,
         destructor_ident: &Ident,
-    ) -> std::result::Result<TraitItemMethod, NotFound> {
+    ) -> RfoodResult<TraitItemMethod> {
         // Filter all methods in the impl to find the one that matches the destructor
         match trait_.items.iter().find_map(|item| {
             return match &*item {
@@ -475,10 +1001,7 @@ impl Gamma {
             };
         }) {
             Some(impl_item_method) => Ok(impl_item_method),
-            None => Err(NotFound {
-                item_name: destructor_ident.to_string(),
-                type_name: "destructor".to_string(),
-            }),
+            None => Err(RfoodError::not_found(&destructor_ident.to_string(), "destructor")),
         }
     }
 
@@ -501,6 +1024,7 @@ impl Gamma {
                 .iter()
                 .filter(|trait_| {
                     self.get_generators(&trait_.ident)
+                        .unwrap_or_default()
                         .iter()
                         .any(|(generator_struct, _)| generator_struct.ident == *generator_ident)
                 })
@@ -508,6 +1032,13 @@ impl Gamma {
         )
     }
 
+    /// Remove a datatype and its consumers so the datatype is treated like any other type, the
+    /// signatures of the consumers are kept
+    pub fn remove_enum(&mut self, enum_ident: &Ident) {
+        self.enums.retain(|enum_| enum_.ident != *enum_ident);
+        self.enum_consumers.remove(enum_ident);
+    }
+
     pub fn add_enum(&mut self, enum_: &ItemEnum) {
         self.enums.push(enum_.clone());
     }
@@ -524,6 +1055,33 @@ impl Gamma {
         self.signatures.insert(consumer.sig.ident.clone(), consumer.sig.clone());
     }
 
+    /// Collect the methods of an inherent impl (an impl without a trait)
+    ///
+    /// Methods of an enum with a self receiver are consumers of the enum, these are stored as the
+    /// equivalent consumer function. Any other methods are only used for their signature.
+    pub fn add_inherent_impl(&mut self, impl_: &ItemImpl) {
+        let self_type = impl_.self_ty.get_delta_type().name;
+        for item in &impl_.items {
+            if let ImplItem::Method(method) = item {
+                match self.get_enum(&self_type) {
+                    Ok(enum_) if has_self_receiver(&method.sig) => {
+                        self.add_enum_consumer(&enum_, &transform_inherent_method_to_consumer(method, &enum_));
+                    }
+                    _ => {
+                        self.inherent_signatures.insert((self_type.clone(), method.sig.ident.clone()), method.sig.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Collect the inherent impls found while visiting
+    fn add_inherent_impls(&mut self) {
+        for impl_ in std::mem::take(&mut self.inherent_impls) {
+            self.add_inherent_impl(&impl_);
+        }
+    }
+
     pub fn add_trait(&mut self, trait_: &ItemTrait) {
         self.traits.push(trait_.clone());
         // Add the destructors
@@ -555,10 +1113,12 @@ impl Gamma {
         self.signatures.insert(destructor.sig.ident.clone(), destructor.sig.clone());
     }
 
-    pub fn get_type_of_field(&self, struct_ident: &Ident, field_ident: &Ident) -> DeltaType {
-        self._structs.iter().find(|struct_| struct_.ident == *struct_ident).unwrap().fields.iter().find(|field| {
-            field.ident.as_ref().unwrap() == field_ident
-        }).unwrap().ty.clone().get_delta_type()
+    pub fn get_type_of_field(&self, struct_ident: &Ident, member: &Member) -> RfoodResult<DeltaType> {
+        get_type_of_fields_member(&self.get_struct_by_name(struct_ident)?.fields, member, false)
+            .ok_or_else(|| RfoodError::not_found(
+                &format!("{}.{}", struct_ident, delta::get_member_ident(member)),
+                "field",
+            ).with_span(member))
     }
 
     pub fn add_struct(&mut self, struct_: &ItemStruct) {
@@ -569,15 +1129,15 @@ impl Gamma {
     /// type.
     pub fn is_subtype_of(&self, type_name: &Ident, super_type_name: &Ident) -> bool {
         if self.is_trait(super_type_name) {
-            return self.get_generators(&super_type_name).iter().any(|(struct_, _)| {
+            return self.get_generators(&super_type_name).unwrap_or_default().iter().any(|(struct_, _)| {
                 struct_.ident == *type_name 
             });
         }
 
         if self.is_enum(super_type_name) {
-            return self.get_enum(&super_type_name).unwrap().variants.iter().any(|variant| {
+            return self.get_enum(&super_type_name).map_or(false, |enum_| enum_.variants.iter().any(|variant| {
                 variant.ident == *type_name 
-            });
+            }));
         }
 
         return false;
@@ -601,31 +1161,27 @@ impl Gamma {
     }
 
     pub fn get_enum_consumers(&self, enum_: &ItemEnum) -> Vec<ItemFn> {
-        if self.enum_consumers.contains_key(&enum_.ident) {
-            Vec::from_iter(self.enum_consumers.get(&enum_.ident).unwrap().values().cloned())
-        } else {
-            Vec::new()
-        }
+        self.enum_consumers
+            .get(&enum_.ident)
+            .map_or_else(Vec::new, |consumers| consumers.values().cloned().collect())
     }
 
     pub fn get_transformed_destructor_signature(
         &self,
         generator_ident: &Ident,
         destructor_ident: &Ident,
-    ) -> Signature {
+    ) -> RfoodResult<Signature> {
         // If the provided generator_ident is not a trait, find its trait
         let enum_ident = self.get_base_type_name_from_type_name(generator_ident);
         // Get the enum
         self.enum_consumers
             .get(&enum_ident)
-            .unwrap()
-            .get(&destructor_ident)
-            .unwrap()
-            .sig
-            .clone()
+            .and_then(|consumers| consumers.get(&destructor_ident))
+            .map(|consumer| consumer.sig.clone())
+            .ok_or_else(|| RfoodError::not_found(&destructor_ident.to_string(), "consumer"))
     }
 
-    pub fn get_transformed_consumer_signature(&self, consumer_ident: &Ident) -> Signature {
+    pub fn get_transformed_consumer_signature(&self, consumer_ident: &Ident) -> RfoodResult<Signature> {
         self.traits
             .iter()
             .find_map(|trait_| {
@@ -638,20 +1194,61 @@ impl Gamma {
                     return None;
                 })
             })
-            .unwrap()
-            .clone()
+            .ok_or_else(|| RfoodError::not_found(&consumer_ident.to_string(), "method"))
     }
 }
 
 impl<'ast> Visit<'ast> for Gamma {
+    fn visit_file(&mut self, i: &'ast File) {
+        // Aliases can be used before they are declared so are collected first
+        let mut type_aliases = CollectTypeAliases::default();
+        type_aliases.visit_file(i);
+        self.type_aliases.extend(type_aliases.aliases);
+        visit_file(self, i);
+        // An impl can come before the enum it is of
+        self.add_inherent_impls();
+    }
+
+    fn visit_item(&mut self, i: &'ast Item) {
+        if let Some(ident) = get_item_ident(i) {
+            self.module_scope
+                .get_module_mut(&self.current_module)
+                .items
+                .insert(ident);
+        }
+        visit_item(self, i);
+    }
+
+    fn visit_item_mod(&mut self, i: &'ast ItemMod) {
+        // Items in the module are collected into its own scope
+        self.current_module.push(i.ident.clone());
+        self.module_scope.get_module_mut(&self.current_module);
+        visit_item_mod(self, i);
+        self.current_module.pop();
+    }
+
+    fn visit_item_use(&mut self, i: &'ast ItemUse) {
+        // Imports from other crates
+        if i.leading_colon.is_some() {
+            return;
+        }
+        let imports = get_use_tree_imports(&i.tree, &[]);
+        self.module_scope
+            .get_module_mut(&self.current_module)
+            .imports
+            .extend(imports);
+    }
+
     fn visit_item_enum(&mut self, i: &'ast ItemEnum) {
         visit_item_enum(self, i);
         self.enums.push(i.clone());
+        self.item_modules.insert(i.ident.clone(), self.current_module.clone());
     }
 
     fn visit_item_trait(&mut self, i: &'ast ItemTrait) {
         visit_item_trait(self, i);
         self.traits.push(i.clone());
+        self.item_modules.insert(i.ident.clone(), self.current_module.clone());
 
         // Filter all the items in the trait and pull out the methods
         let trait_methods = Vec::from_iter(i.items.iter().filter_map(|item| {
@@ -675,25 +1272,43 @@ impl<'ast> Visit<'ast> for Gamma {
         visit_item_impl(self, i);
 
         // Find the trait that is being implemented
-        let trait_ident = i.trait_.as_ref().unwrap().1.segments.first().unwrap().ident.clone();
+        let trait_ident = match &i.trait_ {
+            Some((_, trait_path, _)) => trait_path.segments.last().unwrap().ident.clone(),
+            // Inherent impls do not make the struct a generator
+            None => {
+                self.inherent_impls.push(i.clone());
+                return;
+            }
+        };
 
         // Find the struct that the impl is for
         let struct_name: &Ident = if let Type::Path(type_path) = &*i.self_ty {
-            &type_path.path.segments.first().unwrap().ident
+            &type_path.path.segments.last().unwrap().ident
         } else {
-            panic!("Not a path when visiting item_impl");
+            self.unsupported_impls.entry(trait_ident).or_default().push(RfoodError::new(
+                ErrorCode::UnsupportedType,
+                "Traits can only be implemented for structs",
+            ).with_span(&i.self_ty));
+            return;
         };
-        let struct_ = self.get_struct_by_name(&struct_name);
-
-        // If the generator doesnt have any generators yet add an empty list
-        if !self.generators.contains_key(&trait_ident) {
-            self.generators.insert(trait_ident.clone(), Vec::new());
+        // An impl of a trait for an enum, such as the impls of the foreign supertraits of a
+        // transformed trait, does not make the enum a generator
+        if self.is_enum(struct_name) {
+            return;
         }
+        let struct_ = match self.get_struct_by_name(&struct_name) {
+            Ok(struct_) => struct_,
+            Err(error) => {
+                self.unsupported_impls.entry(trait_ident).or_default().push(error.with_span(&i.self_ty));
+                return;
+            }
+        };
+        self.item_modules.insert(struct_name.clone(), self.current_module.clone());
 
-        // Push the struct to the traits generator list
+        // Push the struct to the traits generator list, which is empty if this is the first
         self.generators
-            .get_mut(&trait_ident)
-            .unwrap()
+            .entry(trait_ident)
+            .or_default()
             .push((struct_.clone(), i.clone()));
     }
 
@@ -702,14 +1317,30 @@ impl<'ast> Visit<'ast> for Gamma {
         // If the first argument of the function is an enum, then it is a consumer so add it to the
         // enum consumers
         if let Some(FnArg::Typed(PatType { ty, .. })) = i.sig.inputs.first() {
-            let first_arg_type = ty.get_delta_type().name;
-            if self.is_enum(&first_arg_type) {
-                self.add_enum_consumer(&self.get_enum(&first_arg_type).unwrap(), i);
+            let expanded_type = self.expand_type_aliases(ty);
+            let first_arg_type = expanded_type.get_delta_type().name;
+            if let Some(enum_) = self.get_enum(&first_arg_type).ok().filter(|enum_| is_enum_given_type_params(&expanded_type, enum_, &i.sig.generics)) {
+                self.add_enum_consumer(&enum_, i);
+                self.item_modules.insert(i.sig.ident.clone(), self.current_module.clone());
             }
         }
     }
 }
 
-pub fn generate_gamma(syntax: &syn::File) -> Gamma {
+/// Collect the type aliases declared in a file, including those in nested modules
+#[derive(Default)]
+struct CollectTypeAliases {
+    aliases: HashMap<Ident, ItemType>,
+}
+impl<'ast> Visit<'ast> for CollectTypeAliases {
+    fn visit_item_type(&mut self, i: &'ast ItemType) {
+        self.aliases.insert(i.ident.clone(), i.clone());
+    }
+
+    // Items inside functions are not in scope for the rest of the file
+    fn visit_item_fn(&mut self, _: &'ast ItemFn) {}
+}
+
+pub fn generate_gamma(syntax: &syn::File) -> RfoodResult<Gamma> {
     Gamma::from_file(syntax)
 }
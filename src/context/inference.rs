@@ -0,0 +1,1584 @@
+//! Local type inference for function bodies
+//!
+//! Every expression is given a type, unknown types are type variables which are solved by
+//! unifying the types the expression is used as, e.g. the argument types of a signature, the
+//! types of struct and variant fields or the type of the other side of a binary operation.
+//! Integer and float literals are variables which can only be solved by a numeric type, if
+//! nothing constrains them they default to `i32`/`f64` as in Rust.
+//!
+//! Expected types are unified ignoring boxes and references, these are added or removed by the
+//! transformation so the code being transformed does not have to agree on them.
+
+use std::collections::HashMap;
+use quote::quote;
+use syn::*;
+use syn::spanned::Spanned;
+use syn::__private::Span;
+use crate::context::delta::*;
+use crate::context::gamma::{Gamma, has_self_receiver};
+use crate::context::errors::*;
+
+/// A type found by inference
+#[derive(Debug, Clone, PartialEq)]
+pub enum InferType {
+    /// A type which is not known yet
+    Var(usize),
+    /// A named type and its generic arguments, e.g. `Vec<i32>`
+    Named(Ident, Vec<InferType>),
+    Ref(Box<InferType>),
+    MutRef(Box<InferType>),
+    Box(Box<InferType>),
+    /// A tuple, the unit type is the empty tuple
+    Tuple(Vec<InferType>),
+    /// An array or slice
+    Array(Box<InferType>),
+    /// A function, function pointer or closure
+    Fn(Vec<InferType>, Box<InferType>),
+    /// The type of an expression which never finishes, e.g. `return`
+    Never,
+}
+
+impl InferType {
+    pub fn named(name: &str) -> Self {
+        InferType::Named(Ident::new(name, Span::call_site()), Vec::new())
+    }
+
+    pub fn generic(name: &str, args: Vec<InferType>) -> Self {
+        InferType::Named(Ident::new(name, Span::call_site()), args)
+    }
+
+    /// The type a shared pointer points to, e.g. `T` of `Rc<T>` or `Arc<T>`
+    fn get_shared_pointer_inner(&self) -> Option<&InferType> {
+        match self {
+            InferType::Named(name, args) if (name == "Rc" || name == "Arc") && args.len() == 1 => Some(&args[0]),
+            _ => None,
+        }
+    }
+
+    /// A shared or mutable borrow of a type
+    pub fn borrow(mutable: bool, type_: InferType) -> Self {
+        if mutable {
+            InferType::MutRef(Box::new(type_))
+        } else {
+            InferType::Ref(Box::new(type_))
+        }
+    }
+
+    pub fn unit() -> Self {
+        InferType::Tuple(Vec::new())
+    }
+}
+
+/// What a type variable can be solved by
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VarKind {
+    Any,
+    Integer,
+    Float,
+}
+
+pub(crate) fn is_integer_name(ident: &Ident) -> bool {
+    ["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize"]
+        .iter()
+        .any(|name| ident == name)
+}
+
+pub(crate) fn is_float_name(ident: &Ident) -> bool {
+    ident == "f32" || ident == "f64"
+}
+
+/// The module of a node and its location in the source, nodes created by the transformation have
+/// no location. Lines and columns start again in each file of a crate, each file is a different
+/// module so the module path tells apart nodes at the same location in different files.
+type SpanKey = (Vec<Ident>, usize, usize, usize, usize);
+
+fn span_key<T: Spanned>(node: &T, module_path: &[Ident]) -> Option<SpanKey> {
+    let span = node.span();
+    let (start, end) = (span.start(), span.end());
+    if start == end {
+        return None;
+    }
+    Some((module_path.to_vec(), start.line, start.column, end.line, end.column))
+}
+
+/// The types inferred for the expressions and variables of a function body
+///
+/// Types are found by the module and location of the expression or variable in the source, so
+/// only the code as it was before the transformation can be looked up. A location with two different
+/// types (e.g. a default method copied into each generator) has no type.
+#[derive(Debug, Clone, Default)]
+pub struct InferredTypes {
+    exprs: HashMap<SpanKey, Option<DeltaType>>,
+    bindings: HashMap<SpanKey, Option<DeltaType>>,
+    /// The types of the variables declared in the body by name, including types delta cannot
+    /// represent (e.g. tuples). A variable declared twice with different types has no type.
+    variables: HashMap<Ident, Option<InferType>>,
+}
+
+impl InferredTypes {
+    /// Get the type of an expression in the given module
+    pub fn get_expr(&self, expr: &Expr, module_path: &[Ident]) -> Option<DeltaType> {
+        self.exprs.get(&span_key(expr, module_path)?).cloned().flatten()
+    }
+
+    /// Get the type of the variable bound by an identifier in a pattern in the given module
+    pub fn get_binding(&self, ident: &Ident, module_path: &[Ident]) -> Option<DeltaType> {
+        self.bindings.get(&span_key(ident, module_path)?).cloned().flatten()
+    }
+
+    /// Check if a variable is declared in the body
+    pub fn has_variable(&self, ident: &Ident) -> bool {
+        self.variables.contains_key(ident)
+    }
+
+    fn insert<K: Eq + std::hash::Hash, T: Clone + PartialEq>(types: &mut HashMap<K, Option<T>>, key: K, type_: Option<T>) {
+        let entry = types.entry(key).or_insert_with(|| type_.clone());
+        if *entry != type_ {
+            *entry = None;
+        }
+    }
+}
+
+/// Substitutions applied to types written in the source
+#[derive(Debug, Clone, Default)]
+struct TypeParams {
+    self_type: Option<InferType>,
+    generics: HashMap<Ident, InferType>,
+}
+
+/// An instantiated signature, the types of the inputs (including any receiver) and the output
+type InferSignature = (Vec<InferType>, InferType);
+
+/// The types of the expressions and the variables bound by patterns, in the order they are found
+#[derive(Default)]
+struct RecordedTypes {
+    exprs: Vec<(SpanKey, InferType)>,
+    bindings: Vec<(Ident, InferType)>,
+}
+
+pub struct TypeInference<'a> {
+    gamma: &'a Gamma,
+    delta: &'a Delta,
+    /// The solution and kind of each type variable
+    vars: Vec<(Option<InferType>, VarKind)>,
+    /// Variables declared in the code being inferred, innermost scope last
+    scopes: Vec<HashMap<Ident, InferType>>,
+    self_type: Option<InferType>,
+    return_type: Option<InferType>,
+    /// The types found for each expression/binding, only recorded when inferring a whole body
+    recorded: Option<RecordedTypes>,
+    /// Errors found while inferring, these are only reported if the type cannot be found
+    errors: Vec<RfoodError>,
+}
+
+impl<'a> TypeInference<'a> {
+    pub fn new(delta: &'a Delta, gamma: &'a Gamma) -> Self {
+        TypeInference {
+            gamma,
+            delta,
+            vars: Vec::new(),
+            scopes: vec![HashMap::new()],
+            self_type: delta.self_ty.as_ref().map(|ident| InferType::Named(ident.clone(), Vec::new())),
+            return_type: None,
+            recorded: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Get the type of an expression
+    ///
+    /// Methods are looked up in the destructors and consumers of gamma, then in the method
+    /// signatures of the standard library, following the types the receiver derefs to
+    ///
+    /// Literals which nothing constrains have their default type. Otherwise an expression which
+    /// cannot be typed uses the type inferred for it in the body being transformed, if there is one
+    pub fn get_type_of_expr(mut self, expr: &Expr) -> RfoodResult<DeltaType> {
+        let type_ = self.infer_expr(expr, None);
+        self.to_delta_type(&type_)
+            .or_else(|| self.delta.inferred.get_expr(expr, &self.delta.module_path))
+            .ok_or_else(|| match self.errors.first() {
+                Some(error) => error.clone(),
+                None => RfoodError::type_inference_failed(expr),
+            })
+    }
+
+    /// Get the types expected of the arguments of a call or method call of the standard library
+    /// (e.g. `Some(..)` or `shapes.push(..)`), `None` if the signature is not known. The signature
+    /// is instantiated by the type of the receiver and the type required of the call, so the
+    /// argument of `Some(..)` required to be an `Option<Box<dyn Shape>>` must be a
+    /// `Box<dyn Shape>`. Receivers are typed by `receiver_type` if it is given.
+    pub fn get_expected_arg_types(
+        mut self,
+        expr: &Expr,
+        receiver_type: Option<&DeltaType>,
+        required_type: Option<&DeltaType>,
+    ) -> Option<Vec<Option<DeltaType>>> {
+        let (inputs, output) = match expr {
+            Expr::Call(ExprCall { func: box Expr::Path(ExprPath { path, .. }), .. }) => self.get_std_function_signature(path)?,
+            Expr::MethodCall(ExprMethodCall { receiver, method, .. }) => {
+                let receiver_type = match receiver_type {
+                    Some(receiver_type) => self.import_delta_type(receiver_type),
+                    None => self.infer_expr(receiver, None),
+                };
+                let self_type = self.strip_pointers(&receiver_type);
+                self.get_std_method_signature(&receiver_type, &self_type, method)?
+            }
+            _ => return None,
+        };
+        if let Some(required_type) = required_type {
+            let required_type = self.import_delta_type(required_type);
+            self.unify_expected(&output, &required_type);
+        }
+        Some(inputs.iter().map(|input| self.to_delta_type(input)).collect())
+    }
+
+    /// Get the types of the variables bound by matching a pattern against an expression, e.g. the
+    /// `shape` of `Some(shape)` matched against an `Option<Box<dyn Shape>>`
+    pub fn get_pattern_types(mut self, pat: &Pat, expr: &Expr) -> Vec<(Ident, Option<DeltaType>)> {
+        let type_ = self.infer_expr(expr, None);
+        self.scopes.push(HashMap::new());
+        self.bind_pattern(pat, &type_);
+        let scope = self.scopes.pop().unwrap();
+        get_pattern_idents(pat)
+            .into_iter()
+            .map(|ident| {
+                let delta_type = scope.get(&ident).and_then(|type_| self.to_delta_type(type_));
+                (ident, delta_type)
+            })
+            .collect()
+    }
+
+    /// Infer the types of every expression and variable in the body of a function
+    pub fn infer_body(mut self, signature: &Signature, block: &Block, self_type: Option<&Ident>) -> InferredTypes {
+        self.recorded = Some(RecordedTypes::default());
+        if let Some(self_type) = self_type {
+            self.self_type = Some(InferType::Named(self_type.clone(), Vec::new()));
+        }
+
+        let mut type_params = TypeParams {
+            self_type: self.self_type.clone(),
+            ..TypeParams::default()
+        };
+        // A closure of a type parameter bound by `Fn` has the type of the bound
+        for (ident, path) in get_fn_bounds(&signature.generics) {
+            let fn_type = self.get_written_type(&Type::Path(TypePath { qself: None, path }), &type_params);
+            type_params.generics.insert(ident, fn_type);
+        }
+        for input in &signature.inputs {
+            match input {
+                FnArg::Receiver(receiver) => {
+                    let self_type = self.self_type.clone().unwrap_or_else(|| self.fresh(VarKind::Any));
+                    let type_ = match receiver.reference {
+                        Some(_) => InferType::borrow(receiver.mutability.is_some(), self_type),
+                        None => self_type,
+                    };
+                    self.declare(&Ident::new("self", Span::call_site()), type_);
+                }
+                FnArg::Typed(PatType { pat, ty, .. }) => {
+                    let type_ = self.get_written_type(ty, &type_params);
+                    self.bind_pattern(pat, &type_);
+                }
+            }
+        }
+
+        let return_type = match &signature.output {
+            ReturnType::Default => InferType::unit(),
+            ReturnType::Type(_, ty) => self.get_written_type(ty, &type_params),
+        };
+        self.return_type = Some(return_type.clone());
+        self.infer_block(block, Some(&return_type));
+
+        let RecordedTypes { exprs, bindings } = self.recorded.take().unwrap();
+        let mut inferred = InferredTypes::default();
+        for (key, type_) in exprs {
+            InferredTypes::insert(&mut inferred.exprs, key, self.to_delta_type(&type_));
+        }
+        for (ident, type_) in bindings {
+            if let Some(key) = span_key(&ident, &self.delta.module_path) {
+                InferredTypes::insert(&mut inferred.bindings, key, self.to_delta_type(&type_));
+            }
+            InferredTypes::insert(&mut inferred.variables, ident, Some(self.zonk(&type_)));
+        }
+        inferred
+    }
+
+    /// Convert a type to the type stored in delta, types which delta cannot represent (e.g.
+    /// tuples) and unsolved variables have no delta type
+    pub fn to_delta_type(&self, type_: &InferType) -> Option<DeltaType> {
+        // Types which are not known, or cannot be written like `!`, are not useful to the transformation
+        Some(self.to_partial_delta_type(type_)).filter(|delta_type| !matches!(delta_type.kind, TypeKind::Other(_)))
+    }
+
+    /// Create the delta type of a type, any parts which are not known yet are `_`
+    fn to_partial_delta_type(&self, type_: &InferType) -> DeltaType {
+        let to_delta_types = |types: &Vec<InferType>| -> Vec<DeltaType> {
+            types.iter().map(|type_| self.to_partial_delta_type(type_)).collect()
+        };
+        match self.resolve(type_) {
+            InferType::Named(name, args) if args.len() == 1 && is_wrapper_ident(&name) => {
+                let delta_type = self.to_partial_delta_type(&args[0]);
+                delta_type.with_ref_type(RefType::from_wrapper(&name, delta_type.ref_type.clone()).unwrap())
+            }
+            InferType::Named(name, args) => DeltaType {
+                name,
+                ref_type: RefType::None,
+                kind: TypeKind::Path(Vec::new(), to_delta_types(&args)),
+            },
+            InferType::Box(inner) => {
+                let delta_type = self.to_partial_delta_type(&inner);
+                delta_type.with_ref_type(RefType::Box(Box::new(delta_type.ref_type.clone())))
+            }
+            InferType::Ref(inner) => {
+                let delta_type = self.to_partial_delta_type(&inner);
+                delta_type.with_ref_type(RefType::Ref(Box::new(delta_type.ref_type.clone())))
+            }
+            InferType::MutRef(inner) => {
+                let delta_type = self.to_partial_delta_type(&inner);
+                delta_type.with_ref_type(RefType::MutRef(Box::new(delta_type.ref_type.clone())))
+            }
+            InferType::Tuple(elems) => DeltaType::tuple(to_delta_types(&elems)),
+            InferType::Array(elem) => DeltaType::unnamed(TypeKind::Array(Box::new(self.to_partial_delta_type(&elem)), None)),
+            InferType::Fn(inputs, output) => DeltaType {
+                name: Ident::new("fn", Span::call_site()),
+                ref_type: RefType::None,
+                kind: TypeKind::Fn(to_delta_types(&inputs), Box::new(self.to_partial_delta_type(&output))),
+            },
+            InferType::Never => DeltaType::unnamed(TypeKind::Other(Box::new(parse_quote!(!)))),
+            InferType::Var(var) => match self.vars[var].1 {
+                VarKind::Integer => DeltaType::new("i32", RefType::None),
+                VarKind::Float => DeltaType::new("f64", RefType::None),
+                VarKind::Any => DeltaType::unknown(),
+            },
+        }
+    }
+
+    /// Create the type of a variable stored in delta
+    fn import_delta_type(&mut self, delta_type: &DeltaType) -> InferType {
+        self.get_written_type(&delta_type.to_type(), &TypeParams::default())
+    }
+
+    fn fresh(&mut self, kind: VarKind) -> InferType {
+        self.vars.push((None, kind));
+        InferType::Var(self.vars.len() - 1)
+    }
+
+    /// Follow the solutions of type variables until a type which is not a solved variable
+    fn resolve(&self, type_: &InferType) -> InferType {
+        let mut type_ = type_.clone();
+        while let InferType::Var(var) = type_ {
+            match &self.vars[var].0 {
+                Some(solution) => type_ = solution.clone(),
+                None => break,
+            }
+        }
+        type_
+    }
+
+    /// Remove any boxes, shared pointers and references, e.g. `&Rc<Shape>` is `Shape`
+    fn strip_pointers(&self, type_: &InferType) -> InferType {
+        match self.resolve(type_) {
+            InferType::Ref(box inner) | InferType::MutRef(box inner) | InferType::Box(box inner) => self.strip_pointers(&inner),
+            type_ => match type_.get_shared_pointer_inner() {
+                Some(inner) => self.strip_pointers(inner),
+                None => type_,
+            },
+        }
+    }
+
+    /// Resolve all the type variables in a type, unsolved numeric variables take their default
+    fn zonk(&self, type_: &InferType) -> InferType {
+        match self.resolve(type_) {
+            InferType::Var(var) => match self.vars[var].1 {
+                VarKind::Integer => InferType::named("i32"),
+                VarKind::Float => InferType::named("f64"),
+                VarKind::Any => InferType::Var(var),
+            },
+            InferType::Named(name, args) => InferType::Named(name, args.iter().map(|arg| self.zonk(arg)).collect()),
+            InferType::Ref(inner) => InferType::Ref(Box::new(self.zonk(&inner))),
+            InferType::MutRef(inner) => InferType::MutRef(Box::new(self.zonk(&inner))),
+            InferType::Box(inner) => InferType::Box(Box::new(self.zonk(&inner))),
+            InferType::Array(inner) => InferType::Array(Box::new(self.zonk(&inner))),
+            InferType::Tuple(elems) => InferType::Tuple(elems.iter().map(|elem| self.zonk(elem)).collect()),
+            InferType::Fn(inputs, output) => InferType::Fn(
+                inputs.iter().map(|input| self.zonk(input)).collect(),
+                Box::new(self.zonk(&output)),
+            ),
+            InferType::Never => InferType::Never,
+        }
+    }
+
+    /// Use a type inferred by another inference, its unsolved variables are replaced with new
+    /// variables
+    fn import(&mut self, type_: &InferType, vars: &mut HashMap<usize, InferType>) -> InferType {
+        match type_ {
+            InferType::Var(var) => match vars.get(var) {
+                Some(new_var) => new_var.clone(),
+                None => {
+                    let new_var = self.fresh(VarKind::Any);
+                    vars.insert(*var, new_var.clone());
+                    new_var
+                }
+            },
+            InferType::Named(name, args) => InferType::Named(name.clone(), args.iter().map(|arg| self.import(arg, vars)).collect()),
+            InferType::Ref(inner) => InferType::Ref(Box::new(self.import(inner, vars))),
+            InferType::MutRef(inner) => InferType::MutRef(Box::new(self.import(inner, vars))),
+            InferType::Box(inner) => InferType::Box(Box::new(self.import(inner, vars))),
+            InferType::Array(inner) => InferType::Array(Box::new(self.import(inner, vars))),
+            InferType::Tuple(elems) => InferType::Tuple(elems.iter().map(|elem| self.import(elem, vars)).collect()),
+            InferType::Fn(inputs, output) => InferType::Fn(
+                inputs.iter().map(|input| self.import(input, vars)).collect(),
+                Box::new(self.import(output, vars)),
+            ),
+            InferType::Never => InferType::Never,
+        }
+    }
+
+    fn occurs(&self, var: usize, type_: &InferType) -> bool {
+        match self.resolve(type_) {
+            InferType::Var(other) => var == other,
+            InferType::Named(_, args) | InferType::Tuple(args) => args.iter().any(|arg| self.occurs(var, arg)),
+            InferType::Ref(inner) | InferType::MutRef(inner) | InferType::Box(inner) | InferType::Array(inner) => self.occurs(var, &inner),
+            InferType::Fn(inputs, output) => {
+                inputs.iter().any(|input| self.occurs(var, input)) || self.occurs(var, &output)
+            }
+            InferType::Never => false,
+        }
+    }
+
+    /// Make two types equal, returns false if they cannot be. Generators and variants are
+    /// treated as equal to their interface/datatype.
+    fn unify(&mut self, left: &InferType, right: &InferType) -> bool {
+        match (self.resolve(left), self.resolve(right)) {
+            (InferType::Var(left), InferType::Var(right)) if left == right => true,
+            (InferType::Var(left), InferType::Var(right)) => {
+                let kind = match (self.vars[left].1, self.vars[right].1) {
+                    (VarKind::Any, kind) | (kind, VarKind::Any) => kind,
+                    (left_kind, right_kind) if left_kind == right_kind => left_kind,
+                    _ => return false,
+                };
+                self.vars[right].1 = kind;
+                self.vars[left].0 = Some(InferType::Var(right));
+                true
+            }
+            (InferType::Var(var), type_) | (type_, InferType::Var(var)) => {
+                let is_allowed = match (self.vars[var].1, &type_) {
+                    (VarKind::Any, _) => true,
+                    (VarKind::Integer, InferType::Named(name, _)) => is_integer_name(name),
+                    (VarKind::Float, InferType::Named(name, _)) => is_float_name(name),
+                    _ => false,
+                };
+                if !is_allowed || self.occurs(var, &type_) {
+                    return false;
+                }
+                self.vars[var].0 = Some(type_);
+                true
+            }
+            (InferType::Never, _) | (_, InferType::Never) => true,
+            (InferType::Named(left_name, left_args), InferType::Named(right_name, right_args)) => {
+                if left_name != right_name {
+                    return self.gamma.is_subtype_of(&left_name, &right_name)
+                        || self.gamma.is_subtype_of(&right_name, &left_name);
+                }
+                // Types from delta have no generic arguments
+                left_args.len() != right_args.len() || self.unify_all(&left_args, &right_args)
+            }
+            // A mutable borrow can be used where a shared borrow is expected
+            (InferType::Ref(left) | InferType::MutRef(left), InferType::Ref(right) | InferType::MutRef(right))
+            | (InferType::Box(left), InferType::Box(right))
+            | (InferType::Array(left), InferType::Array(right)) => self.unify(&left, &right),
+            (InferType::Tuple(left), InferType::Tuple(right)) => {
+                left.len() == right.len() && self.unify_all(&left, &right)
+            }
+            (InferType::Fn(left_inputs, left_output), InferType::Fn(right_inputs, right_output)) => {
+                left_inputs.len() == right_inputs.len()
+                    && self.unify_all(&left_inputs, &right_inputs)
+                    && self.unify(&left_output, &right_output)
+            }
+            _ => false,
+        }
+    }
+
+    fn unify_all(&mut self, left: &[InferType], right: &[InferType]) -> bool {
+        left.iter().zip(right).fold(true, |unified, (left, right)| self.unify(left, right) && unified)
+    }
+
+    /// Unify the type of an expression with the type it is used as. Boxes and references are
+    /// added or removed by the transformation so they are ignored, unless the expression has no
+    /// type yet.
+    fn unify_expected(&mut self, type_: &InferType, expected: &InferType) -> bool {
+        match (self.resolve(type_), self.resolve(expected)) {
+            (InferType::Var(var), _) | (_, InferType::Var(var)) if self.vars[var].1 == VarKind::Any => {
+                self.unify(type_, expected)
+            }
+            _ => {
+                let (type_, expected) = (self.strip_pointers(type_), self.strip_pointers(expected));
+                self.unify(&type_, &expected)
+            }
+        }
+    }
+
+    fn declare(&mut self, ident: &Ident, type_: InferType) {
+        self.scopes.last_mut().unwrap().insert(ident.clone(), type_);
+    }
+
+    fn lookup(&mut self, ident: &Ident) -> Option<InferType> {
+        if let Some(type_) = self.scopes.iter().rev().find_map(|scope| scope.get(ident).cloned()) {
+            return Some(type_);
+        }
+        if let Some(delta_type) = self.delta.types.get(ident) {
+            return Some(self.import_delta_type(&delta_type.clone()));
+        }
+        // A variable of the body being transformed which delta has no type for
+        match self.delta.inferred.variables.get(ident).cloned() {
+            Some(Some(type_)) => Some(self.import(&type_, &mut HashMap::new())),
+            Some(None) => Some(self.fresh(VarKind::Any)),
+            None => None,
+        }
+    }
+
+    fn record_expr(&mut self, expr: &Expr, type_: &InferType) {
+        if let Some(recorded) = &mut self.recorded {
+            if let Some(key) = span_key(expr, &self.delta.module_path) {
+                recorded.exprs.push((key, type_.clone()));
+            }
+        }
+    }
+
+    fn record_binding(&mut self, ident: &Ident, type_: &InferType) {
+        if let Some(recorded) = &mut self.recorded {
+            recorded.bindings.push((ident.clone(), type_.clone()));
+        }
+    }
+
+    /// Create the type of a type written in the source
+    fn get_written_type(&mut self, type_: &Type, type_params: &TypeParams) -> InferType {
+        match type_ {
+            Type::Path(TypePath { qself: None, path }) => {
+                let segment = path.segments.last().unwrap();
+                if path.segments.len() == 1 {
+                    if let Some(generic) = type_params.generics.get(&segment.ident) {
+                        return generic.clone();
+                    }
+                    if segment.ident == "Self" {
+                        if let Some(self_type) = &type_params.self_type {
+                            return self_type.clone();
+                        }
+                    }
+                }
+                if self.gamma.type_aliases.contains_key(&segment.ident) {
+                    let expanded = self.gamma.expand_type_aliases(type_);
+                    if expanded != *type_ {
+                        return self.get_written_type(&expanded, type_params);
+                    }
+                }
+                match &segment.arguments {
+                    PathArguments::Parenthesized(ParenthesizedGenericArguments { inputs, output, .. }) => {
+                        self.get_written_fn_type(inputs.iter(), output, type_params)
+                    }
+                    arguments => {
+                        let args: Vec<InferType> = match arguments {
+                            PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+                                .iter()
+                                .filter_map(|arg| match arg {
+                                    GenericArgument::Type(ty) => Some(self.get_written_type(ty, type_params)),
+                                    _ => None,
+                                })
+                                .collect(),
+                            _ => Vec::new(),
+                        };
+                        if segment.ident == "Box" && args.len() == 1 {
+                            return InferType::Box(Box::new(args.into_iter().next().unwrap()));
+                        }
+                        InferType::Named(segment.ident.clone(), args)
+                    }
+                }
+            }
+            Type::Reference(TypeReference { elem, mutability, .. }) => InferType::borrow(mutability.is_some(), self.get_written_type(elem, type_params)),
+            Type::TraitObject(TypeTraitObject { bounds, .. }) | Type::ImplTrait(TypeImplTrait { bounds, .. }) => {
+                match bounds.iter().find_map(|bound| match bound {
+                    TypeParamBound::Trait(TraitBound { path, .. }) => Some(path.clone()),
+                    _ => None,
+                }) {
+                    Some(path) => self.get_written_type(&Type::Path(TypePath { qself: None, path }), type_params),
+                    None => self.fresh(VarKind::Any),
+                }
+            }
+            Type::Tuple(TypeTuple { elems, .. }) => {
+                InferType::Tuple(elems.iter().map(|elem| self.get_written_type(elem, type_params)).collect())
+            }
+            Type::Array(TypeArray { elem, .. }) | Type::Slice(TypeSlice { elem, .. }) => {
+                InferType::Array(Box::new(self.get_written_type(elem, type_params)))
+            }
+            Type::BareFn(TypeBareFn { inputs, output, .. }) => {
+                self.get_written_fn_type(inputs.iter().map(|input| &input.ty), output, type_params)
+            }
+            Type::Paren(TypeParen { elem, .. }) | Type::Group(TypeGroup { elem, .. }) => self.get_written_type(elem, type_params),
+            Type::Never(_) => InferType::Never,
+            _ => self.fresh(VarKind::Any),
+        }
+    }
+
+    fn get_written_fn_type<'t>(
+        &mut self,
+        inputs: impl Iterator<Item = &'t Type>,
+        output: &ReturnType,
+        type_params: &TypeParams,
+    ) -> InferType {
+        let inputs = inputs.map(|input| self.get_written_type(input, type_params)).collect();
+        let output = match output {
+            ReturnType::Default => InferType::unit(),
+            ReturnType::Type(_, ty) => self.get_written_type(ty, type_params),
+        };
+        InferType::Fn(inputs, Box::new(output))
+    }
+
+    /// Create the types of a signature, each generic parameter is a new type variable (or a
+    /// function type if it is bound by `Fn`)
+    fn instantiate_signature(&mut self, signature: &Signature, self_type: Option<InferType>) -> InferSignature {
+        let type_params = TypeParams {
+            self_type,
+            generics: HashMap::new(),
+        };
+        self.instantiate_signature_with(signature, type_params)
+    }
+
+    /// Create the types of a signature with the substitutions of the item it is in, e.g. the
+    /// generic parameters of an impl
+    fn instantiate_signature_with(&mut self, signature: &Signature, mut type_params: TypeParams) -> InferSignature {
+        let self_type = type_params.self_type.clone();
+
+        for type_param in signature.generics.type_params() {
+            let var = self.fresh(VarKind::Any);
+            type_params.generics.insert(type_param.ident.clone(), var);
+        }
+        for (ident, path) in get_fn_bounds(&signature.generics) {
+            let fn_type = self.get_written_type(&Type::Path(TypePath { qself: None, path }), &type_params);
+            let var = type_params.generics[&ident].clone();
+            self.unify(&var, &fn_type);
+        }
+
+        let inputs = signature.inputs.iter().map(|input| match input {
+            FnArg::Receiver(Receiver { reference, mutability, .. }) => {
+                let self_type = self_type.clone().unwrap_or_else(|| self.fresh(VarKind::Any));
+                match reference {
+                    Some(_) => InferType::borrow(mutability.is_some(), self_type),
+                    None => self_type,
+                }
+            }
+            FnArg::Typed(PatType { ty, .. }) => self.get_written_type(ty, &type_params),
+        }).collect();
+        let output = match &signature.output {
+            ReturnType::Default => InferType::unit(),
+            ReturnType::Type(_, ty) => self.get_written_type(ty, &type_params),
+        };
+        (inputs, output)
+    }
+
+    /// Infer the arguments of a call with the types of the inputs of the function, returns the
+    /// output type
+    fn infer_call_args<'e>(&mut self, args: impl Iterator<Item = &'e Expr>, (inputs, output): InferSignature) -> InferType {
+        let mut inputs = inputs.into_iter();
+        for arg in args {
+            let input = inputs.next();
+            self.infer_expr(arg, input.as_ref());
+        }
+        output
+    }
+
+    /// Get the fields of the struct or variant a path refers to
+    fn get_fields_of_path(&self, path: &Path) -> Option<Fields> {
+        let ident = &path.segments.last().unwrap().ident;
+        self.gamma
+            .get_struct_by_name(ident)
+            .map(|struct_| struct_.fields)
+            .or_else(|_| self.gamma.get_constructor(ident).map(|variant| variant.fields))
+            .ok()
+    }
+
+    fn get_field_type(&mut self, fields: &Fields, member: &Member) -> Option<InferType> {
+        let type_params = TypeParams {
+            self_type: self.self_type.clone(),
+            ..TypeParams::default()
+        };
+        let field = fields
+            .iter()
+            .zip(get_field_members(fields))
+            .find_map(|(field, field_member)| if field_member == *member { Some(field) } else { None })?;
+        Some(self.get_written_type(&field.ty, &type_params))
+    }
+
+    /// Infer the type of an expression, unifying it with the type it is expected to have
+    pub fn infer_expr(&mut self, expr: &Expr, expected: Option<&InferType>) -> InferType {
+        let type_ = self.infer_expr_inner(expr, expected);
+        if let Some(expected) = expected {
+            self.unify_expected(&type_, expected);
+        }
+        self.record_expr(expr, &type_);
+        type_
+    }
+
+    fn infer_expr_inner(&mut self, expr: &Expr, expected: Option<&InferType>) -> InferType {
+        match expr {
+            Expr::Lit(ExprLit { lit, .. }) => {
+                if let Some(delta_type) = self.delta.inferred.get_expr(expr, &self.delta.module_path) {
+                    return self.import_delta_type(&delta_type);
+                }
+                match lit {
+                    Lit::Int(lit_int) if lit_int.suffix().is_empty() => self.fresh(VarKind::Integer),
+                    Lit::Int(lit_int) => InferType::named(lit_int.suffix()),
+                    Lit::Float(lit_float) if lit_float.suffix().is_empty() => self.fresh(VarKind::Float),
+                    Lit::Float(lit_float) => InferType::named(lit_float.suffix()),
+                    Lit::Bool(_) => InferType::named("bool"),
+                    // String literals are `&'static str`, lifetimes are not part of the types
+                    Lit::Str(_) => InferType::Ref(Box::new(InferType::named("str"))),
+                    Lit::Char(_) => InferType::named("char"),
+                    Lit::Byte(_) => InferType::named("u8"),
+                    Lit::ByteStr(_) => InferType::Ref(Box::new(InferType::Array(Box::new(InferType::named("u8"))))),
+                    _ => self.fresh(VarKind::Any),
+                }
+            }
+            Expr::Path(ExprPath { qself, path, .. }) => self.infer_path(qself.as_ref(), path),
+            Expr::Unary(ExprUnary { expr, op: UnOp::Deref(_), .. }) => {
+                let type_ = self.infer_expr(expr, None);
+                match self.resolve(&type_) {
+                    InferType::Ref(box inner) | InferType::MutRef(box inner) | InferType::Box(box inner) => inner,
+                    type_ => type_.get_shared_pointer_inner().cloned().unwrap_or(type_),
+                }
+            }
+            Expr::Unary(ExprUnary { expr, .. }) => self.infer_expr(expr, expected),
+            Expr::Reference(ExprReference { expr, mutability, .. }) => {
+                let expected_inner = match expected.map(|expected| self.resolve(expected)) {
+                    Some(InferType::Ref(inner) | InferType::MutRef(inner)) => Some(*inner),
+                    _ => None,
+                };
+                InferType::borrow(mutability.is_some(), self.infer_expr(expr, expected_inner.as_ref()))
+            }
+            Expr::Paren(ExprParen { expr, .. }) | Expr::Group(ExprGroup { expr, .. }) => self.infer_expr(expr, expected),
+            Expr::Call(ExprCall { args, .. }) if new_box_call_expr(expr).is_ok() => {
+                let expected_inner = match expected.map(|expected| self.resolve(expected)) {
+                    Some(InferType::Box(inner)) => Some(*inner),
+                    _ => None,
+                };
+                let inner = self.infer_expr(args.first().unwrap(), expected_inner.as_ref());
+                InferType::Box(Box::new(inner))
+            }
+            Expr::Call(expr_call) => self.infer_call(expr_call, expr),
+            Expr::MethodCall(expr_method_call) => self.infer_method_call(expr_method_call),
+            Expr::Struct(ExprStruct { path, fields, rest, .. }) => {
+                let type_ = InferType::Named(self.delta.get_type_of_instantiation_path(path, self.gamma), Vec::new());
+                let struct_fields = self.get_fields_of_path(path);
+                for field in fields {
+                    let field_type = struct_fields.as_ref().and_then(|struct_fields| self.get_field_type(struct_fields, &field.member));
+                    self.infer_expr(&field.expr, field_type.as_ref());
+                }
+                if let Some(rest) = rest {
+                    self.infer_expr(rest, Some(&type_));
+                }
+                type_
+            }
+            Expr::Field(ExprField { base, member, .. }) => {
+                let base_type = self.infer_expr(base, None);
+                match (self.strip_pointers(&base_type), member) {
+                    (InferType::Tuple(elems), Member::Unnamed(Index { index, .. })) if (*index as usize) < elems.len() => {
+                        elems[*index as usize].clone()
+                    }
+                    (InferType::Named(name, _), _) => {
+                        let field_type = self
+                            .gamma
+                            .get_struct_by_name(&name)
+                            .map_err(|error| error.with_span(member))
+                            .and_then(|struct_| self.get_field_type(&struct_.fields, member).ok_or_else(|| {
+                                RfoodError::not_found(&format!("{}.{}", name, get_member_ident(member)), "field").with_span(member)
+                            }));
+                        match field_type {
+                            Ok(field_type) => field_type,
+                            Err(error) => {
+                                self.errors.push(error);
+                                self.fresh(VarKind::Any)
+                            }
+                        }
+                    }
+                    _ => self.fresh(VarKind::Any),
+                }
+            }
+            Expr::Binary(ExprBinary { left, right, op, .. }) => self.infer_binary(left, op, right),
+            Expr::If(ExprIf { cond, then_branch, else_branch, .. }) => {
+                self.infer_expr(cond, Some(&InferType::named("bool")));
+                let then_type = self.infer_block(then_branch, expected);
+                match else_branch {
+                    Some((_, else_expr)) => {
+                        let else_type = self.infer_expr(else_expr, Some(&then_type));
+                        match self.resolve(&then_type) {
+                            InferType::Never => else_type,
+                            _ => then_type,
+                        }
+                    }
+                    None => InferType::unit(),
+                }
+            }
+            Expr::Let(ExprLet { pat, expr, .. }) => {
+                let type_ = self.infer_expr(expr, None);
+                self.bind_pattern(pat, &type_);
+                InferType::named("bool")
+            }
+            Expr::Block(ExprBlock { block, .. }) | Expr::Unsafe(ExprUnsafe { block, .. }) => self.infer_block(block, expected),
+            Expr::Match(ExprMatch { expr, arms, .. }) => {
+                let matched_type = self.infer_expr(expr, None);
+                let type_ = match expected {
+                    Some(expected) => expected.clone(),
+                    None => self.fresh(VarKind::Any),
+                };
+                let mut arm_types = Vec::new();
+                for arm in arms {
+                    self.scopes.push(HashMap::new());
+                    self.bind_pattern(&arm.pat, &matched_type);
+                    if let Some((_, guard)) = &arm.guard {
+                        self.infer_expr(guard, Some(&InferType::named("bool")));
+                    }
+                    arm_types.push(self.infer_expr(&arm.body, Some(&type_)));
+                    self.scopes.pop();
+                }
+                // Without an expected type the match has the type of its first arm which returns
+                arm_types
+                    .into_iter()
+                    .find(|arm_type| self.resolve(arm_type) != InferType::Never)
+                    .unwrap_or(InferType::Never)
+            }
+            Expr::Closure(ExprClosure { inputs, output, body, .. }) => {
+                let (expected_inputs, expected_output) = match expected.map(|expected| self.resolve(expected)) {
+                    Some(InferType::Fn(inputs, output)) => (inputs, Some(*output)),
+                    _ => (Vec::new(), None),
+                };
+                let type_params = TypeParams {
+                    self_type: self.self_type.clone(),
+                    ..TypeParams::default()
+                };
+
+                self.scopes.push(HashMap::new());
+                let input_types: Vec<InferType> = inputs.iter().enumerate().map(|(index, input)| {
+                    let type_ = match expected_inputs.get(index) {
+                        Some(expected_input) => expected_input.clone(),
+                        None => self.fresh(VarKind::Any),
+                    };
+                    self.bind_pattern(input, &type_);
+                    type_
+                }).collect();
+                let output_type = match (output, expected_output) {
+                    (ReturnType::Type(_, ty), _) => self.get_written_type(ty, &type_params),
+                    (ReturnType::Default, Some(expected_output)) => expected_output,
+                    (ReturnType::Default, None) => self.fresh(VarKind::Any),
+                };
+                let outer_return_type = self.return_type.replace(output_type.clone());
+                self.infer_expr(body, Some(&output_type));
+                self.return_type = outer_return_type;
+                self.scopes.pop();
+
+                InferType::Fn(input_types, Box::new(output_type))
+            }
+            Expr::Tuple(ExprTuple { elems, .. }) => {
+                let expected_elems = match expected.map(|expected| self.resolve(expected)) {
+                    Some(InferType::Tuple(expected_elems)) if expected_elems.len() == elems.len() => expected_elems,
+                    _ => Vec::new(),
+                };
+                InferType::Tuple(elems.iter().enumerate().map(|(index, elem)| {
+                    self.infer_expr(elem, expected_elems.get(index))
+                }).collect())
+            }
+            Expr::Array(ExprArray { elems, .. }) => {
+                let elem_type = match expected.map(|expected| self.resolve(expected)) {
+                    Some(InferType::Array(elem_type)) => *elem_type,
+                    _ => self.fresh(VarKind::Any),
+                };
+                for elem in elems {
+                    self.infer_expr(elem, Some(&elem_type));
+                }
+                InferType::Array(Box::new(elem_type))
+            }
+            Expr::Repeat(ExprRepeat { expr, len, .. }) => {
+                self.infer_expr(len, Some(&InferType::named("usize")));
+                InferType::Array(Box::new(self.infer_expr(expr, None)))
+            }
+            Expr::Index(ExprIndex { expr, index, .. }) => {
+                let container_type = self.infer_expr(expr, None);
+                let is_range = matches!(**index, Expr::Range(_));
+                match self.strip_pointers(&container_type) {
+                    InferType::Array(elem_type) => {
+                        self.infer_index(index, is_range, InferType::Array(elem_type.clone()), *elem_type)
+                    }
+                    InferType::Named(name, args) if (name == "Vec" || name == "VecDeque") && args.len() == 1 => {
+                        self.infer_index(index, is_range, InferType::Array(Box::new(args[0].clone())), args[0].clone())
+                    }
+                    InferType::Named(name, _) if name == "String" || name == "str" => {
+                        self.infer_index(index, is_range, InferType::named("str"), InferType::named("str"))
+                    }
+                    InferType::Named(name, args) if (name == "HashMap" || name == "BTreeMap") && args.len() == 2 => {
+                        self.infer_expr(index, Some(&args[0]));
+                        args[1].clone()
+                    }
+                    _ => {
+                        self.infer_expr(index, None);
+                        self.fresh(VarKind::Any)
+                    }
+                }
+            }
+            Expr::Range(ExprRange { from, to, .. }) => {
+                let type_ = self.fresh(VarKind::Any);
+                for bound in from.iter().chain(to.iter()) {
+                    self.infer_expr(bound, Some(&type_));
+                }
+                InferType::generic("Range", vec![type_])
+            }
+            Expr::Cast(ExprCast { expr, ty, .. }) => {
+                self.infer_expr(expr, None);
+                let type_params = TypeParams {
+                    self_type: self.self_type.clone(),
+                    ..TypeParams::default()
+                };
+                self.get_written_type(ty, &type_params)
+            }
+            Expr::Return(ExprReturn { expr, .. }) => {
+                if let Some(expr) = expr {
+                    let return_type = self.return_type.clone();
+                    self.infer_expr(expr, return_type.as_ref());
+                }
+                InferType::Never
+            }
+            Expr::Break(ExprBreak { expr, .. }) => {
+                if let Some(expr) = expr {
+                    self.infer_expr(expr, None);
+                }
+                InferType::Never
+            }
+            Expr::Continue(_) => InferType::Never,
+            Expr::Assign(ExprAssign { left, right, .. }) | Expr::AssignOp(ExprAssignOp { left, right, .. }) => {
+                let left_type = self.infer_expr(left, None);
+                self.infer_expr(right, Some(&left_type));
+                InferType::unit()
+            }
+            Expr::While(ExprWhile { cond, body, .. }) => {
+                self.scopes.push(HashMap::new());
+                self.infer_expr(cond, Some(&InferType::named("bool")));
+                self.infer_block(body, None);
+                self.scopes.pop();
+                InferType::unit()
+            }
+            Expr::ForLoop(ExprForLoop { pat, expr, body, .. }) => {
+                let iter_type = self.infer_expr(expr, None);
+                let item_type = self.get_item_type(&iter_type);
+                self.scopes.push(HashMap::new());
+                self.bind_pattern(pat, &item_type);
+                self.infer_block(body, None);
+                self.scopes.pop();
+                InferType::unit()
+            }
+            Expr::Loop(ExprLoop { body, .. }) => {
+                self.infer_block(body, None);
+                self.fresh(VarKind::Any)
+            }
+            Expr::Try(ExprTry { expr, .. }) => {
+                let type_ = self.infer_expr(expr, None);
+                match self.strip_pointers(&type_) {
+                    InferType::Named(name, args) if (name == "Option" || name == "Result") && !args.is_empty() => args[0].clone(),
+                    _ => self.fresh(VarKind::Any),
+                }
+            }
+            Expr::Macro(ExprMacro { mac, .. }) => self.infer_macro(mac),
+            _ => self.fresh(VarKind::Any),
+        }
+    }
+
+    fn infer_index(&mut self, index: &Expr, is_range: bool, slice_type: InferType, elem_type: InferType) -> InferType {
+        if is_range {
+            self.infer_expr(index, Some(&InferType::generic("Range", vec![InferType::named("usize")])));
+            slice_type
+        } else {
+            self.infer_expr(index, Some(&InferType::named("usize")));
+            elem_type
+        }
+    }
+
+    fn infer_path(&mut self, qself: Option<&QSelf>, path: &Path) -> InferType {
+        let ident = &path.segments.last().unwrap().ident;
+        if let Some(type_) = self.lookup(ident) {
+            return type_;
+        }
+        // Unit structs and variants
+        if self.get_fields_of_path(path).is_some() {
+            return InferType::Named(self.delta.get_type_of_instantiation_path(path, self.gamma), Vec::new());
+        }
+        if let Ok(signature) = self.gamma.get_path_signature(qself, path) {
+            let (inputs, output) = self.instantiate_signature(&signature, None);
+            return InferType::Fn(inputs, Box::new(output));
+        }
+        self.errors.push(RfoodError::unknown_variable(ident).with_span(path));
+        self.fresh(VarKind::Any)
+    }
+
+    fn infer_call(&mut self, expr_call: &ExprCall, expr: &Expr) -> InferType {
+        let ExprCall { func, args, .. } = expr_call;
+        if let Expr::Path(ExprPath { qself, path, .. }) = &**func {
+            let ident = &path.segments.last().unwrap().ident;
+
+            // Instantiation of a tuple struct/variant
+            if self.gamma.is_constructor_path(path) && self.lookup(ident).is_none() {
+                let fields = self.get_fields_of_path(path);
+                for (index, arg) in args.iter().enumerate() {
+                    let field_type = fields.as_ref().and_then(|fields| self.get_field_type(fields, &Member::Unnamed(Index::from(index))));
+                    self.infer_expr(arg, field_type.as_ref());
+                }
+                return InferType::Named(self.delta.get_type_of_instantiation_path(path, self.gamma), Vec::new());
+            }
+
+            if self.lookup(ident).is_none() {
+                if let Ok(signature) = self.gamma.get_path_signature(qself.as_ref(), path) {
+                    let signature = self.instantiate_signature(&signature, None);
+                    return self.infer_call_args(args.iter(), signature);
+                }
+                if let Some(signature) = self.get_std_function_signature(path) {
+                    return self.infer_call_args(args.iter(), signature);
+                }
+                if path.segments.len() == 1 {
+                    self.errors.push(RfoodError::not_found(&ident.to_string(), "signature of").with_span(expr));
+                }
+                for arg in args {
+                    self.infer_expr(arg, None);
+                }
+                return self.fresh(VarKind::Any);
+            }
+        }
+
+        // Calling a closure or function stored in a variable
+        let func_type = self.infer_expr(func, None);
+        let inputs: Vec<InferType> = args.iter().map(|_| self.fresh(VarKind::Any)).collect();
+        let output = self.fresh(VarKind::Any);
+        self.unify(&func_type, &InferType::Fn(inputs.clone(), Box::new(output.clone())));
+        self.infer_call_args(args.iter(), (inputs, output))
+    }
+
+    fn infer_method_call(&mut self, expr_method_call: &ExprMethodCall) -> InferType {
+        let ExprMethodCall { receiver, method, args, .. } = expr_method_call;
+        let receiver_type = self.infer_expr(receiver, None);
+        let self_type = self.strip_pointers(&receiver_type);
+
+        if let InferType::Named(name, _) = &self_type {
+            if let Ok(signature) = self.gamma.get_destructor_signature(name, method) {
+                let (mut inputs, output) = self.instantiate_signature(&signature, Some(self_type.clone()));
+                if has_self_receiver(&signature) {
+                    inputs.remove(0);
+                }
+                return self.infer_call_args(args.iter(), (inputs, output));
+            }
+            // A destructor call on the enum (or a variant of it) which is transformed into a call
+            // to a consumer of the enum, e.g. the receiver of `parser.parse(s).unwrap()`
+            let enum_ident = match self.gamma.get_enum_variant_enum(name) {
+                Ok(enum_) if !self.gamma.is_enum(name) => enum_.ident,
+                _ => name.clone(),
+            };
+            if let Ok(signature) = self.gamma.get_inherent_signature(name, method) {
+                let (mut inputs, output) = self.instantiate_signature(&signature, Some(self_type.clone()));
+                if has_self_receiver(&signature) {
+                    inputs.remove(0);
+                }
+                return self.infer_call_args(args.iter(), (inputs, output));
+            }
+            if let Some(consumer) = self.gamma.enum_consumers.get(&enum_ident).and_then(|consumers| consumers.get(method)) {
+                let signature = self.gamma.expand_signature_type_aliases(&consumer.sig);
+                let (mut inputs, output) = self.instantiate_signature(&signature, None);
+                if !inputs.is_empty() {
+                    let enum_type = inputs.remove(0);
+                    let enum_type = self.strip_pointers(&enum_type);
+                    self.unify(&enum_type, &self_type);
+                }
+                return self.infer_call_args(args.iter(), (inputs, output));
+            }
+        }
+
+        if let Some(signature) = self.get_std_method_signature(&receiver_type, &self_type, method) {
+            return self.infer_call_args(args.iter(), signature);
+        }
+
+        if let InferType::Named(name, _) = &self_type {
+            if self.gamma.is_generator_type(name) || self.gamma.is_enum_or_variant(name) || self.gamma.get_struct_by_name(name).is_ok() {
+                self.errors.push(RfoodError::not_found(&method.to_string(), "destructor").with_span(method));
+            }
+        }
+        for arg in args {
+            self.infer_expr(arg, None);
+        }
+        self.fresh(VarKind::Any)
+    }
+
+    fn infer_binary(&mut self, left: &Expr, op: &BinOp, right: &Expr) -> InferType {
+        match op {
+            BinOp::And(_) | BinOp::Or(_) => {
+                self.infer_expr(left, Some(&InferType::named("bool")));
+                self.infer_expr(right, Some(&InferType::named("bool")));
+                InferType::named("bool")
+            }
+            BinOp::Eq(_) | BinOp::Ne(_) | BinOp::Lt(_) | BinOp::Le(_) | BinOp::Gt(_) | BinOp::Ge(_) => {
+                let left_type = self.infer_expr(left, None);
+                self.infer_expr(right, Some(&left_type));
+                InferType::named("bool")
+            }
+            BinOp::Shl(_) | BinOp::Shr(_) | BinOp::ShlEq(_) | BinOp::ShrEq(_) => {
+                let left_type = self.infer_expr(left, None);
+                self.infer_expr(right, None);
+                self.strip_pointers(&left_type)
+            }
+            _ => {
+                // Arithmetic on primitives is between values of the same type, any other type
+                // (e.g. `String + &str`) fails to unify which is ignored
+                let left_type = self.infer_expr(left, None);
+                let right_type = self.infer_expr(right, None);
+                let (left_type, right_type) = (self.strip_pointers(&left_type), self.strip_pointers(&right_type));
+                self.unify(&left_type, &right_type);
+                match op {
+                    BinOp::AddEq(_) | BinOp::SubEq(_) | BinOp::MulEq(_) | BinOp::DivEq(_) | BinOp::RemEq(_)
+                    | BinOp::BitXorEq(_) | BinOp::BitAndEq(_) | BinOp::BitOrEq(_) => InferType::unit(),
+                    _ => left_type,
+                }
+            }
+        }
+    }
+
+    fn infer_macro(&mut self, mac: &Macro) -> InferType {
+        let name = mac.path.segments.last().unwrap().ident.to_string();
+        match name.as_str() {
+            "vec" => {
+                let tokens = &mac.tokens;
+                match parse2::<Expr>(quote!([#tokens])) {
+                    Ok(array) => match self.infer_expr(&array, None) {
+                        InferType::Array(elem_type) => InferType::generic("Vec", vec![*elem_type]),
+                        _ => self.fresh(VarKind::Any),
+                    },
+                    Err(_) => InferType::generic("Vec", vec![self.fresh(VarKind::Any)]),
+                }
+            }
+            "format" => InferType::named("String"),
+            "panic" | "todo" | "unimplemented" | "unreachable" => InferType::Never,
+            "print" | "println" | "eprint" | "eprintln" | "assert" | "assert_eq" | "assert_ne" => InferType::unit(),
+            _ => self.fresh(VarKind::Any),
+        }
+    }
+
+    fn infer_block(&mut self, block: &Block, expected: Option<&InferType>) -> InferType {
+        self.scopes.push(HashMap::new());
+        let mut type_ = InferType::unit();
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            let is_last = index == block.stmts.len() - 1;
+            type_ = match stmt {
+                Stmt::Local(Local { pat, init, .. }) => {
+                    let (pat, declared_type) = match pat {
+                        Pat::Type(PatType { pat, ty, .. }) => {
+                            let type_params = TypeParams {
+                                self_type: self.self_type.clone(),
+                                ..TypeParams::default()
+                            };
+                            (&**pat, Some(self.get_written_type(ty, &type_params)))
+                        }
+                        pat => (pat, None),
+                    };
+                    let init_type = init.as_ref().map(|(_, init)| self.infer_expr(init, declared_type.as_ref()));
+                    let type_ = declared_type.or(init_type).unwrap_or_else(|| self.fresh(VarKind::Any));
+                    self.bind_pattern(pat, &type_);
+                    InferType::unit()
+                }
+                Stmt::Expr(expr) if is_last => self.infer_expr(expr, expected),
+                Stmt::Expr(expr) => {
+                    self.infer_expr(expr, None);
+                    InferType::unit()
+                }
+                // A block ending with e.g. `return x;` never finishes
+                Stmt::Semi(expr, _) => match self.infer_expr(expr, None) {
+                    InferType::Never => InferType::Never,
+                    _ => InferType::unit(),
+                },
+                Stmt::Item(_) => InferType::unit(),
+            };
+        }
+        self.scopes.pop();
+        type_
+    }
+
+    /// Declare the variables bound by a pattern matching a value of the type
+    fn bind_pattern(&mut self, pat: &Pat, type_: &InferType) {
+        // Matching a reference binds references to the fields
+        let (matched_type, borrow) = match self.resolve(type_) {
+            InferType::Ref(box inner) => (self.resolve(&inner), Some(false)),
+            InferType::MutRef(box inner) => (self.resolve(&inner), Some(true)),
+            matched_type => (matched_type, None),
+        };
+        let wrap = |type_: InferType| match borrow {
+            Some(mutable) => InferType::borrow(mutable, type_),
+            None => type_,
+        };
+
+        match pat {
+            Pat::Ident(PatIdent { ident, by_ref, mutability, subpat, .. }) => {
+                let type_ = match by_ref {
+                    Some(_) => InferType::borrow(mutability.is_some(), type_.clone()),
+                    None => type_.clone(),
+                };
+                self.record_binding(ident, &type_);
+                self.declare(ident, type_.clone());
+                if let Some((_, subpat)) = subpat {
+                    self.bind_pattern(subpat, &type_);
+                }
+            }
+            Pat::Type(PatType { pat, ty, .. }) => {
+                let type_params = TypeParams {
+                    self_type: self.self_type.clone(),
+                    ..TypeParams::default()
+                };
+                let declared_type = self.get_written_type(ty, &type_params);
+                self.unify(&declared_type, type_);
+                self.bind_pattern(pat, &declared_type);
+            }
+            Pat::Tuple(PatTuple { elems, .. }) => {
+                let elem_types = match matched_type {
+                    InferType::Tuple(elem_types) if elem_types.len() == elems.len() => elem_types,
+                    _ => {
+                        let elem_types: Vec<InferType> = elems.iter().map(|_| self.fresh(VarKind::Any)).collect();
+                        self.unify(&matched_type, &InferType::Tuple(elem_types.clone()));
+                        elem_types
+                    }
+                };
+                for (elem, elem_type) in elems.iter().zip(elem_types) {
+                    self.bind_pattern(elem, &wrap(elem_type));
+                }
+            }
+            Pat::Struct(PatStruct { path, fields, .. }) => {
+                let struct_fields = self.get_fields_of_path(path);
+                for field in fields {
+                    let field_type = struct_fields
+                        .as_ref()
+                        .and_then(|struct_fields| self.get_field_type(struct_fields, &field.member))
+                        .unwrap_or_else(|| self.fresh(VarKind::Any));
+                    self.bind_pattern(&field.pat, &wrap(field_type));
+                }
+            }
+            Pat::TupleStruct(PatTupleStruct { path, pat: PatTuple { elems, .. }, .. }) => {
+                let ident = &path.segments.last().unwrap().ident;
+                let std_args = match &matched_type {
+                    InferType::Named(name, args) if name == "Option" || name == "Result" => args.clone(),
+                    _ => Vec::new(),
+                };
+                let struct_fields = self.get_fields_of_path(path);
+                for (index, elem) in elems.iter().enumerate() {
+                    let elem_type = match (ident.to_string().as_str(), std_args.as_slice()) {
+                        ("Some" | "Ok", [inner, ..]) => Some(inner.clone()),
+                        ("Err", [_, error]) => Some(error.clone()),
+                        _ => struct_fields
+                            .as_ref()
+                            .and_then(|struct_fields| self.get_field_type(struct_fields, &Member::Unnamed(Index::from(index)))),
+                    }.unwrap_or_else(|| self.fresh(VarKind::Any));
+                    self.bind_pattern(elem, &wrap(elem_type));
+                }
+            }
+            Pat::Reference(PatReference { pat, .. }) => {
+                let inner = if borrow.is_some() { matched_type } else { self.fresh(VarKind::Any) };
+                self.bind_pattern(pat, &inner);
+            }
+            Pat::Box(PatBox { pat, .. }) => {
+                let inner = match matched_type {
+                    InferType::Box(box inner) => inner,
+                    _ => self.fresh(VarKind::Any),
+                };
+                self.bind_pattern(pat, &wrap(inner));
+            }
+            Pat::Slice(PatSlice { elems, .. }) => {
+                let elem_type = match matched_type {
+                    InferType::Array(box elem_type) => elem_type,
+                    _ => self.fresh(VarKind::Any),
+                };
+                for elem in elems {
+                    self.bind_pattern(elem, &wrap(elem_type.clone()));
+                }
+            }
+            Pat::Or(PatOr { cases, .. }) => {
+                for case in cases {
+                    self.bind_pattern(case, type_);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Get the type of the items of something iterated over in a for loop
+    fn get_item_type(&mut self, type_: &InferType) -> InferType {
+        let (inner, borrow) = match self.resolve(type_) {
+            InferType::Ref(box inner) => (self.strip_pointers(&inner), Some(false)),
+            InferType::MutRef(box inner) => (self.strip_pointers(&inner), Some(true)),
+            type_ => (self.strip_pointers(&type_), None),
+        };
+        let item_type = match inner {
+            InferType::Array(box elem_type) => elem_type,
+            InferType::Named(name, args) if args.len() == 1 && ["Vec", "VecDeque", "HashSet", "BTreeSet", "Option"].iter().any(|container| name == container) => {
+                args[0].clone()
+            }
+            InferType::Named(name, args) if args.len() == 2 && (name == "HashMap" || name == "BTreeMap") => {
+                InferType::Tuple(args)
+            }
+            // Iterators and ranges are never borrowed in a for loop
+            InferType::Named(name, args) if args.len() == 1 && (name == "Iter" || name == "Range") => return args[0].clone(),
+            _ => return self.fresh(VarKind::Any),
+        };
+        match borrow {
+            Some(mutable) => InferType::borrow(mutable, item_type),
+            None => item_type,
+        }
+    }
+
+    /// The signatures of common functions of the standard library, e.g. `String::from`
+    fn get_std_function_signature(&mut self, path: &Path) -> Option<InferSignature> {
+        let idents: Vec<String> = path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+        let idents: Vec<&str> = idents.iter().map(|ident| ident.as_str()).collect();
+        let any = self.fresh(VarKind::Any);
+        match idents.as_slice() {
+            [.., "String", "new"] => Some((vec![], InferType::named("String"))),
+            [.., "String", "from"] => Some((vec![any], InferType::named("String"))),
+            [.., "Vec", "new"] => Some((vec![], InferType::generic("Vec", vec![any]))),
+            [.., "Vec", "with_capacity"] => Some((vec![InferType::named("usize")], InferType::generic("Vec", vec![any]))),
+            [.., "Rc" | "Arc" | "RefCell", "new"] => {
+                let wrapper = idents[idents.len() - 2];
+                Some((vec![any.clone()], InferType::generic(wrapper, vec![any])))
+            }
+            // The boxes converted into shared pointers by the transformation
+            [.., "Rc" | "Arc", "from"] => {
+                let wrapper = idents[idents.len() - 2];
+                Some((vec![InferType::Box(Box::new(any.clone()))], InferType::generic(wrapper, vec![any])))
+            }
+            [.., "Rc" | "Arc", "clone"] => Some((vec![InferType::Ref(Box::new(any.clone()))], any)),
+            [.., "HashMap", "new"] => {
+                let value = self.fresh(VarKind::Any);
+                Some((vec![], InferType::generic("HashMap", vec![any, value])))
+            }
+            ["Some"] => Some((vec![any.clone()], InferType::generic("Option", vec![any]))),
+            ["Ok"] => {
+                let error = self.fresh(VarKind::Any);
+                Some((vec![any.clone()], InferType::generic("Result", vec![any, error])))
+            }
+            ["Err"] => {
+                let value = self.fresh(VarKind::Any);
+                Some((vec![any.clone()], InferType::generic("Result", vec![value, any])))
+            }
+            _ => None,
+        }
+    }
+
+    /// The signatures of the methods of the standard library (or any type in the method signatures
+    /// of gamma), the receiver is not included in the inputs
+    fn get_std_method_signature(&mut self, receiver_type: &InferType, self_type: &InferType, method: &Ident) -> Option<InferSignature> {
+        // Methods of every type
+        match method.to_string().as_str() {
+            // Cloning a reference clones the value it refers to
+            "clone" => return Some((vec![], match self.resolve(receiver_type) {
+                InferType::Ref(box inner) | InferType::MutRef(box inner) => inner,
+                type_ => type_,
+            })),
+            "to_string" => return Some((vec![], InferType::named("String"))),
+            "eq" | "ne" => return Some((vec![InferType::Ref(Box::new(self_type.clone()))], InferType::named("bool"))),
+            // Iterating a borrowed collection iterates over borrows of its items
+            "into_iter" if matches!(self.resolve(receiver_type), InferType::Ref(_) | InferType::MutRef(_)) => {
+                return self.get_std_method_signature(receiver_type, self_type, &Ident::new("iter", method.span()));
+            }
+            _ => (),
+        }
+        self.get_known_method_signature(self_type, method, 0)
+    }
+
+    /// Find the signature of a method in the impls of the method signatures of gamma, followed by
+    /// the impls of the types the type derefs to
+    fn get_known_method_signature(&mut self, self_type: &InferType, method: &Ident, derefs: usize) -> Option<InferSignature> {
+        let type_name = match self.resolve(self_type) {
+            // Literals use the methods of their default type, without being solved by it
+            InferType::Var(var) => match self.vars[var].1 {
+                VarKind::Integer => "i32".to_string(),
+                VarKind::Float => "f64".to_string(),
+                VarKind::Any => return None,
+            },
+            InferType::Named(name, _) => name.to_string(),
+            InferType::Array(_) => "[]".to_string(),
+            _ => return None,
+        };
+
+        let gamma = self.gamma;
+        let method_signatures = &gamma.method_signatures;
+        for (item_impl, signature) in method_signatures.get_method_impls(&type_name, method) {
+            if let Some(type_params) = self.match_impl(item_impl, self_type) {
+                let (mut inputs, output) = self.instantiate_signature_with(signature, type_params);
+                if has_self_receiver(signature) {
+                    inputs.remove(0);
+                }
+                return Some((inputs, output));
+            }
+        }
+
+        // Guard against impls which deref to themselves
+        if derefs < 8 {
+            for (item_impl, target) in method_signatures.get_deref_impls(&type_name) {
+                if let Some(type_params) = self.match_impl(item_impl, self_type) {
+                    let target = self.get_written_type(target, &type_params);
+                    return self.get_known_method_signature(&target, method, derefs + 1);
+                }
+            }
+        }
+        None
+    }
+
+    /// Match the type of an impl with a type, returns the substitutions of the generic parameters
+    /// of the impl. Unlike unification the type is not changed, e.g. an `Iter<&T>` impl does not
+    /// match an iterator whose items are not known to be borrows.
+    fn match_impl(&mut self, item_impl: &ItemImpl, self_type: &InferType) -> Option<TypeParams> {
+        let mut type_params = TypeParams {
+            self_type: Some(self_type.clone()),
+            generics: HashMap::new(),
+        };
+        for type_param in item_impl.generics.type_params() {
+            let var = self.fresh(VarKind::Any);
+            type_params.generics.insert(type_param.ident.clone(), var);
+        }
+        // The impls of the numeric types are picked for literals by their name
+        if matches!(self.resolve(self_type), InferType::Var(_)) {
+            return Some(type_params);
+        }
+
+        let impl_type = self.get_written_type(&item_impl.self_ty, &type_params);
+        let vars = self.vars.clone();
+        let mut unsolved = Vec::new();
+        self.get_unsolved_vars(self_type, &mut unsolved);
+        if self.unify(&impl_type, self_type) && unsolved.iter().all(|var| matches!(self.resolve(&InferType::Var(*var)), InferType::Var(_))) {
+            Some(type_params)
+        } else {
+            self.vars = vars;
+            None
+        }
+    }
+
+    fn get_unsolved_vars(&self, type_: &InferType, unsolved: &mut Vec<usize>) {
+        match self.resolve(type_) {
+            InferType::Var(var) => unsolved.push(var),
+            InferType::Named(_, args) | InferType::Tuple(args) => args.iter().for_each(|arg| self.get_unsolved_vars(arg, unsolved)),
+            InferType::Ref(inner) | InferType::MutRef(inner) | InferType::Box(inner) | InferType::Array(inner) => self.get_unsolved_vars(&inner, unsolved),
+            InferType::Fn(inputs, output) => {
+                inputs.iter().for_each(|input| self.get_unsolved_vars(input, unsolved));
+                self.get_unsolved_vars(&output, unsolved);
+            }
+            InferType::Never => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::gamma::generate_gamma;
+
+    fn get_type(input: &str, gamma: &Gamma) -> RfoodResult<DeltaType> {
+        TypeInference::new(&Delta::new(), gamma).get_type_of_expr(&parse_str(input).unwrap())
+    }
+
+    #[test]
+    fn test_get_type_of_expr() {
+        let gamma = generate_gamma(&parse_str("struct Point { x: u8, y: u8 }").unwrap()).unwrap();
+
+        assert_eq!(get_type("Point { x: 1, y: 2 }.x + 1", &gamma).unwrap().name, "u8");
+        assert_eq!(get_type("if true { 1.0 } else { 2.0 }", &gamma).unwrap().name, "f64");
+        assert_eq!(get_type("{ let f = |a| a * 2u64; f(3) }", &gamma).unwrap().name, "u64");
+        assert_eq!(get_type("vec![(1, true)][0].1", &gamma).unwrap().name, "bool");
+        assert_eq!(get_type("\"point\"", &gamma).unwrap().to_string(), "& str");
+    }
+
+    #[test]
+    fn test_get_type_of_expr_methods() {
+        let gamma = generate_gamma(&parse_str("
+            trait Shape { fn area(&self) -> u32; }
+            struct Square { side: u32 }
+            impl Shape for Square { fn area(&self) -> u32 { self.side * self.side } }
+        ").unwrap()).unwrap();
+
+        assert_eq!(get_type("Square { side: 2 }.area()", &gamma).unwrap().to_string(), "u32");
+        assert_eq!(get_type("Some(Box::new(Square { side: 1 })).unwrap().area()", &gamma).unwrap().to_string(), "u32");
+        assert_eq!(get_type("String::from(\"a\").trim()", &gamma).unwrap().to_string(), "& str");
+        assert_eq!(get_type("std::rc::Rc::new(vec![1u8]).len()", &gamma).unwrap().to_string(), "usize");
+    }
+
+    #[test]
+    fn test_get_type_of_expr_fallback() {
+        let gamma = generate_gamma(&parse_str("struct Square { side: u32 }").unwrap()).unwrap();
+
+        assert_eq!(get_type("1", &gamma).unwrap().name, "i32");
+        assert_eq!(get_type("1.5", &gamma).unwrap().name, "f64");
+        assert_eq!(get_type("Square { side: 2 }.perimeter()", &gamma).unwrap_err().code, ErrorCode::NotFound);
+        assert_eq!(get_type("Default::default()", &gamma).unwrap_err().code, ErrorCode::TypeInferenceFailed);
+
+        // The type inferred for the expression in the body is used
+        let function: ItemFn = parse_str("fn total() -> u8 { let x = Default::default(); x }").unwrap();
+        let mut delta = Delta::new();
+        delta.collect_for_body(&function.sig, &function.block, None, &Gamma::empty());
+        if let Stmt::Local(Local { init: Some((_, init)), .. }) = &function.block.stmts[0] {
+            assert_eq!(TypeInference::new(&delta, &gamma).get_type_of_expr(init).unwrap().name, "u8");
+        }
+    }
+
+    #[test]
+    fn test_get_expected_arg_types() {
+        let (delta, gamma) = (Delta::new(), Gamma::empty());
+
+        let required_type = parse_str::<Type>("Option<Box<dyn Shape>>").unwrap().get_delta_type();
+        let arg_types = TypeInference::new(&delta, &gamma)
+            .get_expected_arg_types(&parse_str("Some(shape)").unwrap(), None, Some(&required_type))
+            .unwrap();
+        assert_eq!(arg_types[0].as_ref().unwrap().to_string(), "Box < Shape >");
+
+        let receiver_type = parse_str::<Type>("Vec<(i32, bool)>").unwrap().get_delta_type();
+        let arg_types = TypeInference::new(&delta, &gamma)
+            .get_expected_arg_types(&parse_str("pairs.push(pair)").unwrap(), Some(&receiver_type), None)
+            .unwrap();
+        assert_eq!(arg_types[0].as_ref().unwrap().to_string(), "(i32 , bool)");
+    }
+
+    #[test]
+    fn test_infer_expr() {
+        let (delta, gamma) = (Delta::new(), Gamma::empty());
+        let mut inference = TypeInference::new(&delta, &gamma);
+        let mut infer = |input: &str, expected: Option<InferType>| {
+            let type_ = inference.infer_expr(&parse_str(input).unwrap(), expected.as_ref());
+            inference.to_delta_type(&type_).unwrap().to_string()
+        };
+
+        // Variables are solved by how they are used later
+        assert_eq!(infer("{ let mut sides = Vec::new(); sides.push(2u8); sides }", None), "Vec < u8 >");
+        assert_eq!(infer("if true { 1 } else { 2u16 }", None), "u16");
+        assert_eq!(infer("|a, b| a + b * 2.0", None), "fn (f64 , f64) -> f64");
+        // Boxes and references are ignored when unifying with the expected type
+        assert_eq!(infer("&5", Some(InferType::Box(Box::new(InferType::named("u64"))))), "& u64");
+    }
+
+    #[test]
+    fn test_infer_body() {
+        let gamma = generate_gamma(&parse_str("
+            trait Shape { fn area(&self) -> i32; }
+            struct Pair { first: Box<dyn Shape>, second: Box<dyn Shape> }
+        ").unwrap()).unwrap();
+        let function: ItemFn = parse_str("
+            fn total(pair: Pair) -> i32 {
+                let (first, second) = (pair.first, pair.second);
+                first.area() + second.area()
+            }
+        ").unwrap();
+        let mut delta = Delta::new();
+        delta.module_path = vec![parse_str("shapes").unwrap()];
+        delta.collect_for_sig(&function.sig, None).unwrap();
+        delta.collect_for_body(&function.sig, &function.block, None, &gamma);
+
+        // The variables bound by a tuple are typed by the tuple
+        if let Stmt::Local(Local { pat, init: Some((_, init)), .. }) = &function.block.stmts[0] {
+            let first = &get_pattern_idents(pat)[0];
+            assert_eq!(delta.inferred.get_binding(first, &delta.module_path).unwrap().to_string(), "Box < Shape >");
+            assert_eq!(delta.inferred.get_expr(init, &delta.module_path).unwrap().to_string(), "(Box < Shape > , Box < Shape >)");
+            // The same location in another module is in another file
+            assert!(delta.inferred.get_expr(init, &[parse_str("units").unwrap()]).is_none());
+        }
+    }
+}
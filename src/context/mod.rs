@@ -1,3 +1,5 @@
 pub mod gamma;
 pub mod delta;
 pub mod errors;
+pub mod inference;
+pub mod signatures;
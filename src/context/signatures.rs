@@ -0,0 +1,378 @@
+//! The signatures of the methods of types which are not declared in the code being transformed,
+//! e.g. `Vec::push`
+//!
+//! The signatures are written as the impls they are found in, the bodies of the methods are
+//! ignored. Type inference and the coercion of the arguments of method calls fall back on these
+//! signatures when the method is not a destructor.
+//!
+//! ```text
+//! impl<T> Vec<T> {
+//!     fn push(&mut self, value: T) {}
+//! }
+//! impl<T> Deref for Vec<T> {
+//!     type Target = [T];
+//! }
+//! ```
+//!
+//! The methods of the type a type derefs to are methods of the type too, e.g. the slice methods
+//! of `Vec`. Iterators are all written as `Iter<Item>`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use syn::*;
+use crate::context::errors::*;
+
+/// The methods of the standard library known by default, the methods of the numeric types are
+/// added for each numeric type
+const STD_SIGNATURES: &str = "
+    impl<T> [T] {
+        fn len(&self) -> usize {}
+        fn is_empty(&self) -> bool {}
+        fn contains(&self, value: &T) -> bool {}
+        fn get(&self, index: usize) -> Option<&T> {}
+        fn get_mut(&mut self, index: usize) -> Option<&mut T> {}
+        fn first(&self) -> Option<&T> {}
+        fn last(&self) -> Option<&T> {}
+        fn iter(&self) -> Iter<&T> {}
+        fn iter_mut(&mut self) -> Iter<&mut T> {}
+        fn into_iter(self) -> Iter<T> {}
+        fn to_vec(&self) -> Vec<T> {}
+        fn sort(&mut self) {}
+        fn sort_by<F: Fn(&T, &T) -> Ordering>(&mut self, compare: F) {}
+        fn reverse(&mut self) {}
+        fn swap(&mut self, a: usize, b: usize) {}
+    }
+
+    impl<T> Vec<T> {
+        fn push(&mut self, value: T) {}
+        fn pop(&mut self) -> Option<T> {}
+        fn insert(&mut self, index: usize, value: T) {}
+        fn remove(&mut self, index: usize) -> T {}
+        fn clear(&mut self) {}
+        fn truncate(&mut self, len: usize) {}
+        fn extend<I>(&mut self, values: I) {}
+        fn retain<F: Fn(&T) -> bool>(&mut self, keep: F) {}
+    }
+    impl<T> Deref for Vec<T> {
+        type Target = [T];
+    }
+
+    impl<T> VecDeque<T> {
+        fn len(&self) -> usize {}
+        fn is_empty(&self) -> bool {}
+        fn contains(&self, value: &T) -> bool {}
+        fn get(&self, index: usize) -> Option<&T> {}
+        fn front(&self) -> Option<&T> {}
+        fn back(&self) -> Option<&T> {}
+        fn push_front(&mut self, value: T) {}
+        fn push_back(&mut self, value: T) {}
+        fn pop_front(&mut self) -> Option<T> {}
+        fn pop_back(&mut self) -> Option<T> {}
+        fn iter(&self) -> Iter<&T> {}
+        fn into_iter(self) -> Iter<T> {}
+        fn clear(&mut self) {}
+    }
+
+    impl String {
+        fn push_str(&mut self, string: &str) {}
+        fn push(&mut self, c: char) {}
+        fn as_str(&self) -> &str {}
+        fn clear(&mut self) {}
+    }
+    impl Deref for String {
+        type Target = str;
+    }
+
+    impl str {
+        fn len(&self) -> usize {}
+        fn is_empty(&self) -> bool {}
+        fn trim(&self) -> &str {}
+        fn to_owned(&self) -> String {}
+        fn to_uppercase(&self) -> String {}
+        fn to_lowercase(&self) -> String {}
+        fn repeat(&self, n: usize) -> String {}
+        fn contains<P>(&self, pattern: P) -> bool {}
+        fn starts_with<P>(&self, pattern: P) -> bool {}
+        fn ends_with<P>(&self, pattern: P) -> bool {}
+        fn chars(&self) -> Iter<char> {}
+        fn split<P>(&self, pattern: P) -> Iter<&str> {}
+        fn split_whitespace(&self) -> Iter<&str> {}
+        fn lines(&self) -> Iter<&str> {}
+    }
+
+    impl<T> Option<T> {
+        fn unwrap(self) -> T {}
+        fn expect(self, message: &str) -> T {}
+        fn unwrap_or(self, default: T) -> T {}
+        fn unwrap_or_default(self) -> T {}
+        fn unwrap_or_else<F: Fn() -> T>(self, default: F) -> T {}
+        fn is_some(&self) -> bool {}
+        fn is_none(&self) -> bool {}
+        fn as_ref(&self) -> Option<&T> {}
+        fn as_mut(&mut self) -> Option<&mut T> {}
+        fn take(&mut self) -> Option<T> {}
+        fn map<U, F: Fn(T) -> U>(self, f: F) -> Option<U> {}
+        fn map_or<U, F: Fn(T) -> U>(self, default: U, f: F) -> U {}
+        fn and_then<U, F: Fn(T) -> Option<U>>(self, f: F) -> Option<U> {}
+        fn filter<P: Fn(&T) -> bool>(self, predicate: P) -> Option<T> {}
+        fn ok_or<E>(self, error: E) -> Result<T, E> {}
+    }
+    impl<T> Option<&T> {
+        fn cloned(self) -> Option<T> {}
+        fn copied(self) -> Option<T> {}
+    }
+
+    impl<T, E> Result<T, E> {
+        fn unwrap(self) -> T {}
+        fn expect(self, message: &str) -> T {}
+        fn unwrap_err(self) -> E {}
+        fn unwrap_or(self, default: T) -> T {}
+        fn unwrap_or_default(self) -> T {}
+        fn unwrap_or_else<F: Fn(E) -> T>(self, default: F) -> T {}
+        fn is_ok(&self) -> bool {}
+        fn is_err(&self) -> bool {}
+        fn ok(self) -> Option<T> {}
+        fn err(self) -> Option<E> {}
+        fn map<U, F: Fn(T) -> U>(self, f: F) -> Result<U, E> {}
+        fn map_err<O, F: Fn(E) -> O>(self, f: F) -> Result<T, O> {}
+        fn and_then<U, F: Fn(T) -> Result<U, E>>(self, f: F) -> Result<U, E> {}
+    }
+
+    impl<K, V> HashMap<K, V> {
+        fn len(&self) -> usize {}
+        fn is_empty(&self) -> bool {}
+        fn get(&self, key: &K) -> Option<&V> {}
+        fn get_mut(&mut self, key: &K) -> Option<&mut V> {}
+        fn contains_key(&self, key: &K) -> bool {}
+        fn insert(&mut self, key: K, value: V) -> Option<V> {}
+        fn remove(&mut self, key: &K) -> Option<V> {}
+        fn keys(&self) -> Iter<&K> {}
+        fn values(&self) -> Iter<&V> {}
+        fn values_mut(&mut self) -> Iter<&mut V> {}
+        fn iter(&self) -> Iter<(&K, &V)> {}
+        fn into_iter(self) -> Iter<(K, V)> {}
+        fn clear(&mut self) {}
+    }
+
+    impl<K, V> BTreeMap<K, V> {
+        fn len(&self) -> usize {}
+        fn is_empty(&self) -> bool {}
+        fn get(&self, key: &K) -> Option<&V> {}
+        fn get_mut(&mut self, key: &K) -> Option<&mut V> {}
+        fn contains_key(&self, key: &K) -> bool {}
+        fn insert(&mut self, key: K, value: V) -> Option<V> {}
+        fn remove(&mut self, key: &K) -> Option<V> {}
+        fn keys(&self) -> Iter<&K> {}
+        fn values(&self) -> Iter<&V> {}
+        fn values_mut(&mut self) -> Iter<&mut V> {}
+        fn iter(&self) -> Iter<(&K, &V)> {}
+        fn into_iter(self) -> Iter<(K, V)> {}
+        fn clear(&mut self) {}
+    }
+
+    impl<T> HashSet<T> {
+        fn len(&self) -> usize {}
+        fn is_empty(&self) -> bool {}
+        fn contains(&self, value: &T) -> bool {}
+        fn insert(&mut self, value: T) -> bool {}
+        fn remove(&mut self, value: &T) -> bool {}
+        fn iter(&self) -> Iter<&T> {}
+        fn into_iter(self) -> Iter<T> {}
+    }
+
+    impl<T> RefCell<T> {
+        fn borrow(&self) -> &T {}
+        fn borrow_mut(&self) -> &mut T {}
+    }
+
+    impl<T> Iter<T> {
+        fn next(&mut self) -> Option<T> {}
+        fn map<U, F: Fn(T) -> U>(self, f: F) -> Iter<U> {}
+        fn filter<P: Fn(&T) -> bool>(self, predicate: P) -> Iter<T> {}
+        fn filter_map<U, F: Fn(T) -> Option<U>>(self, f: F) -> Iter<U> {}
+        fn find<P: Fn(&T) -> bool>(self, predicate: P) -> Option<T> {}
+        fn position<P: Fn(T) -> bool>(self, predicate: P) -> Option<usize> {}
+        fn any<P: Fn(T) -> bool>(self, predicate: P) -> bool {}
+        fn all<P: Fn(T) -> bool>(self, predicate: P) -> bool {}
+        fn for_each<F: Fn(T)>(self, f: F) {}
+        fn fold<B, F: Fn(B, T) -> B>(self, init: B, f: F) -> B {}
+        fn enumerate(self) -> Iter<(usize, T)> {}
+        fn zip<U>(self, other: Iter<U>) -> Iter<(T, U)> {}
+        fn rev(self) -> Iter<T> {}
+        fn skip(self, n: usize) -> Iter<T> {}
+        fn take(self, n: usize) -> Iter<T> {}
+        fn into_iter(self) -> Iter<T> {}
+        fn last(self) -> Option<T> {}
+        fn max(self) -> Option<T> {}
+        fn min(self) -> Option<T> {}
+        fn count(self) -> usize {}
+        fn collect<B>(self) -> B {}
+    }
+    impl<T> Iter<&T> {
+        fn cloned(self) -> Iter<T> {}
+        fn copied(self) -> Iter<T> {}
+        fn sum(self) -> T {}
+        fn product(self) -> T {}
+    }
+    impl<T> Iter<T> {
+        fn sum(self) -> T {}
+        fn product(self) -> T {}
+    }
+    impl<T> Deref for Range<T> {
+        type Target = Iter<T>;
+    }
+";
+
+/// The methods of every numeric type
+const NUMERIC_SIGNATURES: &str = "
+    fn abs(self) -> Self {}
+    fn signum(self) -> Self {}
+    fn min(self, other: Self) -> Self {}
+    fn max(self, other: Self) -> Self {}
+    fn clamp(self, min: Self, max: Self) -> Self {}
+    fn is_positive(self) -> bool {}
+    fn is_negative(self) -> bool {}
+    fn pow(self, exp: u32) -> Self {}
+    fn sqrt(self) -> Self {}
+    fn floor(self) -> Self {}
+    fn ceil(self) -> Self {}
+    fn round(self) -> Self {}
+    fn powi(self, n: i32) -> Self {}
+    fn powf(self, n: Self) -> Self {}
+";
+
+const NUMERIC_TYPES: [&str; 14] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32", "f64",
+];
+
+/// A table of the method signatures of types, keyed by the name of the type
+///
+/// # Examples
+///
+/// ```
+/// use syn::*;
+/// use rfood::context::signatures::MethodSignatures;
+///
+/// let signatures = MethodSignatures::default()
+///     .with_source("impl Counter { fn count(&self) -> u32 {} }")
+///     .unwrap();
+///
+/// let method: Ident = parse_str("count").unwrap();
+/// let (_, signature) = signatures.get_method_impls("Counter", &method)[0];
+/// assert_eq!(quote::quote!(#signature).to_string(), "fn count (& self) -> u32");
+///
+/// // Adding signatures does not change the standard library the default tables share
+/// assert!(MethodSignatures::default().get_method_impls("Counter", &method).is_empty());
+///
+/// // The standard library is known by default
+/// let method: Ident = parse_str("push").unwrap();
+/// assert_eq!(signatures.get_method_impls("Vec", &method).len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MethodSignatures {
+    /// The impls of each type, the impls added last are searched first so they can replace the
+    /// signatures already known
+    impls: Rc<HashMap<String, Vec<ItemImpl>>>,
+}
+
+impl MethodSignatures {
+    /// A table without any signatures, not even those of the standard library
+    pub fn empty() -> Self {
+        MethodSignatures { impls: Rc::new(HashMap::new()) }
+    }
+
+    /// Add the signatures of the impls in the source of a file, every item must be an impl
+    pub fn with_source(mut self, source: &str) -> RfoodResult<Self> {
+        let file = parse_file(source)?;
+        let mut added: HashMap<String, Vec<ItemImpl>> = HashMap::new();
+        for item in file.items {
+            let item_impl = match item {
+                Item::Impl(item_impl) => item_impl,
+                item => return Err(RfoodError::unsupported_syntax("Only impls can be given as method signatures").with_span(&item)),
+            };
+            let type_name = get_type_name(&item_impl.self_ty)
+                .ok_or_else(|| RfoodError::invalid_type("Method signatures can only be given for named types and slices").with_span(&item_impl.self_ty))?;
+            added.entry(type_name).or_default().push(item_impl);
+        }
+
+        let impls = Rc::make_mut(&mut self.impls);
+        for (type_name, mut type_impls) in added {
+            let known_impls = impls.entry(type_name).or_default();
+            type_impls.append(known_impls);
+            *known_impls = type_impls;
+        }
+        Ok(self)
+    }
+
+    /// Add the signatures of the impls in a file
+    pub fn with_file(self, path: &Path) -> RfoodResult<Self> {
+        let source = std::fs::read_to_string(path)?;
+        self.with_source(&source).map_err(|error| error.in_file(path))
+    }
+
+    /// The impls of a type which have a method, along with the signature of the method, in the
+    /// order they are searched
+    pub fn get_method_impls(&self, type_name: &str, method: &Ident) -> Vec<(&ItemImpl, &Signature)> {
+        self.get_impls(type_name)
+            .filter_map(|item_impl| {
+                item_impl.items.iter().find_map(|impl_item| match impl_item {
+                    ImplItem::Method(ImplItemMethod { sig, .. }) if sig.ident == *method => Some((item_impl, sig)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    /// The `Deref` impls of a type, along with the target type
+    pub fn get_deref_impls(&self, type_name: &str) -> Vec<(&ItemImpl, &Type)> {
+        self.get_impls(type_name)
+            .filter(|item_impl| match &item_impl.trait_ {
+                Some((_, path, _)) => path.segments.last().unwrap().ident == "Deref",
+                None => false,
+            })
+            .filter_map(|item_impl| {
+                item_impl.items.iter().find_map(|impl_item| match impl_item {
+                    ImplItem::Type(ImplItemType { ident, ty, .. }) if ident == "Target" => Some((item_impl, ty)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    fn get_impls(&self, type_name: &str) -> impl Iterator<Item = &ItemImpl> {
+        self.impls.get(type_name).into_iter().flatten()
+    }
+}
+
+thread_local! {
+    /// The signatures of the standard library, these are parsed the first time they are used and
+    /// shared by every table created from them. Syntax nodes cannot be shared between threads so
+    /// each thread has its own.
+    static STD_METHOD_SIGNATURES: MethodSignatures = {
+        let numeric_impls: String = NUMERIC_TYPES
+            .iter()
+            .map(|name| format!("impl {} {{ {} }}", name, NUMERIC_SIGNATURES))
+            .collect();
+        MethodSignatures::empty()
+            .with_source(&(STD_SIGNATURES.to_string() + &numeric_impls))
+            .expect("The signatures of the standard library are valid")
+    };
+}
+
+impl Default for MethodSignatures {
+    /// The signatures of the standard library, the impls are shared until signatures are added
+    fn default() -> Self {
+        STD_METHOD_SIGNATURES.with(MethodSignatures::clone)
+    }
+}
+
+/// The name the impls of a type are found by, `[]` for slices and arrays
+pub fn get_type_name(type_: &Type) -> Option<String> {
+    match type_ {
+        Type::Path(TypePath { qself: None, path }) => Some(path.segments.last()?.ident.to_string()),
+        Type::Slice(_) | Type::Array(_) => Some("[]".to_string()),
+        Type::Paren(TypeParen { elem, .. }) | Type::Group(TypeGroup { elem, .. }) => get_type_name(elem),
+        _ => None,
+    }
+}
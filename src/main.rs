@@ -7,8 +7,15 @@ extern crate proc_macro;
 // extern crate rustc_typeck;
 
 use clap::Parser;
-use rfood::transform::transformer::transform_file;
-use rfood::cli::{Cli, Commands};
+use rfood::transform::transformer::{transform_crate, transform_file};
+use rfood::transform::check::check_file;
+use rfood::transform::selection::Selection;
+use rfood::transform::plan::TransformPlan;
+use rfood::transform::transformer::TransformMode;
+use rfood::context::errors::{RfoodError, RfoodResult};
+use rfood::context::signatures::MethodSignatures;
+use rfood::cli::{Cli, Commands, PlanArgs};
+use rfood::cli::logging::init_logging;
 
 // use std::env;
 use std::fs::File;
@@ -27,11 +34,62 @@ fn print_goal() {
   println!("{:#?}\n\n", syntax);
 }
 
+/// The plan for the transform options, per type directions replace the transform type
+fn get_plan(args: &PlanArgs, mode: &TransformMode) -> RfoodResult<TransformPlan> {
+    let plan = if args.to_fp.is_empty() && args.to_oop.is_empty() {
+        TransformPlan::new(args.transform_type.clone(), Selection::from_strs(&args.include, &args.exclude)?)
+    } else {
+        TransformPlan::from_directions(&args.to_fp, &args.to_oop)?
+    };
+    let method_signatures = args.signatures
+        .iter()
+        .try_fold(MethodSignatures::default(), |method_signatures, path| method_signatures.with_file(path))?;
+    Ok(plan.with_mode(*mode).with_pointer(args.pointer).with_signatures(method_signatures))
+}
+
+/// Report the errors skipped by a best effort transformation
+fn report_skipped(skipped: Vec<RfoodError>) {
+    for error in &skipped {
+        eprintln!("{}\n", error);
+    }
+    if !skipped.is_empty() {
+        eprintln!("warning: {} errors were skipped, the code was left as it is or replaced with todo!()", skipped.len());
+    }
+}
+
 fn main() {
     let args = Cli::parse();
+    init_logging(args.verbose, args.trace_json);
+
+    let result = match &args.command {
+        Commands::PrintTest => {
+            print_goal();
+            Ok(())
+        },
+        Commands::Transform{path, plan, output_path, mode} => {
+            get_plan(plan, mode)
+                .and_then(|plan| transform_file(path, output_path, &plan))
+                .map(report_skipped)
+        },
+        Commands::Check{path, plan} => {
+            get_plan(plan, &TransformMode::Strict)
+                .and_then(|plan| check_file(path, &plan))
+                .map(|report| {
+                    println!("{}", report);
+                    if !report.is_transformable() {
+                        std::process::exit(1);
+                    }
+                })
+        },
+        Commands::TransformCrate{path, plan, output_path, mode} => {
+            get_plan(plan, mode)
+                .and_then(|plan| transform_crate(path, output_path, &plan))
+                .map(report_skipped)
+        },
+    };
 
-    match &args.command {
-        Commands::PrintTest => print_goal(),
-        Commands::Transform{path, output_path, transform_type} => transform_file(path, output_path, transform_type),
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,396 @@
+use std::fmt;
+use std::fs;
+use std::path::Path as FilePath;
+
+use quote::ToTokens;
+use syn::visit::{visit_path_segment, Visit};
+use syn::*;
+
+use crate::context::errors::*;
+use crate::context::gamma::{generate_gamma, Gamma};
+use crate::transform::plan::{TransformPlan, TransformStep};
+use crate::transform::selection::Selection;
+use crate::transform::transformer::{transform_planned_syntax, TransformMode, TransformType};
+use crate::transform::visitors::is_collection;
+
+/// Whether a trait (OOP to FP) or enum (FP to OOP) can be transformed
+#[derive(Debug, Clone)]
+pub struct TypeCheck {
+    /// The direction the type is transformed in
+    pub transform_type: TransformType,
+    /// The module the type is declared in
+    pub module_path: Vec<Ident>,
+    pub ident: Ident,
+    /// Why the type cannot be transformed, empty if it can be
+    pub violations: Vec<RfoodError>,
+}
+
+impl TypeCheck {
+    pub fn is_transformable(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// The path of the type from the root of the file, e.g. `shapes::Shape`
+    pub fn path_string(&self) -> String {
+        self.module_path
+            .iter()
+            .chain(std::iter::once(&self.ident))
+            .map(|ident| ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
+
+/// The result of checking the selected types of a file
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::check::check_string;
+/// use rfood::transform::plan::TransformPlan;
+/// use rfood::transform::selection::Selection;
+/// use rfood::transform::transformer::TransformType;
+///
+/// let input = "
+///     trait Shape { fn copy(&self) -> Option<Self>; }
+///     trait Area { fn area(&self) -> i32; }
+///     struct Square { side: i32 }
+///     impl Shape for Square { fn copy(&self) -> Option<Self> { Some(Square { side: self.side }) } }
+///     struct Circle { radius: i32 }
+///     impl Area for Circle { fn area(&self) -> i32 { 3 * self.radius * self.radius } }
+/// ";
+/// let plan = TransformPlan::new(TransformType::OOPToFP, Selection::default());
+/// let report = check_string(input, &plan).unwrap();
+/// assert!(!report.is_transformable());
+/// assert_eq!(report.blocked().map(|type_check| type_check.path_string()).collect::<Vec<_>>(), vec!["Shape"]);
+/// assert_eq!(report.transformable().map(|type_check| type_check.path_string()).collect::<Vec<_>>(), vec!["Area"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    pub types: Vec<TypeCheck>,
+}
+
+impl CheckReport {
+    /// Check if all the selected types can be transformed
+    pub fn is_transformable(&self) -> bool {
+        self.types.iter().all(TypeCheck::is_transformable)
+    }
+
+    pub fn transformable(&self) -> impl Iterator<Item = &TypeCheck> {
+        self.types.iter().filter(|type_check| type_check.is_transformable())
+    }
+
+    pub fn blocked(&self) -> impl Iterator<Item = &TypeCheck> {
+        self.types.iter().filter(|type_check| !type_check.is_transformable())
+    }
+
+    /// Set the file of all the violations
+    pub fn in_file(mut self, path: &FilePath) -> Self {
+        for type_check in &mut self.types {
+            type_check.violations = type_check
+                .violations
+                .drain(..)
+                .map(|violation| violation.in_file(path))
+                .collect();
+        }
+        self
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for type_check in &self.types {
+            if type_check.is_transformable() {
+                writeln!(f, "ok: {}", type_check.path_string())?;
+            } else {
+                writeln!(f, "blocked: {}", type_check.path_string())?;
+                for violation in &type_check.violations {
+                    for line in violation.to_string().lines() {
+                        writeln!(f, "    {}", line)?;
+                    }
+                }
+            }
+        }
+        let kind = if self.types.iter().all(|type_check| matches!(type_check.transform_type, TransformType::OOPToFP)) {
+            "traits"
+        } else if self.types.iter().all(|type_check| matches!(type_check.transform_type, TransformType::FPToOOP)) {
+            "enums"
+        } else {
+            "traits/enums"
+        };
+        write!(
+            f,
+            "{} of {} {} can be transformed",
+            self.transformable().count(),
+            self.types.len(),
+            kind
+        )
+    }
+}
+
+/// Finds any reference to a type, either by its name or as `Self`
+struct FindType<'a> {
+    ident: &'a Ident,
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for FindType<'_> {
+    fn visit_path_segment(&mut self, i: &'ast PathSegment) {
+        if i.ident == *self.ident || i.ident == "Self" {
+            self.found = true;
+        }
+        visit_path_segment(self, i);
+    }
+}
+
+fn mentions_type(type_: &Type, ident: &Ident) -> bool {
+    let mut find_type = FindType { ident, found: false };
+    find_type.visit_type(type_);
+    find_type.found
+}
+
+/// Check if every instance of the type in a type is held in a pointer, e.g. `Box<dyn Shape>`,
+/// `Option<Box<Self>>`, `Rc<RefCell<Expr>>` or `Vec<Expr>`, as the std collections store their
+/// items on the heap as well
+fn is_held_in_pointer(type_: &Type, ident: &Ident) -> bool {
+    match type_ {
+        Type::Reference(_) => true,
+        Type::Path(TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+            let args = get_generic_type_args(segment);
+            if is_pointer_ident(&segment.ident) || is_collection(&segment.ident) {
+                args.into_iter().all(|arg| is_pointee_of_type(arg, ident) || is_held_in_pointer(arg, ident))
+            } else {
+                !is_type(type_, ident) && args.into_iter().all(|arg| is_held_in_pointer(arg, ident))
+            }
+        }
+        Type::Tuple(TypeTuple { elems, .. }) => elems.iter().all(|elem| is_held_in_pointer(elem, ident)),
+        Type::Array(TypeArray { elem, .. })
+        | Type::Slice(TypeSlice { elem, .. })
+        | Type::Paren(TypeParen { elem, .. })
+        | Type::Group(TypeGroup { elem, .. }) => is_held_in_pointer(elem, ident),
+        _ => !mentions_type(type_, ident),
+    }
+}
+
+/// Check if a type pointed to is the type, `dyn Shape`, `Shape`, `Self` or any of them in a
+/// `RefCell`
+fn is_pointee_of_type(type_: &Type, ident: &Ident) -> bool {
+    match type_ {
+        Type::Path(TypePath { qself: None, path }) if path.segments.last().unwrap().ident == "RefCell" => {
+            matches!(get_generic_type_args(path.segments.last().unwrap()).as_slice(), [inner_type] if is_type(inner_type, ident))
+        }
+        _ => is_type(type_, ident),
+    }
+}
+
+/// Check if a type is the type itself, `dyn Shape`, `Shape` or `Self`
+fn is_type(type_: &Type, ident: &Ident) -> bool {
+    let path = match type_ {
+        Type::Path(TypePath { qself: None, path }) => path,
+        Type::TraitObject(TypeTraitObject { bounds, .. }) => match bounds.first() {
+            Some(TypeParamBound::Trait(TraitBound { path, .. })) => path,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    let last_ident = &path.segments.last().unwrap().ident;
+    last_ident == ident || (last_ident == "Self" && path.segments.len() == 1)
+}
+
+fn get_generic_type_args(segment: &PathSegment) -> Vec<&Type> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(type_) => Some(type_),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_pointer_ident(ident: &Ident) -> bool {
+    ident == "Box" || ident == "Rc" || ident == "Arc"
+}
+
+/// Check the restrictions on a trait
+fn check_trait(trait_: &ItemTrait, gamma: &Gamma) -> Vec<RfoodError> {
+    let mut violations = Vec::new();
+
+    // If a trait returns an instance of itself it must be `Self` or a dyn box
+    for destructor in gamma.get_destructors(&trait_.ident) {
+        if let ReturnType::Type(_, box return_type) = &destructor.sig.output {
+            let is_self = matches!(return_type, Type::Path(TypePath { qself: None, path }) if path.is_ident("Self"));
+            if !is_held_in_pointer(return_type, &trait_.ident) && !is_self {
+                violations.push(RfoodError::new(
+                    ErrorCode::UnsupportedType,
+                    format!(
+                        "Method `{}` returns `{}`, an instance of the trait must be returned as `Box<dyn {}>`",
+                        destructor.sig.ident,
+                        return_type.to_token_stream(),
+                        trait_.ident,
+                    ),
+                ).with_span(return_type));
+            }
+        }
+    }
+
+    violations
+}
+
+/// Check the restrictions on an enum
+fn check_enum(enum_: &ItemEnum) -> Vec<RfoodError> {
+    let mut violations = Vec::new();
+
+    // If an enum contains an instance of itself it must be a box
+    for variant in &enum_.variants {
+        for field in &variant.fields {
+            if !is_held_in_pointer(&field.ty, &enum_.ident) {
+                violations.push(RfoodError::new(
+                    ErrorCode::UnsupportedType,
+                    format!(
+                        "Variant `{}` contains `{}`, an instance of the enum must be stored as `Box<{}>`",
+                        variant.ident,
+                        field.ty.to_token_stream(),
+                        enum_.ident,
+                    ),
+                ).with_span(&field.ty));
+            }
+        }
+    }
+
+    violations
+}
+
+/// Check which of the selected traits (OOP to FP) or enums (FP to OOP) of a file can be
+/// transformed by a plan
+///
+/// The restrictions of the transformation are checked first, a type which passes them is then
+/// transformed on its own (after the previous steps of the plan) with the pointer and method
+/// signatures of the plan to find any other case the transformation does not handle. Any type
+/// named in a selection which is not declared in the file is reported as blocked.
+pub fn check_syntax(syntax: &syn::File, plan: &TransformPlan) -> RfoodResult<CheckReport> {
+    let mut type_checks = Vec::new();
+    for (step_index, step) in plan.steps.iter().enumerate() {
+        type_checks.extend(check_step(syntax, plan, step_index, step)?);
+    }
+    Ok(CheckReport { types: type_checks })
+}
+
+/// Check the selected types of a step of a plan
+fn check_step(syntax: &syn::File, plan: &TransformPlan, step_index: usize, step: &TransformStep) -> RfoodResult<Vec<TypeCheck>> {
+    let TransformStep { transform_type, selection } = step;
+    let gamma = generate_gamma(syntax)?;
+
+    let types: Vec<(Ident, Vec<RfoodError>)> = match transform_type {
+        TransformType::OOPToFP => gamma
+            .traits
+            .iter()
+            .map(|trait_| (trait_.ident.clone(), check_trait(trait_, &gamma)))
+            .collect(),
+        TransformType::FPToOOP => gamma
+            .enums
+            .iter()
+            .map(|enum_| (enum_.ident.clone(), check_enum(enum_)))
+            .collect(),
+    };
+
+    let mut type_checks: Vec<TypeCheck> = types
+        .into_iter()
+        .map(|(ident, violations)| TypeCheck {
+            transform_type: transform_type.clone(),
+            module_path: gamma.get_item_module(&ident),
+            ident,
+            violations,
+        })
+        .filter(|type_check| selection.is_selected(&type_check.module_path, &type_check.ident))
+        .collect();
+
+    for type_check in type_checks.iter_mut().filter(|type_check| type_check.is_transformable()) {
+        let only_type = TransformStep {
+            transform_type: transform_type.clone(),
+            selection: Selection::from_strs(&[type_check.path_string()], &[])?,
+        };
+        let type_plan = TransformPlan {
+            steps: plan.steps[..step_index].iter().cloned().chain(std::iter::once(only_type)).collect(),
+            mode: TransformMode::Strict,
+            ..plan.clone()
+        };
+        if let Err(error) = transform_planned_syntax(syntax.clone(), &type_plan) {
+            type_check.violations.push(error);
+        }
+    }
+
+    // Included types which were not found
+    let kind = match transform_type {
+        TransformType::OOPToFP => "trait",
+        TransformType::FPToOOP => "enum",
+    };
+    for path in &selection.include {
+        let path_selection = Selection { include: vec![path.clone()], exclude: Vec::new() };
+        if !type_checks.iter().any(|type_check| path_selection.is_selected(&type_check.module_path, &type_check.ident)) {
+            let path_string = path.to_token_stream().to_string().replace(' ', "");
+            let mut idents: Vec<Ident> = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.clone())
+                .skip_while(|ident| ident == "crate")
+                .collect();
+            let ident = idents.pop().ok_or_else(|| {
+                RfoodError::new(ErrorCode::NotFound, format!("`{}` does not name a {}", path_string, kind))
+            })?;
+            type_checks.push(TypeCheck {
+                transform_type: transform_type.clone(),
+                violations: vec![RfoodError::not_found(&path_string, kind)],
+                module_path: idents,
+                ident,
+            });
+        }
+    }
+
+    Ok(type_checks)
+}
+
+/// Check which of the selected types of a string can be transformed by a plan
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::check::check_string;
+/// use rfood::transform::plan::TransformPlan;
+/// use rfood::transform::selection::Selection;
+/// use rfood::transform::transformer::TransformType;
+///
+/// let input = "
+///     enum Expr { Lit(i32), Neg(Option<Box<Expr>>), Sum(Vec<Expr>) }
+///     fn eval(expr: &Expr) -> i32 {
+///         match expr {
+///             Expr::Lit(n) => *n,
+///             Expr::Neg(inner) => inner.as_ref().map_or(0, |inner| -eval(inner)),
+///             Expr::Sum(exprs) => exprs.iter().map(eval).sum(),
+///         }
+///     }
+///     enum Tree { Leaf, Node(Option<Tree>) }
+/// ";
+/// let plan = TransformPlan::new(TransformType::FPToOOP, Selection::default());
+/// let report = check_string(input, &plan).unwrap();
+/// assert_eq!(report.transformable().map(|type_check| type_check.path_string()).collect::<Vec<_>>(), vec!["Expr"]);
+/// assert_eq!(report.blocked().map(|type_check| type_check.path_string()).collect::<Vec<_>>(), vec!["Tree"]);
+///
+/// let plan = TransformPlan::new(TransformType::FPToOOP, Selection::from_strs(&["crate"], &[]).unwrap());
+/// assert!(check_string(input, &plan).is_err());
+/// ```
+pub fn check_string(input: &str, plan: &TransformPlan) -> RfoodResult<CheckReport> {
+    let syntax = syn::parse_file(input)?;
+    check_syntax(&syntax, plan)
+}
+
+/// Check which of the selected types of a file can be transformed by a plan
+pub fn check_file(path: &FilePath, plan: &TransformPlan) -> RfoodResult<CheckReport> {
+    let src = fs::read_to_string(path)?;
+    check_string(&src, plan)
+        .map(|report| report.in_file(path))
+        .map_err(|error| error.in_file(path))
+}
@@ -2,3 +2,6 @@
 
 pub mod transformer;
 pub mod visitors;
+pub mod selection;
+pub mod plan;
+pub mod check;
@@ -0,0 +1,115 @@
+use crate::context::delta::Pointer;
+use crate::context::errors::RfoodResult;
+use crate::context::signatures::MethodSignatures;
+use crate::transform::selection::Selection;
+use crate::transform::transformer::{TransformMode, TransformType};
+
+/// A step of a plan, the selected types are transformed in one direction
+#[derive(Clone)]
+pub struct TransformStep {
+    pub transform_type: TransformType,
+    pub selection: Selection,
+}
+
+/// The steps to transform a file (or crate) with, each step is transformed from the output of the
+/// previous step so gamma is always collected from the current code
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::plan::TransformPlan;
+/// use rfood::transform::transformer::TransformType;
+///
+/// let plan = TransformPlan::from_directions(&["Context"], &["Expr"]).unwrap();
+/// assert_eq!(plan.steps.len(), 2);
+/// assert!(matches!(plan.steps[0].transform_type, TransformType::OOPToFP));
+/// assert!(matches!(plan.steps[1].transform_type, TransformType::FPToOOP));
+/// ```
+#[derive(Clone)]
+pub struct TransformPlan {
+    pub steps: Vec<TransformStep>,
+    /// How code which cannot be transformed is handled, strict unless set with `with_mode`
+    pub mode: TransformMode,
+    /// The pointer the trait objects created from FP to OOP are held in, `Box` unless set with
+    /// `with_pointer`
+    pub pointer: Pointer,
+    /// The signatures of the methods of types which are not in the code, the standard library
+    /// unless extended with `with_signatures`
+    pub signatures: MethodSignatures,
+}
+
+impl TransformPlan {
+    /// Create a plan which transforms the selected types in one direction
+    pub fn new(transform_type: TransformType, selection: Selection) -> Self {
+        TransformPlan {
+            steps: vec![TransformStep { transform_type, selection }],
+            mode: TransformMode::default(),
+            pointer: Pointer::default(),
+            signatures: MethodSignatures::default(),
+        }
+    }
+
+    /// Create a plan which transforms the traits in `to_fp` into enums and the enums in `to_oop`
+    /// into traits
+    ///
+    /// The traits are transformed first, the consumers of the enums can then be transformed with
+    /// the new enums in their signatures already. Fails if any of the types is not a valid path.
+    pub fn from_directions<S: AsRef<str>>(to_fp: &[S], to_oop: &[S]) -> RfoodResult<Self> {
+        let steps = [(TransformType::OOPToFP, to_fp), (TransformType::FPToOOP, to_oop)]
+            .into_iter()
+            // An empty selection would transform every type
+            .filter(|(_, types)| !types.is_empty())
+            .map(|(transform_type, types)| {
+                Ok(TransformStep {
+                    transform_type,
+                    selection: Selection::from_strs(types, &[])?,
+                })
+            })
+            .collect::<RfoodResult<_>>()?;
+
+        Ok(TransformPlan {
+            steps,
+            mode: TransformMode::default(),
+            pointer: Pointer::default(),
+            signatures: MethodSignatures::default(),
+        })
+    }
+
+    /// Set how code which cannot be transformed is handled
+    pub fn with_mode(self, mode: TransformMode) -> Self {
+        TransformPlan { mode, ..self }
+    }
+
+    /// Set the pointer the trait objects created from FP to OOP are held in
+    pub fn with_pointer(self, pointer: Pointer) -> Self {
+        TransformPlan { pointer, ..self }
+    }
+
+    /// Set the signatures of the methods of types which are not in the code, e.g. those of a
+    /// dependency
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rfood::context::signatures::MethodSignatures;
+    /// use rfood::transform::plan::TransformPlan;
+    /// use rfood::transform::transformer::transform_planned_string;
+    ///
+    /// let input = "
+    ///     trait Shape { fn draw(&self, canvas: &mut Canvas); }
+    ///     struct Circle { radius: f64 }
+    ///     impl Shape for Circle { fn draw(&self, canvas: &mut Canvas) { canvas.circle(self.radius) } }
+    /// ".to_string();
+    /// let plan = TransformPlan::from_directions(&["Shape"], &[]).unwrap();
+    /// assert!(transform_planned_string(input.clone(), &plan).is_err());
+    ///
+    /// let signatures = MethodSignatures::default()
+    ///     .with_source("impl Canvas { fn circle(&mut self, radius: f64) {} }")
+    ///     .unwrap();
+    /// let (output, _) = transform_planned_string(input, &plan.with_signatures(signatures)).unwrap();
+    /// assert!(output.contains("canvas . circle (* radius)"));
+    /// ```
+    pub fn with_signatures(self, signatures: MethodSignatures) -> Self {
+        TransformPlan { signatures, ..self }
+    }
+}
@@ -0,0 +1,75 @@
+use syn::*;
+
+use crate::context::errors::{ErrorCode, RfoodError, RfoodResult};
+
+/// Select which traits (OOP to FP) or enums (FP to OOP) are transformed
+///
+/// Types are named by their path from the root of the file (or crate), e.g. `shapes::Shape`. A
+/// name without any modules matches the type in any module.
+///
+/// # Examples
+///
+/// ```
+/// use syn::*;
+/// use rfood::transform::selection::Selection;
+/// use rfood::context::errors::ErrorCode;
+///
+/// let selection = Selection::from_strs(&["shapes::Shape"], &[]).unwrap();
+/// let shapes = vec![parse_str::<Ident>("shapes").unwrap()];
+/// assert!(selection.is_selected(&shapes, &parse_str::<Ident>("Shape").unwrap()));
+/// assert!(!selection.is_selected(&Vec::new(), &parse_str::<Ident>("Shape").unwrap()));
+/// assert!(!selection.is_selected(&shapes, &parse_str::<Ident>("Colour").unwrap()));
+/// assert_eq!(Selection::from_strs(&["shapes::"], &[]).unwrap_err().code, ErrorCode::ParseFailed);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// The types to transform, if this is empty every type is transformed
+    pub include: Vec<Path>,
+    /// The types which are never transformed
+    pub exclude: Vec<Path>,
+}
+
+/// Check if the path names the item declared in the module
+fn is_path_of_item(path: &Path, module_path: &[Ident], ident: &Ident) -> bool {
+    let idents: Vec<&Ident> = path
+        .segments
+        .iter()
+        .map(|segment| &segment.ident)
+        .skip_while(|segment_ident| *segment_ident == "crate")
+        .collect();
+
+    match idents.split_last() {
+        Some((last, [])) => *last == ident,
+        Some((last, modules)) => *last == ident && modules.iter().copied().eq(module_path.iter()),
+        None => false,
+    }
+}
+
+impl Selection {
+    /// Create a selection from the paths of the types to include and exclude, fails if any of
+    /// them is not a valid path
+    pub fn from_strs<S: AsRef<str>>(include: &[S], exclude: &[S]) -> RfoodResult<Self> {
+        let parse_paths = |paths: &[S]| -> RfoodResult<Vec<Path>> {
+            paths
+                .iter()
+                .map(|path| {
+                    parse_str::<Path>(path.as_ref()).map_err(|_| {
+                        RfoodError::new(ErrorCode::ParseFailed, format!("Invalid type path {:?}", path.as_ref()))
+                    })
+                })
+                .collect()
+        };
+
+        Ok(Selection {
+            include: parse_paths(include)?,
+            exclude: parse_paths(exclude)?,
+        })
+    }
+
+    /// Check if the type declared in the module should be transformed
+    pub fn is_selected(&self, module_path: &[Ident], ident: &Ident) -> bool {
+        (self.include.is_empty()
+            || self.include.iter().any(|path| is_path_of_item(path, module_path, ident)))
+            && !self.exclude.iter().any(|path| is_path_of_item(path, module_path, ident))
+    }
+}
@@ -1,4 +1,7 @@
 use ast::print::write_and_fmt;
+use ast::modules::{get_file_of_module, load_crate, split_crate};
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
@@ -7,34 +10,44 @@ use syn::punctuated::Punctuated;
 use syn::visit::*;
 use syn::visit_mut::*;
 use syn::*;
+use syn::__private::Span;
 
 use crate::context;
 use crate::utils::utils::PopFirst;
 use context::delta::*;
 use context::errors::*;
 use context::gamma::*;
+use context::signatures::MethodSignatures;
 
 use crate::ast;
 use ast::create::*;
 
 use crate::transform;
 use transform::visitors::*;
+use transform::selection::Selection;
+use transform::plan::TransformPlan;
 
 use quote::quote;
+use tracing::{debug, debug_span, info, info_span, trace};
 
-use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
 
-#[derive(clap::ArgEnum, Clone)]
+#[derive(clap::ArgEnum, Clone, Debug)]
 pub enum TransformType {
     OOPToFP,
     FPToOOP,
 }
 
-fn remove_item_from_syntax(syntax: &mut syn::File, item: syn::Item) {
-    let index = syntax.items.iter().position(|sitem| *sitem == item);
-    if index.is_some() {
-        syntax.items.remove(index.unwrap());
-    }
+/// How code which cannot be transformed is handled
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransformMode {
+    /// Fail on the first error
+    #[default]
+    Strict,
+    /// Leave the code that cannot be transformed as it is and report it. A trait that cannot be
+    /// transformed is kept, an item is kept in its original form and an expression is replaced
+    /// with a `todo!()` holding the error and the original expression
+    BestEffort,
 }
 
 #[wasm_bindgen]
@@ -43,159 +56,615 @@ pub fn hello_world(name: String) -> String {
 }
 
 #[wasm_bindgen]
-pub fn transform_string_binding(input: String, oop: bool) -> String {
+pub fn transform_string_binding(input: String, oop: bool) -> std::result::Result<String, JsValue> {
     transform_string(
         input,
         if oop {&TransformType::OOPToFP} else {&TransformType::FPToOOP}
-    )
+    ).map_err(|error| JsValue::from_str(&error.to_string()))
 }
 
-pub fn transform_string(input: String, transform_type: &TransformType) -> String {
-    let mut syntax: syn::File = syn::parse_file(&input).expect("Unable to parse file");
-    let mut transformed_syntax = syn::File {
-        items: Vec::new(),
-        ..syntax.clone()
-    };
+/// Transform a string of Rust code
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::transformer::{transform_string, TransformType};
+/// use rfood::context::errors::ErrorCode;
+///
+/// let input = "
+///     trait Shape { fn area(&self) -> f64; }
+///     struct Circle { radius: f64 }
+///     impl Shape for Circle {
+///         fn area(&self) -> f64 { self.radius * SCALE.sqrt() }
+///     }
+/// ".to_string();
+/// let error = transform_string(input, &TransformType::OOPToFP).unwrap_err();
+/// assert_eq!(error.code, ErrorCode::UnknownVariable);
+/// assert_eq!(error.location.unwrap().line, 5);
+///
+/// let input = "
+///     enum Shape { Square { side: u32 } }
+///     fn unit() -> Self { Shape::Square { side: 1 } }
+/// ".to_string();
+/// let error = transform_string(input, &TransformType::FPToOOP).unwrap_err();
+/// assert_eq!(error.code, ErrorCode::UnsupportedSyntax);
+/// assert_eq!(error.location.unwrap().line, 3);
+/// ```
+pub fn transform_string(input: String, transform_type: &TransformType) -> RfoodResult<String> {
+    transform_selected_string(input, transform_type, &Selection::default())
+}
+
+/// Transform only the selected traits/enums in a string, any code using them is still updated
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::transformer::{transform_selected_string, TransformType};
+/// use rfood::transform::selection::Selection;
+///
+/// let input = "
+///     enum Predicate { Eq { a: i32, b: i32 } }
+///     enum Operator { Filter { pred: Predicate } }
+/// ".to_string();
+/// let output = transform_selected_string(input, &TransformType::FPToOOP, &Selection::from_strs(&["Predicate"], &[]).unwrap()).unwrap();
+/// assert!(output.contains("trait Predicate"));
+/// assert!(output.contains("enum Operator"));
+/// assert!(output.contains("dyn Predicate"));
+/// ```
+pub fn transform_selected_string(input: String, transform_type: &TransformType, selection: &Selection) -> RfoodResult<String> {
+    let syntax: syn::File = syn::parse_file(&input)?;
+    let transformed_syntax = transform_syntax(syntax, transform_type, selection)?;
+
+    Ok(quote!(#transformed_syntax).to_string())
+}
+
+/// Transform a string with each step of a plan in turn
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::transformer::transform_planned_string;
+/// use rfood::transform::plan::TransformPlan;
+///
+/// let input = "
+///     trait Shape { fn area(&self) -> i32; }
+///     struct Square { side: i32 }
+///     impl Shape for Square { fn area(&self) -> i32 { self.side * self.side } }
+///     enum Scene { Single { shape: Box<dyn Shape> } }
+///     fn total_area(scene: Scene) -> i32 {
+///         match scene { Scene::Single { shape } => shape.area() }
+///     }
+/// ".to_string();
+/// let (output, skipped) = transform_planned_string(input, &TransformPlan::from_directions(&["Shape"], &["Scene"]).unwrap()).unwrap();
+/// assert!(output.contains("enum Shape"));
+/// assert!(output.contains("trait Scene"));
+/// assert!(!output.contains("dyn Shape"));
+/// assert!(skipped.is_empty());
+/// ```
+pub fn transform_planned_string(input: String, plan: &TransformPlan) -> RfoodResult<(String, Vec<RfoodError>)> {
+    let syntax: syn::File = syn::parse_file(&input)?;
+    let (transformed_syntax, skipped) = transform_planned_syntax(syntax, plan)?;
+
+    Ok((quote!(#transformed_syntax).to_string(), skipped))
+}
+
+/// Transform a file with each step of a plan in turn, gamma is collected again for every step.
+/// Along with the transformed file the errors skipped in best effort mode are returned
+pub fn transform_planned_syntax(syntax: syn::File, plan: &TransformPlan) -> RfoodResult<(syn::File, Vec<RfoodError>)> {
+    plan.steps.iter().try_fold((syntax, Vec::new()), |(syntax, mut skipped), step| {
+        let (syntax, step_skipped) = catch_internal_errors(|| {
+            transform_selected_syntax(syntax, &step.transform_type, &step.selection, plan.mode, plan.pointer, &plan.signatures)
+        })?;
+        skipped.extend(step_skipped);
+        Ok((syntax, skipped))
+    })
+}
+
+/// Transform the selected traits/enums of a file including those in its modules
+///
+/// Any case the transformation does not handle is returned as an internal error
+pub fn transform_syntax(syntax: syn::File, transform_type: &TransformType, selection: &Selection) -> RfoodResult<syn::File> {
+    transform_syntax_in_mode(syntax, transform_type, selection, TransformMode::Strict).map(|(syntax, _)| syntax)
+}
+
+/// Transform the selected traits/enums of a file, returning the errors skipped in best effort mode
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::transformer::{transform_syntax_in_mode, TransformMode, TransformType};
+/// use rfood::transform::selection::Selection;
+/// use rfood::context::errors::ErrorCode;
+///
+/// let input = syn::parse_str("
+///     trait Shape { fn area(&self) -> f64; }
+///     struct Circle { radius: f64 }
+///     impl Shape for Circle { fn area(&self) -> f64 { self.radius * SCALE.sqrt() } }
+///     struct Square { side: f64 }
+///     impl Shape for Square { fn area(&self) -> f64 { self.side * self.side } }
+/// ").unwrap();
+/// let (output, skipped) = transform_syntax_in_mode(input, &TransformType::OOPToFP, &Selection::default(), TransformMode::BestEffort).unwrap();
+/// let output = quote::quote!(#output).to_string();
+/// assert!(output.contains("enum Shape"));
+/// assert!(output.contains("todo !"));
+/// assert_eq!(skipped.len(), 1);
+/// assert_eq!(skipped[0].code, ErrorCode::UnknownVariable);
+/// ```
+///
+/// The errors skipped in a module record the module, to find the file of the module in a crate
+///
+/// ```
+/// use rfood::transform::transformer::{transform_syntax_in_mode, TransformMode, TransformType};
+/// use rfood::transform::selection::Selection;
+///
+/// let input = syn::parse_str("
+///     mod shapes {
+///         pub trait Shape { fn area(&self) -> f64; }
+///         pub struct Circle { pub radius: f64 }
+///         impl Shape for Circle { fn area(&self) -> f64 { self.radius * SCALE.sqrt() } }
+///     }
+/// ").unwrap();
+/// let (_, skipped) = transform_syntax_in_mode(input, &TransformType::OOPToFP, &Selection::default(), TransformMode::BestEffort).unwrap();
+/// assert_eq!(skipped[0].module_path, Some(vec![syn::parse_str::<syn::Ident>("shapes").unwrap()]));
+/// ```
+pub fn transform_syntax_in_mode(syntax: syn::File, transform_type: &TransformType, selection: &Selection, mode: TransformMode) -> RfoodResult<(syn::File, Vec<RfoodError>)> {
+    catch_internal_errors(|| transform_selected_syntax(syntax, transform_type, selection, mode, Pointer::default(), &MethodSignatures::default()))
+}
+
+fn transform_selected_syntax(
+    mut syntax: syn::File,
+    transform_type: &TransformType,
+    selection: &Selection,
+    mode: TransformMode,
+    pointer: Pointer,
+    signatures: &MethodSignatures,
+) -> RfoodResult<(syn::File, Vec<RfoodError>)> {
+    // The transformation identifies items by name, so renamed imports of the items being
+    // transformed are replaced by their names first
+    ReplaceRenamedImports { gamma: &generate_gamma(&syntax)?, module_path: Vec::new() }.visit_file_mut(&mut syntax);
+
+    // Enums have no associated types, the associated types of the traits become type parameters
+    // of the traits (or the type all the generators give them) before the traits are transformed.
+    // The associated consts become methods, which are transformed like any other destructor. The
+    // supertraits with the same generators are merged into the traits first, so their associated
+    // items are replaced along with those of the traits
+    if matches!(transform_type, TransformType::OOPToFP) {
+        ReplaceSupertraits::new(&generate_gamma(&syntax)?, selection).visit_file_mut(&mut syntax);
+        ReplaceStaticMethods::new(&generate_gamma(&syntax)?, selection).visit_file_mut(&mut syntax);
+        ReplaceAssociatedTypes::new(&generate_gamma(&syntax)?, selection).visit_file_mut(&mut syntax);
+        ReplaceAssociatedConsts::new(&generate_gamma(&syntax)?, selection).visit_file_mut(&mut syntax);
+    }
 
     // Generate global gamma context
-    let mut gamma: Gamma = generate_gamma(&syntax);
+    let mut gamma: Gamma = generate_gamma(&syntax)?;
+    gamma.mode = mode;
+    gamma.pointer = pointer;
+    gamma.method_signatures = signatures.clone();
     let gamma_mut_borrow = &mut gamma;
 
+    // The items created for each transformed trait/enum, along with the module they belong in
+    let mut transformed_items: Vec<(Vec<Ident>, Vec<Item>)> = Vec::new();
+    // The items replaced by the transformed items
+    let mut removed_items: Vec<Item> = Vec::new();
+    // The imports of the generators/consumers and variants which are removed must be updated
+    let mut removed_imports: HashMap<Ident, Option<Ident>> = HashMap::new();
+    let mut removed_enums: HashSet<Ident> = HashSet::new();
+    // The traits transformed into enums, which modules may have imported only for the generators
+    let mut transformed_traits: HashSet<Ident> = HashSet::new();
+
     match transform_type {
         // Stage 1
         TransformType::OOPToFP => {
             // Transform all the interfaces
-            println!("Transorming all traits");
-
-            for item in syntax.items.clone() {
-                match &item {
-                    Item::Trait(trait_) => {
-                        // Add the transformed items to the transformed syntax
-                        transformed_syntax
-                            .items
-                            .append(&mut transform_trait(&trait_, gamma_mut_borrow));
-                    
-                        // Remove the original trait from the syntax
-                        for (item_struct, item_impl) in gamma_mut_borrow.get_generators(&trait_.ident) {
-                            remove_item_from_syntax(&mut syntax, syn::Item::Struct(item_struct));
-                            remove_item_from_syntax(&mut syntax, syn::Item::Impl(item_impl));
-                        }
-                        remove_item_from_syntax(&mut syntax, syn::Item::Trait(trait_.clone()));
-                    },
-                    // TODO 
-                    // Item::Struct(struct_) => {}
-                    // TODO 
-                    // Item::Fn(struct_) => {}
-                    _ => () 
+            info!("Transforming all traits");
+
+            for trait_ in gamma_mut_borrow.traits.clone() {
+                // The datatype is created in the module of the trait
+                let module_path = gamma_mut_borrow.get_item_module(&trait_.ident);
+                if !selection.is_selected(&module_path, &trait_.ident) {
+                    continue;
+                }
+                // A trait which cannot be transformed is kept along with its generators
+                match transform_trait(&trait_, gamma_mut_borrow) {
+                    Ok(items) => transformed_items.push((module_path, items)),
+                    Err(error) => {
+                        gamma_mut_borrow.skip_error(error.with_span(&trait_.ident).in_module(&module_path))?;
+                        continue;
+                    }
+                }
+                transformed_traits.insert(trait_.ident.clone());
+            
+                // Remove the original trait from the syntax
+                for (item_struct, item_impl) in gamma_mut_borrow.get_generators(&trait_.ident)? {
+                    removed_imports.insert(item_struct.ident.clone(), None);
+                    removed_items.push(syn::Item::Struct(item_struct));
+                    removed_items.push(syn::Item::Impl(item_impl));
+                }
+                // The impls of the supertraits for the generators are replaced by impls for the enum
+                for bound in trait_.supertraits.iter() {
+                    if let TypeParamBound::Trait(TraitBound { path, .. }) = bound {
+                        let supertrait_impls = get_supertrait_impls(&trait_, &path.segments.last().unwrap().ident, gamma_mut_borrow);
+                        removed_items.extend(supertrait_impls.unwrap_or_default().into_iter().map(|(_, item_impl)| syn::Item::Impl(item_impl)));
+                    }
                 }
+                removed_items.push(syn::Item::Trait(trait_.clone()));
+            }
+            RemoveItems { items: removed_items }.visit_file_mut(&mut syntax);
+
+            // Update other types, the fields of the generators may contain other transformed traits
+            let mut collect_type_params = CollectDatatypeTypeParams { gamma: &gamma, positions: HashMap::new() };
+            collect_type_params.visit_file(&syntax);
+            for (_, items) in transformed_items.iter() {
+                items.iter().for_each(|item| collect_type_params.visit_item(item));
             }
-            println!("Transormed all traits");
+            let type_param_positions = collect_type_params.positions;
+            transform_oop_item_types(&mut syntax.items, &gamma, &type_param_positions);
+            for (_, items) in transformed_items.iter_mut() {
+                transform_oop_item_types(items, &gamma, &type_param_positions);
+            }
+            info!("Transformed all traits");
         }
         TransformType::FPToOOP => {
             // Transform all the enums
-            println!("Transorming all the enums");
+            info!("Transforming all the enums");
+
+            // Enums which are not selected are treated like any other type
+            for enum_ in gamma_mut_borrow.enums.clone() {
+                if !selection.is_selected(&gamma_mut_borrow.get_item_module(&enum_.ident), &enum_.ident) {
+                    gamma_mut_borrow.remove_enum(&enum_.ident);
+                }
+            }
             
             for enum_ in gamma_mut_borrow.enums.clone() {
                 // Get the consumers for the enum
                 let consumers = gamma_mut_borrow.get_enum_consumers(&enum_);
 
-                // 1st parse, transform types
-                transformed_syntax
-                    .items
-                    .extend(transform_enum(&enum_, gamma_mut_borrow));
+                // 1st parse, transform types, the interface is created in the module of the enum
+                let module_path = gamma_mut_borrow.get_item_module(&enum_.ident);
+                // An enum which cannot be transformed is kept along with its consumers
+                match transform_enum(&enum_, gamma_mut_borrow) {
+                    Ok(items) => transformed_items.push((module_path, items)),
+                    Err(error) => {
+                        gamma_mut_borrow.skip_error(error.with_span(&enum_.ident).in_module(&module_path))?;
+                        gamma_mut_borrow.remove_enum(&enum_.ident);
+                        continue;
+                    }
+                }
 
                 // For all the consumers, for each arm create a method in each impl
                 for consumer in consumers {
-                    remove_item_from_syntax(&mut syntax, syn::Item::Fn(consumer.clone()));
+                    removed_imports.insert(consumer.sig.ident.clone(), Some(enum_.ident.clone()));
+                    removed_items.push(syn::Item::Fn(consumer.clone()));
                 }
-                remove_item_from_syntax(&mut syntax, syn::Item::Enum(enum_.clone()));
+                removed_enums.insert(enum_.ident.clone());
+                removed_items.push(syn::Item::Enum(enum_.clone()));
             }
+            RemoveItems { items: removed_items }.visit_file_mut(&mut syntax);
 
             // Update other types
-            let type_transformer = |type_| transform_type_fp(type_, &gamma);
-
-            for item in syntax.items.iter_mut() {
-                match item {
-                    // If a struct is defined and the transform is FP to OOP, then fix the attrs 
-                    Item::Struct(struct_) => {
-                        *item = Item::Struct(ItemStruct{
-                            fields: transform_type_struct_fields(
-                                &struct_.fields,
-                                type_transformer
-                            ),
-                            ..struct_.clone()
-                        })
-                    },
-                    // Top level functions should also have types transformed
-                    Item::Fn(fn_) if !gamma.is_consumer(&fn_.sig.ident) => {
-                        fn_.sig = transform_singature_types(&fn_.sig, type_transformer)
-                    },
-                    Item::Trait(item_trait) => {
-                        for item in item_trait.items.iter_mut() {
-                            if let TraitItem::Method(trait_item_method) = item {
-                                trait_item_method.sig = transform_singature_types(
-                                    &trait_item_method.sig, type_transformer
-                                ) 
-                            }
-                        }
-                    },
-                    Item::Impl(item_impl) => {
-                        for item in item_impl.items.iter_mut() {
-                            if let ImplItem::Method(impl_item_method) = item {
-                                impl_item_method.sig = transform_singature_types(
-                                    &impl_item_method.sig, type_transformer
-                                ) 
-                            }
-                        }
-                    }
-                    _ => ()
-                }
-            }
+            transform_fp_item_types(&mut syntax.items, &gamma)?;
 
-            println!("Transormed all the enums");
+            info!("Transformed all the enums");
         }
     }
 
+    UpdateImports {
+        gamma: &gamma,
+        removed: removed_imports,
+        removed_enums: removed_enums.clone(),
+        module_path: Vec::new(),
+    }.visit_file_mut(&mut syntax);
+
     // Collect gamma for the transformed and untouched code
     let old_gamma = gamma;
 
     let mut gamma = Gamma::empty();
     gamma.visit_file(&syntax);
-    gamma.visit_file(&transformed_syntax);
-    gamma.set_mutable_consumers(old_gamma.mutable_consumers);
+    for (module_path, items) in &transformed_items {
+        gamma.visit_module_items(module_path, items);
+    }
+    gamma.set_mutable_consumers(old_gamma.mutable_consumers.clone());
+    gamma.mode = old_gamma.mode;
+    gamma.pointer = old_gamma.pointer;
+    gamma.method_signatures = old_gamma.method_signatures.clone();
+    gamma.check_errors()?;
+
+    // The transformed items go at the start of their module
+    for (module_path, items) in transformed_items.into_iter().rev() {
+        let module_items = get_module_items_mut(&mut syntax.items, &module_path).ok_or_else(|| RfoodError::new(
+            ErrorCode::Internal,
+            format!("Module {} of transformed items not found", quote!(#(#module_path)::*)),
+        ))?;
+        module_items.splice(0..0, items);
+    }
   
-    let mut delta = Delta::new();
     // Stage 2 - Transform all the new items and any untransformed items
-    for item in &syntax.items {
+    syntax.items = transform_module_items(&syntax.items, &transform_type, &gamma, &Delta::new())?;
+
+    // The type parameters of the traits created from the enums which the destructors return, and
+    // which each generator gives one of its own type parameters, become associated types
+    // The destructors which only return a constant become associated consts of traits which are
+    // not used as trait objects
+    if matches!(transform_type, TransformType::FPToOOP) {
+        IntroduceAssociatedTypes::new(&gamma, &removed_enums).visit_file_mut(&mut syntax);
+        IntroduceAssociatedConsts::new(&syntax, &removed_enums).visit_file_mut(&mut syntax);
+    }
+
+    // The trait objects created from the enums may be held in a pointer which is not in the prelude
+    if matches!(transform_type, TransformType::FPToOOP) && gamma.pointer != Pointer::Box {
+        add_pointer_imports(&mut syntax.items, gamma.pointer);
+    }
+
+    // A module which only implemented a trait for its generators no longer uses the enum
+    if matches!(transform_type, TransformType::OOPToFP) {
+        remove_unused_imports(&mut syntax.items, &transformed_traits);
+    }
+
+    let mut skipped = old_gamma.take_skipped();
+    skipped.extend(gamma.take_skipped());
+    Ok((syntax, skipped))
+}
+
+/// Import a pointer in each module which uses it but does not import it yet, including inline
+/// modules
+fn add_pointer_imports(items: &mut Vec<Item>, pointer: Pointer) {
+    for item in items.iter_mut() {
+        if let Item::Mod(ItemMod { content: Some((_, module_items)), .. }) = item {
+            add_pointer_imports(module_items, pointer);
+        }
+    }
+
+    let ident = pointer.ident();
+    let mut find_uses = FindTypeUses { ident: &ident, used: false, imported: false };
+    for item in items.iter() {
+        find_uses.visit_item(item);
+    }
+    if find_uses.used && !find_uses.imported {
+        items.insert(0, Item::Use(create_pointer_import(pointer)));
+    }
+}
+
+/// Remove the private imports of the given items from each module which does not use them,
+/// including inline modules
+fn remove_unused_imports(items: &mut Vec<Item>, idents: &HashSet<Ident>) {
+    for item in items.iter_mut() {
+        if let Item::Mod(ItemMod { content: Some((_, module_items)), .. }) = item {
+            remove_unused_imports(module_items, idents);
+        }
+    }
+
+    for ident in idents {
+        let mut find_uses = FindTypeUses { ident, used: false, imported: false };
+        for item in items.iter() {
+            find_uses.visit_item(item);
+        }
+        if find_uses.used || !find_uses.imported {
+            continue;
+        }
+        items.retain_mut(|item| match item {
+            Item::Use(item_use) if matches!(item_use.vis, Visibility::Inherited) => {
+                match remove_use_name(&item_use.tree, ident) {
+                    Some(tree) => {
+                        item_use.tree = tree;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => true,
+        });
+    }
+}
+
+/// Remove the imports of an item from a use tree, None if nothing is left
+fn remove_use_name(tree: &UseTree, ident: &Ident) -> Option<UseTree> {
+    match tree {
+        UseTree::Path(use_path) => Some(UseTree::Path(UsePath {
+            tree: Box::new(remove_use_name(&use_path.tree, ident)?),
+            ..use_path.clone()
+        })),
+        UseTree::Name(use_name) if &use_name.ident == ident => None,
+        UseTree::Group(use_group) => {
+            let items: Punctuated<UseTree, Token![,]> = use_group
+                .items
+                .iter()
+                .filter_map(|tree| remove_use_name(tree, ident))
+                .collect();
+            if items.is_empty() {
+                None
+            } else {
+                Some(UseTree::Group(UseGroup { items, ..use_group.clone() }))
+            }
+        }
+        _ => Some(tree.clone()),
+    }
+}
+
+/// Get the items of an inline module in a list of items, the module path is relative to the items
+fn get_module_items_mut<'a>(items: &'a mut Vec<Item>, module_path: &[Ident]) -> Option<&'a mut Vec<Item>> {
+    match module_path.split_first() {
+        Some((module_ident, rest)) => items.iter_mut().find_map(|item| match item {
+            Item::Mod(ItemMod { ident, content: Some((_, module_items)), .. }) if ident == module_ident => {
+                get_module_items_mut(module_items, rest)
+            }
+            _ => None,
+        }),
+        None => Some(items),
+    }
+}
+
+/// Transform the types of the items left after transforming the enums from FP to OOP, any inline
+/// modules are transformed as well
+fn transform_fp_item_types(items: &mut Vec<Item>, gamma: &Gamma) -> RfoodResult<()> {
+    transform_enum_inherent_impls(items, gamma)?;
+    transform_item_types(items, &|type_| transform_type_fp(type_, gamma));
+    Ok(())
+}
+
+/// Transform the types of the items left after transforming the traits from OOP to FP, any inline
+/// modules are transformed as well
+fn transform_oop_item_types(items: &mut Vec<Item>, gamma: &Gamma, type_param_positions: &HashMap<Ident, Vec<usize>>) {
+    for item in items.iter_mut() {
+        ReplaceDatatypeTypeParams { gamma, positions: type_param_positions }.visit_item_mut(item);
+    }
+    transform_item_types(items, &|type_| transform_type_oop(type_, gamma));
+}
+
+/// The consumers in the inherent impls of the enums have been transformed, anything left is moved
+/// to an impl of the trait object
+fn transform_enum_inherent_impls(items: &mut Vec<Item>, gamma: &Gamma) -> RfoodResult<()> {
+    *items = items.iter().map(|item| match item {
+        Item::Impl(item_impl) if item_impl.trait_.is_none()
+            && gamma.is_enum(&item_impl.self_ty.get_delta_type().name) =>
+        {
+            transform_enum_inherent_impl(item_impl)
+        },
+        _ => Ok(Some(item.clone())),
+    }).collect::<RfoodResult<Vec<Option<Item>>>>()?.into_iter().flatten().collect();
+
+    for item in items.iter_mut() {
+        if let Item::Mod(ItemMod { content: Some((_, module_items)), .. }) = item {
+            transform_enum_inherent_impls(module_items, gamma)?;
+        }
+    }
+    Ok(())
+}
+
+/// Transform the types of fields and signatures of the items which are not transformed
+fn transform_item_types(items: &mut Vec<Item>, type_transformer: &dyn Fn(Type) -> Type) {
+    for item in items.iter_mut() {
+        match item {
+            Item::Struct(struct_) => {
+                struct_.fields = transform_type_struct_fields(&struct_.fields, type_transformer)
+            },
+            // Enums which are not transformed may contain the transformed types
+            Item::Enum(enum_) => {
+                for variant in enum_.variants.iter_mut() {
+                    variant.fields = transform_type_struct_fields(&variant.fields, type_transformer);
+                }
+            },
+            // Top level functions should also have types transformed, the consumers of the
+            // transformed enums have already been removed
+            Item::Fn(fn_) => {
+                fn_.sig = transform_singature_types(&fn_.sig, type_transformer)
+            },
+            Item::Const(item_const) => {
+                *item_const.ty = type_transformer(*item_const.ty.clone())
+            },
+            Item::Static(item_static) => {
+                *item_static.ty = type_transformer(*item_static.ty.clone())
+            },
+            // Aliases of types containing the transformed types, e.g. `type ShapeRef = Box<dyn Shape>`
+            Item::Type(item_type) => {
+                *item_type.ty = type_transformer(*item_type.ty.clone())
+            },
+            Item::Trait(item_trait) => {
+                for item in item_trait.items.iter_mut() {
+                    if let TraitItem::Method(trait_item_method) = item {
+                        trait_item_method.sig = transform_singature_types(
+                            &trait_item_method.sig, type_transformer
+                        ) 
+                    }
+                }
+            },
+            Item::Impl(item_impl) => {
+                for item in item_impl.items.iter_mut() {
+                    if let ImplItem::Method(impl_item_method) = item {
+                        impl_item_method.sig = transform_singature_types(
+                            &impl_item_method.sig, type_transformer
+                        ) 
+                    }
+                }
+            }
+            Item::Mod(ItemMod { content: Some((_, module_items)), .. }) => {
+                transform_item_types(module_items, type_transformer)
+            }
+            _ => ()
+        }
+    }
+}
+
+/// Transform all the items in a module, each inline module is transformed with its own delta
+pub fn transform_module_items(
+    items: &[Item],
+    transform_type: &TransformType,
+    gamma: &Gamma,
+    delta: &Delta,
+) -> RfoodResult<Vec<Item>> {
+    let mut delta = delta.clone();
+    for item in items {
         collect_constants(
             &item,
+            gamma,
             &mut delta,
         )
     }
-    for item in transformed_syntax.items.iter_mut() {
-        *item = transform_item(&item, &transform_type, &gamma, &mut delta)
-    }
-    for item in &syntax.items {
-        transformed_syntax
-            .items
-            .push(transform_item(item, &transform_type, &gamma, &mut delta));
-    }
 
-    quote!(#transformed_syntax).to_string()
+    items.iter().map(|item| match item {
+        Item::Mod(item_mod @ ItemMod { content: Some((brace, module_items)), .. }) => Ok(Item::Mod(ItemMod {
+            content: Some((
+                *brace,
+                transform_module_items(module_items, transform_type, gamma, &delta.for_module(&item_mod.ident))?,
+            )),
+            ..item_mod.clone()
+        })),
+        // An item which cannot be transformed is kept in its original form
+        _ => catch_internal_errors(|| {
+            let _span = debug_span!("item", name = %get_item_ident(item).map_or(String::new(), |ident| ident.to_string())).entered();
+            transform_item(item, transform_type, gamma, &mut delta)
+        })
+            .or_else(|error| {
+                gamma.skip_error(error.with_span(item).in_module(&delta.module_path))?;
+                Ok(item.clone())
+            }),
+    }).collect()
 }
 
-pub fn transform_file(path: &PathBuf, output_path: &PathBuf, transform_type: &TransformType) {
+/// Transform a file, returning the errors skipped in best effort mode
+pub fn transform_file(path: &PathBuf, output_path: &PathBuf, plan: &TransformPlan) -> RfoodResult<Vec<RfoodError>> {
     //-- Do the transfrom --//
-    let mut file = File::open(path).expect("Unable to open file");
+    let mut file = File::open(path)?;
 
     let mut src = String::new();
-    file.read_to_string(&mut src).expect("Unable to read file");
+    file.read_to_string(&mut src)?;
 
-    let transformed_syntax = transform_string(src, transform_type);
+    let (transformed_syntax, skipped) = transform_planned_string(src, plan).map_err(|error| error.in_file(path))?;
     // Write output to file
-    if write_and_fmt(output_path, transformed_syntax).is_err() {
-        panic!("Unable to write output file");
+    write_and_fmt(output_path, transformed_syntax)?;
+    Ok(skipped.into_iter().map(|error| error.in_file(path)).collect())
+}
+
+/// Transform a crate, starting from its root file (e.g. `src/lib.rs`) all the modules declared in
+/// other files are loaded so one gamma is collected for the whole crate. Each transformed file is
+/// written to the same place relative to the root file in the output directory. The errors skipped
+/// in best effort mode are returned.
+pub fn transform_crate(root_path: &PathBuf, output_dir: &PathBuf, plan: &TransformPlan) -> RfoodResult<Vec<RfoodError>> {
+    let (syntax, files) = load_crate(root_path)?;
+    let in_module_file = |error: RfoodError| match error.module_path.as_ref().and_then(|module_path| get_file_of_module(module_path, &files)) {
+        Some(file) => error.in_file(&file.path),
+        None => error,
+    };
+    let (transformed_syntax, skipped) = transform_planned_syntax(syntax, plan).map_err(in_module_file)?;
+
+    let root_dir = root_path.parent().ok_or_else(|| RfoodError::new(
+        ErrorCode::Io,
+        format!("Crate root {:?} is not in a directory", root_path),
+    ))?;
+    // Files are loaded before the modules they declare, rustfmt follows module declarations so
+    // write them in reverse to make sure the module files exist when their parent is formatted
+    for (file, file_syntax) in split_crate(&transformed_syntax, &files).into_iter().rev() {
+        let relative_path = file.path.strip_prefix(root_dir).map_err(|_| RfoodError::new(
+            ErrorCode::Io,
+            format!("Module file {:?} is outside of the crate directory {:?}", file.path, root_dir),
+        ))?;
+        let output_path = output_dir.join(relative_path);
+
+        if let Some(output_file_dir) = output_path.parent() {
+            fs::create_dir_all(output_file_dir)?;
+        }
+        write_and_fmt(&output_path, quote!(#file_syntax))?;
     }
+    Ok(skipped.into_iter().map(in_module_file).collect())
 }
 
 /// Transform a type
@@ -206,21 +675,18 @@ pub fn transform_type_fp_consumer(type_: Type, consumer: Ident) -> Type {
     // If DT 
 }
 
-pub fn transform_type_fp(type_: Type, gamma: &Gamma) -> Type {
-    let dt = type_.get_delta_type();
+/// Replace the enums of the transformed datatypes with boxed trait objects, including enums
+/// nested in other types, e.g. `Vec<Shape>` becomes `Vec<Box<dyn Shape>>`
+pub fn transform_type_fp(mut type_: Type, gamma: &Gamma) -> Type {
+    ReplaceDatatypeEnums { gamma }.visit_type_mut(&mut type_);
+    type_
+}
 
-    if gamma.is_enum(&dt.name) {
-        match dt.ref_type {
-            RefType::None => create_dyn_box_of_type(&type_),
-            RefType::Box(_) => create_dyn_box_of_type(
-                &create_type_from_ident(&dt.name)
-            ),
-            _ => type_
-        }
-        // Create dynamic box of type
-    } else {
-        type_
-    }
+/// Replace the trait objects of the transformed traits with their enums, e.g. `Box<dyn Shape>`
+/// becomes `Box<Shape>` and `Vec<Box<dyn Shape>>` becomes `Vec<Shape>`
+pub fn transform_type_oop(mut type_: Type, gamma: &Gamma) -> Type {
+    ReplaceDatatypeTraitObjects { gamma }.visit_type_mut(&mut type_);
+    type_
 }
 
 pub fn transform_type_struct_fields<F>(fields: &Fields, type_transformer: F) -> Fields where F: Fn(Type) -> Type {
@@ -259,128 +725,458 @@ pub fn transform_singature_types<F>(sig: &Signature, type_transformer: F) -> Sig
 
 /// Transform a interface (trait) into a datatype (enum)
 ///
-/// This transforms the trait it self as well as the implementations of the trait
-pub fn transform_trait(trait_: &ItemTrait, gamma: &mut Gamma) -> Vec<Item> {
+/// This transforms the trait it self as well as the implementations of the trait, along with its
+/// associated types and consts, supertraits, static methods and generic methods
+pub fn transform_trait(trait_: &ItemTrait, gamma: &mut Gamma) -> RfoodResult<Vec<Item>> {
+    let _span = info_span!("trait", name = %trait_.ident).entered();
+    info!("Transforming trait");
+    gamma.check_trait_impls(&trait_.ident)?;
+
+    // The associated types which are left are given different types by the generators
+    if let Some(TraitItem::Type(type_item)) = trait_.items.iter().find(|item| matches!(item, TraitItem::Type(_))) {
+        return Err(RfoodError::unsupported_syntax(format!(
+            "The generators of `{}` give the associated type `{}` different types, it is only transformed if they give it the same type or each give it one of their type parameters",
+            trait_.ident, type_item.ident,
+        )).with_span(type_item));
+    }
+    // The associated consts which are left have the name of a destructor
+    if let Some(TraitItem::Const(const_item)) = trait_.items.iter().find(|item| matches!(item, TraitItem::Const(_))) {
+        return Err(RfoodError::unsupported_syntax(format!(
+            "The associated const `{}` of `{}` becomes the consumer `{}`, which is already a destructor",
+            const_item.ident, trait_.ident, const_item.ident.to_string().to_lowercase(),
+        )).with_span(const_item));
+    }
+
     // Create enum varaint for each generator of the trait
     let variants: Vec<syn::Variant> = Vec::from_iter(
         gamma
-            .get_generators(&trait_.ident)
+            .get_generators(&trait_.ident)?
             .iter()
             .map(|(generator, _)| create_enum_variant(&generator.ident, generator.fields.clone())),
     );
 
     // Create the enum
-    let new_enum = ast::create::create_enum(
+    let mut new_enum = ast::create::create_enum(
         &trait_.ident,
         variants,
         &trait_.generics,
         trait_.vis.clone(),
     );
+
+    // The supertraits left are foreign traits, they are derived or implemented by the enum
+    let (derives, supertrait_impls) = transform_supertraits(trait_, &new_enum, gamma)?;
+    if !derives.is_empty() {
+        new_enum.attrs.push(parse_quote!(#[derive(#(#derives),*)]));
+    }
     gamma.add_enum(&new_enum);
 
     // For each destructor of the trait create a new consumer of the enum
-    let mut consumers = Vec::from_iter(
-        gamma
-            .get_destructors(&trait_.ident)
-            .iter()
-            .map(|destructor| transform_destructor(trait_, destructor, &new_enum, gamma)),
-    );
+    let mut consumers = gamma
+        .get_destructors(&trait_.ident)
+        .iter()
+        .map(|destructor| transform_destructor(trait_, destructor, &new_enum, gamma))
+        .collect::<RfoodResult<Vec<Item>>>()?;
 
     let mut items = vec![Item::Enum(new_enum.clone())];
+    items.extend(supertrait_impls.into_iter().map(Item::Impl));
     items.append(&mut consumers);
-    return items;
+    return Ok(items);
 }
 
-pub fn transform_consumer_fn_to_trait_item(consumer: &ItemFn, gamma: &mut Gamma) -> TraitItemMethod {
-    TraitItemMethod {
+/// Get the impls of a foreign trait for each generator of a trait, None if a generator does not
+/// implement it
+pub fn get_supertrait_impls(trait_: &ItemTrait, supertrait_ident: &Ident, gamma: &Gamma) -> Option<Vec<(ItemStruct, ItemImpl)>> {
+    let supertrait_impls = gamma.generators.get(supertrait_ident).cloned().unwrap_or_default();
+    gamma
+        .get_generators(&trait_.ident)
+        .ok()?
+        .into_iter()
+        .map(|(generator, _)| supertrait_impls.iter().find(|(struct_, _)| struct_.ident == generator.ident).cloned())
+        .collect()
+}
+
+/// Transform the supertraits of a trait into the derives and impls of the enum
+///
+/// A supertrait every generator derives is derived by the enum, as the generators derive it, one
+/// every generator implements is implemented by the enum, each method matching on self with an
+/// arm for each generator. Marker traits and lifetimes hold for the enum when they hold for its
+/// variants so are dropped. Local supertraits are merged into the trait beforehand if they have
+/// the same generators, the trait is not transformed if any are left
+fn transform_supertraits(trait_: &ItemTrait, enum_: &ItemEnum, gamma: &Gamma) -> RfoodResult<(Vec<Path>, Vec<ItemImpl>)> {
+    let generators = gamma.get_generators(&trait_.ident)?;
+    let mut derives = Vec::new();
+    let mut impls = Vec::new();
+    for bound in trait_.supertraits.iter() {
+        let path = match bound {
+            TypeParamBound::Trait(TraitBound { path, .. }) => path,
+            TypeParamBound::Lifetime(_) => continue,
+        };
+        let ident = &path.segments.last().unwrap().ident;
+        if gamma.is_trait(ident) {
+            return Err(RfoodError::unsupported_syntax(format!(
+                "The supertrait `{}` of `{}` is only transformed along with it when it is selected and implemented by the same generators",
+                ident, trait_.ident,
+            )).with_span(path));
+        }
+        // A trait without generators becomes an enum without variants, which can not be used
+        if generators.is_empty() || ["Send", "Sync", "Sized", "Unpin"].contains(&ident.to_string().as_str()) {
+            continue;
+        }
+
+        let generator_derives: Option<Vec<Path>> = generators.iter().map(|(generator, _)| get_derive(&generator.attrs, ident)).collect();
+        if let Some(derive) = generator_derives.and_then(|generator_derives| generator_derives.into_iter().next()) {
+            derives.push(derive);
+        } else if let Some(supertrait_impls) = get_supertrait_impls(trait_, ident, gamma) {
+            impls.push(transform_supertrait_impl(path, &supertrait_impls, enum_, gamma)?);
+        } else {
+            return Err(RfoodError::unsupported_syntax(format!(
+                "The generators of `{}` neither all derive nor all implement its supertrait `{}`",
+                trait_.ident, ident,
+            )).with_span(path));
+        }
+    }
+    Ok((derives, impls))
+}
+
+/// Get the path a trait is derived with by the derive attributes of an item, if it is derived
+fn get_derive(attrs: &[Attribute], trait_ident: &Ident) -> Option<Path> {
+    attrs.iter().filter(|attr| attr.path.is_ident("derive")).find_map(|attr| match attr.parse_meta() {
+        Ok(Meta::List(MetaList { nested, .. })) => nested.into_iter().find_map(|meta| match meta {
+            NestedMeta::Meta(Meta::Path(path)) if path.segments.last().unwrap().ident == *trait_ident => Some(path),
+            _ => None,
+        }),
+        _ => None,
+    })
+}
+
+/// Transform the impls of a foreign trait for the generators into an impl for the enum, e.g.
+/// `impl Display for Square` and `impl Display for Circle` become `impl Display for Shape` where
+/// `fmt` matches on self with the body of each impl in the arm of its variant
+fn transform_supertrait_impl(
+    path: &Path,
+    supertrait_impls: &[(ItemStruct, ItemImpl)],
+    enum_: &ItemEnum,
+    gamma: &Gamma,
+) -> RfoodResult<ItemImpl> {
+    let self_ident = Ident::new("self", Span::call_site());
+    let mut items = Vec::new();
+    for item in supertrait_impls[0].1.items.iter() {
+        let method = match item {
+            ImplItem::Method(method) => method,
+            // The other items, e.g. associated types, must be the same for every generator
+            item if supertrait_impls.iter().all(|(_, impl_)| impl_.items.contains(item)) => {
+                items.push(item.clone());
+                continue;
+            }
+            item => return Err(RfoodError::unsupported_syntax(format!(
+                "The generators of `{}` implement the items of `{}` differently",
+                enum_.ident, quote!(#path),
+            )).with_span(item)),
+        };
+        if !has_self_receiver(&method.sig) || is_mutable_self(&method.sig) {
+            return Err(RfoodError::unsupported_syntax(format!(
+                "The method `{}` of `{}` must take `&self` or `self` to be implemented by the enum",
+                method.sig.ident, quote!(#path),
+            )).with_span(&method.sig));
+        }
+
+        let destructor = TraitItemMethod {
+            attrs: Vec::new(),
+            sig: method.sig.clone(),
+            default: None,
+            semi_token: None,
+        };
+        let arms = supertrait_impls
+            .iter()
+            .map(|(generator, impl_)| transform_destructor_impl(generator, &destructor, &enum_.ident, &self_ident, impl_, gamma))
+            .collect::<RfoodResult<Vec<Arm>>>()?;
+        items.push(ImplItem::Method(ImplItemMethod {
+            block: Block {
+                brace_token: token::Brace::default(),
+                stmts: vec![Stmt::Expr(create_match_statement(&self_ident, arms))],
+            },
+            ..method.clone()
+        }));
+    }
+
+    let enum_ident = &enum_.ident;
+    let (impl_generics, type_generics, where_clause) = enum_.generics.split_for_impl();
+    Ok(ItemImpl {
+        items,
+        ..parse_quote!(impl #impl_generics #path for #enum_ident #type_generics #where_clause {})
+    })
+}
+
+pub fn transform_consumer_fn_to_trait_item(consumer: &ItemFn, gamma: &mut Gamma) -> RfoodResult<TraitItemMethod> {
+    Ok(TraitItemMethod {
         attrs: consumer.attrs.clone(),
-        sig: transform_consumer_signature(&consumer.sig, gamma),
+        sig: transform_consumer_signature(&consumer.sig, gamma)?,
         default: None,
         semi_token: Some(token::Semi::default()),
-    }
+    })
 }
 
-pub fn transform_enum(enum_: &ItemEnum, gamma: &mut Gamma) -> Vec<Item> {
-    // Create a trait
-    let consumers = gamma.get_enum_consumers(enum_);
-    let trait_methods: Vec<TraitItemMethod> = Vec::from_iter(
-        consumers
-            .iter()
-            .map(|consumer| transform_consumer_fn_to_trait_item(&consumer, gamma)),
+/// Transform a method in an inherent impl of an enum into a consumer function of the enum
+///
+/// The receiver is replaced by an argument named after the enum, e.g. `fn area(&self)` becomes
+/// `fn area(shape: &Shape)`, and any use of self/Self in the method is replaced to match
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::transformer::{transform_string, TransformType};
+///
+/// let input = "
+///     enum Shape { Square { side: u32 } }
+///     impl Shape {
+///         fn unit() -> Self { Shape::Square { side: 1 } }
+///         fn area(&self) -> u32 { match self { Shape::Square { side } => side * side } }
+///     }
+/// ".to_string();
+/// let output = transform_string(input, &TransformType::FPToOOP).unwrap();
+/// assert!(output.contains("trait Shape { fn area (& self) -> u32 ; }"));
+/// assert!(output.contains("impl Shape for Square { fn area (& self) -> u32 { self . side * self . side } }"));
+/// assert!(output.contains("impl dyn Shape { fn unit () -> Box :: < dyn Shape > { Box :: new (Square { side : 1 }) } }"));
+/// ```
+pub fn transform_inherent_method_to_consumer(method: &ImplItemMethod, enum_: &ItemEnum) -> ItemFn {
+    let enum_instance_name = transform_type_to_name(&enum_.ident);
+    let mut inputs = method.sig.inputs.clone();
+
+    let consumer_arg = match inputs.pop_first().unwrap() {
+        FnArg::Receiver(Receiver { reference, mutability, .. }) => {
+            let mut arg = create_consumer_signature_arg(
+                &enum_.ident,
+                &enum_instance_name,
+                reference.is_some(),
+                &enum_.generics,
+            );
+            if let FnArg::Typed(PatType { ty: box Type::Reference(type_reference), .. }) = &mut arg {
+                type_reference.mutability = mutability;
+            }
+            arg
+        },
+        // self: Box<Self>
+        FnArg::Typed(pat_type) => FnArg::Typed(PatType {
+            pat: Box::new(create_pat_ident(&enum_instance_name)),
+            ..pat_type
+        }),
+    };
+    inputs.insert(0, consumer_arg);
+
+    let mut consumer = create_function(
+        Signature {
+            inputs,
+            ..method.sig.clone()
+        },
+        method.block.stmts.clone(),
+        method.vis.clone(),
     );
+    consumer.attrs = method.attrs.clone();
 
-    let mut trait_ = create_trait(
-        &enum_.ident,
-        &trait_methods
-            .iter()
-            .zip(consumers.iter())
-            .map(|(method, consumer)| {
-                TraitItem::Method(TraitItemMethod {
-                    // For each consumer if there is no match statement, add a default impl to the
-                    // trait
-                    default: if get_consumer_match_statement(&consumer).is_ok() {
+    let mut rs = ReplaceSelf {
+        enum_name: enum_instance_name,
+    };
+    rs.visit_block_mut(&mut consumer.block);
+
+    let mut rst = ReplaceSelfType {
+        self_type: enum_.ident.clone(),
+    };
+    rst.visit_item_fn_mut(&mut consumer);
+
+    consumer
+}
+
+/// Transform an inherent impl of an enum into an inherent impl of the trait object
+///
+/// The methods with a self receiver have been transformed into methods of the trait, any other
+/// associated items are kept. If nothing is left the impl is removed.
+pub fn transform_enum_inherent_impl(item_impl: &ItemImpl) -> RfoodResult<Option<Item>> {
+    let items: Vec<ImplItem> = item_impl
+        .items
+        .iter()
+        .filter(|item| !matches!(item, ImplItem::Method(method) if has_self_receiver(&method.sig)))
+        .cloned()
+        .collect();
+
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    let mut new_impl = ItemImpl {
+        items,
+        ..item_impl.clone()
+    };
+
+    // Self is now the trait object which is not sized
+    let mut rst = ReplaceSelfType {
+        self_type: item_impl.self_ty.get_delta_type().name,
+    };
+    rst.visit_item_impl_mut(&mut new_impl);
+    new_impl.self_ty = Box::new(create_dyn_of_type(&item_impl.self_ty)?);
+
+    Ok(Some(Item::Impl(new_impl)))
+}
+
+/// Transform a datatype (enum) into an interface (trait)
+///
+/// This creates the trait along with a struct and an implementation of the trait for each variant.
+/// The trait has the type parameters of the enum, those which the consumers return (and every
+/// variant uses) become associated types once the transformation is complete. Consumers which
+/// only return a constant become associated consts, unless the trait is used as a trait object
+///
+/// # Examples
+///
+/// ```
+/// use rfood::transform::transformer::{transform_string, TransformType};
+///
+/// let input = "
+///     enum Parser<T> { Fixed { value: T } }
+///     fn parse<T: Clone>(parser: &Parser<T>) -> T {
+///         match parser { Parser::Fixed { value } => value.clone() }
+///     }
+///     fn run(parser: &Parser<i32>) -> i32 { parse(parser) }
+/// ".to_string();
+/// let output = transform_string(input, &TransformType::FPToOOP).unwrap();
+/// assert!(output.contains("trait Parser { type T : Clone ; fn parse (& self) -> Self :: T ; }"));
+/// assert!(output.contains("impl < T : Clone > Parser for Fixed < T > { type T = T ;"));
+/// assert!(output.contains("parser : & dyn Parser < T = i32 >"));
+/// ```
+///
+/// ```
+/// use rfood::transform::transformer::{transform_string, TransformType};
+///
+/// let input = "
+///     enum Shape { Square { side: u32 } }
+///     fn sides(shape: &Shape) -> u32 { match shape { Shape::Square { .. } => 4 } }
+/// ".to_string();
+/// let output = transform_string(input, &TransformType::FPToOOP).unwrap();
+/// assert!(output.contains("trait Shape { const SIDES : u32 ; fn sides (& self) -> u32 { Self :: SIDES } }"));
+/// assert!(output.contains("impl Shape for Square { const SIDES : u32 = 4 ; }"));
+/// ```
+///
+/// A consumer with type parameters of its own becomes a method which is only required of sized
+/// types, so the trait can still be used as a trait object
+///
+/// ```
+/// use rfood::transform::transformer::{transform_string, TransformType};
+///
+/// let input = "
+///     enum Shape { Square { side: u32 } }
+///     fn scale<F: Fn(u32) -> u32>(shape: &Shape, f: F) -> u32 { match shape { Shape::Square { side } => f(*side) } }
+///     fn doubled() -> u32 { scale::<fn(u32) -> u32>(&Shape::Square { side: 2 }, |side| side * 2) }
+/// ".to_string();
+/// let output = transform_string(input, &TransformType::FPToOOP).unwrap();
+/// assert!(output.contains("fn scale < F : Fn (u32) -> u32 > (& self , f : F) -> u32 where Self : Sized ;"));
+/// assert!(output.contains("Square { side : 2 } . scale :: < fn (u32) -> u32 > (| side | side * 2)"));
+/// ```
+pub fn transform_enum(enum_: &ItemEnum, gamma: &mut Gamma) -> RfoodResult<Vec<Item>> {
+    let _span = info_span!("enum", name = %enum_.ident).entered();
+    info!("Transforming enum");
+
+    // Create a trait, the type parameters of the consumers given to the enum are named after the
+    // type parameters of the enum as they become the type parameters of the trait
+    let consumers: Vec<ItemFn> = gamma
+        .get_enum_consumers(enum_)
+        .iter()
+        .map(|consumer| rename_consumer_enum_params(consumer, enum_, gamma))
+        .collect();
+    let trait_methods: Vec<TraitItemMethod> = consumers
+        .iter()
+        .map(|consumer| transform_consumer_fn_to_trait_item(&consumer, gamma))
+        .collect::<RfoodResult<_>>()?;
+
+    let trait_items = trait_methods
+        .iter()
+        .zip(consumers.iter())
+        .map(|(method, consumer)| {
+            Ok(TraitItem::Method(TraitItemMethod {
+                // For each consumer if there is no match statement, add a default impl to the
+                // trait
+                default: if get_consumer_match_statement(&consumer).is_ok() {
+                    None
+                } else {
+                    // If the return type is the trait, we cannot use the default impl
+                    let return_type = gamma.expand_signature_type_aliases(&consumer.sig).output.get_delta_type(None)?;
+                    if return_type.map_or(false, |return_type| return_type.name == enum_.ident) {
                         None
                     } else {
-                        // If the return type is the trait, we cannot use the default impl
-                        let return_type = consumer.sig.output.get_delta_type(None);
-                        if return_type.is_some() && return_type.unwrap().name == enum_.ident {
-                            None
-                        } else {
-                            if let Expr::Block(block) = transform_consumer_expr(
-                                    &Expr::Block(ExprBlock{
-                                        block: *consumer.block.clone(),
-                                        attrs: Vec::new(),
-                                        label: None,
-                                    }),
-                                    get_fn_arg_name(&consumer.sig.inputs.first().unwrap()),
-                                    Vec::new(),
-                                    &gamma,
-                            ) {
-                                Some(block.block)
-                            } else {
-                                panic!("This should always be a block!")
+                        match transform_consumer_expr(
+                                &Expr::Block(ExprBlock{
+                                    block: *consumer.block.clone(),
+                                    attrs: Vec::new(),
+                                    label: None,
+                                }),
+                                get_consumer_self_name(consumer)?,
+                                Vec::new(),
+                                &gamma,
+                        ) {
+                            Expr::Block(block) => Some(block.block),
+                            expr => {
+                                return Err(RfoodError::new(ErrorCode::Internal, "The body of a consumer was not transformed into a block")
+                                    .with_span(&expr))
                             }
                         }
-                    },
-                    ..method.clone()
-                })
-            })
-            .collect::<Vec<TraitItem>>(),
-        enum_.vis.clone(),
-    );
+                    }
+                },
+                ..method.clone()
+            }))
+        })
+        .collect::<RfoodResult<Vec<TraitItem>>>()?;
+    let mut trait_ = create_trait(&enum_.ident, &trait_items, enum_.vis.clone());
+    // The trait has the type parameters of the enum, with the bounds the consumers give them
+    trait_.generics = enum_.generics.clone();
+    for consumer in consumers.iter() {
+        for param in consumer.sig.generics.params.iter() {
+            if matches!(param, GenericParam::Type(param) if is_enum_type_param(enum_, &param.ident)) {
+                merge_generic_param(&mut trait_.generics, param.clone());
+            }
+        }
+    }
     gamma.add_trait(&trait_);
 
     let mut items = vec![Item::Trait(trait_.clone())];
 
     // For each variant of the enum create a struct and an impl
     for variant in enum_.variants.iter() {
-        // Create the struct
-        let struct_ = create_struct(
+        // Create the struct, with the type parameters of the enum used by the fields of the variant
+        let mut struct_ = create_struct(
             &variant.ident,
             &enum_.ident,
             transform_type_struct_fields(&variant.fields.clone(), |type_: Type| transform_type_fp(type_, gamma)),
             enum_.vis.clone(),
         );
-        println!("Adding {} struct to gamma", struct_.ident);
+        struct_.generics = get_variant_generics(enum_, variant);
+        debug!("Adding {} struct to gamma", struct_.ident);
         gamma.add_struct(&struct_);
         items.push(Item::Struct(struct_.clone()));
 
         // Add emtpy generator to gamma
-        let impl_ = create_impl(&enum_.ident, &variant.ident, Vec::new());
+        let impl_ = create_generator_impl(&trait_, &struct_, Vec::new());
         gamma.add_generator(&trait_, &struct_, &impl_);
 
         // Collect methods
         // TODO handle trait method
-        let impl_items =
+        let impl_items: Vec<ImplItem> =
             consumers
                 .iter()
                 .zip(trait_methods.iter())
-                .filter_map(|(consumer, trait_method)| {
+                .map(|(consumer, trait_method)| -> RfoodResult<Option<ImplItem>> {
+                    let _span = debug_span!("consumer", name = %consumer.sig.ident, variant = %variant.ident).entered();
+
+                    // The variables bound by the arm for this variant, these become fields of self
+                    let mut trait_attributes = Vec::new();
+
                     // Get the expr for the new destructor
-                    let consumer_expr: Option<Expr> =
-                        match get_match_expr_for_enum(consumer, &variant.ident) {
+                    let consumer_expr: Expr =
+                        match get_match_arm_for_enum(consumer, &variant.ident) {
                             // If there is an arm in the match statement, we can use it
-                            Ok(expr) => Some(expr),
+                            Ok(arm) => {
+                                trait_attributes = get_pattern_bindings(&arm.pat)
+                                    .into_iter()
+                                    .map(|(pat_ident, member)| (pat_ident.ident, member))
+                                    .collect();
+                                *arm.body
+                            },
                             // Otherwise we will have to use the method body for all the cases
                             Err(e) => {
                                 // 1. The trait has a default impl for this method. This is only possible if the
@@ -388,42 +1184,31 @@ pub fn transform_enum(enum_: &ItemEnum, gamma: &mut Gamma) -> Vec<Item> {
                                 //    I.e. it is not possible to have a defualt implementation with sig:
                                 //      fn foo() -> Self / fn foo() -> Box<Self>
                                 //    In this case we can just use the default impl so no impl is needed here
-                                let return_type = consumer.sig.output.get_delta_type(None);
-                                if return_type.is_some() && return_type.unwrap().name != enum_.ident
+                                let return_type = gamma.expand_signature_type_aliases(&consumer.sig).output.get_delta_type(None)?;
+                                if return_type.map_or(false, |return_type| return_type.name != enum_.ident)
                                 {
-                                    return None;
+                                    return Ok(None);
                                 }
 
                                 // 2. Same as above but the return type is the same as the enum. In this case we
                                 //    need to copy the default impl each time.
                                 //    TODO create a function and use it here instead of duplicate code
-                                Some(Expr::Block(ExprBlock {
+                                Expr::Block(ExprBlock {
                                     block: *consumer.block.clone(),
                                     attrs: Vec::new(),
                                     label: None,
-                                }))
+                                })
                             }
                         };
 
-                    // If we dont get an expr for this destructor then we can skip it. (This will be
-                    // because the expr is already defined in the default impl of the trait)
-                    if consumer_expr.is_none() {
-                        return None;
-                    }
-
                     let expr = transform_consumer_expr(
-                        &consumer_expr.unwrap(),
-                        get_fn_arg_name(&consumer.sig.inputs.first().unwrap()),
-                        Vec::from_iter(
-                            variant
-                                .fields
-                                .iter()
-                                .map(|field| field.ident.clone().unwrap()),
-                        ),
+                        &consumer_expr,
+                        get_consumer_self_name(consumer)?,
+                        trait_attributes,
                         gamma,
                     );
 
-                    Some(ImplItem::Method(create_impl_method(
+                    Ok(Some(ImplItem::Method(create_impl_method(
                         &trait_method.sig,
                         // If the expr is already a block take its block
                         &if let Expr::Block(expr_block) = expr {
@@ -435,17 +1220,95 @@ pub fn transform_enum(enum_: &ItemEnum, gamma: &mut Gamma) -> Vec<Item> {
                                 stmts: vec![Stmt::Expr(expr)],
                             }
                         },
-                    )))
-                });
+                    ))))
+                })
+                .collect::<RfoodResult<Vec<Option<ImplItem>>>>()?
+                .into_iter()
+                .flatten()
+                .collect();
 
         // Create the impl
-        let impl_ = create_impl(&enum_.ident, &variant.ident, Vec::from_iter(impl_items));
+        let impl_ = create_generator_impl(&trait_, &struct_, impl_items);
         // Update gamma with real impl
         gamma.add_generator(&trait_, &struct_, &impl_);
         items.push(Item::Impl(impl_));
     }
 
-    return items;
+    Ok(items)
+}
+
+/// Get the name of the enum argument of a consumer, which becomes `self` in the trait
+fn get_consumer_self_name(consumer: &ItemFn) -> RfoodResult<Ident> {
+    match consumer.sig.inputs.first() {
+        Some(fn_arg) => get_fn_arg_name(fn_arg),
+        None => Err(RfoodError::new(ErrorCode::Internal, format!("Consumer `{}` has no arguments", consumer.sig.ident))
+            .with_span(&consumer.sig)),
+    }
+}
+
+/// Check if a type parameter is one of the type parameters of an enum
+fn is_enum_type_param(enum_: &ItemEnum, ident: &Ident) -> bool {
+    enum_.generics.type_params().any(|param| param.ident == *ident)
+}
+
+/// The generics of the struct a variant of an enum becomes, the type parameters of the enum which
+/// are used by the fields of the variant
+fn get_variant_generics(enum_: &ItemEnum, variant: &Variant) -> Generics {
+    let params: Punctuated<GenericParam, Token![,]> = enum_
+        .generics
+        .type_params()
+        .filter(|param| variant.fields.iter().any(|field| is_type_used(&field.ty, &param.ident)))
+        .map(|param| GenericParam::Type(TypeParam { bounds: Punctuated::new(), colon_token: None, ..param.clone() }))
+        .collect();
+    if params.is_empty() {
+        return Generics::default();
+    }
+    Generics { lt_token: Some(Default::default()), params, gt_token: Some(Default::default()), where_clause: None }
+}
+
+/// Create the impl of the trait an enum becomes for the struct a variant becomes, e.g.
+/// `impl<T: Display> Parser<T> for Fixed<T>`
+fn create_generator_impl(trait_: &ItemTrait, struct_: &ItemStruct, items: Vec<ImplItem>) -> ItemImpl {
+    let mut impl_ = create_impl(&trait_.ident, &struct_.ident, items);
+    impl_.generics = trait_.generics.clone();
+    let trait_args = trait_.generics.type_params().map(|param| create_type_from_ident(&param.ident)).collect();
+    if let Some((_, trait_path, _)) = &mut impl_.trait_ {
+        add_generic_type_args(trait_path, trait_args);
+    }
+    if let Type::Path(TypePath { path, .. }) = &mut *impl_.self_ty {
+        let struct_args = struct_.generics.type_params().map(|param| create_type_from_ident(&param.ident)).collect();
+        add_generic_type_args(path, struct_args);
+    }
+    impl_
+}
+
+/// Rename the type parameters of a consumer which it gives to the enum after the type parameters
+/// of the enum, e.g. `fn parse<T>(parser: &Parser<T>) -> T` of `enum Parser<Output>` becomes
+/// `fn parse<Output>(parser: &Parser<Output>) -> Output`
+fn rename_consumer_enum_params(consumer: &ItemFn, enum_: &ItemEnum, gamma: &Gamma) -> ItemFn {
+    let mut consumer = consumer.clone();
+    let signature = gamma.expand_signature_type_aliases(&consumer.sig);
+    let enum_args = match signature.inputs.first() {
+        Some(FnArg::Typed(PatType { ty, .. })) => get_enum_type_args(ty, &enum_.ident),
+        _ => Vec::new(),
+    };
+    let renames: HashMap<Ident, Ident> = enum_
+        .generics
+        .type_params()
+        .zip(enum_args.iter())
+        .filter_map(|(enum_param, arg)| match arg {
+            Type::Path(TypePath { qself: None, path }) => path
+                .get_ident()
+                .filter(|ident| **ident != enum_param.ident && consumer.sig.generics.type_params().any(|param| param.ident == **ident))
+                .map(|ident| (ident.clone(), enum_param.ident.clone())),
+            _ => None,
+        })
+        .collect();
+    if !renames.is_empty() {
+        rename_type_params(&mut consumer.sig.generics, &renames);
+        ReplaceTypeParams { types: get_renamed_types(&renames) }.visit_item_fn_mut(&mut consumer);
+    }
+    consumer
 }
 
 /// Transforms a destructor of a trait into a consumer of the enum
@@ -459,35 +1322,18 @@ fn transform_destructor(
     destructor: &TraitItemMethod,
     enum_: &ItemEnum,
     gamma: &mut Gamma,
-) -> Item {
+) -> RfoodResult<Item> {
+    let _span = debug_span!("destructor", name = %destructor.sig.ident).entered();
+
     // Collect all the generics from all the implementations of the trait destructor
     let mut generics = trait_.generics.clone();
     let enum_generics = trait_.generics.clone();
-    for (_, generator_impl) in gamma.get_generators(&trait_.ident) {
-        // For the implementation find the generics for the trait
-        let trait_generics = get_generics_from_path_segment(
-            &*generator_impl.trait_.unwrap().1.segments.first().unwrap(),
-        );
-
-        // For the implementation find all the generics
-        let impl_generics = generator_impl.generics;
-
-        // Remove the generics that are from the trait from all the generics of the implementation
-        let struct_generics: Vec<GenericParam> = Vec::from_iter(
-            impl_generics
-                .params
-                .iter()
-                .filter(|param| {
-                    !trait_generics
-                        .params
-                        .iter()
-                        .any(|trait_param| trait_param == *param)
-                })
-                .cloned(),
-        );
-
-        // Add the generics that a just for the struct (not from the trait) to the list of generics
-        generics.params.extend(struct_generics);
+    for (_, generator_impl) in gamma.get_generators(&trait_.ident)? {
+        // Add the generics of the implementation, the bounds of a generic which is already in the
+        // list (e.g. from the trait or another implementation) are added to the existing generic
+        for param in generator_impl.generics.params {
+            merge_generic_param(&mut generics, param);
+        }
     }
 
     let (mut signature, enum_instance_name) = transform_destructor_signature(
@@ -496,13 +1342,13 @@ fn transform_destructor(
         &generics,
         &enum_generics,
         gamma,
-    );
+    )?;
 
     let mut arms: Vec<syn::Arm> = Vec::new();
     // If any of the impl do not have an implementation of the destructor then we need to create a
     // wildcard argument
     let mut wild_card_arm_required = false;
-    for (generator, generator_impl) in gamma.get_generators(&trait_.ident).iter() {
+    for (generator, generator_impl) in gamma.get_generators(&trait_.ident)?.iter() {
         let result = transform_destructor_impl(
             generator,
             destructor,
@@ -516,7 +1362,8 @@ fn transform_destructor(
             Ok(arm) => {
                 arms.push(arm);
             }
-            Err(NotFound { .. }) => wild_card_arm_required = true,
+            Err(RfoodError { code: ErrorCode::NotFound, .. }) => wild_card_arm_required = true,
+            Err(error) => return Err(error),
         }
     }
 
@@ -524,10 +1371,12 @@ fn transform_destructor(
     if wild_card_arm_required {
         // Get impl in the trait
         let mut body = Expr::Block(ExprBlock {
-            block: Gamma::get_destructor_impl_for_trait(trait_, &destructor.sig.ident)
-                .unwrap()
+            block: Gamma::get_destructor_impl_for_trait(trait_, &destructor.sig.ident)?
                 .default
-                .unwrap(),
+                .ok_or_else(|| RfoodError::not_found(
+                    &destructor.sig.ident.to_string(),
+                    "implementation of destructor",
+                ).with_span(destructor))?,
             attrs: Vec::new(),
             label: None,
         });
@@ -548,7 +1397,7 @@ fn transform_destructor(
     if is_dyn_box_generator_return(&signature, gamma) {
         // TODO change return type
         signature = Signature {
-            output: transform_dyn_box_destructor_signature_output(&signature.output),
+            output: transform_dyn_box_destructor_signature_output(&signature.output)?,
             ..signature
         };
 
@@ -566,14 +1415,40 @@ fn transform_destructor(
     }
 
     gamma.add_enum_consumer(&enum_, &func);
-    Item::Fn(func)
+    Ok(Item::Fn(func))
+}
+
+/// Add a generic param to generics, if generics already has a param with the same name only the
+/// bounds which it does not have are added to it
+fn merge_generic_param(generics: &mut Generics, param: GenericParam) {
+    let existing = generics.params.iter_mut().find(|existing| match (&**existing, &param) {
+        (GenericParam::Type(existing), GenericParam::Type(param)) => existing.ident == param.ident,
+        (GenericParam::Lifetime(existing), GenericParam::Lifetime(param)) => existing.lifetime == param.lifetime,
+        (GenericParam::Const(existing), GenericParam::Const(param)) => existing.ident == param.ident,
+        _ => false,
+    });
+    match (existing, param) {
+        (Some(GenericParam::Type(existing)), GenericParam::Type(param)) => {
+            for bound in param.bounds {
+                if !existing.bounds.iter().any(|existing_bound| *existing_bound == bound) {
+                    existing.colon_token.get_or_insert_with(Default::default);
+                    existing.bounds.push(bound);
+                }
+            }
+        }
+        (Some(_), _) => {}
+        (None, param) => generics.params.push(param),
+    }
 }
 
-pub fn transform_dyn_box_destructor_signature_output(output: &ReturnType) -> ReturnType {
-    if let ReturnType::Type(_, type_) = output {
-        return create_return_type_from_ident(&type_.get_delta_type().name);
+pub fn transform_dyn_box_destructor_signature_output(output: &ReturnType) -> RfoodResult<ReturnType> {
+    match output {
+        ReturnType::Type(_, type_) => Ok(create_return_type_from_ident(&type_.get_delta_type().name)),
+        ReturnType::Default => Err(RfoodError::new(
+            ErrorCode::UnsupportedType,
+            "Destructors which return a generator must have a return type".to_string(),
+        )),
     }
-    panic!("Unsupported return type for destructor");
 }
 
 /// Transform a function implementation of a destructor into an arm of the consumers match
@@ -591,15 +1466,25 @@ fn transform_destructor_impl(
     enum_instance_name: &Ident,
     impl_: &ItemImpl,
     gamma: &Gamma,
-) -> std::result::Result<Arm, NotFound> {
+) -> RfoodResult<Arm> {
     // Find the implementation of the method
-    let method_result = Gamma::get_destructor_impl_for_generator(&impl_, &destructor.sig.ident);
-    if method_result.is_err() {
-        return Err(method_result.err().unwrap());
+    let method = Gamma::get_destructor_impl_for_generator(&impl_, &destructor.sig.ident)?;
+    let mut block: Block = method.block;
+
+    // The type parameters of the method are named as in the trait, e.g. `B` of
+    // `fn fold<B>(&self, init: B) -> B` becomes `A` of `fn fold<A>(&self, init: A) -> A`
+    let renames: HashMap<Ident, Ident> = method
+        .sig
+        .generics
+        .type_params()
+        .zip(destructor.sig.generics.type_params())
+        .filter(|(impl_param, trait_param)| impl_param.ident != trait_param.ident)
+        .map(|(impl_param, trait_param)| (impl_param.ident.clone(), trait_param.ident.clone()))
+        .collect();
+    if !renames.is_empty() {
+        ReplaceTypeParams { types: get_renamed_types(&renames) }.visit_block_mut(&mut block);
     }
 
-    let mut block: Block = method_result.unwrap().block;
-
     // The name of the varaibles created in the below let expressions
     let mut self_mutable_fields = Vec::new();
     // let mut new_delta = new_delta.clone();
@@ -624,26 +1509,31 @@ fn transform_destructor_impl(
         //     &block,
         //     0
         // );
-        for field in generator.fields.iter() {
-            let field_name = field.ident.clone().unwrap();
-            self_mutable_fields.push(field_name.clone());
-        }
+        self_mutable_fields = get_struct_attrs(&generator);
 
         // TODO Add in return statement
         block = add_stmts_to_block(
-            &Stmt::Expr(Expr::Struct(ExprStruct {
-                attrs: Vec::new(),
-                path: create_path_from_ident(&generator.ident),
-                brace_token: token::Brace::default(),
-                dot2_token: None,
-                fields: Punctuated::from_iter(self_mutable_fields.iter().map(|field| FieldValue {
+            &Stmt::Expr(if let Fields::Unnamed(_) = generator.fields {
+                // Tuple structs are instantiated with a call
+                create_function_call(
+                    &generator.ident,
+                    Punctuated::from_iter(self_mutable_fields.iter().map(create_expr_from_ident)),
+                )
+            } else {
+                Expr::Struct(ExprStruct {
                     attrs: Vec::new(),
-                    member: Member::Named(field.clone()),
-                    colon_token: None,
-                    expr: Expr::Path(create_expr_path_from_path(create_path_from_ident(&field))),
-                })),
-                rest: None,
-            })),
+                    path: create_path_from_ident(&generator.ident),
+                    brace_token: token::Brace::default(),
+                    dot2_token: None,
+                    fields: Punctuated::from_iter(self_mutable_fields.iter().map(|field| FieldValue {
+                        attrs: Vec::new(),
+                        member: Member::Named(field.clone()),
+                        colon_token: None,
+                        expr: Expr::Path(create_expr_path_from_path(create_path_from_ident(&field))),
+                    })),
+                    rest: None,
+                })
+            }),
             &block,
             block.stmts.len(),
         );
@@ -658,11 +1548,7 @@ fn transform_destructor_impl(
     // TODO Maybe problaly clone it?
     // TODO Then for every *radius = something remove the deref => radius = something
     let mut rsfa = ReplaceSelfFieldAssignments {
-        self_fields: generator
-            .fields
-            .iter()
-            .map(|field| field.ident.clone().unwrap())
-            .collect(),
+        self_fields: get_struct_attrs(&generator),
     };
     rsfa.visit_expr_mut(&mut expr);
 
@@ -676,7 +1562,12 @@ fn transform_destructor_impl(
 
     // Create the arm of the match statement
     let path = ast::create::create_path_for_enum(enum_name, &generator.ident);
-    Ok(ast::create::create_match_arm(
+    let create_arm = if let Fields::Unnamed(_) = generator.fields {
+        ast::create::create_tuple_match_arm
+    } else {
+        ast::create::create_match_arm
+    };
+    Ok(create_arm(
         path,
         get_struct_attrs(&generator),
         expr,
@@ -717,7 +1608,7 @@ fn transform_destructor_expr(
 fn transform_consumer_expr(
     expr: &Expr,
     self_arg_name: Ident,
-    trait_attributes: Vec<Ident>,
+    trait_attributes: Vec<(Ident, Member)>,
     gamma: &Gamma,
 ) -> Expr {
     let mut expr_clone = expr.clone();
@@ -747,7 +1638,7 @@ fn transform_destructor_signature(
     generics: &Generics,
     enum_generics: &Generics,
     gamma: &mut Gamma,
-) -> (Signature, Ident) {
+) -> RfoodResult<(Signature, Ident)> {
     let enum_instance_name = transform_type_to_name(enum_name);
 
     // Transform arguments
@@ -791,7 +1682,9 @@ fn transform_destructor_signature(
 
     if is_mutable_self(&signature) {
         if !matches!(signature.output, ReturnType::Default) {
-            panic!("Transforming mutable destructors without outputs not supported");
+            return Err(RfoodError::unsupported_syntax(
+                "Destructors with a mutable self receiver cannot return a value",
+            ).with_span(&signature.output));
         }
         output = ReturnType::Type(
             token::RArrow::default(),
@@ -802,64 +1695,102 @@ fn transform_destructor_signature(
         );
     }
 
+    // A destructor returning `Self` returns the enum
+    let enum_type = Type::Path(TypePath {
+        qself: None,
+        path: match enum_generics.params.is_empty() {
+            true => create_path_from_ident(enum_name),
+            false => add_generics_to_path_segment(PathSegment::from(enum_name.clone()), enum_generics).into(),
+        },
+    });
+    ReplaceTypeParams {
+        types: HashMap::from([(Ident::new("Self", Span::call_site()), enum_type)]),
+    }.visit_return_type_mut(&mut output);
+
+    // The generics of the method itself are kept alongside those of the trait and its impls
+    let mut generics = generics.clone();
+    for param in signature.generics.params.iter().cloned() {
+        merge_generic_param(&mut generics, param);
+    }
+    // A bound on `Self` (e.g. `where Self: Sized`) has no meaning for a function
+    if let Some(where_clause) = &signature.generics.where_clause {
+        generics.make_where_clause().predicates.extend(where_clause.predicates.iter().filter(|predicate| {
+            !matches!(predicate, WherePredicate::Type(PredicateType { bounded_ty: Type::Path(TypePath { qself: None, path }), .. }) if path.is_ident("Self"))
+        }).cloned());
+        if generics.where_clause.as_ref().is_some_and(|where_clause| where_clause.predicates.is_empty()) {
+            generics.where_clause = None;
+        }
+    }
+
     let sig = syn::Signature {
         inputs: new_inputs,
-        generics: generics.clone(),
+        generics,
         output,
         ..signature.clone()
     };
     gamma.set_signature(&sig.ident, &sig);
 
-    (
+    Ok((
         sig,
         enum_instance_name.clone(),
-    )
+    ))
 }
 
-pub fn transform_consumer_signature(signature: &Signature, gamma: &mut Gamma) -> Signature {
+pub fn transform_consumer_signature(signature: &Signature, gamma: &mut Gamma) -> RfoodResult<Signature> {
     let mut inputs = signature.inputs.clone();
 
-    // Get the self arg
-    let consumer_arg: FnArg = inputs.iter().next().unwrap().clone();
-    let self_type = consumer_arg.get_delta_type(None);
+    // Get the self arg, an alias of the enum is expanded to find how it is taken
+    let consumer_arg: FnArg = gamma
+        .expand_signature_type_aliases(signature)
+        .inputs
+        .first()
+        .cloned()
+        .ok_or_else(|| RfoodError::new(ErrorCode::Internal, format!("Consumer `{}` has no arguments", signature.ident)).with_span(signature))?;
+    let self_type = consumer_arg.get_delta_type(None)?;
 
     // Ignoring the first element transfrom each argument
-    let mut new_inputs = Vec::from_iter(inputs.iter().skip(1).map(|arg| {
-        // TODO make all args with type of enum, Box<dyn T>
-        let type_ = arg.get_delta_type(None);
-        if gamma.is_enum(&type_.name) && matches!(type_.ref_type, RefType::Box(_) | RefType::None)
-        {
-            // Create box dyn of fn arg
-            return create_dyn_box_arg(&arg);
-        }
-        arg.clone()
-    }));
+    let mut new_inputs = Vec::from_iter(inputs.iter().skip(1).cloned());
+    // A consumer taking the enum by value or in a pointer takes self in the same pointer, e.g.
+    // `self: Rc<Self>`, otherwise in the pointer the trait objects are held in
+    let consumer_ref_type = consumer_arg.get_ref_type();
     new_inputs.insert(
         0,
-        create_self_fn_arg(if matches!(consumer_arg.get_ref_type(), RefType::Ref(_)) {
-            consumer_arg.get_ref_type()
-        } else {
-            RefType::Box(Box::new(RefType::None))
+        create_self_fn_arg(match consumer_ref_type.get_pointer() {
+            _ if consumer_ref_type.is_ref() => consumer_ref_type,
+            Some(pointer) => pointer.wrap(RefType::None),
+            None => gamma.pointer.wrap(RefType::None),
         }),
     );
 
-    let mut output = signature.output.clone();
-
-    if let ReturnType::Type(ra, ty) = &output {
-        if gamma.is_enum(&ty.get_delta_type().name) {
-            // Create box dyn of fn arg
-            output = ReturnType::Type(*ra, Box::new(create_dyn_box_of_type(&ty)))
+    // Any enums in the other arguments and the output are now trait objects
+    let mut sig = transform_singature_types(
+        &Signature {
+            inputs: syn::punctuated::Punctuated::from_iter(new_inputs),
+            ..signature.clone()
+        },
+        |type_| transform_type_fp(type_, gamma),
+    );
+    // The type parameters of the enum are the type parameters of the trait
+    if let Ok(enum_) = gamma.get_enum(&self_type.name) {
+        sig.generics.params = sig
+            .generics
+            .params
+            .into_iter()
+            .filter(|param| !matches!(param, GenericParam::Type(param) if is_enum_type_param(&enum_, &param.ident)))
+            .collect();
+        if sig.generics.params.is_empty() {
+            sig.generics.lt_token = None;
+            sig.generics.gt_token = None;
         }
     }
-
-    let sig = Signature {
-        inputs: syn::punctuated::Punctuated::from_iter(new_inputs),
-        output,
-        ..signature.clone()
-    };
+    // A method with type parameters cannot be called on a trait object, it is only required of
+    // sized types so the trait can still be used as one
+    if sig.generics.type_params().next().is_some() {
+        sig.generics.make_where_clause().predicates.push(parse_quote!(Self: Sized));
+    }
     gamma.set_signature(&sig.ident, &sig);
 
-    sig
+    Ok(sig)
 }
 
 /// Given the name of a type get a sensible name for the object
@@ -884,25 +1815,27 @@ fn transform_struct_instantiation_path_for_enum(
     expr_struct: &ExprStruct,
     gamma: &Gamma,
     delta: &Delta,
-) -> Path {
+) -> RfoodResult<Path> {
     // Get the name of the enum
     let datatype_name = gamma
-        .get_enum_variant_enum(&expr_struct.path.get_delta_type().name)
-        .unwrap();
-    // Add the enum in front of the struct
-    let mut new_path_vec = vec![PathSegment {
-        ident: datatype_name.ident.clone(),
-        arguments: PathArguments::None,
-    }];
-    new_path_vec.append(&mut Vec::from_iter(
-        expr_struct.path.segments.clone().iter().cloned(),
-    ));
+        .get_enum_variant_enum(&expr_struct.path.get_delta_type().name)?;
+    // Add the variant to the path to the enum
+    let mut path = gamma.get_relative_item_path(&delta.module_path, &datatype_name.ident);
+    path.segments.push(expr_struct.path.segments.last().unwrap().clone());
+    Ok(path)
+}
 
-    // Add it to the front of the struct path segments
-    Path {
-        segments: Punctuated::from_iter(new_path_vec),
-        ..expr_struct.path.clone()
-    }
+/// Get the path to a generator struct from the path used for the enum variant it was created
+/// from, e.g. `Shape::Circle` becomes `Circle`
+fn transform_variant_path_to_struct_path(
+    variant_path: &Path,
+    gamma: &Gamma,
+    delta: &Delta,
+) -> Path {
+    let variant_segment = variant_path.segments.last().unwrap();
+    let mut path = gamma.get_relative_item_path(&delta.module_path, &variant_segment.ident);
+    *path.segments.last_mut().unwrap() = variant_segment.clone();
+    path
 }
 
 fn transform_expr_type(
@@ -910,67 +1843,118 @@ fn transform_expr_type(
     current_type: &DeltaType,
     required_type: &EType,
     gamma: &Gamma,
-) -> Expr {
-    println!("Transforming expr type for {:?} to {:?}", current_type, required_type);
+) -> RfoodResult<Expr> {
+    trace!(?current_type, ?required_type, "Transforming expr type");
 
     // If the current type equivalent to the required type
     if let EType::DeltaType(required_type) = required_type {
         if current_type.is_equaivalent(&required_type, &gamma) {
-            return expr.clone();
+            return Ok(expr.clone());
         }
     }
 
     match &required_type {
-        EType::Any | EType::None => expr.clone(),
-        EType::RefType(rt) | &EType::DeltaType(DeltaType{ref_type: rt, ..}) => match (&current_type.ref_type, rt){
-            (left, right) if left == right => expr.clone(),
-            // Box -> None / Ref -> None
-            (RefType::Box(box inner) | RefType::Ref(box inner), RefType::None) => create_dereference_of_expr(
-                &transform_expr_type(
-                    expr,
-                    &DeltaType{name: current_type.name.clone(), ref_type: inner.clone()},
-                    &EType::RefType(RefType::None),
-                    &gamma
-                )
-            ),
-            // None -> Box
-            (RefType::None, RefType::Box(box inner)) => create_box_of_expr(
-                &transform_expr_type(
-                    expr,
-                    &DeltaType{name: current_type.name.clone(), ref_type: RefType::None},
-                    &EType::RefType(inner.clone()),
-                    &gamma
-                )
-            ),
-            // None -> Ref
-            (RefType::None, RefType::Ref(box inner)) => create_reference_of_expr(
-                &transform_expr_type(
-                    expr,
-                    &DeltaType{name: current_type.name.clone(), ref_type: RefType::None},
-                    &EType::RefType(inner.clone()),
-                    &gamma
-                )
-            ),
-            (RefType::Ref(box current_inner), RefType::Ref(box required_inner)) | (RefType::Box(box current_inner), RefType::Box(box required_inner)) => {
-                transform_expr_type(
-                    expr,
-                    &DeltaType{name: current_type.name.clone(), ref_type: current_inner.clone()},
-                    &EType::RefType(required_inner.clone()),
-                    &gamma
-                )
-            },
-            // Box -> Ref / Ref -> Box
-            (RefType::Box(box current_inner), RefType::Ref(_)) | (RefType::Ref(box current_inner), RefType::Box(_)) => {
-                transform_expr_type(
-                    &create_dereference_of_expr(expr),
-                    &DeltaType{name: current_type.name.clone(), ref_type: current_inner.clone()},
-                    required_type,
-                    &gamma
-                )
-            },
-            _ => panic!("Cannot transform {:?} to {:?}", current_type, required_type),
+        EType::Any | EType::None => Ok(expr.clone()),
+        EType::RefType(required) | &EType::DeltaType(DeltaType{ref_type: required, ..}) => {
+            Ok(transform_expr_ref_type(expr, &current_type.ref_type, required))
+        }
+    }
+}
+
+/// Change the boxes and references around an expression, e.g. an expression of `&Box<T>` is
+/// required as a `&T` becomes `&**expr`
+///
+/// The pointers which are already around the expression are dereferenced if the required pointers
+/// are inside of them, otherwise the required pointers are created around the expression, e.g.
+/// `Box::new(expr)` or `&mut expr`
+fn transform_expr_ref_type(expr: &Expr, current: &RefType, required: &RefType) -> Expr {
+    if current == required {
+        return expr.clone();
+    }
+    // A mutable borrow can be used as a shared borrow
+    if let (RefType::MutRef(box current_inner), RefType::Ref(box required_inner)) = (current, required) {
+        if current_inner == required_inner {
+            return expr.clone();
         }
     }
+    // Move the value out of the pointers around it, e.g. `*expr` for a `Box<T>` required as a `T`
+    if let Some(depth) = current.get_depth_of(required) {
+        return create_dereference_of_pointers(expr, current, depth, false);
+    }
+    match required {
+        // Borrow the value inside the current pointers if possible, e.g. `&*expr` for a `Box<T>`
+        // required as a `&T`, otherwise borrow the value once it has the required pointers
+        RefType::Ref(box inner) | RefType::MutRef(box inner) => {
+            let mutable = matches!(required, RefType::MutRef(_));
+            let inner_expr = match current.get_depth_of(inner) {
+                Some(depth) => create_dereference_of_pointers(expr, current, depth, mutable),
+                None => transform_expr_ref_type(expr, current, inner),
+            };
+            create_reference_of_expr(&inner_expr, mutable)
+        },
+        RefType::Box(box inner) | RefType::Rc(box inner) | RefType::Arc(box inner) | RefType::RefCell(box inner) => {
+            let wrapper = required.get_wrapper_ident().unwrap();
+            // A box can be converted into a shared pointer without moving the value out of it,
+            // which is not possible for a trait object, e.g. `Rc::from(expr)`
+            if let (RefType::Box(box current_inner), RefType::Rc(_) | RefType::Arc(_)) = (current, required) {
+                if current_inner == inner {
+                    let from: Path = parse_quote!(#wrapper::from);
+                    return create_path_call(&from, Punctuated::from_iter(vec![expr.clone()]));
+                }
+            }
+            create_wrapper_of_expr(&wrapper, &transform_expr_ref_type(expr, current, inner))
+        },
+        // Every pointer contains `None` so it is always found above
+        RefType::None => unreachable!(),
+    }
+}
+
+/// Dereference the outer `depth` pointers around an expression, the value in a `RefCell` is
+/// borrowed before it is dereferenced, e.g. `*expr.borrow()`
+fn create_dereference_of_pointers(expr: &Expr, current: &RefType, depth: usize, mutable: bool) -> Expr {
+    let mut pointer = current;
+    let mut expr = expr.clone();
+    for _ in 0..depth {
+        expr = match pointer {
+            RefType::RefCell(_) => {
+                let borrow = Ident::new(if mutable { "borrow_mut" } else { "borrow" }, Span::call_site());
+                // Method calls dereference their receiver so `(*expr).borrow()` is `expr.borrow()`
+                let receiver = remove_deference_of_expr(&expr);
+                create_dereference_of_expr(&create_method_call(&borrow, &receiver, &Punctuated::new()))
+            },
+            _ => create_dereference_of_expr(&expr),
+        };
+        pointer = match pointer.inner() {
+            Some(inner) => inner,
+            None => break,
+        };
+    }
+    expr
+}
+
+/// Check if a binary operator compares its operands
+fn is_comparison(op: &BinOp) -> bool {
+    matches!(op, BinOp::Eq(_) | BinOp::Ne(_) | BinOp::Lt(_) | BinOp::Le(_) | BinOp::Gt(_) | BinOp::Ge(_))
+}
+
+/// Transform the arguments of a tuple struct/variant instantiation, each argument is typed by the
+/// field in the same position
+fn transform_tuple_instantiation_args(
+    args: &Punctuated<Expr, Token![,]>,
+    fields: &Fields,
+    transform_type: &TransformType,
+    gamma: &Gamma,
+    delta: &Delta,
+) -> RfoodResult<Punctuated<Expr, Token![,]>> {
+    args.iter().zip(get_field_types(&gamma.expand_fields_type_aliases(fields))).map(|(arg, required_type)| {
+        transform_expr(
+            &clean_type(arg),
+            transform_type,
+            gamma,
+            &delta,
+            EType::DeltaType(required_type),
+        )
+    }).collect()
 }
 
 fn transform_block(
@@ -979,9 +1963,9 @@ fn transform_block(
     gamma: &Gamma,
     delta: &Delta,
     return_type: EType,
-) -> Block {
+) -> RfoodResult<Block> {
     let mut delta = delta.clone();
-    let stmts = Vec::from_iter(block.stmts.iter().enumerate().map(|(index, stmt)| {
+    let stmts = block.stmts.iter().enumerate().map(|(index, stmt)| {
         transform_statement(
             &stmt,
             transform_type,
@@ -992,11 +1976,38 @@ fn transform_block(
             } else {
                 EType::None
             },
-        )
-    }));
-    Block {
+        ).or_else(|error| {
+            gamma.skip_error(error.clone().in_module(&delta.module_path))?;
+            // Keep any variable declared by the statement if its type is known
+            if let Stmt::Local(local) = stmt {
+                let _ = delta.collect_for_local(local, gamma);
+            }
+            Ok(create_skipped_statement(stmt, &error))
+        })
+    }).collect::<RfoodResult<Vec<Stmt>>>()?;
+    Ok(Block {
         stmts,
         ..block.clone()
+    })
+}
+
+/// Create the placeholder for a statement which could not be transformed in best effort mode, the
+/// expression of the statement is replaced with a `todo!()` and any variable it declares is kept
+fn create_skipped_statement(stmt: &Stmt, error: &RfoodError) -> Stmt {
+    let create_placeholder = |expr: &Expr| create_todo_expr(&format!(
+        "{}: {}, could not transform `{}`",
+        error.code.code(),
+        error.message,
+        quote!(#expr),
+    ));
+    match stmt {
+        Stmt::Local(local @ Local { init: Some((eq, expr)), .. }) => Stmt::Local(Local {
+            init: Some((*eq, Box::new(create_placeholder(expr)))),
+            ..local.clone()
+        }),
+        Stmt::Expr(expr) => Stmt::Expr(create_placeholder(expr)),
+        Stmt::Semi(expr, semi) => Stmt::Semi(create_placeholder(expr), *semi),
+        _ => stmt.clone(),
     }
 }
 
@@ -1006,8 +2017,16 @@ fn transform_expr_inner(
     gamma: &Gamma,
     delta: &mut Delta,
     return_type: EType,
-) -> Expr {
+) -> RfoodResult<Expr> {
     match (transform_type, expr) {
+        // Any type is allowed so there is nothing to add the deref back, e.g. the left of a binary
+        // operation
+        (_, Expr::Unary(expr_unary @ ExprUnary { op: UnOp::Deref(_), .. })) if matches!(return_type, EType::Any) => {
+            Ok(Expr::Unary(ExprUnary {
+                expr: Box::new(transform_expr(&expr_unary.expr, transform_type, gamma, delta, EType::Any)?),
+                ..expr_unary.clone()
+            }))
+        },
         (_, Expr::Unary(ExprUnary { expr, op: UnOp::Deref(_), .. })) => {
             // Remove the deref at this stage so it can be added back as required
             transform_expr(&expr, &transform_type, &gamma, &delta, return_type.clone())
@@ -1022,12 +2041,12 @@ fn transform_expr_inner(
             //         return transform_expr_type(&expr, &current_type, &delta_type, gamma);
             //     }
             // }
-            return expr.clone();
+            return Ok(expr.clone());
         },
         (TransformType::OOPToFP, Expr::MethodCall(expr_method_call))
-            if gamma.is_consumer(&expr_method_call.method) =>
+            if gamma.is_consumer_method_call(expr_method_call, delta) =>
         {
-            println!("Transforming expr method call");
+            trace!("Transforming expr method call");
 
             let ExprMethodCall {
                 receiver,
@@ -1039,83 +2058,131 @@ fn transform_expr_inner(
             let mut new_args = vec![*receiver.clone()];
             let old_args: Vec<Expr> = args.iter().cloned().collect();
             new_args.extend(old_args);
-            let mut fn_expr = create_function_call(&method, Punctuated::from_iter(new_args));
+            let mut path = gamma.get_relative_item_path(&delta.module_path, &method);
+            // The type arguments of the method are given to the consumer, e.g.
+            // `shape.scale::<f64>(2.0)` becomes `scale::<f64>(&shape, 2.0)`
+            if let Some(MethodTurbofish { args: type_args, .. }) = &expr_method_call.turbofish {
+                let type_args = type_args.iter().map(|arg| match arg {
+                    GenericMethodArgument::Type(type_) => GenericArgument::Type(type_.clone()),
+                    GenericMethodArgument::Const(expr) => GenericArgument::Const(expr.clone()),
+                }).collect();
+                let type_args = align_type_args(type_args, &gamma.get_signature(method)?);
+                path.segments.last_mut().unwrap().arguments = PathArguments::AngleBracketed(parse_quote!(::<#(#type_args),*>));
+            }
+            let mut fn_expr = create_path_call(&path, Punctuated::from_iter(new_args));
 
             // Perform regular transform on function call
-            fn_expr = transform_expr(&fn_expr, &transform_type, &gamma, &delta, return_type.clone());
+            fn_expr = transform_expr(&fn_expr, &transform_type, &gamma, &delta, return_type.clone())?;
 
             // If the method is a mutable self call
             if gamma.is_mutable_consumer(&expr_method_call.method) {
                 // Overwrite the receiver
-                Expr::Assign(create_assignment_expr(*receiver.clone(), fn_expr.clone()))
+                Ok(Expr::Assign(create_assignment_expr(*receiver.clone(), fn_expr.clone())))
             } else {
-                fn_expr
+                Ok(fn_expr)
             }
         }
         // Any other method call, transform all the args and the receiver
         (_, Expr::MethodCall(method_call)) => {
             // Get the signature of the method call, NOTE this will fail if any method call are
             // made which are not on destructors TODO fix
-            let reciever_type = delta
-                .get_type_of_expr(&method_call.receiver, &gamma)
-                .unwrap();
-            let signature = gamma.get_signature(&method_call.method);
+            let reciever_type = delta.get_type_of_expr(&method_call.receiver, &gamma)?;
+            let signature = match gamma
+                .get_inherent_signature(&reciever_type.name, &method_call.method)
+                .or_else(|_| gamma.get_signature(&method_call.method))
+            {
+                Ok(signature) => signature,
+                // Methods of the standard library, e.g. `Vec::push`, are typed by inference
+                Err(error) => {
+                    let reciever_type = transform_inferred_type(&reciever_type, transform_type, gamma);
+                    let arg_types = delta
+                        .get_expected_arg_types(expr, Some(&reciever_type), get_required_delta_type(&return_type), gamma)
+                        .ok_or_else(|| error.with_span(&method_call.method))?;
+                    return Ok(Expr::MethodCall(ExprMethodCall {
+                        receiver: Box::new(transform_expr(
+                            &method_call.receiver,
+                            transform_type,
+                            gamma,
+                            delta,
+                            get_own_type(&method_call.receiver, gamma, delta),
+                        )?),
+                        args: transform_args_of_expected_types(&method_call.args, arg_types, transform_type, gamma, delta)?,
+                        ..method_call.clone()
+                    }));
+                }
+            };
             
-            println!("Transforming method call {}", signature.as_ref().unwrap().ident);
-            println!("Delta is {:?}", delta);
+            debug!(method = %signature.ident, "Transforming method call");
+            trace!(?delta);
 
-            Expr::MethodCall(ExprMethodCall {
+            // A receiver in a pointer, e.g. `self: Rc<Self>`, is required in the same pointer
+            let receiver_pointer = match signature.inputs.first() {
+                Some(receiver) => receiver.get_delta_type(Some(reciever_type.name.clone()))?.ref_type.get_pointer(),
+                None => None,
+            };
+
+            Ok(Expr::MethodCall(ExprMethodCall {
                 receiver: Box::new(transform_expr(
                     &method_call.receiver,
                     transform_type,
                     gamma,
                     &delta,
-                    if matches!(signature.as_ref().unwrap().inputs.first().unwrap().get_delta_type(Some(reciever_type.name.clone())).ref_type, RefType::Box(_)) {
-                        EType::RefType(RefType::Box(Box::new(RefType::None)))
-                    } else {
-                        EType::Any
+                    match receiver_pointer {
+                        Some(pointer) => EType::RefType(pointer.wrap(RefType::None)),
+                        None => EType::Any,
                     }
-                )),
+                )?),
                 // Skip one to skip the receiver argument
-                args: Punctuated::from_iter(method_call.args.iter().enumerate().map(
+                args: method_call.args.iter().enumerate().map(
                     |(index, arg)| {
-                        println!("Transforming type of arg to {:?}", signature.as_ref().unwrap().inputs[index + 1].get_delta_type(Some(reciever_type.name.clone())));
+                        let arg_type = signature.inputs[index + 1].get_delta_type(Some(reciever_type.name.clone()))?;
+                        trace!(?arg_type, "Transforming type of arg");
                         transform_expr(
                             arg,
                             transform_type,
                             gamma,
                             &delta,
-                            match &signature {
-                                Ok(signature) => EType::DeltaType(
-                                    signature.inputs[index + 1]
-                                        .get_delta_type(Some(reciever_type.name.clone())),
-                                ),
-                                Err(_) => EType::Any,
-                            },
+                            EType::DeltaType(arg_type),
                         )
                     },
-                )),
+                ).collect::<RfoodResult<_>>()?,
                 ..method_call.clone()
-            })
+            }))
         }
         // If the experssion is calling a consumer and we are transforming from FP to OOP
         // Then we should replace the call with a method call
-        (TransformType::FPToOOP, Expr::Call(expr_call))
-            if gamma.is_destructor(&get_function_call_name(expr_call)) =>
+        (TransformType::FPToOOP, Expr::Call(expr_call @ ExprCall { func: box Expr::Path(ExprPath { path: fn_path, .. }), .. }))
+            if gamma.is_destructor(&get_path_call_name(fn_path)) =>
         {
             // Extract the first argument to the function
             let mut args = expr_call.args.clone();
-            let first_arg = clean_type(&args.pop_first().unwrap());
+            let mut first_arg = clean_type(&args.pop_first().unwrap());
+            // Method calls dereference their receiver, so `area(&**shape)` is `shape.area()`
+            while let Expr::Unary(ExprUnary { op: UnOp::Deref(_), .. }) = first_arg {
+                first_arg = remove_deference_of_expr(&first_arg);
+            }
 
             // Create method call
-            let method_call =
-                create_method_call(&get_function_call_name(expr_call), &first_arg, &args);
+            let mut method_call =
+                create_method_call(&get_path_call_name(fn_path), &first_arg, &args);
+            // The type arguments of the consumer are given to the method, without those of the enum
+            if let (
+                Expr::Path(ExprPath { path, .. }),
+                Expr::MethodCall(ExprMethodCall { method, turbofish, .. }),
+            ) = (&*expr_call.func, &mut method_call) {
+                if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args: type_args, .. }) = &path.segments.last().unwrap().arguments {
+                    let type_args = align_type_args(type_args.iter().cloned().collect(), &gamma.get_signature(method)?);
+                    if !type_args.is_empty() {
+                        *turbofish = Some(parse_quote!(::<#(#type_args),*>));
+                    }
+                }
+            }
             // Performance regular transformations to the new method call (fix typing of args)
             transform_expr(&method_call, &transform_type, &gamma, &delta, return_type.clone())
         }
         (_, Expr::Call(expr_call)) if new_box_call_expr(expr).is_ok() => {
             // Get the inner extression of the box call
-            let mut inner_expr = new_box_call_expr(expr).unwrap();
+            let mut inner_expr = new_box_call_expr(expr)?;
 
             // Transform the inner expression
             inner_expr = transform_expr(
@@ -1124,41 +2191,145 @@ fn transform_expr_inner(
                 &gamma,
                 &delta,
                 return_type.clone(),
-            );
+            )?;
 
             // If the expected return type is not a box or the inner type is a box, then just
             // return the inner expression
             if let EType::DeltaType(dt) = &return_type {
                 if !matches!(dt.ref_type, RefType::Box(_))
                     || matches!(
-                        delta.get_type_of_expr(&inner_expr, gamma).unwrap().ref_type,
+                        delta.get_type_of_expr(&inner_expr, gamma)?.ref_type,
                         RefType::Box(_)
                     )
                 {
-                    return inner_expr;
+                    return Ok(inner_expr);
                 }
             }
 
             // Otherwise recreate a box of the inner expression
-            create_box_of_expr(&inner_expr)
+            Ok(create_box_of_expr(&inner_expr))
+        }
+        // Creating a shared pointer or cell, e.g. `Rc::new(expr)`, is kept so values are still
+        // shared after the transformation
+        (_, Expr::Call(_)) if new_wrapper_call_expr(expr).is_some() => {
+            let (wrapper, inner_expr) = new_wrapper_call_expr(expr).unwrap();
+
+            // If the wrapper is required then the inner expression is required as the type inside it
+            let inner_type = match &return_type {
+                EType::DeltaType(delta_type) if delta_type.ref_type.get_wrapper_ident().as_ref() == Some(&wrapper) => {
+                    delta_type.ref_type.inner().map_or(EType::Any, |inner| EType::DeltaType(delta_type.with_ref_type(inner.clone())))
+                }
+                _ => EType::Any,
+            };
+            let inner_expr = transform_expr(&inner_expr, transform_type, gamma, delta, inner_type)?;
+            Ok(create_wrapper_of_expr(&wrapper, &inner_expr))
+        }
+        // Instantiation of a tuple generator, add the enum to the path
+        (TransformType::OOPToFP, Expr::Call(expr_call @ ExprCall { func: box Expr::Path(ExprPath { path, .. }), .. }))
+            if gamma.get_constructor(&get_path_call_name(path)).is_ok()
+                && gamma.is_path_to_item(&delta.module_path, path) =>
+        {
+            let variant_ident = get_path_call_name(path);
+            let enum_ = gamma.get_enum_variant_enum(&variant_ident)?;
+            let variant = gamma.get_constructor(&variant_ident)?;
+
+            let mut enum_path = gamma.get_relative_item_path(&delta.module_path, &enum_.ident);
+            enum_path.segments.push(PathSegment { ident: variant_ident, arguments: PathArguments::None });
+
+            Ok(Expr::Call(ExprCall {
+                func: Box::new(Expr::Path(create_expr_path_from_path(enum_path))),
+                args: transform_tuple_instantiation_args(
+                    &expr_call.args,
+                    &variant.fields,
+                    transform_type,
+                    gamma,
+                    &delta,
+                )?,
+                ..expr_call.clone()
+            }))
+        }
+        // Instantiation of a tuple variant, remove the enum from the path
+        (TransformType::FPToOOP, Expr::Call(expr_call @ ExprCall { func: box Expr::Path(ExprPath { path, .. }), .. }))
+            if gamma.get_generator_trait(&get_path_call_name(path)).is_some()
+                && gamma.is_path_to_item(&delta.module_path, path) =>
+        {
+            let struct_ident = get_path_call_name(path);
+            let struct_ = gamma.get_struct_by_name(&struct_ident)?;
+
+            Ok(Expr::Call(ExprCall {
+                func: Box::new(Expr::Path(create_expr_path_from_path(
+                    transform_variant_path_to_struct_path(path, gamma, &delta)
+                ))),
+                args: transform_tuple_instantiation_args(
+                    &expr_call.args,
+                    &struct_.fields,
+                    transform_type,
+                    gamma,
+                    &delta,
+                )?,
+                ..expr_call.clone()
+            }))
+        }
+        // The arguments of a closure keep their own types, unless the closure is bound by `Fn`
+        // (e.g. `f: F` where `F: Fn(i32) -> i32`) which gives the types of its arguments
+        (_, Expr::Call(expr_call)) if is_closure_call(expr_call, delta) => {
+            let input_types = match &*expr_call.func {
+                Expr::Path(ExprPath { path, .. }) => match delta.types.get(&get_path_call_name(path)) {
+                    Some(DeltaType { kind: TypeKind::Fn(inputs, _), .. }) => inputs.clone(),
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            };
+            Ok(Expr::Call(ExprCall {
+                func: Box::new(transform_expr(&expr_call.func, transform_type, gamma, delta, get_own_type(&expr_call.func, gamma, delta))?),
+                args: expr_call.args.iter().enumerate().map(|(index, arg)| {
+                    let arg_type = match input_types.get(index) {
+                        Some(input_type) => EType::DeltaType(input_type.clone()),
+                        None => get_own_type(arg, gamma, delta),
+                    };
+                    transform_expr(arg, transform_type, gamma, delta, arg_type)
+                }).collect::<RfoodResult<_>>()?,
+                ..expr_call.clone()
+            }))
         }
         (_, Expr::Call(expr_call)) => {
             if let ExprCall {
-                func: box Expr::Path(ExprPath { path, .. }),
+                func: box Expr::Path(ExprPath { qself, path, .. }),
                 ..
             } = expr_call
             {
 
 
-                let signature = gamma
-                    .get_signature(&get_function_call_name(expr_call))
-                    .unwrap();
-                println!("\n\nTransforming call {:?}", signature.ident);
-                println!("Signature is {:?}", signature);
-                println!("Current first expression is {:?}", expr_call.args.iter().next().unwrap());
+                let signature = match gamma.get_path_signature(qself.as_ref(), path) {
+                    Ok(signature) => signature,
+                    // Calls of the standard library, e.g. `Some(..)`, are typed by inference
+                    Err(error) => {
+                        let arg_types = delta
+                            .get_expected_arg_types(expr, None, get_required_delta_type(&return_type), gamma)
+                            .ok_or_else(|| error.with_span(path))?;
+                        return Ok(Expr::Call(ExprCall {
+                            args: transform_args_of_expected_types(&expr_call.args, arg_types, transform_type, gamma, delta)?,
+                            ..expr_call.clone()
+                        }));
+                    }
+                };
+                debug!(function = %signature.ident, "Transforming call");
+                trace!(?signature, first_arg = ?expr_call.args.first());
 
                 let e = Expr::Call(ExprCall {
                     func: Box::new(match &*expr_call.func {
+                        // Associated functions of an enum are moved to the trait object
+                        Expr::Path(ExprPath { qself: None, path, .. })
+                            if matches!(transform_type, TransformType::FPToOOP)
+                                && path.segments.len() >= 2
+                                && gamma.is_trait(&path.segments[path.segments.len() - 2].ident) =>
+                        {
+                            let trait_path = Path {
+                                segments: path.segments.iter().take(path.segments.len() - 1).cloned().collect(),
+                                ..path.clone()
+                            };
+                            Expr::Path(create_dyn_trait_fn_path(&trait_path, &get_path_call_name(path)))
+                        },
                         Expr::Path(_) => *expr_call.func.clone(),
                         _ => transform_expr(
                                 &expr_call.func,
@@ -1166,9 +2337,9 @@ fn transform_expr_inner(
                                 gamma,
                                 &delta,
                                 EType::Any,
-                        ),
+                        )?,
                     }),
-                    args: Punctuated::from_iter(expr_call.args.iter().enumerate().map(
+                    args: expr_call.args.iter().enumerate().map(
                         |(index, arg)| {
                             transform_expr(
                                 // Remove existing typing from fn arg
@@ -1176,55 +2347,59 @@ fn transform_expr_inner(
                                 transform_type,
                                 gamma,
                                 &delta,
-                                EType::DeltaType(signature.inputs[index].get_delta_type(None)),
+                                EType::DeltaType(signature.inputs[index].get_delta_type(None)?),
                             )
                         },
-                    )),
+                    ).collect::<RfoodResult<_>>()?,
                     ..expr_call.clone()
                 });
-                println!("Transformed call {:?}\n\n", signature.ident);
-                return e;
+                debug!(function = %signature.ident, "Transformed call");
+                return Ok(e);
             }
-            panic!("Cannot transform non path calls")
+            Err(RfoodError::unsupported_syntax("Only calls of functions by their path are supported").with_span(expr_call))
         }
         (_, Expr::Return(expr_return)) if expr_return.expr.is_some() => {
-            Expr::Return(ExprReturn {
+            Ok(Expr::Return(ExprReturn {
                 expr: Some(Box::new(transform_expr(
                     &expr_return.clone().expr.unwrap(),
                     transform_type,
                     gamma,
                     &delta,
                     return_type.clone(),
-                ))),
+                )?)),
                 ..expr_return.clone()
             }
-        )},
+        ))},
         (TransformType::OOPToFP, Expr::Struct(expr_struct))
-            if gamma.is_enum_or_variant(&expr_struct.path.get_delta_type().name) =>
+            if gamma.is_enum_or_variant(&expr_struct.path.get_delta_type().name)
+                && gamma.is_path_to_item(&delta.module_path, &expr_struct.path) =>
         {
             let struct_ = Expr::Struct(ExprStruct {
-                path: transform_struct_instantiation_path_for_enum(expr_struct, gamma, &delta),
-                fields: Punctuated::from_iter(expr_struct.fields.iter().map(|field| {
+                path: transform_struct_instantiation_path_for_enum(expr_struct, gamma, &delta)?,
+                fields: expr_struct.fields.iter().map(|field| {
                     // Get the enum
                     let enum_variant_ident = expr_struct.path.get_delta_type().name;
                     let enum_variant =
-                        gamma.get_enum_variant(&enum_variant_ident, &enum_variant_ident);
+                        gamma.get_enum_variant(&enum_variant_ident, &enum_variant_ident)?;
                     let mut enum_delta = Delta::new();
-                    enum_delta.collect_for_enum_variant(&enum_variant.unwrap(), false);
+                    enum_delta.collect_for_enum_variant(&Variant {
+                        fields: gamma.expand_fields_type_aliases(&enum_variant.fields),
+                        ..enum_variant
+                    }, false);
 
-                    let required_type = enum_delta.get_type_of_member(&field.member);
+                    let required_type = enum_delta.get_type_of_member(&field.member)?;
                     let new_expr = transform_expr(
                         &field.expr,
                         transform_type,
                         gamma,
                         &delta,
                         EType::DeltaType(required_type.clone()),
-                    );
+                    )?;
 
                     // Check type of expr matches required type
                     // let new_expr_type = delta.get_type_of_expr(&new_expr, gamma).unwrap();
 
-                    FieldValue {
+                    Ok(FieldValue {
                         expr: new_expr,
                         // expr: transform_expr_type(
                         //     &new_expr,
@@ -1233,8 +2408,8 @@ fn transform_expr_inner(
                         //     &gamma,
                         // ),
                         ..field.clone()
-                    }
-                })),
+                    })
+                }).collect::<RfoodResult<_>>()?,
                 ..expr_struct.clone()
             });
             // if let EType::DeltaType(dt) = return_type {
@@ -1245,43 +2420,38 @@ fn transform_expr_inner(
             //         &gamma,
             //     );
             // }
-            return struct_;
+            return Ok(struct_);
         }
         (TransformType::FPToOOP, Expr::Struct(expr_struct))
             if gamma.get_generator_trait(&expr_struct.path.segments.last().unwrap().ident)
-                .is_some() =>
+                .is_some() && gamma.is_path_to_item(&delta.module_path, &expr_struct.path) =>
         {
             let struct_ = Expr::Struct(ExprStruct {
-                path: Path {
-                    leading_colon: None,
-                    segments: Punctuated::from_iter(vec![expr_struct
-                        .path
-                        .segments
-                        .last()
-                        .unwrap()
-                        .clone()]),
-                },
-                fields: Punctuated::from_iter(expr_struct.fields.iter().map(|field| {
+                path: transform_variant_path_to_struct_path(&expr_struct.path, gamma, &delta),
+                fields: expr_struct.fields.iter().map(|field| {
                     // Get the enum
                     let struct_ident = get_path_call_name(&expr_struct.path);
-                    let struct_ = gamma.get_struct_by_name(&struct_ident);
+                    let struct_ = gamma.get_struct_by_name(&struct_ident)?;
                     let mut struct_delta = Delta::new();
                     // TODO check this...
-                    struct_delta.collect_for_struct(&struct_, RefType::None);
+                    struct_delta.collect_for_struct(&ItemStruct {
+                        fields: gamma.expand_fields_type_aliases(&struct_.fields),
+                        ..struct_
+                    }, RefType::None);
 
-                    let required_type = struct_delta.get_type_of_member(&field.member);
+                    let required_type = struct_delta.get_type_of_member(&field.member)?;
                     let new_expr = transform_expr(
                         &field.expr,
                         transform_type,
                         gamma,
                         &delta,
                         EType::DeltaType(required_type.clone()),
-                    );
+                    )?;
 
                     // Check type of expr matches required type
                     // let new_expr_type = delta.get_type_of_expr(&new_expr, gamma).unwrap();
 
-                    FieldValue {
+                    Ok(FieldValue {
                         // TODO Move this transform type into transform expr
                         expr: new_expr,
                         // expr: transform_expr_type(
@@ -1291,8 +2461,8 @@ fn transform_expr_inner(
                         //     &gamma,
                         // ),
                         ..field.clone()
-                    }
-                })),
+                    })
+                }).collect::<RfoodResult<_>>()?,
                 ..expr_struct.clone()
             });
             // if let EType::DeltaType(dt) = return_type {
@@ -1303,29 +2473,63 @@ fn transform_expr_inner(
             //         &gamma,
             //     );
             // }
-            return struct_;
+            return Ok(struct_);
         }
-        (_, Expr::Block(expr_block)) => Expr::Block(ExprBlock {
+        (_, Expr::Struct(expr_struct)) => {
+            // Any other struct keeps its path, the fields are typed by the struct if it is known
+            let struct_delta = gamma.get_struct_by_name(&get_path_call_name(&expr_struct.path)).ok().map(|struct_| {
+                let mut struct_delta = Delta::new();
+                struct_delta.collect_for_struct(&ItemStruct {
+                    fields: gamma.expand_fields_type_aliases(&struct_.fields),
+                    ..struct_
+                }, RefType::None);
+                struct_delta
+            });
+            Ok(Expr::Struct(ExprStruct {
+                fields: expr_struct.fields.iter().map(|field| {
+                    let required_type = match struct_delta.as_ref().map(|struct_delta| struct_delta.clone().get_type_of_member(&field.member)) {
+                        Some(Ok(required_type)) => EType::DeltaType(required_type),
+                        _ => EType::Any,
+                    };
+                    Ok(FieldValue {
+                        expr: transform_expr(&field.expr, transform_type, gamma, &delta, required_type)?,
+                        ..field.clone()
+                    })
+                }).collect::<RfoodResult<_>>()?,
+                ..expr_struct.clone()
+            }))
+        },
+        (_, Expr::Block(expr_block)) => Ok(Expr::Block(ExprBlock {
             block: transform_block(
                 &expr_block.block,
                 transform_type,
                 gamma,
                 &delta,
                 return_type.clone(),
-            ),
+            )?,
             ..expr_block.clone()
-        }),
+        })),
         (_, Expr::Match(expr_match)) => {
-            println!("Transforming expr match");
-            println!("Transforming pat");
+            trace!("Transforming expr match");
+            // The datatypes and fields (which are borrowed from self) are matched by reference, any
+            // other value (e.g. an `Option` variable) is matched as it is
+            let matched_type = match (&*expr_match.expr, delta.get_type_of_expr(&expr_match.expr, gamma)) {
+                (expr, Ok(delta_type)) if !matches!(expr, Expr::Field(_))
+                    && !gamma.is_enum(&delta_type.name)
+                    && !gamma.is_trait(&delta_type.name) =>
+                {
+                    EType::DeltaType(delta_type)
+                }
+                _ => EType::RefType(RefType::Ref(Box::new(RefType::None))),
+            };
             let e1 = Box::new(transform_expr(
                     &*expr_match.expr,
                     transform_type,
                     gamma,
                     &delta,
-                    EType::RefType(RefType::Ref(Box::new(RefType::None))),
-                ));
-            println!("Transformed pat, e1: {:?}", e1);
+                    matched_type,
+                )?);
+            trace!(expr = ?e1, "Transformed match expression");
             let e = Expr::Match(ExprMatch {
                 // Transform the match epxr,
                 // expr: Box::new(transform_expr(
@@ -1335,7 +2539,7 @@ fn transform_expr_inner(
                 //     &delta,
                 //     EType::Any,
                 // )),
-                expr: e1,
+                expr: e1.clone(),
                 // Transform the body of the match with the context of the struct (all borrows)
                 arms: expr_match
                     .arms
@@ -1345,49 +2549,52 @@ fn transform_expr_inner(
                         // happen for enums)
                         // Then each value collected is a borrow
                         // TODO
-                        delta.collect_for_arm(&arm, &gamma);
-                        Arm {
+                        delta.collect_for_arm(&arm, &e1, &gamma)?;
+                        Ok(Arm {
                             body: Box::new(transform_expr(
                                 &arm.body,
                                 transform_type,
                                 gamma,
                                 &delta,
                                 return_type.clone(),
-                            )),
+                            )?),
                             ..arm.clone()
-                        }
+                        })
                     })
-                    .collect(),
+                    .collect::<RfoodResult<_>>()?,
                 ..expr_match.clone()
             });
-            println!("Done Transforming expr match");
-            e
+            trace!("Transformed expr match");
+            Ok(e)
         }
         (_, Expr::Macro(expr_macro)) => {
             // Try and parse the macros parameters into expressions
-            let parser = Punctuated::<Expr, Token![,]>::parse_separated_nonempty;
-            let params = parser.parse2(expr_macro.mac.tokens.clone());
-
-            if params.is_err() {
-                panic!("Could not parse macro params, only macros with expr params are supported");
-            }
-
-            let params = params.unwrap();
-            // Transform the parameter expressions
-            let params: Punctuated<Expr, Token![,]> = params
-                .iter()
-                .map(|param| {
-                    transform_expr(param, transform_type, gamma, &delta, return_type.clone())
-                })
-                .collect();
+            let parser = Punctuated::<Expr, Token![,]>::parse_terminated;
+            let params = parser.parse2(expr_macro.mac.tokens.clone()).map_err(|_| {
+                RfoodError::unsupported_syntax("Only macros with expression parameters are supported")
+                    .with_span(&expr_macro.mac)
+            })?;
+
+            // Transform the parameter expressions, the items of a `vec!` are typed by the vec and
+            // the parameters of any other macro keep their own types
+            let params: Punctuated<Expr, Token![,]> = if expr_macro.mac.path.is_ident("vec") {
+                transform_items(&params, transform_type, gamma, delta, &return_type)?
+            } else {
+                params
+                    .iter()
+                    .map(|param| {
+                        transform_expr(param, transform_type, gamma, &delta, get_own_type(param, gamma, delta))
+                    })
+                    .collect::<RfoodResult<_>>()?
+            };
 
-            Expr::Macro(ExprMacro {
+            Ok(Expr::Macro(ExprMacro {
                 mac: Macro {
                     tokens: quote!(#params),
                     ..expr_macro.mac.clone()
                 },
                 ..expr_macro.clone()
-            })
+            }))
         }
         (_, Expr::Binary(expr_binary)) => {
             let new_left_expr = transform_expr(
@@ -1396,10 +2603,25 @@ fn transform_expr_inner(
                 gamma,
                 &delta,
                 EType::Any,
-            );
-            let new_left_expr_type = delta.get_type_of_expr(&new_left_expr, gamma).unwrap();
+            )?;
+            let new_left_expr_type = delta.get_type_of_expr(&new_left_expr, gamma)?;
+            // Arithmetic on numbers is implemented for any mix of values and references, e.g.
+            // `count + 1` of a `count: &u64`, only comparisons need the same references. Operands
+            // of different types are also left as they are, e.g. `String + &str`, and anything
+            // added to a string stays borrowed, e.g. `String + &String`
+            let right_required_type = match delta.get_type_of_expr(&expr_binary.right, gamma) {
+                _ if new_left_expr_type.is_number() && !is_comparison(&expr_binary.op) => EType::Any,
+                Ok(right_type) if new_left_expr_type.name == "String" && !is_comparison(&expr_binary.op) => {
+                    EType::DeltaType(match right_type.ref_type.is_ref() {
+                        true => right_type,
+                        false => right_type.with_ref_type(RefType::Ref(Box::new(right_type.ref_type.clone()))),
+                    })
+                }
+                Ok(right_type) if right_type.name != new_left_expr_type.name => EType::Any,
+                _ => EType::DeltaType(new_left_expr_type),
+            };
 
-            Expr::Binary(ExprBinary{
+            Ok(Expr::Binary(ExprBinary{
                 left: Box::new(new_left_expr),
                 right: Box::new(
                     transform_expr(
@@ -1407,25 +2629,44 @@ fn transform_expr_inner(
                         transform_type,
                         gamma,
                         &delta,
-                        EType::DeltaType(new_left_expr_type.clone()),
-                    )
+                        right_required_type,
+                    )?
                 ),
                 ..expr_binary.clone()
-            })
+            }))
         },
         (_, Expr::Reference(expr_ref)) => {
             if let EType::RefType(ref_type) | EType::DeltaType(DeltaType{ref_type, ..}) = &return_type {
-                if let RefType::Ref(inner_ref_type) = ref_type {
-                    Expr::Reference(ExprReference{
+                // An expression which is already borrowed is not borrowed again, e.g. `&shape` of
+                // a `shape: &Shape` is just `shape`
+                let is_borrowed = matches!(
+                    delta.get_type_of_expr(&expr_ref.expr, gamma),
+                    Ok(current_type) if current_type.ref_type.is_ref() && current_type.ref_type.inner() == ref_type.inner()
+                );
+                if is_borrowed && ref_type.is_ref() {
+                    transform_expr(
+                        &expr_ref.expr,
+                        transform_type,
+                        gamma,
+                        &delta,
+                        return_type,
+                    )
+                } else if let RefType::Ref(inner_ref_type) | RefType::MutRef(inner_ref_type) = ref_type {
+                    // The borrowed value is required as the type inside the reference
+                    let inner_return_type = match &return_type {
+                        EType::DeltaType(delta_type) => EType::DeltaType(delta_type.with_ref_type(*inner_ref_type.clone())),
+                        _ => EType::RefType(*inner_ref_type.clone()),
+                    };
+                    Ok(Expr::Reference(ExprReference{
                         expr: Box::new(transform_expr(
                             &expr_ref.expr,
                             transform_type,
                             gamma,
                             &delta,
-                            EType::RefType(*inner_ref_type.clone())
-                        )),
+                            inner_return_type,
+                        )?),
                         ..expr_ref.clone()
-                    })
+                    }))
                 } else {
                     transform_expr(
                         &expr_ref.expr,
@@ -1436,13 +2677,16 @@ fn transform_expr_inner(
                     )
                 }
             } else {
-                *expr_ref.expr.clone()
+                Ok(Expr::Reference(ExprReference {
+                    expr: Box::new(transform_expr(&expr_ref.expr, transform_type, gamma, &delta, return_type)?),
+                    ..expr_ref.clone()
+                }))
             }
         }
         (_, Expr::If(expr_if)) => {
-            Expr::If(ExprIf{
+            Ok(Expr::If(ExprIf{
                 cond: Box::new(
-                    transform_expr(&*expr_if.cond, transform_type, gamma, &delta, EType::DeltaType(DeltaType::new("bool", RefType::None)))
+                    transform_expr(&*expr_if.cond, transform_type, gamma, &delta, EType::DeltaType(DeltaType::new("bool", RefType::None)))?
                 ),
                 then_branch: transform_block(
                     &expr_if.then_branch,
@@ -1450,7 +2694,7 @@ fn transform_expr_inner(
                     gamma,
                     &delta,
                     return_type.clone(),
-                ),
+                )?,
                 else_branch: if let Some((else_token, box else_branch)) = expr_if.else_branch.clone() {
                     Some((
                         else_token,
@@ -1460,54 +2704,285 @@ fn transform_expr_inner(
                             gamma,
                             &delta,
                             return_type.clone(),
-                        ))
+                        )?)
                     ))
                 } else {
                     None
                 },
                 ..expr_if.clone()
-            })
+            }))
         },
         (_, Expr::Paren(expr_paren)) => {
-            Expr::Paren(ExprParen{
+            Ok(Expr::Paren(ExprParen{
                 expr: Box::new(transform_expr(
                     &expr_paren.expr,
                     transform_type,
                     gamma,
                     &delta,
                     return_type.clone(),
-                )),
+                )?),
                 ..expr_paren.clone()
-            })
+            }))
+        },
+        (_, Expr::Assign(expr_assign)) => {
+            // The value must have the type of the place it is assigned to
+            let left_type = match delta.get_type_of_expr(&expr_assign.left, gamma) {
+                Ok(left_type) => EType::DeltaType(left_type),
+                Err(_) => EType::Any,
+            };
+            Ok(Expr::Assign(ExprAssign {
+                right: Box::new(transform_expr(
+                    &expr_assign.right,
+                    transform_type,
+                    gamma,
+                    &delta,
+                    left_type,
+                )?),
+                ..expr_assign.clone()
+            }))
+        },
+        (_, Expr::Tuple(expr_tuple)) => {
+            Ok(Expr::Tuple(ExprTuple {
+                elems: transform_items(&expr_tuple.elems, transform_type, gamma, delta, &return_type)?,
+                ..expr_tuple.clone()
+            }))
+        },
+        (_, Expr::Array(expr_array)) => {
+            Ok(Expr::Array(ExprArray {
+                elems: transform_items(&expr_array.elems, transform_type, gamma, delta, &return_type)?,
+                ..expr_array.clone()
+            }))
+        },
+        (_, Expr::Index(expr_index)) => {
+            Ok(Expr::Index(ExprIndex {
+                expr: Box::new(transform_expr(&expr_index.expr, transform_type, gamma, delta, get_own_type(&expr_index.expr, gamma, delta))?),
+                index: Box::new(transform_expr(&expr_index.index, transform_type, gamma, delta, get_own_type(&expr_index.index, gamma, delta))?),
+                ..expr_index.clone()
+            }))
+        },
+        (_, Expr::Closure(expr_closure)) => {
+            // The arguments of a closure are only typed by inference
+            let mut closure_delta = delta.clone();
+            for input in &expr_closure.inputs {
+                closure_delta.collect_for_inferred_pattern(input);
+            }
+            let body_type = get_own_type(&expr_closure.body, gamma, &closure_delta);
+            Ok(Expr::Closure(ExprClosure {
+                body: Box::new(transform_expr(&expr_closure.body, transform_type, gamma, &closure_delta, body_type)?),
+                ..expr_closure.clone()
+            }))
+        },
+        (_, Expr::ForLoop(expr_for_loop)) => {
+            let mut body_delta = delta.clone();
+            body_delta.collect_for_inferred_pattern(&expr_for_loop.pat);
+            Ok(Expr::ForLoop(ExprForLoop {
+                expr: Box::new(transform_expr(&expr_for_loop.expr, transform_type, gamma, delta, get_own_type(&expr_for_loop.expr, gamma, delta))?),
+                body: transform_block(&expr_for_loop.body, transform_type, gamma, &body_delta, EType::None)?,
+                ..expr_for_loop.clone()
+            }))
         },
+        // Copying an expression is only safe if nothing in it needs to be transformed
+        _ if contains_transformable_expr(expr) => Err(RfoodError::unsupported_syntax(
+            "Expressions of this kind are not transformed but contain calls, method calls or struct instantiations which may need to be",
+        ).with_span(expr)),
         _ => {
-            println!("Skipping unsupported {:?} with delta {:?}", expr, delta);
-            expr.clone()
+            trace!(expr = %quote!(#expr), "Skipping unsupported expression");
+            trace!(?delta);
+            Ok(expr.clone())
+        }
+    }
+}
+
+/// Check if a call is of a closure, i.e. the function called is a variable or an expression
+/// Align the type arguments of a call with the type parameters of the function called, the type
+/// parameters of a method come after any of the enum in its consumer, e.g. `::<f64>` of the method
+/// is `::<_, f64>` of the consumer
+fn align_type_args(mut type_args: Vec<GenericArgument>, signature: &Signature) -> Vec<GenericArgument> {
+    let count = signature.generics.type_params().count() + signature.generics.const_params().count();
+    if type_args.len() > count {
+        return type_args.split_off(type_args.len() - count);
+    }
+    while type_args.len() < count {
+        type_args.insert(0, parse_quote!(_));
+    }
+    type_args
+}
+
+fn is_closure_call(expr_call: &ExprCall, delta: &Delta) -> bool {
+    match &*expr_call.func {
+        Expr::Path(ExprPath { path, .. }) => path.segments.len() == 1 && delta.is_variable(&get_path_call_name(path)),
+        _ => true,
+    }
+}
+
+/// Get the type an expression has before it is transformed, this is required of an expression
+/// when nothing else is so that any reference it takes is kept
+fn get_own_type(expr: &Expr, gamma: &Gamma, delta: &Delta) -> EType {
+    match delta.get_type_of_expr(expr, gamma) {
+        Ok(delta_type) => EType::DeltaType(delta_type),
+        Err(_) => EType::Any,
+    }
+}
+
+/// Get the type of the value of a let statement, a variable may be typed by how it is used later
+/// (e.g. `let mut shapes = Vec::new();`)
+fn get_type_of_local(pat: &Pat, init: &Expr, gamma: &Gamma, delta: &Delta) -> Option<DeltaType> {
+    let binding_type = match pat {
+        Pat::Ident(PatIdent { ident, subpat: None, .. }) => delta.inferred.get_binding(ident, &delta.module_path),
+        _ => None,
+    };
+    binding_type.or_else(|| delta.get_type_of_expr(init, gamma).ok())
+}
+
+fn get_required_delta_type(required_type: &EType) -> Option<&DeltaType> {
+    match required_type {
+        EType::DeltaType(delta_type) => Some(delta_type),
+        _ => None,
+    }
+}
+
+/// Types inferred from the code being transformed use the representation of the datatypes before
+/// they were transformed, e.g. `vec![Shape::Circle { .. }]` is a `Vec<Shape>`. Get the type in
+/// the representation of the transformed code, `Vec<Box<dyn Shape>>` once `Shape` is a trait.
+/// Only the types nested in the type are changed, the value of a type itself is transformed (e.g.
+/// into a `Circle`)
+fn transform_inferred_type(delta_type: &DeltaType, transform_type: &TransformType, gamma: &Gamma) -> DeltaType {
+    let delta_type = delta_type.map_inner_types(&|inner| {
+        let inner = transform_inferred_type(inner, transform_type, gamma);
+        match transform_type {
+            TransformType::FPToOOP => get_trait_object_type(&inner, gamma),
+            TransformType::OOPToFP => get_enum_type(&inner, gamma),
+        }
+    });
+    match transform_type {
+        // The items of collections and slices are not boxed
+        TransformType::OOPToFP if is_collection(&delta_type.name) || matches!(delta_type.kind, TypeKind::Array(_, None)) => {
+            delta_type.map_inner_types(&|inner| match &inner.ref_type {
+                RefType::Box(box RefType::None) if gamma.is_enum(&inner.name) => inner.with_ref_type(RefType::None),
+                _ => inner.clone(),
+            })
         }
+        _ => delta_type,
+    }
+}
+
+/// Get the type of a variable once it is transformed, a mutable variable holding a value of an enum
+/// may be assigned another variant so from FP to OOP it holds a trait object, e.g.
+/// `let mut light = Light::Dimmer(10)` becomes `let mut light: Box<dyn Light> = Box::new(Dimmer(10))`
+fn get_type_of_transformed_local(pat: &Pat, init_type: &DeltaType, transform_type: &TransformType, gamma: &Gamma) -> DeltaType {
+    match (transform_type, pat) {
+        (TransformType::FPToOOP, Pat::Ident(PatIdent { mutability: Some(_), .. })) if init_type.ref_type == RefType::None => {
+            get_trait_object_type(&transform_inferred_type(init_type, transform_type, gamma), gamma)
+        }
+        _ => transform_inferred_type(init_type, transform_type, gamma),
+    }
+}
+
+/// Get the type a type written in the code being transformed has in the representation of the
+/// transformed code, e.g. the `Vec<Box<dyn Shape>>` in `let shapes: Vec<Box<dyn Shape>> = ..`
+/// is a `Vec<Shape>` once `Shape` is an enum
+fn transform_written_type(delta_type: &DeltaType, transform_type: &TransformType, gamma: &Gamma) -> DeltaType {
+    let delta_type = transform_inferred_type(delta_type, transform_type, gamma);
+    match transform_type {
+        TransformType::FPToOOP => get_trait_object_type(&delta_type, gamma),
+        TransformType::OOPToFP => get_enum_type(&delta_type, gamma),
     }
 }
 
+/// A trait object of a transformed trait is its enum, e.g. `dyn Shape` is `Shape`
+fn get_enum_type(delta_type: &DeltaType, gamma: &Gamma) -> DeltaType {
+    match &delta_type.kind {
+        TypeKind::TraitObject(modules, args) if gamma.is_enum(&delta_type.name) => DeltaType {
+            kind: TypeKind::Path(modules.clone(), args.clone()),
+            ..delta_type.clone()
+        },
+        _ => delta_type.clone(),
+    }
+}
+
+/// A trait can only be used as a trait object behind a pointer, a trait which is not behind a
+/// pointer is put in the pointer the trait objects are held in, a box by default
+fn get_trait_object_type(delta_type: &DeltaType, gamma: &Gamma) -> DeltaType {
+    match &delta_type.kind {
+        TypeKind::Path(modules, args) | TypeKind::TraitObject(modules, args) if gamma.is_trait(&delta_type.name) => DeltaType {
+            kind: TypeKind::TraitObject(modules.clone(), args.clone()),
+            ref_type: match &delta_type.ref_type {
+                RefType::None => gamma.pointer.wrap(RefType::None),
+                ref_type => ref_type.clone(),
+            },
+            ..delta_type.clone()
+        },
+        _ => delta_type.clone(),
+    }
+}
+
+/// Transform the items of a tuple, array or `vec!`, each item is required to have the type the
+/// required type has for it or its own type if it has none
+fn transform_items(
+    items: &Punctuated<Expr, Token![,]>,
+    transform_type: &TransformType,
+    gamma: &Gamma,
+    delta: &Delta,
+    required_type: &EType,
+) -> RfoodResult<Punctuated<Expr, Token![,]>> {
+    // The items are the same if the value is required behind a pointer, e.g. `&vec![..]`
+    let item_types: Vec<Option<DeltaType>> = match required_type {
+        EType::DeltaType(delta_type) => match &delta_type.kind {
+            TypeKind::Tuple(elems) if elems.len() == items.len() => elems.iter().cloned().map(Some).collect(),
+            TypeKind::Array(elem, _) => vec![Some(*elem.clone()); items.len()],
+            TypeKind::Path(_, args) if delta_type.name == "Vec" && args.len() == 1 => vec![Some(args[0].clone()); items.len()],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    transform_args_of_expected_types(items, item_types, transform_type, gamma, delta)
+}
+
+/// Transform the arguments of a call, each argument is required to have its expected type or its
+/// own type if nothing is expected of it
+fn transform_args_of_expected_types(
+    args: &Punctuated<Expr, Token![,]>,
+    arg_types: Vec<Option<DeltaType>>,
+    transform_type: &TransformType,
+    gamma: &Gamma,
+    delta: &Delta,
+) -> RfoodResult<Punctuated<Expr, Token![,]>> {
+    args.iter().enumerate().map(|(index, arg)| {
+        let required_type = match arg_types.get(index) {
+            Some(Some(arg_type)) if !arg_type.is_unknown() => EType::DeltaType(arg_type.clone()),
+            _ => get_own_type(arg, gamma, delta),
+        };
+        transform_expr(arg, transform_type, gamma, delta, required_type)
+    }).collect()
+}
+
 fn transform_expr(
     expr: &Expr,
     transform_type: &TransformType,
     gamma: &Gamma,
     delta: &Delta,
     return_type: EType,
-) -> Expr {
+) -> RfoodResult<Expr> {
     // Clone the delta at this stage
     let mut delta = delta.clone();
 
-    // Transform the expr
-    let expr = clean_type(&transform_expr_inner(expr, transform_type, gamma, &mut delta, return_type.clone()));
+    // Transform the expr, the boxes and references are added back for the required type. An
+    // expression which can have any type keeps its own
+    let expr = transform_expr_inner(expr, transform_type, gamma, &mut delta, return_type.clone())?;
+    let expr = match return_type {
+        EType::Any => expr,
+        _ => clean_type(&expr),
+    };
                 
-    // Transform the expression type
+    // Transform the expression type, expressions which cannot be typed are left as they are
     let expr_type = delta.get_type_of_expr(&expr, gamma);
     match expr_type {
         Ok(et) => {
-            println!("Expr {:?}", expr);
+            trace!(?expr, "Transforming type of expression");
             transform_expr_type(&expr, &et, &return_type, gamma)
         },
-        _ => expr
+        Err(RfoodError { code: ErrorCode::TypeInferenceFailed, .. }) => Ok(expr),
+        Err(error) => Err(error),
     }
     // expr.clone()
 }
@@ -1518,50 +2993,99 @@ fn transform_statement(
     gamma: &Gamma,
     delta: &mut Delta,
     return_type: EType,
-) -> Stmt {
+) -> RfoodResult<Stmt> {
     match statement {
         Stmt::Local(local) => {
-            let init_unwrap = local.init.as_ref().unwrap();
+            let (eq, init) = local.init.as_ref().ok_or_else(|| {
+                RfoodError::unsupported_syntax("A let statement must have a value").with_span(local)
+            })?;
+
+            // Types containing the transformed datatypes are written in their new representation,
+            // e.g. `let shapes: Vec<Shape> = ..` becomes `let shapes: Vec<Box<dyn Shape>> = ..`.
+            // A value which is not written with a type gets one if its type changes
+            let (pat, required_type) = match &local.pat {
+                // A type written with aliases is already in its new representation as the aliases
+                // were transformed with the other items
+                Pat::Type(pat_type) if gamma.expand_type_aliases(&pat_type.ty) != *pat_type.ty => {
+                    (local.pat.clone(), EType::DeltaType(gamma.expand_type_aliases(&pat_type.ty).get_delta_type()))
+                }
+                Pat::Type(pat_type) => {
+                    let written_type = pat_type.ty.get_delta_type();
+                    let transformed_type = transform_written_type(&written_type, transform_type, gamma);
+                    let pat = if transformed_type != written_type {
+                        Pat::Type(PatType { ty: Box::new(transformed_type.to_type()), ..pat_type.clone() })
+                    } else {
+                        local.pat.clone()
+                    };
+                    (pat, EType::DeltaType(transformed_type))
+                }
+                pat => match get_type_of_local(pat, init, gamma, delta) {
+                    Some(init_type) if !get_type_of_transformed_local(pat, &init_type, transform_type, gamma).is_equaivalent(&init_type, gamma) => {
+                        let transformed_type = get_type_of_transformed_local(pat, &init_type, transform_type, gamma);
+                        let pat = Pat::Type(PatType {
+                            attrs: Vec::new(),
+                            pat: Box::new(pat.clone()),
+                            colon_token: Default::default(),
+                            ty: Box::new(transformed_type.to_type()),
+                        });
+                        (pat, EType::DeltaType(transformed_type))
+                    }
+                    _ => (pat.clone(), EType::Any),
+                },
+            };
+
             let trans_local = Local {
+                pat,
                 init: Some((
-                    init_unwrap.0,
+                    *eq,
                     Box::new(transform_expr(
-                        &init_unwrap.1,
+                        &init,
                         transform_type,
                         &gamma,
                         delta,
-                        EType::Any,
-                    )),
+                        required_type,
+                    )?),
                 )),
                 ..local.clone()
             };
-            delta.collect_for_local(&trans_local, gamma);
-            Stmt::Local(trans_local)
+            delta.collect_for_local(&trans_local, gamma)?;
+            Ok(Stmt::Local(trans_local))
         },
         Stmt::Semi(expr, semi) => {
-            Stmt::Semi(
-                transform_expr(&expr, transform_type, gamma, delta, return_type),
+            Ok(Stmt::Semi(
+                transform_expr(&expr, transform_type, gamma, delta, return_type)?,
                 *semi,
-            )
+            ))
         }
-        Stmt::Expr(expr) => Stmt::Expr(transform_expr(
+        Stmt::Expr(expr) => Ok(Stmt::Expr(transform_expr(
             &expr,
             transform_type,
             gamma,
             delta,
             return_type,
-        )),
-        _ => panic!("Unsupported statement {:?}", statement),
+        )?)),
+        Stmt::Item(item) => Err(RfoodError::unsupported_syntax("Items inside functions are not supported").with_span(item)),
     }
+    .map_err(|error| error.with_span(statement))
 }
 
 pub fn collect_constants(
     item: &syn::Item,
+    gamma: &Gamma,
     delta: &mut Delta,
 ) {
     match item {
         Item::Const(item_cost) => {
-            delta.collect_for_const(&item_cost);
+            delta.collect_for_const(&ItemConst {
+                ty: Box::new(gamma.expand_type_aliases(&item_cost.ty)),
+                ..item_cost.clone()
+            });
+        },
+        Item::Static(item_static) => {
+            delta.collect_for_static(&ItemStatic {
+                ty: Box::new(gamma.expand_type_aliases(&item_static.ty)),
+                ..item_static.clone()
+            });
         },
         _ => ()
     }
@@ -1572,23 +3096,44 @@ pub fn transform_item(
     transform_type: &TransformType,
     gamma: &Gamma,
     delta: &mut Delta,
-) -> syn::Item {
+) -> RfoodResult<syn::Item> {
     match item {
-        Item::Fn(item_fn) => Item::Fn(transform_function(item_fn, transform_type, gamma, delta)),
+        Item::Fn(item_fn) => Ok(Item::Fn(transform_function(item_fn, transform_type, gamma, delta)?)),
         Item::Impl(item_impl) => {
             let for_type = item_impl.self_ty.get_delta_type().name;
-            Item::Impl(ItemImpl {
+            Ok(Item::Impl(ItemImpl {
                 items: item_impl
                     .items
                     .iter()
                     .map(|item| transform_impl_item(item, &for_type, transform_type, gamma, delta))
-                    .collect(),
+                    .collect::<RfoodResult<_>>()?,
                 ..item_impl.clone()
-            })
+            }))
         },
+        // The value of a constant is built with its (already transformed) type
+        Item::Const(item_const) => Ok(Item::Const(ItemConst {
+            expr: Box::new(transform_expr(
+                &item_const.expr,
+                transform_type,
+                gamma,
+                delta,
+                EType::DeltaType(gamma.expand_type_aliases(&item_const.ty).get_delta_type()),
+            )?),
+            ..item_const.clone()
+        })),
+        Item::Static(item_static) => Ok(Item::Static(ItemStatic {
+            expr: Box::new(transform_expr(
+                &item_static.expr,
+                transform_type,
+                gamma,
+                delta,
+                EType::DeltaType(gamma.expand_type_aliases(&item_static.ty).get_delta_type()),
+            )?),
+            ..item_static.clone()
+        })),
         _ => {
-            println!("Skipping transforming of item, {:?}", item);
-            item.clone()
+            trace!(?item, "Skipping transforming of item");
+            Ok(item.clone())
         },
     }
 }
@@ -1599,59 +3144,62 @@ pub fn transform_impl_item(
     transform_type: &TransformType,
     gamma: &Gamma,
     delta: &Delta,
-) -> syn::ImplItem {
+) -> RfoodResult<syn::ImplItem> {
     let mut delta = delta.clone(); 
     match impl_item {
         ImplItem::Method(impl_item_method) => {
-            let return_type = impl_item_method
-                .sig
-                .output
-                .get_delta_type(Some(impl_for_type.clone()));
+            // Types are reasoned about with their aliases expanded
+            let sig = gamma.expand_signature_type_aliases(&impl_item_method.sig);
+            let return_type = sig.output.get_delta_type(Some(impl_for_type.clone()))?;
             let block_return_type = match return_type {
                 Some(rt) => EType::DeltaType(rt),
                 None => EType::None,
             };
 
-            ImplItem::Method(ImplItemMethod {
+            Ok(ImplItem::Method(ImplItemMethod {
                 block: {
-                    delta.collect_for_sig(&impl_item_method.sig, Some(impl_for_type));
+                    delta.collect_for_sig(&sig, Some(impl_for_type))?;
+                    delta.collect_for_body(&sig, &impl_item_method.block, Some(impl_for_type), gamma);
                     transform_block(
                         &impl_item_method.block,
                         transform_type,
                         gamma,
                         &delta,
                         block_return_type,
-                    )
+                    )?
                 },
                 ..impl_item_method.clone()
-            })
+            }))
         }
         _ => {
             // println!("Skipping unsupported {:?}", impl_item);
-            impl_item.clone()
+            Ok(impl_item.clone())
         }
     }
 }
 
 /// Transform all the statements in a fuction
-fn transform_function(func: &ItemFn, transform_type: &TransformType, gamma: &Gamma, delta: &Delta) -> syn::ItemFn {
+fn transform_function(func: &ItemFn, transform_type: &TransformType, gamma: &Gamma, delta: &Delta) -> RfoodResult<syn::ItemFn> {
     let mut delta = delta.clone();
-    delta.collect_for_sig(&func.sig, None);
+    // Types are reasoned about with their aliases expanded
+    let sig = gamma.expand_signature_type_aliases(&func.sig);
+    delta.collect_for_sig(&sig, None)?;
+    delta.collect_for_body(&sig, &func.block, None, gamma);
 
-    let return_type = func.sig.output.get_delta_type(None);
+    let return_type = sig.output.get_delta_type(None)?;
     let block_return_type = match return_type {
         Some(rt) => EType::DeltaType(rt),
         None => EType::None,
     };
 
-    ItemFn {
+    Ok(ItemFn {
         block: Box::new(transform_block(
             &func.block,
             transform_type,
             gamma,
             &delta,
             block_return_type,
-        )),
+        )?),
         ..func.clone()
-    }
+    })
 }
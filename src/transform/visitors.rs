@@ -1,13 +1,20 @@
+use std::collections::{HashMap, HashSet};
+
 use syn::*;
+use syn::visit::{visit_expr, visit_item_impl, visit_item_trait, visit_path, visit_type_trait_object, Visit};
 use syn::visit_mut::*;
+use syn::parse::Parser;
 use syn::punctuated::Punctuated;
 use syn::__private::Span;
+use quote::{format_ident, quote};
 
 use crate::context;
 use crate::ast;
 use crate::utils::utils::PopFirst;
-use context::delta::{Delta, get_ident_from_path, new_box_call_expr, GetDeltaType, get_function_call_name, clean_type};
-use context::gamma::Gamma;
+use context::delta::{Delta, get_ident_from_path, get_member_ident, new_box_call_expr, GetDeltaType, get_function_call_name, clean_type};
+use context::gamma::{Gamma, get_relative_module_path, has_self_receiver};
+use crate::transform::selection::Selection;
+use crate::transform::transformer::transform_type_to_name;
 use ast::create::*;
 
 /// Expr is self
@@ -29,7 +36,7 @@ impl VisitMut for ReplaceFieldCalls {
     fn visit_expr_mut(&mut self, expr: &mut Expr) {
         visit_expr_mut(self, expr);
         if let syn::Expr::Field(syn::ExprField{
-            member: syn::Member::Named(ident),
+            member,
             base,
             ..
         }) = expr.clone() {
@@ -42,12 +49,16 @@ impl VisitMut for ReplaceFieldCalls {
                 return;
             }
             
-            *expr = create_expr_from_ident(&ident);
+            *expr = create_expr_from_ident(&get_member_ident(&member));
             // if !self.self_mut_fields.contains(&ident) {
             //     *expr = create_dereference_of_expr(expr);
             // }
         }
     }
+
+    fn visit_macro_mut(&mut self, i: &mut Macro) {
+        visit_macro_params_mut(self, i);
+    }
 }
 
 pub struct ReplaceSelf {
@@ -60,6 +71,35 @@ impl VisitMut for ReplaceSelf {
             *i = create_expr_path_to_ident(&self.enum_name);
         }
     }
+
+    fn visit_macro_mut(&mut self, i: &mut Macro) {
+        visit_macro_params_mut(self, i);
+    }
+}
+
+/// Visit the parameters of a macro which are expressions, e.g. `self.side` in
+/// `format!("{}", self.side)`, the tokens of any other macro are left as they are
+fn visit_macro_params_mut<V: VisitMut>(visitor: &mut V, mac: &mut Macro) {
+    let parser = Punctuated::<Expr, Token![,]>::parse_terminated;
+    if let Ok(mut params) = parser.parse2(mac.tokens.clone()) {
+        params.iter_mut().for_each(|param| visitor.visit_expr_mut(param));
+        mac.tokens = quote!(#params);
+    }
+}
+
+/// Replace the Self type (and paths starting with Self) with a concrete type
+pub struct ReplaceSelfType {
+    pub self_type: Ident,
+}
+impl VisitMut for ReplaceSelfType {
+    fn visit_path_mut(&mut self, i: &mut Path) {
+        visit_path_mut(self, i);
+        if let Some(segment) = i.segments.first_mut() {
+            if segment.ident == "Self" {
+                segment.ident = self.self_type.clone();
+            }
+        }
+    }
 }
 
 pub struct ReplaceDynBoxDestructorReturnStatements;
@@ -87,7 +127,8 @@ impl VisitMut for ReplaceDynBoxDestructorReturnStatements {
 /// When transforming from a consumer to a destructor, we need to add self infront of any literals
 /// that come from the enum.
 pub struct TransformConsumer {
-    pub trait_attributes: Vec<Ident>,
+    /// The variables bound from the enum variant and the field of the struct each is now stored in
+    pub trait_attributes: Vec<(Ident, Member)>,
     pub self_arg_name: Ident,
     pub gamma: Gamma,
 }
@@ -99,8 +140,8 @@ impl VisitMut for TransformConsumer {
                 let var_name = &expr_path.path.segments.first().unwrap().ident.clone();
                 // If the path is one of the values in the trait then we need to add self infront
                 // of it.
-                if self.trait_attributes.contains(&var_name) {
-                    *i = create_self_field_call(var_name)
+                if let Some((_, member)) = self.trait_attributes.iter().find(|(ident, _)| ident == var_name) {
+                    *i = create_self_member_call(member)
                 // Otherwise if the var is the self arg then we need to replace it self
                 } 
                 else if var_name == &self.self_arg_name {
@@ -111,12 +152,14 @@ impl VisitMut for TransformConsumer {
                 }
             },
             Expr::Call(expr_call) => {
-                let fn_name = get_function_call_name(&expr_call);
                 // If the method is not a consumer method then no transformation is needed
-                if !self.gamma.is_consumer(&fn_name) {
-                    visit_expr_mut(self, i);
-                    return;
-                }
+                let fn_name = match get_function_call_name(expr_call) {
+                    Some(fn_name) if self.gamma.is_consumer(&fn_name) => fn_name,
+                    _ => {
+                        visit_expr_mut(self, i);
+                        return;
+                    }
+                };
 
                 // Get the args, removing the first arg as it is self TODO it could not be
                 let mut args = expr_call.args.clone();
@@ -147,7 +190,7 @@ impl VisitMut for TransformGenerators {
         if let Expr::Struct(expr_struct) = i {
             let expr_ident: Ident = get_ident_from_path(&expr_struct.path);
             // If the struct is a generator of the trait
-            if self.gamma.get_generators(&self.trait_.ident).iter().any(|(struct_, _)| struct_.ident == expr_ident) {
+            if self.gamma.get_generators(&self.trait_.ident).unwrap_or_default().iter().any(|(struct_, _)| struct_.ident == expr_ident) {
                 // Add path to the enum
                 let enum_path = create_path_for_enum(&self.trait_.ident, &expr_ident);
 
@@ -181,3 +224,1890 @@ impl VisitMut for ReplaceSelfFieldAssignments {
     fn visit_expr_assign_op_mut(&mut self, i: &mut ExprAssignOp) {
     }
 }
+
+/// Remove items from a file and all of its inline modules
+pub struct RemoveItems {
+    pub items: Vec<Item>,
+}
+impl VisitMut for RemoveItems {
+    fn visit_file_mut(&mut self, i: &mut File) {
+        i.items.retain(|item| !self.items.contains(item));
+        visit_file_mut(self, i);
+    }
+
+    fn visit_item_mod_mut(&mut self, i: &mut ItemMod) {
+        if let Some((_, items)) = &mut i.content {
+            items.retain(|item| !self.items.contains(item));
+        }
+        visit_item_mod_mut(self, i);
+    }
+}
+
+/// Replace the trait objects and `impl Trait` types of traits which are now enums with the enum,
+/// e.g. `dyn Shape` and `impl Shape` become `Shape`
+pub struct ReplaceDatatypeTraitObjects<'a> {
+    pub gamma: &'a Gamma,
+}
+impl VisitMut for ReplaceDatatypeTraitObjects<'_> {
+    fn visit_type_mut(&mut self, i: &mut Type) {
+        visit_type_mut(self, i);
+        if let Type::TraitObject(TypeTraitObject { bounds, .. }) | Type::ImplTrait(TypeImplTrait { bounds, .. }) = i {
+            if let Some(TypeParamBound::Trait(TraitBound { path, .. })) = bounds.first() {
+                if self.gamma.is_enum(&get_ident_from_path(path)) {
+                    *i = Type::Path(TypePath { qself: None, path: path.clone() });
+                }
+            }
+        }
+
+        // The items of collections and slices are already stored behind a pointer so the enums in
+        // them do not need a box, e.g. `Vec<Box<Shape>>` becomes `Vec<Shape>`
+        let items: Vec<&mut Type> = match i {
+            Type::Slice(TypeSlice { elem, .. }) => vec![&mut **elem],
+            Type::Path(TypePath { qself: None, path }) if is_collection(&path.segments.last().unwrap().ident) => get_generic_type_args_mut(path),
+            _ => Vec::new(),
+        };
+        for item in items {
+            if let Some(inner) = get_box_inner_type_mut(item) {
+                if get_enum_type_path(inner, self.gamma).is_some() {
+                    *item = inner.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Get the type parameters bounded by a trait which is now an enum, along with the enum each one is
+/// replaced with
+fn get_datatype_type_params(generics: &Generics, gamma: &Gamma) -> HashMap<Ident, Type> {
+    let get_datatype_bound = |bounds: &Punctuated<TypeParamBound, Token![+]>| bounds.iter().find_map(|bound| match bound {
+        TypeParamBound::Trait(TraitBound { path, .. }) if gamma.is_enum(&get_ident_from_path(path)) => {
+            Some(Type::Path(TypePath { qself: None, path: path.clone() }))
+        }
+        _ => None,
+    });
+
+    let mut params = HashMap::new();
+    for param in generics.type_params() {
+        if let Some(type_) = get_datatype_bound(&param.bounds) {
+            params.insert(param.ident.clone(), type_);
+        }
+    }
+    if let Some(where_clause) = &generics.where_clause {
+        for predicate in where_clause.predicates.iter() {
+            if let WherePredicate::Type(PredicateType { bounded_ty: Type::Path(TypePath { qself: None, path }), bounds, .. }) = predicate {
+                if let (Some(ident), Some(type_)) = (path.get_ident(), get_datatype_bound(bounds)) {
+                    params.insert(ident.clone(), type_);
+                }
+            }
+        }
+    }
+    params
+}
+
+/// Collect the positions of the type parameters bounded by a trait which is now an enum for every
+/// generic item, function and method, e.g. `[0]` for `struct Holder<T: Shape>`
+pub struct CollectDatatypeTypeParams<'a> {
+    pub gamma: &'a Gamma,
+    pub positions: HashMap<Ident, Vec<usize>>,
+}
+impl CollectDatatypeTypeParams<'_> {
+    fn collect(&mut self, ident: &Ident, generics: &Generics) {
+        let params = get_datatype_type_params(generics, self.gamma);
+        let positions: Vec<usize> = generics
+            .type_params()
+            .enumerate()
+            .filter(|(_, param)| params.contains_key(&param.ident))
+            .map(|(position, _)| position)
+            .collect();
+        if !positions.is_empty() {
+            self.positions.insert(ident.clone(), positions);
+        }
+    }
+}
+impl<'ast> Visit<'ast> for CollectDatatypeTypeParams<'_> {
+    fn visit_item_struct(&mut self, i: &'ast ItemStruct) {
+        self.collect(&i.ident, &i.generics);
+    }
+
+    fn visit_item_enum(&mut self, i: &'ast ItemEnum) {
+        self.collect(&i.ident, &i.generics);
+    }
+
+    fn visit_item_type(&mut self, i: &'ast ItemType) {
+        self.collect(&i.ident, &i.generics);
+    }
+
+    fn visit_item_trait(&mut self, i: &'ast ItemTrait) {
+        self.collect(&i.ident, &i.generics);
+        visit_item_trait(self, i);
+    }
+
+    fn visit_signature(&mut self, i: &'ast Signature) {
+        self.collect(&i.ident, &i.generics);
+    }
+}
+
+/// Replace the type parameters of items, functions and methods bounded by a trait which is now an
+/// enum with the enum, e.g. `fn area<T: Shape>(shape: &T)` becomes `fn area(shape: &Shape)`. The
+/// matching type arguments are removed wherever the items are used, e.g. `Holder<Square>` becomes
+/// `Holder` and `double::<Square>(square)` becomes `double(square)`
+pub struct ReplaceDatatypeTypeParams<'a> {
+    pub gamma: &'a Gamma,
+    /// The positions of the removed type parameters, from [`CollectDatatypeTypeParams`]
+    pub positions: &'a HashMap<Ident, Vec<usize>>,
+}
+impl ReplaceDatatypeTypeParams<'_> {
+    /// Remove the type parameters bounded by a datatype from the generics and replace them in the
+    /// node they belong to
+    fn replace_datatype_type_params<T>(&self, node: &mut T, get_generics: impl Fn(&mut T) -> &mut Generics, visit: impl Fn(&mut ReplaceTypeParams, &mut T)) {
+        let generics = get_generics(node);
+        let replaced = get_datatype_type_params(generics, self.gamma);
+        if replaced.is_empty() {
+            return;
+        }
+
+        generics.params = generics.params.clone().into_iter().filter(|param| {
+            !matches!(param, GenericParam::Type(TypeParam { ident, .. }) if replaced.contains_key(ident))
+        }).collect();
+        if let Some(where_clause) = &mut generics.where_clause {
+            where_clause.predicates = where_clause.predicates.clone().into_iter().filter(|predicate| {
+                !matches!(predicate, WherePredicate::Type(PredicateType { bounded_ty: Type::Path(TypePath { qself: None, path }), .. })
+                    if path.get_ident().map_or(false, |ident| replaced.contains_key(ident)))
+            }).collect();
+        }
+        if generics.params.is_empty() {
+            generics.lt_token = None;
+            generics.gt_token = None;
+        }
+        if generics.where_clause.as_ref().map_or(false, |where_clause| where_clause.predicates.is_empty()) {
+            generics.where_clause = None;
+        }
+        visit(&mut ReplaceTypeParams { types: replaced }, node);
+    }
+
+    /// Remove the type arguments in the positions of the removed type parameters of an item
+    fn remove_datatype_type_args<T>(&self, ident: &Ident, args: &mut Punctuated<T, Token![,]>, is_type: impl Fn(&T) -> bool) {
+        let positions = match self.positions.get(ident) {
+            Some(positions) => positions,
+            None => return,
+        };
+        let mut position = 0;
+        *args = std::mem::take(args).into_iter().filter(|arg| {
+            if !is_type(arg) {
+                return true;
+            }
+            position += 1;
+            !positions.contains(&(position - 1))
+        }).collect();
+    }
+}
+impl VisitMut for ReplaceDatatypeTypeParams<'_> {
+    fn visit_item_struct_mut(&mut self, i: &mut ItemStruct) {
+        self.replace_datatype_type_params(i, |i| &mut i.generics, |replace, i| replace.visit_item_struct_mut(i));
+        visit_item_struct_mut(self, i);
+    }
+
+    fn visit_item_enum_mut(&mut self, i: &mut ItemEnum) {
+        self.replace_datatype_type_params(i, |i| &mut i.generics, |replace, i| replace.visit_item_enum_mut(i));
+        visit_item_enum_mut(self, i);
+    }
+
+    fn visit_item_type_mut(&mut self, i: &mut ItemType) {
+        self.replace_datatype_type_params(i, |i| &mut i.generics, |replace, i| replace.visit_item_type_mut(i));
+        visit_item_type_mut(self, i);
+    }
+
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        self.replace_datatype_type_params(i, |i| &mut i.generics, |replace, i| replace.visit_item_trait_mut(i));
+        visit_item_trait_mut(self, i);
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        self.replace_datatype_type_params(i, |i| &mut i.generics, |replace, i| replace.visit_item_impl_mut(i));
+        visit_item_impl_mut(self, i);
+    }
+
+    fn visit_item_fn_mut(&mut self, i: &mut ItemFn) {
+        self.replace_datatype_type_params(i, |i| &mut i.sig.generics, |replace, i| replace.visit_item_fn_mut(i));
+        visit_item_fn_mut(self, i);
+    }
+
+    fn visit_impl_item_method_mut(&mut self, i: &mut ImplItemMethod) {
+        self.replace_datatype_type_params(i, |i| &mut i.sig.generics, |replace, i| replace.visit_impl_item_method_mut(i));
+        visit_impl_item_method_mut(self, i);
+    }
+
+    fn visit_trait_item_method_mut(&mut self, i: &mut TraitItemMethod) {
+        self.replace_datatype_type_params(i, |i| &mut i.sig.generics, |replace, i| replace.visit_trait_item_method_mut(i));
+        visit_trait_item_method_mut(self, i);
+    }
+
+    fn visit_path_segment_mut(&mut self, i: &mut PathSegment) {
+        if let (PathArguments::AngleBracketed(arguments), true) = (&mut i.arguments, self.positions.contains_key(&i.ident)) {
+            self.remove_datatype_type_args(&i.ident, &mut arguments.args, |arg| matches!(arg, GenericArgument::Type(_)));
+            if arguments.args.is_empty() {
+                i.arguments = PathArguments::None;
+            }
+        }
+        visit_path_segment_mut(self, i);
+    }
+
+    fn visit_expr_method_call_mut(&mut self, i: &mut ExprMethodCall) {
+        if let Some(turbofish) = &mut i.turbofish {
+            self.remove_datatype_type_args(&i.method, &mut turbofish.args, |arg| matches!(arg, GenericMethodArgument::Type(_)));
+            if turbofish.args.is_empty() {
+                i.turbofish = None;
+            }
+        }
+        visit_expr_method_call_mut(self, i);
+    }
+}
+
+/// Replace type parameters with the given types
+pub struct ReplaceTypeParams {
+    pub types: HashMap<Ident, Type>,
+}
+impl VisitMut for ReplaceTypeParams {
+    fn visit_type_mut(&mut self, i: &mut Type) {
+        if let Type::Path(TypePath { qself: None, path }) = i {
+            if let Some(type_) = path.get_ident().and_then(|ident| self.types.get(ident)) {
+                *i = type_.clone();
+                return;
+            }
+        }
+        visit_type_mut(self, i);
+    }
+}
+
+/// Replace type aliases with the types they stand for. The aliases being expanded are tracked so
+/// an alias which refers to a type of the same name (e.g. `type Shape = shapes::Shape`) is only
+/// expanded once
+pub struct ReplaceTypeAliases<'a> {
+    aliases: &'a HashMap<Ident, ItemType>,
+    expanding: Vec<Ident>,
+}
+impl<'a> ReplaceTypeAliases<'a> {
+    pub fn new(aliases: &'a HashMap<Ident, ItemType>) -> Self {
+        ReplaceTypeAliases { aliases, expanding: Vec::new() }
+    }
+}
+impl<'a> VisitMut for ReplaceTypeAliases<'a> {
+    fn visit_type_mut(&mut self, i: &mut Type) {
+        // Expand the arguments first, e.g. `Vec<ShapeRef>`
+        visit_type_mut(self, i);
+
+        if let Type::Path(TypePath { qself: None, path }) = i {
+            let segment = path.segments.last().unwrap();
+            let alias = match self.aliases.get(&segment.ident) {
+                Some(alias) if !self.expanding.contains(&segment.ident) => alias,
+                _ => return,
+            };
+
+            let args: Vec<Type> = match &segment.arguments {
+                PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(type_) => Some(type_.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let mut expanded = *alias.ty.clone();
+            ReplaceTypeParams {
+                types: alias
+                    .generics
+                    .type_params()
+                    .map(|param| param.ident.clone())
+                    .zip(args)
+                    .collect(),
+            }
+            .visit_type_mut(&mut expanded);
+
+            // The aliased type may itself use aliases
+            self.expanding.push(alias.ident.clone());
+            self.visit_type_mut(&mut expanded);
+            self.expanding.pop();
+            *i = expanded;
+        }
+    }
+}
+
+/// Replace the enums of the transformed datatypes with trait objects, wherever they are in a
+/// type. An enum which is not behind a box or reference is boxed, e.g. `Vec<Shape>` becomes
+/// `Vec<Box<dyn Shape>>`
+pub struct ReplaceDatatypeEnums<'a> {
+    pub gamma: &'a Gamma,
+}
+impl VisitMut for ReplaceDatatypeEnums<'_> {
+    fn visit_type_mut(&mut self, i: &mut Type) {
+        if let Some(path) = get_enum_type_path(i, self.gamma) {
+            *i = Type::Path(TypePath { qself: None, path: create_dyn_pointer_of_path(&path, self.gamma.pointer) });
+            return;
+        }
+
+        // A pointer or reference already gives the enum a size, only the `dyn` is needed
+        let pointee = match i {
+            Type::Reference(TypeReference { elem, .. }) => Some(&mut **elem),
+            _ => get_pointer_inner_type_mut(i),
+        };
+        // A cell behind a pointer is given a size by the pointer as well, e.g. `Rc<RefCell<dyn Shape>>`
+        let pointee = match pointee {
+            Some(Type::Path(TypePath { qself: None, path })) if path.segments.last().unwrap().ident == "RefCell" => {
+                get_generic_type_args_mut(path).into_iter().next()
+            }
+            pointee => pointee,
+        };
+        match pointee.and_then(|pointee| get_enum_type_path(pointee, self.gamma).map(|path| (pointee, path))) {
+            Some((pointee, path)) => *pointee = create_dyn_of_path(&path),
+            None => visit_type_mut(self, i),
+        }
+    }
+}
+
+/// The path of a type which is one of the enums in gamma, written without `dyn`
+fn get_enum_type_path(type_: &Type, gamma: &Gamma) -> Option<Path> {
+    match type_ {
+        Type::Path(TypePath { qself: None, path }) if gamma.is_enum(&path.segments.last().unwrap().ident) => Some(path.clone()),
+        _ => None,
+    }
+}
+
+/// Check if a type is one of the std collections, which store their items on the heap
+pub fn is_collection(ident: &Ident) -> bool {
+    ["Vec", "VecDeque", "LinkedList", "HashSet", "BTreeSet", "BinaryHeap", "HashMap", "BTreeMap"]
+        .iter()
+        .any(|collection| ident == collection)
+}
+
+fn get_generic_type_args_mut(path: &mut Path) -> Vec<&mut Type> {
+    match &mut path.segments.last_mut().unwrap().arguments {
+        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+            .iter_mut()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(type_) => Some(type_),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Get the type inside a `Box<T>`
+fn get_box_inner_type_mut(type_: &mut Type) -> Option<&mut Type> {
+    get_wrapper_inner_type_mut(type_, &["Box"])
+}
+
+/// Get the type inside a `Box<T>`, `Rc<T>` or `Arc<T>`
+fn get_pointer_inner_type_mut(type_: &mut Type) -> Option<&mut Type> {
+    get_wrapper_inner_type_mut(type_, &["Box", "Rc", "Arc"])
+}
+
+fn get_wrapper_inner_type_mut<'a>(type_: &'a mut Type, wrappers: &[&str]) -> Option<&'a mut Type> {
+    match type_ {
+        Type::Path(TypePath { qself: None, path })
+            if wrappers.iter().any(|wrapper| path.segments.last().unwrap().ident == wrapper) =>
+        {
+            get_generic_type_args_mut(path).into_iter().next()
+        }
+        _ => None,
+    }
+}
+
+/// Finds any expression the transformation may need to change, these are calls, method calls,
+/// struct instantiations and macros (which may contain any of the others)
+#[derive(Default)]
+pub struct FindTransformableExprs {
+    pub found: bool,
+}
+impl<'ast> Visit<'ast> for FindTransformableExprs {
+    fn visit_expr(&mut self, i: &'ast Expr) {
+        match i {
+            Expr::Call(_) | Expr::MethodCall(_) | Expr::Struct(_) | Expr::Macro(_) => self.found = true,
+            _ => visit_expr(self, i),
+        }
+    }
+}
+
+pub fn contains_transformable_expr(expr: &Expr) -> bool {
+    let mut find_exprs = FindTransformableExprs::default();
+    find_exprs.visit_expr(expr);
+    find_exprs.found
+}
+
+/// Finds the uses and imports of a type in the items of a module, nested modules are not searched,
+/// e.g. the `Rc` of a pointer the trait objects are held in
+pub struct FindTypeUses<'a> {
+    pub ident: &'a Ident,
+    pub used: bool,
+    pub imported: bool,
+}
+impl<'ast> Visit<'ast> for FindTypeUses<'_> {
+    fn visit_item_mod(&mut self, _: &'ast ItemMod) {}
+
+    fn visit_path(&mut self, i: &'ast Path) {
+        if i.segments.iter().any(|segment| &segment.ident == self.ident) {
+            self.used = true;
+        }
+        visit_path(self, i);
+    }
+
+    fn visit_use_name(&mut self, i: &'ast UseName) {
+        self.imported |= &i.ident == self.ident;
+    }
+
+    fn visit_use_rename(&mut self, i: &'ast UseRename) {
+        self.imported |= &i.rename == self.ident;
+    }
+}
+
+/// Replace the names of renamed imports of the traits, enums, generators, constructors and
+/// consumers with the names of the items, e.g. `use shapes::Shape as S` becomes
+/// `use shapes::Shape` and `Box<dyn S>` becomes `Box<dyn Shape>`. These items are identified by
+/// name throughout the transformation
+pub struct ReplaceRenamedImports<'a> {
+    pub gamma: &'a Gamma,
+    pub module_path: Vec<Ident>,
+}
+impl ReplaceRenamedImports<'_> {
+    /// Get the names of the items renamed by the imports of the current module
+    fn get_renames(&self) -> HashMap<Ident, Ident> {
+        let imports = match self.gamma.module_scope.get_module(&self.module_path) {
+            Some(scope) => &scope.imports,
+            None => return HashMap::new(),
+        };
+        imports
+            .keys()
+            .filter_map(|name| {
+                let (_, idents) = self.gamma.resolve_path(&self.module_path, &Path::from(name.clone()))?;
+                match idents.as_slice() {
+                    [ident] if ident != name && self.is_transformed_item(ident) => Some((name.clone(), ident.clone())),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn is_transformed_item(&self, ident: &Ident) -> bool {
+        self.gamma.is_trait(ident)
+            || self.gamma.is_enum_or_variant(ident)
+            || self.gamma.is_generator_type(ident)
+            || self.gamma.is_consumer(ident)
+    }
+}
+impl VisitMut for ReplaceRenamedImports<'_> {
+    fn visit_file_mut(&mut self, i: &mut File) {
+        let renames = self.get_renames();
+        if !renames.is_empty() {
+            ReplaceRenames { renames }.visit_file_mut(i);
+        }
+        for item in i.items.iter_mut() {
+            if let Item::Mod(item_mod) = item {
+                self.visit_item_mod_mut(item_mod);
+            }
+        }
+    }
+
+    fn visit_item_mod_mut(&mut self, i: &mut ItemMod) {
+        self.module_path.push(i.ident.clone());
+        let renames = self.get_renames();
+        if let Some((_, items)) = &mut i.content {
+            if !renames.is_empty() {
+                let mut replace_renames = ReplaceRenames { renames };
+                items.iter_mut().for_each(|item| replace_renames.visit_item_mut(item));
+            }
+            for item in items.iter_mut() {
+                if let Item::Mod(item_mod) = item {
+                    self.visit_item_mod_mut(item_mod);
+                }
+            }
+        }
+        self.module_path.pop();
+    }
+}
+
+/// Replace the names given to items by the imports of a module, see [`ReplaceRenamedImports`]
+struct ReplaceRenames {
+    renames: HashMap<Ident, Ident>,
+}
+impl VisitMut for ReplaceRenames {
+    // Nested modules have their own imports
+    fn visit_item_mod_mut(&mut self, _: &mut ItemMod) {}
+
+    fn visit_path_mut(&mut self, i: &mut Path) {
+        if i.leading_colon.is_none() {
+            if let Some(ident) = self.renames.get(&i.segments[0].ident) {
+                i.segments[0].ident = ident.clone();
+            }
+        }
+        visit_path_mut(self, i);
+    }
+
+    fn visit_use_tree_mut(&mut self, i: &mut UseTree) {
+        if let UseTree::Rename(UseRename { ident, rename, .. }) = i {
+            if self.renames.get(rename) == Some(ident) {
+                *i = UseTree::Name(UseName { ident: ident.clone() });
+                return;
+            }
+        }
+        visit_use_tree_mut(self, i);
+    }
+}
+
+/// Update the imports of the items removed by a transformation. Imports of generator structs,
+/// consumers and enum variants are removed as the code using them is rewritten with the path of
+/// the item replacing them. A consumer becomes a method, so its trait is imported in its place
+pub struct UpdateImports<'a> {
+    pub gamma: &'a Gamma,
+    /// The generators and consumers removed, along with the trait a consumer is now a method of
+    pub removed: HashMap<Ident, Option<Ident>>,
+    /// The enums whose variants are removed
+    pub removed_enums: HashSet<Ident>,
+    pub module_path: Vec<Ident>,
+}
+impl UpdateImports<'_> {
+    fn update_items(&mut self, items: &mut Vec<Item>) {
+        let mut added_imports = Vec::new();
+        items.retain_mut(|item| match item {
+            Item::Use(item_use) if item_use.leading_colon.is_none() => {
+                match self.update_use_tree(&item_use.tree, &[], &mut added_imports) {
+                    Some(tree) => {
+                        item_use.tree = tree;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => true,
+        });
+
+        // A trait declared in or already imported into the module is referred to by its name
+        for path in added_imports.into_iter().filter(|path| path.segments.len() > 1) {
+            let ident = &path.segments.last().unwrap().ident;
+            let mut find_uses = FindTypeUses { ident, used: false, imported: false };
+            items.iter().for_each(|item| find_uses.visit_item(item));
+            if !find_uses.imported {
+                items.insert(0, Item::Use(create_use_of_path(&path)));
+            }
+        }
+
+        for item in items.iter_mut() {
+            if let Item::Mod(item_mod @ ItemMod { content: Some(_), .. }) = item {
+                self.module_path.push(item_mod.ident.clone());
+                self.update_items(&mut item_mod.content.as_mut().unwrap().1);
+                self.module_path.pop();
+            }
+        }
+    }
+
+    /// Remove the imports of removed items from a use tree, None if nothing is left
+    fn update_use_tree(&self, tree: &UseTree, prefix: &[Ident], added_imports: &mut Vec<Path>) -> Option<UseTree> {
+        let with_prefix = |ident: &Ident| -> Path {
+            Path {
+                leading_colon: None,
+                segments: prefix.iter().chain(std::iter::once(ident)).cloned().map(PathSegment::from).collect(),
+            }
+        };
+        match tree {
+            UseTree::Path(use_path) => Some(UseTree::Path(UsePath {
+                tree: Box::new(self.update_use_tree(
+                    &use_path.tree,
+                    &prefix.iter().chain(std::iter::once(&use_path.ident)).cloned().collect::<Vec<_>>(),
+                    added_imports,
+                )?),
+                ..use_path.clone()
+            })),
+            UseTree::Name(UseName { ident }) | UseTree::Rename(UseRename { ident, .. }) => {
+                match self.gamma.resolve_path(&self.module_path, &with_prefix(ident)) {
+                    Some((_, idents)) => match idents.as_slice() {
+                        [item] if self.removed.contains_key(item) => {
+                            if let Some(trait_ident) = &self.removed[item] {
+                                added_imports.push(self.gamma.get_relative_item_path(&self.module_path, trait_ident));
+                            }
+                            None
+                        }
+                        [enum_, _] if self.removed_enums.contains(enum_) => None,
+                        _ => Some(tree.clone()),
+                    },
+                    None => Some(tree.clone()),
+                }
+            }
+            // The variants of an enum, e.g. `use Expr::*`
+            UseTree::Glob(_) if !prefix.is_empty() => {
+                let path = Path {
+                    leading_colon: None,
+                    segments: prefix.iter().cloned().map(PathSegment::from).collect(),
+                };
+                match self.gamma.resolve_path(&self.module_path, &path) {
+                    Some((_, idents)) if idents.len() == 1 && self.removed_enums.contains(&idents[0]) => None,
+                    _ => Some(tree.clone()),
+                }
+            }
+            UseTree::Glob(_) => Some(tree.clone()),
+            UseTree::Group(use_group) => {
+                let items: Punctuated<UseTree, Token![,]> = use_group
+                    .items
+                    .iter()
+                    .filter_map(|tree| self.update_use_tree(tree, prefix, added_imports))
+                    .collect();
+                if items.is_empty() {
+                    None
+                } else {
+                    Some(UseTree::Group(UseGroup { items, ..use_group.clone() }))
+                }
+            }
+        }
+    }
+}
+impl VisitMut for UpdateImports<'_> {
+    fn visit_file_mut(&mut self, i: &mut File) {
+        self.update_items(&mut i.items);
+    }
+}
+
+/// How an associated type of a trait being transformed into an enum is represented by the enum
+#[derive(Clone)]
+enum AssociatedType {
+    /// Every generator gives the associated type the same type, so it is replaced by that type
+    Type(Type),
+    /// Each generator gives the associated type one of its type parameters, so it becomes a type
+    /// parameter of the trait (and of the enum)
+    Param(TypeParam),
+}
+
+/// Replace the associated types of the traits being transformed into enums, as an enum has no
+/// associated types, e.g.
+///
+/// ```text
+/// trait Parser { type Output; fn parse(&self) -> Self::Output; }
+/// impl<T> Parser for Fixed<T> { type Output = T; .. }
+/// ```
+///
+/// becomes `trait Parser<Output> { fn parse(&self) -> Output; }` implemented by
+/// `impl<Output> Parser<Output> for Fixed<Output>`. The bindings of the associated types in the
+/// rest of the code are updated to match, e.g. `dyn Parser<Output = i32>` becomes
+/// `dyn Parser<i32>`. A trait whose generators give an associated type different types which are
+/// not type parameters is left as it is.
+pub struct ReplaceAssociatedTypes {
+    /// The associated types of each trait, in the order they are declared
+    traits: HashMap<Ident, Vec<(Ident, AssociatedType)>>,
+    /// The type parameters of each generator struct which are renamed after the associated type
+    /// they are given as
+    struct_params: HashMap<Ident, HashMap<Ident, Ident>>,
+    /// The type parameters of the impl of each trait for each generator which are renamed
+    impl_params: HashMap<(Ident, Ident), HashMap<Ident, Ident>>,
+}
+impl ReplaceAssociatedTypes {
+    pub fn new(gamma: &Gamma, selection: &Selection) -> Self {
+        let mut replace = ReplaceAssociatedTypes {
+            traits: HashMap::new(),
+            struct_params: HashMap::new(),
+            impl_params: HashMap::new(),
+        };
+
+        'traits: for trait_ in gamma.traits.iter() {
+            let type_items: Vec<&TraitItemType> = trait_.items.iter().filter_map(|item| match item {
+                TraitItem::Type(type_item) => Some(type_item),
+                _ => None,
+            }).collect();
+            if type_items.is_empty() || !selection.is_selected(&gamma.get_item_module(&trait_.ident), &trait_.ident) {
+                continue;
+            }
+            let generators = gamma.generators.get(&trait_.ident).cloned().unwrap_or_default();
+
+            let mut associated_types = Vec::new();
+            let mut renames: Vec<(Ident, Ident, Ident, Ident)> = Vec::new();
+            for type_item in type_items {
+                let given_types = match generators
+                    .iter()
+                    .map(|(struct_, impl_)| get_impl_type_item(impl_, &type_item.ident).map(|type_| (struct_, impl_, type_)))
+                    .collect::<Option<Vec<_>>>()
+                {
+                    Some(given_types) => given_types,
+                    None => continue 'traits,
+                };
+
+                let is_same_type = given_types.windows(2).all(|pair| pair[0].2 == pair[1].2)
+                    && given_types.iter().all(|(_, impl_, type_)| {
+                        !impl_.generics.type_params().any(|param| is_type_used(type_, &param.ident))
+                    });
+                if is_same_type && !given_types.is_empty() {
+                    associated_types.push((type_item.ident.clone(), AssociatedType::Type(given_types[0].2.clone())));
+                    continue;
+                }
+
+                for (struct_, impl_, type_) in given_types {
+                    match get_generator_type_param(struct_, impl_, &type_) {
+                        Some((impl_param, struct_param)) => {
+                            renames.push((struct_.ident.clone(), impl_param, struct_param, type_item.ident.clone()))
+                        }
+                        None => continue 'traits,
+                    }
+                }
+                associated_types.push((type_item.ident.clone(), AssociatedType::Param(TypeParam {
+                    attrs: Vec::new(),
+                    ident: type_item.ident.clone(),
+                    colon_token: type_item.colon_token,
+                    bounds: type_item.bounds.clone(),
+                    eq_token: None,
+                    default: None,
+                })));
+            }
+
+            for (struct_ident, impl_param, struct_param, associated_type) in renames {
+                replace.struct_params.entry(struct_ident.clone()).or_default().insert(struct_param, associated_type.clone());
+                replace.impl_params.entry((trait_.ident.clone(), struct_ident)).or_default().insert(impl_param, associated_type);
+            }
+            replace.traits.insert(trait_.ident.clone(), associated_types);
+        }
+        replace
+    }
+
+    /// The types `Self::Output` is replaced with in a trait, or in an impl of it
+    fn get_self_types(&self, trait_ident: &Ident, impl_: Option<&ItemImpl>) -> HashMap<Ident, Type> {
+        self.traits[trait_ident]
+            .iter()
+            .map(|(ident, associated_type)| {
+                let type_ = match (associated_type, impl_) {
+                    (AssociatedType::Type(type_), _) => type_.clone(),
+                    // The impl gives it one of its type parameters, which is renamed after it
+                    (AssociatedType::Param(_), Some(impl_)) => get_impl_type_item(impl_, ident).unwrap(),
+                    (AssociatedType::Param(_), None) => create_type_from_ident(ident),
+                };
+                (ident.clone(), type_)
+            })
+            .collect()
+    }
+}
+impl VisitMut for ReplaceAssociatedTypes {
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        if self.traits.contains_key(&i.ident) {
+            i.items.retain(|item| !matches!(item, TraitItem::Type(_)));
+            for (_, associated_type) in &self.traits[&i.ident] {
+                if let AssociatedType::Param(param) = associated_type {
+                    i.generics.params.push(GenericParam::Type(param.clone()));
+                }
+            }
+            let mut replace_self_types = ReplaceSelfAssociatedTypes { types: self.get_self_types(&i.ident, None) };
+            for item in i.items.iter_mut() {
+                replace_self_types.visit_trait_item_mut(item);
+            }
+        }
+        visit_item_trait_mut(self, i);
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        let trait_ident = i.trait_.as_ref().map(|(_, path, _)| path.segments.last().unwrap().ident.clone());
+        if let Some(trait_ident) = trait_ident.filter(|trait_ident| self.traits.contains_key(trait_ident)) {
+            let mut replace_self_types = ReplaceSelfAssociatedTypes { types: self.get_self_types(&trait_ident, Some(i)) };
+            i.items.retain(|item| !matches!(item, ImplItem::Type(_)));
+            for item in i.items.iter_mut() {
+                replace_self_types.visit_impl_item_mut(item);
+            }
+
+            let struct_ident = i.self_ty.get_delta_type().name;
+            if let Some(renames) = self.impl_params.get(&(trait_ident.clone(), struct_ident)) {
+                rename_type_params(&mut i.generics, renames);
+                let mut replace_params = ReplaceTypeParams { types: get_renamed_types(renames) };
+                replace_params.visit_type_mut(&mut i.self_ty);
+                for item in i.items.iter_mut() {
+                    replace_params.visit_impl_item_mut(item);
+                }
+            }
+
+            // The type parameters the associated types became are given by the impl
+            let args: Vec<Type> = self.traits[&trait_ident]
+                .iter()
+                .filter(|(_, associated_type)| matches!(associated_type, AssociatedType::Param(_)))
+                .map(|(ident, _)| create_type_from_ident(ident))
+                .collect();
+            add_generic_type_args(&mut i.trait_.as_mut().unwrap().1, args);
+        }
+        visit_item_impl_mut(self, i);
+    }
+
+    fn visit_item_struct_mut(&mut self, i: &mut ItemStruct) {
+        if let Some(renames) = self.struct_params.get(&i.ident) {
+            rename_type_params(&mut i.generics, renames);
+            ReplaceTypeParams { types: get_renamed_types(renames) }.visit_fields_mut(&mut i.fields);
+        }
+        visit_item_struct_mut(self, i);
+    }
+
+    fn visit_path_mut(&mut self, i: &mut Path) {
+        visit_path_mut(self, i);
+
+        let segment = i.segments.last_mut().unwrap();
+        let associated_types = match self.traits.get(&segment.ident) {
+            Some(associated_types) => associated_types,
+            None => return,
+        };
+        if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = &mut segment.arguments {
+            if !args.iter().any(|arg| matches!(arg, GenericArgument::Binding(_))) {
+                return;
+            }
+            let bindings: HashMap<Ident, Type> = args.iter().filter_map(|arg| match arg {
+                GenericArgument::Binding(Binding { ident, ty, .. }) => Some((ident.clone(), ty.clone())),
+                _ => None,
+            }).collect();
+            let mut new_args: Vec<GenericArgument> = args
+                .iter()
+                .filter(|arg| !matches!(arg, GenericArgument::Binding(Binding { ident, .. }) if associated_types.iter().any(|(associated, _)| associated == ident)))
+                .cloned()
+                .collect();
+            for (ident, associated_type) in associated_types {
+                if let (AssociatedType::Param(_), Some(type_)) = (associated_type, bindings.get(ident)) {
+                    new_args.push(GenericArgument::Type(type_.clone()));
+                }
+            }
+            *args = Punctuated::from_iter(new_args);
+            if args.is_empty() {
+                segment.arguments = PathArguments::None;
+            }
+        }
+    }
+}
+
+/// Replace the associated types of `Self`, e.g. `Self::Output` or `<Self as Parser>::Output`
+struct ReplaceSelfAssociatedTypes {
+    types: HashMap<Ident, Type>,
+}
+impl VisitMut for ReplaceSelfAssociatedTypes {
+    fn visit_type_mut(&mut self, i: &mut Type) {
+        if let Type::Path(TypePath { qself, path }) = i {
+            let is_self = match qself {
+                Some(QSelf { ty: box Type::Path(TypePath { qself: None, path: self_path }), .. }) => self_path.is_ident("Self"),
+                Some(_) => false,
+                None => path.segments.len() == 2 && path.segments[0].ident == "Self",
+            };
+            if let Some(type_) = self.types.get(&path.segments.last().unwrap().ident).filter(|_| is_self) {
+                *i = type_.clone();
+                return;
+            }
+        }
+        visit_type_mut(self, i);
+    }
+}
+
+/// The type an impl gives one of the associated types of the trait
+fn get_impl_type_item(impl_: &ItemImpl, ident: &Ident) -> Option<Type> {
+    impl_.items.iter().find_map(|item| match item {
+        ImplItem::Type(ImplItemType { ident: type_ident, ty, .. }) if type_ident == ident => Some(ty.clone()),
+        _ => None,
+    })
+}
+
+/// The type parameter of an impl a type is, along with the type parameter of the struct the impl
+/// is for which it is given as, e.g. `T` and `U` for `impl<T> Parser for Fixed<T>` of
+/// `struct Fixed<U>`
+fn get_generator_type_param(struct_: &ItemStruct, impl_: &ItemImpl, type_: &Type) -> Option<(Ident, Ident)> {
+    let impl_param = match type_ {
+        Type::Path(TypePath { qself: None, path }) => path.get_ident()?,
+        _ => return None,
+    };
+    impl_.generics.type_params().find(|param| &param.ident == impl_param)?;
+
+    let self_args = match &*impl_.self_ty {
+        Type::Path(TypePath { qself: None, path }) => match &path.segments.last().unwrap().arguments {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let index = self_args
+        .iter()
+        .filter(|arg| matches!(arg, GenericArgument::Type(_)))
+        .position(|arg| matches!(arg, GenericArgument::Type(Type::Path(TypePath { qself: None, path })) if path.is_ident(impl_param)))?;
+    let struct_param = struct_.generics.type_params().nth(index)?;
+    Some((impl_param.clone(), struct_param.ident.clone()))
+}
+
+/// Check if a type parameter is used in a type
+pub fn is_type_used(type_: &Type, ident: &Ident) -> bool {
+    let mut find_uses = FindTypeUses { ident, used: false, imported: false };
+    find_uses.visit_type(type_);
+    find_uses.used
+}
+
+/// Rename the type parameters of generics, including their uses in the where clause
+pub fn rename_type_params(generics: &mut Generics, renames: &HashMap<Ident, Ident>) {
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(TypeParam { ident, .. }) = param {
+            if let Some(rename) = renames.get(ident) {
+                *ident = rename.clone();
+            }
+        }
+    }
+    if let Some(where_clause) = &mut generics.where_clause {
+        ReplaceTypeParams { types: get_renamed_types(renames) }.visit_where_clause_mut(where_clause);
+    }
+}
+
+/// The types to replace each renamed type parameter with
+pub fn get_renamed_types(renames: &HashMap<Ident, Ident>) -> HashMap<Ident, Type> {
+    renames.iter().map(|(ident, rename)| (ident.clone(), create_type_from_ident(rename))).collect()
+}
+
+/// Add generic type arguments to the last segment of a path, after any arguments it has
+pub fn add_generic_type_args(path: &mut Path, types: Vec<Type>) {
+    if types.is_empty() {
+        return;
+    }
+    let segment = path.segments.last_mut().unwrap();
+    if let PathArguments::None = segment.arguments {
+        segment.arguments = PathArguments::AngleBracketed(parse_quote!(<>));
+    }
+    if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = &mut segment.arguments {
+        args.extend(types.into_iter().map(GenericArgument::Type));
+    }
+}
+
+/// Turn the type parameters of the traits created from enums into associated types, where the
+/// destructors return a type which depends on the type parameter and each generator gives it one
+/// of its own type parameters, e.g.
+///
+/// ```text
+/// trait Parser<Output> { fn parse(&self) -> Output; }
+/// impl<Output> Parser<Output> for Fixed<Output> { .. }
+/// ```
+///
+/// becomes `trait Parser { type Output; fn parse(&self) -> Self::Output; }` implemented by
+/// `impl<Output> Parser for Fixed<Output> { type Output = Output; .. }`. The rest of the code
+/// gives the associated types by name, e.g. `dyn Parser<i32>` becomes `dyn Parser<Output = i32>`.
+pub struct IntroduceAssociatedTypes {
+    /// The number of type parameters of each trait, and those which become associated types by
+    /// their position
+    traits: HashMap<Ident, (usize, Vec<(usize, TypeParam)>)>,
+}
+impl IntroduceAssociatedTypes {
+    pub fn new(gamma: &Gamma, trait_idents: &HashSet<Ident>) -> Self {
+        let mut traits = HashMap::new();
+        for trait_ in gamma.traits.iter().filter(|trait_| trait_idents.contains(&trait_.ident)) {
+            let generators = gamma.generators.get(&trait_.ident).cloned().unwrap_or_default();
+            let associated_types: Vec<(usize, TypeParam)> = trait_
+                .generics
+                .type_params()
+                .enumerate()
+                .filter(|(_, param)| is_returned_by_destructor(trait_, &param.ident))
+                .filter(|(index, _)| {
+                    generators.iter().all(|(struct_, impl_)| {
+                        let trait_path = &impl_.trait_.as_ref().unwrap().1;
+                        get_generic_type_args(trait_path)
+                            .get(*index)
+                            .and_then(|type_| get_generator_type_param(struct_, impl_, type_))
+                            .is_some()
+                    })
+                })
+                .map(|(index, param)| (index, param.clone()))
+                .collect();
+            if !associated_types.is_empty() {
+                traits.insert(trait_.ident.clone(), (trait_.generics.type_params().count(), associated_types));
+            }
+        }
+        IntroduceAssociatedTypes { traits }
+    }
+}
+impl VisitMut for IntroduceAssociatedTypes {
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        if let Some((_, associated_types)) = self.traits.get(&i.ident) {
+            let is_associated_type = |ident: &Ident| associated_types.iter().any(|(_, param)| param.ident == *ident);
+            i.generics.params = i
+                .generics
+                .params
+                .iter()
+                .filter(|param| !matches!(param, GenericParam::Type(param) if is_associated_type(&param.ident)))
+                .cloned()
+                .collect();
+            if i.generics.params.is_empty() {
+                i.generics.lt_token = None;
+                i.generics.gt_token = None;
+            }
+
+            // Uses of the type parameters in the trait are now uses of the associated types of Self
+            let mut replace_params = ReplaceTypeParams {
+                types: associated_types
+                    .iter()
+                    .map(|(_, param)| {
+                        let ident = &param.ident;
+                        (ident.clone(), parse_quote!(Self::#ident))
+                    })
+                    .collect(),
+            };
+            for item in i.items.iter_mut() {
+                replace_params.visit_trait_item_mut(item);
+            }
+            let type_items = associated_types.iter().map(|(_, TypeParam { ident, colon_token, bounds, .. })| {
+                TraitItem::Type(parse_quote!(type #ident #colon_token #bounds;))
+            });
+            i.items.splice(0..0, type_items);
+        }
+        visit_item_trait_mut(self, i);
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        let trait_ident = i.trait_.as_ref().map(|(_, path, _)| path.segments.last().unwrap().ident.clone());
+        if let Some((_, associated_types)) = trait_ident.and_then(|trait_ident| self.traits.get(&trait_ident)) {
+            // The impl gives the associated types the types it gave the type parameters
+            let trait_path = &mut i.trait_.as_mut().unwrap().1;
+            let args = get_generic_type_args(trait_path);
+            let type_items: Vec<ImplItem> = associated_types
+                .iter()
+                .map(|(index, TypeParam { ident, .. })| {
+                    let type_ = &args[*index];
+                    ImplItem::Type(parse_quote!(type #ident = #type_;))
+                })
+                .collect();
+            let remaining_args: Vec<Type> = args
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| !associated_types.iter().any(|(associated_index, _)| associated_index == index))
+                .map(|(_, type_)| type_)
+                .collect();
+            let segment = trait_path.segments.last_mut().unwrap();
+            segment.arguments = PathArguments::None;
+            add_generic_type_args(trait_path, remaining_args);
+            i.items.splice(0..0, type_items);
+        }
+        visit_item_impl_mut(self, i);
+    }
+
+    fn visit_path_mut(&mut self, i: &mut Path) {
+        visit_path_mut(self, i);
+
+        let segment = i.segments.last_mut().unwrap();
+        let (param_count, associated_types) = match self.traits.get(&segment.ident) {
+            Some(trait_params) => trait_params,
+            None => return,
+        };
+        if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = &mut segment.arguments {
+            let type_args: Vec<Type> = args.iter().filter_map(|arg| match arg {
+                GenericArgument::Type(type_) => Some(type_.clone()),
+                _ => None,
+            }).collect();
+            // The path gives the trait all its type parameters by position
+            if type_args.len() != *param_count || type_args.len() != args.len() {
+                return;
+            }
+            let mut new_args: Vec<GenericArgument> = type_args
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !associated_types.iter().any(|(associated_index, _)| associated_index == index))
+                .map(|(_, type_)| GenericArgument::Type(type_.clone()))
+                .collect();
+            for (index, TypeParam { ident, .. }) in associated_types {
+                let type_ = &type_args[*index];
+                new_args.push(GenericArgument::Binding(parse_quote!(#ident = #type_)));
+            }
+            *args = Punctuated::from_iter(new_args);
+        }
+    }
+}
+
+/// Check if a destructor of a trait returns a type which uses a type parameter
+fn is_returned_by_destructor(trait_: &ItemTrait, ident: &Ident) -> bool {
+    trait_.items.iter().any(|item| match item {
+        TraitItem::Method(TraitItemMethod { sig: Signature { output: ReturnType::Type(_, type_), .. }, .. }) => {
+            is_type_used(type_, ident)
+        }
+        _ => false,
+    })
+}
+
+/// The generic type arguments of the last segment of a path
+pub fn get_generic_type_args(path: &Path) -> Vec<Type> {
+    match &path.segments.last().unwrap().arguments {
+        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(type_) => Some(type_.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Replace the associated consts of the traits being transformed into enums with methods taking
+/// `&self`, which become consumers of the enum like any other destructor, e.g.
+///
+/// ```text
+/// trait Shape { const SIDES: u32; }
+/// impl Shape for Square { const SIDES: u32 = 4; }
+/// ```
+///
+/// becomes `trait Shape { fn sides(&self) -> u32; }` implemented by
+/// `impl Shape for Square { fn sides(&self) -> u32 { 4 } }`. A provided method which only returns
+/// the const, e.g. `fn sides(&self) -> u32 { Self::SIDES }`, is the method it is replaced with. The
+/// uses of the consts are replaced to match, `Self::SIDES` in the trait and its impls becomes
+/// `self.sides()`, `T::SIDES` of a type parameter bounded by the trait becomes a call on the
+/// argument of that type and `Square::SIDES` is replaced by the value the impl gives it
+pub struct ReplaceAssociatedConsts {
+    /// The method each associated const of each trait is replaced with
+    traits: HashMap<Ident, HashMap<Ident, Ident>>,
+    /// The methods of each trait which only return an associated const, these are removed
+    accessors: HashMap<Ident, Vec<Ident>>,
+    /// The value each generator gives each associated const
+    values: HashMap<(Ident, Ident), Expr>,
+    /// The trait whose items (or impl) are being visited
+    self_trait: Option<Ident>,
+    /// The generator whose inherent impl is being visited
+    self_generator: Option<Ident>,
+    /// The type parameters bounded by a trait in the function being visited, along with the trait
+    /// and the argument holding a value of the type parameter
+    type_param_args: HashMap<Ident, (Ident, Ident)>,
+}
+impl ReplaceAssociatedConsts {
+    pub fn new(gamma: &Gamma, selection: &Selection) -> Self {
+        let mut replace = ReplaceAssociatedConsts {
+            traits: HashMap::new(),
+            accessors: HashMap::new(),
+            values: HashMap::new(),
+            self_trait: None,
+            self_generator: None,
+            type_param_args: HashMap::new(),
+        };
+
+        for trait_ in gamma.traits.iter() {
+            let const_items: Vec<&TraitItemConst> = trait_.items.iter().filter_map(|item| match item {
+                TraitItem::Const(const_item) => Some(const_item),
+                _ => None,
+            }).collect();
+            if const_items.is_empty() || !selection.is_selected(&gamma.get_item_module(&trait_.ident), &trait_.ident) {
+                continue;
+            }
+            let generators = gamma.generators.get(&trait_.ident).cloned().unwrap_or_default();
+
+            let mut methods = HashMap::new();
+            for const_item in const_items {
+                let method = match get_const_accessor(trait_, &const_item.ident) {
+                    Some(accessor) => {
+                        replace.accessors.entry(trait_.ident.clone()).or_default().push(accessor.clone());
+                        accessor
+                    }
+                    None => {
+                        let method = Ident::new(&const_item.ident.to_string().to_lowercase(), const_item.ident.span());
+                        // A const whose method would clash with a destructor is left, so the trait
+                        // is not transformed
+                        if trait_.items.iter().any(|item| matches!(item, TraitItem::Method(item_method) if item_method.sig.ident == method)) {
+                            continue;
+                        }
+                        method
+                    }
+                };
+                methods.insert(const_item.ident.clone(), method);
+
+                for (struct_, impl_) in generators.iter() {
+                    let value = impl_.items.iter().find_map(|item| match item {
+                        ImplItem::Const(ImplItemConst { ident, expr, .. }) if *ident == const_item.ident => Some(expr.clone()),
+                        _ => None,
+                    });
+                    if let Some(value) = value.or_else(|| const_item.default.as_ref().map(|(_, expr)| expr.clone())) {
+                        replace.values.insert((struct_.ident.clone(), const_item.ident.clone()), value);
+                    }
+                }
+            }
+            replace.traits.insert(trait_.ident.clone(), methods);
+        }
+        replace
+    }
+
+    /// The type parameters of a signature bounded by one of the traits, along with the trait and
+    /// the argument which holds a value of the type parameter, e.g. `T`, `Shape` and `shape` for
+    /// `fn corners<T: Shape>(shape: &T)`
+    fn get_type_param_args(&self, sig: &Signature) -> HashMap<Ident, (Ident, Ident)> {
+        let mut bounded: Vec<(Ident, &Punctuated<TypeParamBound, Token![+]>)> = sig
+            .generics
+            .type_params()
+            .map(|param| (param.ident.clone(), &param.bounds))
+            .collect();
+        if let Some(where_clause) = &sig.generics.where_clause {
+            for predicate in where_clause.predicates.iter() {
+                if let WherePredicate::Type(PredicateType { bounded_ty: Type::Path(TypePath { qself: None, path }), bounds, .. }) = predicate {
+                    if let Some(ident) = path.get_ident() {
+                        bounded.push((ident.clone(), bounds));
+                    }
+                }
+            }
+        }
+
+        let mut type_param_args = HashMap::new();
+        for (param, bounds) in bounded {
+            let trait_ident = bounds.iter().find_map(|bound| match bound {
+                TypeParamBound::Trait(TraitBound { path, .. }) => Some(path.segments.last().unwrap().ident.clone()),
+                _ => None,
+            }).filter(|trait_ident| self.traits.contains_key(trait_ident));
+            let arg = sig.inputs.iter().find_map(|input| match input {
+                FnArg::Typed(PatType { pat: box Pat::Ident(PatIdent { ident, .. }), ty, .. })
+                    if ty.get_delta_type().name == param => Some(ident.clone()),
+                _ => None,
+            });
+            if let (Some(trait_ident), Some(arg)) = (trait_ident, arg) {
+                type_param_args.insert(param, (trait_ident, arg));
+            }
+        }
+        type_param_args
+    }
+
+    /// The expression an associated const of a type is replaced with, if it is one of the consts
+    /// being replaced
+    fn get_const_expr(&self, type_ident: &Ident, const_ident: &Ident) -> Option<Expr> {
+        if type_ident == "Self" {
+            if let Some(method) = self.self_trait.as_ref().and_then(|trait_ident| self.traits[trait_ident].get(const_ident)) {
+                return Some(parse_quote!(self.#method()));
+            }
+            return self.self_generator.as_ref().and_then(|generator| self.get_const_expr(generator, const_ident));
+        }
+        if let Some((trait_ident, arg)) = self.type_param_args.get(type_ident) {
+            let method = self.traits[trait_ident].get(const_ident)?;
+            return Some(parse_quote!(#arg.#method()));
+        }
+        self.values.get(&(type_ident.clone(), const_ident.clone())).map(|value| match value {
+            Expr::Lit(_) | Expr::Path(_) => value.clone(),
+            _ => parse_quote!((#value)),
+        })
+    }
+
+    fn visit_in_function<F: FnOnce(&mut Self)>(&mut self, sig: &Signature, visit: F) {
+        let mut type_param_args = self.get_type_param_args(sig);
+        std::mem::swap(&mut self.type_param_args, &mut type_param_args);
+        visit(self);
+        self.type_param_args = type_param_args;
+    }
+}
+impl VisitMut for ReplaceAssociatedConsts {
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        let methods = match self.traits.get(&i.ident) {
+            Some(methods) => methods.clone(),
+            None => return visit_item_trait_mut(self, i),
+        };
+        let accessors = self.accessors.get(&i.ident).cloned().unwrap_or_default();
+        i.items = i.items.drain(..).filter_map(|item| match item {
+            TraitItem::Const(TraitItemConst { attrs, ident, ty, default, .. }) if methods.contains_key(&ident) => {
+                let method = &methods[&ident];
+                Some(TraitItem::Method(match default {
+                    Some((_, expr)) => parse_quote!(#(#attrs)* fn #method(&self) -> #ty { #expr }),
+                    None => parse_quote!(#(#attrs)* fn #method(&self) -> #ty;),
+                }))
+            }
+            TraitItem::Method(method) if accessors.contains(&method.sig.ident) => None,
+            item => Some(item),
+        }).collect();
+
+        self.self_trait = Some(i.ident.clone());
+        visit_item_trait_mut(self, i);
+        self.self_trait = None;
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        let trait_ident = i.trait_.as_ref().map(|(_, path, _)| path.segments.last().unwrap().ident.clone());
+        match trait_ident.filter(|trait_ident| self.traits.contains_key(trait_ident)) {
+            Some(trait_ident) => {
+                let methods = &self.traits[&trait_ident];
+                for item in i.items.iter_mut() {
+                    if let ImplItem::Const(ImplItemConst { attrs, ident, ty, expr, .. }) = item {
+                        if let Some(method) = methods.get(ident) {
+                            *item = ImplItem::Method(parse_quote!(#(#attrs)* fn #method(&self) -> #ty { #expr }));
+                        }
+                    }
+                }
+                self.self_trait = Some(trait_ident);
+                visit_item_impl_mut(self, i);
+                self.self_trait = None;
+            }
+            // An inherent impl of a generator, where `Self::SIDES` is the value the generator gives
+            None if i.trait_.is_none() => {
+                self.self_generator = Some(i.self_ty.get_delta_type().name);
+                visit_item_impl_mut(self, i);
+                self.self_generator = None;
+            }
+            None => visit_item_impl_mut(self, i),
+        }
+    }
+
+    fn visit_item_fn_mut(&mut self, i: &mut ItemFn) {
+        let sig = i.sig.clone();
+        self.visit_in_function(&sig, |visitor| visit_item_fn_mut(visitor, i));
+    }
+
+    fn visit_impl_item_method_mut(&mut self, i: &mut ImplItemMethod) {
+        let sig = i.sig.clone();
+        self.visit_in_function(&sig, |visitor| visit_impl_item_method_mut(visitor, i));
+    }
+
+    fn visit_trait_item_method_mut(&mut self, i: &mut TraitItemMethod) {
+        let sig = i.sig.clone();
+        self.visit_in_function(&sig, |visitor| visit_trait_item_method_mut(visitor, i));
+    }
+
+    fn visit_expr_mut(&mut self, i: &mut Expr) {
+        if let Expr::Path(ExprPath { qself, path, .. }) = i {
+            // `Square::SIDES`, `T::SIDES`, `Self::SIDES` or `<Square as Shape>::SIDES`
+            let type_ident = match qself {
+                Some(QSelf { ty, .. }) if path.segments.len() == 2 => Some(ty.get_delta_type().name),
+                None if path.segments.len() == 2 => Some(path.segments[0].ident.clone()),
+                _ => None,
+            };
+            let const_ident = &path.segments.last().unwrap().ident;
+            if let Some(expr) = type_ident.and_then(|type_ident| self.get_const_expr(&type_ident, const_ident)) {
+                *i = expr;
+                return;
+            }
+        }
+        visit_expr_mut(self, i);
+    }
+}
+
+/// A provided method of a trait which only returns one of its associated consts, e.g.
+/// `fn sides(&self) -> u32 { Self::SIDES }`
+fn get_const_accessor(trait_: &ItemTrait, const_ident: &Ident) -> Option<Ident> {
+    trait_.items.iter().find_map(|item| match item {
+        TraitItem::Method(TraitItemMethod { sig, default: Some(block), .. })
+            if sig.inputs.len() == 1 && matches!(sig.inputs.first(), Some(FnArg::Receiver(Receiver { reference: Some(_), mutability: None, .. }))) =>
+        {
+            match block.stmts.as_slice() {
+                [Stmt::Expr(Expr::Path(ExprPath { qself: None, path, .. }))]
+                    if path.segments.len() == 2 && path.segments[0].ident == "Self" && path.segments[1].ident == *const_ident =>
+                {
+                    Some(sig.ident.clone())
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Turn the destructors of the traits created from enums which only return a constant into
+/// associated consts, e.g. `fn sides(&self) -> u32` implemented as `4` by `Square` becomes
+/// `const SIDES: u32;` implemented as `const SIDES: u32 = 4;`. The destructor is kept in the trait
+/// returning the const, `fn sides(&self) -> u32 { Self::SIDES }`, so the code calling it is
+/// unchanged. A trait with associated consts cannot be made into a trait object, so the
+/// destructors of a trait which is used as one are left as they are
+pub struct IntroduceAssociatedConsts {
+    /// The destructors of each trait which become associated consts, along with the const
+    traits: HashMap<Ident, HashMap<Ident, Ident>>,
+}
+impl IntroduceAssociatedConsts {
+    pub fn new(syntax: &File, trait_idents: &HashSet<Ident>) -> Self {
+        let mut find_items = FindTraitItems { trait_idents, traits: Vec::new(), impls: Vec::new(), trait_objects: HashSet::new() };
+        find_items.visit_file(syntax);
+
+        let mut traits = HashMap::new();
+        for trait_ in find_items.traits.iter().filter(|trait_| !find_items.trait_objects.contains(&trait_.ident)) {
+            let impls: Vec<&ItemImpl> = find_items
+                .impls
+                .iter()
+                .filter(|impl_| impl_.trait_.as_ref().unwrap().1.segments.last().unwrap().ident == trait_.ident)
+                .collect();
+            let consts: HashMap<Ident, Ident> = trait_
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    TraitItem::Method(method) if is_constant_destructor(method, &impls) => {
+                        let const_ident = Ident::new(&method.sig.ident.to_string().to_uppercase(), method.sig.ident.span());
+                        let is_declared = trait_.items.iter().any(|item| matches!(item, TraitItem::Const(item_const) if item_const.ident == const_ident));
+                        (!is_declared).then(|| (method.sig.ident.clone(), const_ident))
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !consts.is_empty() {
+                traits.insert(trait_.ident.clone(), consts);
+            }
+        }
+        IntroduceAssociatedConsts { traits }
+    }
+}
+impl VisitMut for IntroduceAssociatedConsts {
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        if let Some(consts) = self.traits.get(&i.ident) {
+            let mut const_items = Vec::new();
+            for item in i.items.iter_mut() {
+                if let TraitItem::Method(TraitItemMethod { sig, default, .. }) = item {
+                    if let (Some(const_ident), ReturnType::Type(_, ty)) = (consts.get(&sig.ident), &sig.output) {
+                        const_items.push(TraitItem::Const(match default.as_ref().and_then(get_constant_body) {
+                            Some(expr) => parse_quote!(const #const_ident: #ty = #expr;),
+                            None => parse_quote!(const #const_ident: #ty;),
+                        }));
+                        *default = Some(parse_quote!({ Self::#const_ident }));
+                    }
+                }
+            }
+            i.items.splice(0..0, const_items);
+        }
+        visit_item_trait_mut(self, i);
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        let trait_ident = i.trait_.as_ref().map(|(_, path, _)| path.segments.last().unwrap().ident.clone());
+        if let Some(consts) = trait_ident.and_then(|trait_ident| self.traits.get(&trait_ident)) {
+            let mut const_items = Vec::new();
+            i.items.retain(|item| match item {
+                ImplItem::Method(ImplItemMethod { sig, block, .. }) => {
+                    match (consts.get(&sig.ident), &sig.output, get_constant_body(block)) {
+                        (Some(const_ident), ReturnType::Type(_, ty), Some(expr)) => {
+                            const_items.push(ImplItem::Const(parse_quote!(const #const_ident: #ty = #expr;)));
+                            false
+                        }
+                        _ => true,
+                    }
+                }
+                _ => true,
+            });
+            i.items.splice(0..0, const_items);
+        }
+        visit_item_impl_mut(self, i);
+    }
+}
+
+/// Finds the given traits along with their impls, and which of them are used as trait objects
+struct FindTraitItems<'a> {
+    trait_idents: &'a HashSet<Ident>,
+    traits: Vec<ItemTrait>,
+    impls: Vec<ItemImpl>,
+    trait_objects: HashSet<Ident>,
+}
+impl<'ast> Visit<'ast> for FindTraitItems<'_> {
+    fn visit_item_trait(&mut self, i: &'ast ItemTrait) {
+        if self.trait_idents.contains(&i.ident) {
+            self.traits.push(i.clone());
+        }
+        visit_item_trait(self, i);
+    }
+
+    fn visit_item_impl(&mut self, i: &'ast ItemImpl) {
+        if let Some((_, path, _)) = &i.trait_ {
+            if self.trait_idents.contains(&path.segments.last().unwrap().ident) {
+                self.impls.push(i.clone());
+            }
+        }
+        visit_item_impl(self, i);
+    }
+
+    fn visit_type_trait_object(&mut self, i: &'ast TypeTraitObject) {
+        for bound in i.bounds.iter() {
+            if let TypeParamBound::Trait(TraitBound { path, .. }) = bound {
+                self.trait_objects.insert(path.segments.last().unwrap().ident.clone());
+            }
+        }
+        visit_type_trait_object(self, i);
+    }
+}
+
+/// Check if a destructor only returns a constant in every impl of the trait, it takes only `&self`
+/// and returns a type which does not depend on `Self`
+fn is_constant_destructor(method: &TraitItemMethod, impls: &[&ItemImpl]) -> bool {
+    let sig = &method.sig;
+    let is_self_only = sig.inputs.len() == 1
+        && matches!(sig.inputs.first(), Some(FnArg::Receiver(Receiver { reference: Some(_), mutability: None, .. })));
+    let is_constant_type = matches!(&sig.output, ReturnType::Type(_, ty) if !is_type_used(ty, &Ident::new("Self", Span::call_site())));
+    let default = method.default.as_ref().map(get_constant_body);
+    is_self_only && is_constant_type && sig.generics.params.is_empty() && !matches!(default, Some(None)) && impls.iter().all(|impl_| {
+        match impl_.items.iter().find_map(|item| match item {
+            ImplItem::Method(impl_method) if impl_method.sig.ident == sig.ident => Some(impl_method),
+            _ => None,
+        }) {
+            Some(impl_method) => get_constant_body(&impl_method.block).is_some(),
+            None => default.is_some(),
+        }
+    })
+}
+
+/// The expression of a block which only returns a constant, e.g. `{ 4 }` or `{ return "square"; }`
+fn get_constant_body(block: &Block) -> Option<Expr> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Return(ExprReturn { expr: Some(box expr), .. }))] | [Stmt::Semi(Expr::Return(ExprReturn { expr: Some(box expr), .. }), _)] | [Stmt::Expr(expr)] => {
+            is_constant_expr(expr).then(|| expr.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Check if an expression is a constant, built from literals and paths to other constants
+fn is_constant_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) => true,
+        Expr::Path(ExprPath { path, .. }) => !path.segments.iter().any(|segment| segment.ident == "self"),
+        Expr::Unary(ExprUnary { op, expr, .. }) => !matches!(op, UnOp::Deref(_)) && is_constant_expr(expr),
+        Expr::Binary(ExprBinary { left, right, .. }) => is_constant_expr(left) && is_constant_expr(right),
+        Expr::Paren(ExprParen { expr, .. }) | Expr::Group(ExprGroup { expr, .. }) | Expr::Cast(ExprCast { expr, .. }) => is_constant_expr(expr),
+        Expr::Tuple(ExprTuple { elems, .. }) | Expr::Array(ExprArray { elems, .. }) => elems.iter().all(is_constant_expr),
+        _ => false,
+    }
+}
+
+/// Merge the local supertraits of the traits being transformed into enums into the traits when
+/// they are implemented by the same generators, e.g.
+///
+/// ```text
+/// trait Named { fn name(&self) -> String; }
+/// trait Shape: Named + Debug { fn area(&self) -> f64; }
+/// ```
+///
+/// becomes `trait Shape: Debug { fn area(&self) -> f64; fn name(&self) -> String; }` and each
+/// `impl Named for Square` is merged into `impl Shape for Square`, so the destructors of both
+/// traits become consumers of the same enum. The code naming a merged supertrait names the trait
+/// instead. Foreign supertraits, such as `Debug`, are left for the enum to derive or implement
+pub struct ReplaceSupertraits<'a> {
+    gamma: &'a Gamma,
+    /// The supertraits merged into each trait, including the supertraits of the supertraits
+    traits: HashMap<Ident, Vec<ItemTrait>>,
+    /// The trait each supertrait is merged into
+    merged: HashMap<Ident, Ident>,
+    module_path: Vec<Ident>,
+}
+impl<'a> ReplaceSupertraits<'a> {
+    pub fn new(gamma: &'a Gamma, selection: &Selection) -> Self {
+        let is_selected = |trait_: &ItemTrait| selection.is_selected(&gamma.get_item_module(&trait_.ident), &trait_.ident);
+        let get_generators = |trait_: &ItemTrait| -> HashSet<Ident> {
+            gamma.generators.get(&trait_.ident).into_iter().flatten().map(|(struct_, _)| struct_.ident.clone()).collect()
+        };
+        // A supertrait without type parameters which is also selected can be merged
+        let get_mergeable_supertraits = |trait_: &ItemTrait| -> Vec<ItemTrait> {
+            trait_
+                .supertraits
+                .iter()
+                .filter_map(|bound| match bound {
+                    TypeParamBound::Trait(TraitBound { path, .. }) if path.segments.last().unwrap().arguments.is_empty() => {
+                        gamma.get_trait(&path.segments.last().unwrap().ident).ok()
+                    }
+                    _ => None,
+                })
+                .filter(|supertrait| {
+                    is_selected(supertrait) && supertrait.generics.params.is_empty() && get_generators(supertrait) == get_generators(trait_)
+                })
+                .collect()
+        };
+
+        let mut replace = ReplaceSupertraits {
+            gamma,
+            traits: HashMap::new(),
+            merged: HashMap::new(),
+            module_path: Vec::new(),
+        };
+
+        // The supertraits are merged into the traits which are not merged into another trait
+        let supertraits: HashSet<Ident> = gamma
+            .traits
+            .iter()
+            .filter(|trait_| is_selected(trait_))
+            .flat_map(&get_mergeable_supertraits)
+            .map(|supertrait| supertrait.ident)
+            .collect();
+        for trait_ in gamma.traits.iter().filter(|trait_| is_selected(trait_) && !supertraits.contains(&trait_.ident)) {
+            let mut merged = Vec::new();
+            let mut pending = get_mergeable_supertraits(trait_);
+            while !pending.is_empty() {
+                let supertrait = pending.remove(0);
+                if supertrait.ident == trait_.ident || replace.merged.contains_key(&supertrait.ident) {
+                    continue;
+                }
+                replace.merged.insert(supertrait.ident.clone(), trait_.ident.clone());
+                pending.extend(get_mergeable_supertraits(&supertrait));
+                merged.push(supertrait);
+            }
+            if !merged.is_empty() {
+                replace.traits.insert(trait_.ident.clone(), merged);
+            }
+        }
+        replace
+    }
+
+    fn is_merged_bound(&self, bound: &TypeParamBound) -> bool {
+        matches!(bound, TypeParamBound::Trait(TraitBound { path, .. }) if self.merged.contains_key(&path.segments.last().unwrap().ident))
+    }
+}
+impl VisitMut for ReplaceSupertraits<'_> {
+    fn visit_file_mut(&mut self, i: &mut File) {
+        if self.merged.is_empty() {
+            return;
+        }
+
+        // The imports of a merged supertrait import the trait it is merged into instead
+        UpdateImports {
+            gamma: self.gamma,
+            removed: self.merged.iter().map(|(supertrait, trait_)| (supertrait.clone(), Some(trait_.clone()))).collect(),
+            removed_enums: HashSet::new(),
+            module_path: Vec::new(),
+        }.visit_file_mut(i);
+
+        let mut removed = Vec::new();
+        for supertrait in self.traits.values().flatten() {
+            removed.push(Item::Trait(supertrait.clone()));
+            removed.extend(self.gamma.generators.get(&supertrait.ident).into_iter().flatten().map(|(_, impl_)| Item::Impl(impl_.clone())));
+        }
+        RemoveItems { items: removed }.visit_file_mut(i);
+
+        visit_file_mut(self, i);
+    }
+
+    fn visit_item_mod_mut(&mut self, i: &mut ItemMod) {
+        self.module_path.push(i.ident.clone());
+        visit_item_mod_mut(self, i);
+        self.module_path.pop();
+    }
+
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        if let Some(supertraits) = self.traits.get(&i.ident) {
+            // The supertraits of the merged supertraits which are not merged are kept
+            let mut bounds: Vec<TypeParamBound> = Vec::new();
+            for bound in i.supertraits.iter().chain(supertraits.iter().flat_map(|supertrait| supertrait.supertraits.iter())) {
+                if !self.is_merged_bound(bound) && !bounds.contains(bound) {
+                    bounds.push(bound.clone());
+                }
+            }
+            i.colon_token = (!bounds.is_empty()).then(Default::default);
+            i.supertraits = Punctuated::from_iter(bounds);
+            i.items.extend(supertraits.iter().flat_map(|supertrait| supertrait.items.clone()));
+        }
+        visit_item_trait_mut(self, i);
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        let trait_ident = i.trait_.as_ref().map(|(_, path, _)| path.segments.last().unwrap().ident.clone());
+        if let Some(supertraits) = trait_ident.and_then(|trait_ident| self.traits.get(&trait_ident)) {
+            let struct_ident = i.self_ty.get_delta_type().name;
+            for supertrait in supertraits {
+                let generators = self.gamma.generators.get(&supertrait.ident).into_iter().flatten();
+                if let Some((_, impl_)) = generators.into_iter().find(|(struct_, _)| struct_.ident == struct_ident) {
+                    i.items.extend(impl_.items.clone());
+                }
+            }
+        }
+        visit_item_impl_mut(self, i);
+    }
+
+    fn visit_path_mut(&mut self, i: &mut Path) {
+        let position = i.segments.iter().position(|segment| self.merged.contains_key(&segment.ident));
+        if let Some(position) = position {
+            let trait_ident = self.merged[&i.segments[position].ident].clone();
+            if position == 0 {
+                i.segments[0].ident = trait_ident;
+            } else {
+                // A path to the supertrait is replaced by the path to the trait
+                let mut path = self.gamma.get_relative_item_path(&self.module_path, &trait_ident);
+                path.segments.last_mut().unwrap().arguments = i.segments[position].arguments.clone();
+                path.segments.extend(i.segments.iter().skip(position + 1).cloned());
+                *i = path;
+            }
+        }
+        visit_path_mut(self, i);
+    }
+
+    // A bound on a merged supertrait becomes a bound on the trait, which may already be there
+    fn visit_type_param_mut(&mut self, i: &mut TypeParam) {
+        visit_type_param_mut(self, i);
+        dedup_bounds(&mut i.bounds);
+    }
+
+    fn visit_predicate_type_mut(&mut self, i: &mut PredicateType) {
+        visit_predicate_type_mut(self, i);
+        dedup_bounds(&mut i.bounds);
+    }
+}
+
+fn dedup_bounds(bounds: &mut Punctuated<TypeParamBound, Token![+]>) {
+    let mut deduped: Vec<TypeParamBound> = Vec::new();
+    for bound in bounds.iter() {
+        if !deduped.contains(bound) {
+            deduped.push(bound.clone());
+        }
+    }
+    *bounds = Punctuated::from_iter(deduped);
+}
+
+/// Replace the static methods (those without a receiver) of the traits being transformed into
+/// enums with functions, as an enum has no value to call them on. The implementation of each
+/// generator becomes a function named after the generator, e.g. `fn unit() -> Self` implemented
+/// by `Circle` becomes `fn circle_unit() -> Shape`, which returns the `Circle` variant once the
+/// trait is transformed, and `Circle::unit()` is replaced by `circle_unit()`. A provided static
+/// method which no generator overrides becomes a single function named after it, so it can also
+/// be called on a type parameter bounded by the trait, e.g. `T::origin()`
+pub struct ReplaceStaticMethods<'a> {
+    gamma: &'a Gamma,
+    /// The functions which replace the static methods of each trait
+    functions: HashMap<Ident, Vec<ItemFn>>,
+    /// The function each static method of a generator (or of a trait, when it is not overridden)
+    /// is replaced by, along with the trait
+    paths: HashMap<(Ident, Ident), (Ident, Ident)>,
+    /// The type `Self` is in the items being visited
+    self_type: Option<Ident>,
+    /// The traits bounding the type parameters of the function being visited
+    type_params: HashMap<Ident, Ident>,
+    module_path: Vec<Ident>,
+}
+impl<'a> ReplaceStaticMethods<'a> {
+    pub fn new(gamma: &'a Gamma, selection: &Selection) -> Self {
+        let mut replace = ReplaceStaticMethods {
+            gamma,
+            functions: HashMap::new(),
+            paths: HashMap::new(),
+            self_type: None,
+            type_params: HashMap::new(),
+            module_path: Vec::new(),
+        };
+
+        for trait_ in gamma.traits.iter() {
+            let static_methods: Vec<&TraitItemMethod> = trait_.items.iter().filter_map(|item| match item {
+                TraitItem::Method(method) if !has_self_receiver(&method.sig) => Some(method),
+                _ => None,
+            }).collect();
+            if static_methods.is_empty() || !selection.is_selected(&gamma.get_item_module(&trait_.ident), &trait_.ident) {
+                continue;
+            }
+            let generators = gamma.generators.get(&trait_.ident).cloned().unwrap_or_default();
+
+            let mut functions = Vec::new();
+            for method in static_methods {
+                let implementations: Vec<(&ItemStruct, Option<&ImplItemMethod>)> = generators
+                    .iter()
+                    .map(|(struct_, impl_)| (struct_, impl_.items.iter().find_map(|item| match item {
+                        ImplItem::Method(impl_method) if impl_method.sig.ident == method.sig.ident => Some(impl_method),
+                        _ => None,
+                    })))
+                    .collect();
+
+                match &method.default {
+                    Some(default) if implementations.iter().all(|(_, impl_method)| impl_method.is_none()) => {
+                        functions.push(create_static_method_function(trait_, &method.sig, &method.sig.ident, default, &trait_.ident));
+                        replace.paths.insert((trait_.ident.clone(), method.sig.ident.clone()), (method.sig.ident.clone(), trait_.ident.clone()));
+                    }
+                    default => {
+                        for (struct_, impl_method) in implementations {
+                            let block = match (impl_method, default) {
+                                (Some(impl_method), _) => &impl_method.block,
+                                (None, Some(default)) => default,
+                                (None, None) => continue,
+                            };
+                            let ident = format_ident!("{}_{}", transform_type_to_name(&struct_.ident), method.sig.ident);
+                            functions.push(create_static_method_function(trait_, &method.sig, &ident, block, &struct_.ident));
+                            replace.paths.insert((struct_.ident.clone(), method.sig.ident.clone()), (ident, trait_.ident.clone()));
+                        }
+                    }
+                }
+            }
+            replace.functions.insert(trait_.ident.clone(), functions);
+        }
+        replace
+    }
+
+    /// Add the functions replacing the static methods of a trait after the trait
+    fn add_functions(&self, items: &mut Vec<Item>) {
+        for (trait_ident, functions) in self.functions.iter() {
+            let position = items.iter().position(|item| matches!(item, Item::Trait(trait_) if trait_.ident == *trait_ident));
+            if let Some(position) = position {
+                items.splice(position + 1..position + 1, functions.iter().cloned().map(Item::Fn));
+            }
+        }
+    }
+
+    /// The path to the function replacing a static method of a type, if it is one of the methods
+    /// being replaced
+    fn get_function_path(&self, type_ident: &Ident, method_ident: &Ident) -> Option<Path> {
+        let type_ident = match type_ident {
+            ident if ident == "Self" => self.self_type.as_ref()?,
+            ident => self.type_params.get(ident).unwrap_or(ident),
+        };
+        let (function, trait_ident) = self.paths.get(&(type_ident.clone(), method_ident.clone()))?;
+        Some(get_relative_module_path(&self.module_path, &self.gamma.get_item_module(trait_ident), function))
+    }
+
+    fn visit_in_function<F: FnOnce(&mut Self)>(&mut self, generics: &Generics, visit: F) {
+        let mut type_params = self.type_params.clone();
+        for param in generics.type_params() {
+            let trait_ident = param.bounds.iter().find_map(|bound| match bound {
+                TypeParamBound::Trait(TraitBound { path, .. }) => Some(path.segments.last().unwrap().ident.clone()),
+                _ => None,
+            });
+            if let Some(trait_ident) = trait_ident.filter(|trait_ident| self.functions.contains_key(trait_ident)) {
+                type_params.insert(param.ident.clone(), trait_ident);
+            }
+        }
+        std::mem::swap(&mut self.type_params, &mut type_params);
+        visit(self);
+        self.type_params = type_params;
+    }
+}
+impl VisitMut for ReplaceStaticMethods<'_> {
+    fn visit_file_mut(&mut self, i: &mut File) {
+        self.add_functions(&mut i.items);
+        visit_file_mut(self, i);
+    }
+
+    fn visit_item_mod_mut(&mut self, i: &mut ItemMod) {
+        if let Some((_, items)) = &mut i.content {
+            self.add_functions(items);
+        }
+        self.module_path.push(i.ident.clone());
+        visit_item_mod_mut(self, i);
+        self.module_path.pop();
+    }
+
+    fn visit_item_trait_mut(&mut self, i: &mut ItemTrait) {
+        if self.functions.contains_key(&i.ident) {
+            i.items.retain(|item| !matches!(item, TraitItem::Method(method) if !has_self_receiver(&method.sig)));
+        }
+        self.self_type = Some(i.ident.clone());
+        visit_item_trait_mut(self, i);
+        self.self_type = None;
+    }
+
+    fn visit_item_impl_mut(&mut self, i: &mut ItemImpl) {
+        let trait_ident = i.trait_.as_ref().map(|(_, path, _)| path.segments.last().unwrap().ident.clone());
+        if trait_ident.filter(|trait_ident| self.functions.contains_key(trait_ident)).is_some() {
+            i.items.retain(|item| !matches!(item, ImplItem::Method(method) if !has_self_receiver(&method.sig)));
+        }
+        self.self_type = Some(i.self_ty.get_delta_type().name);
+        visit_item_impl_mut(self, i);
+        self.self_type = None;
+    }
+
+    fn visit_item_fn_mut(&mut self, i: &mut ItemFn) {
+        let generics = i.sig.generics.clone();
+        self.visit_in_function(&generics, |visitor| visit_item_fn_mut(visitor, i));
+    }
+
+    fn visit_impl_item_method_mut(&mut self, i: &mut ImplItemMethod) {
+        let generics = i.sig.generics.clone();
+        self.visit_in_function(&generics, |visitor| visit_impl_item_method_mut(visitor, i));
+    }
+
+    fn visit_expr_path_mut(&mut self, i: &mut ExprPath) {
+        // `Circle::unit`, `Self::unit`, `T::origin` or `<Circle as Shape>::unit`
+        let type_ident = match &i.qself {
+            Some(QSelf { ty, .. }) if i.path.segments.len() == 2 => Some(ty.get_delta_type().name),
+            None if i.path.segments.len() == 2 => Some(i.path.segments[0].ident.clone()),
+            _ => None,
+        };
+        let method_ident = &i.path.segments.last().unwrap().ident;
+        if let Some(path) = type_ident.and_then(|type_ident| self.get_function_path(&type_ident, method_ident)) {
+            *i = create_expr_path_from_path(path);
+            return;
+        }
+        visit_expr_path_mut(self, i);
+    }
+
+    fn visit_macro_mut(&mut self, i: &mut Macro) {
+        visit_macro_params_mut(self, i);
+    }
+}
+
+/// Create the function replacing a static method of a trait, `Self` is the trait (which becomes
+/// the enum) in the signature and the type implementing the method in the body
+fn create_static_method_function(trait_: &ItemTrait, sig: &Signature, ident: &Ident, block: &Block, self_type: &Ident) -> ItemFn {
+    let mut sig = Signature { ident: ident.clone(), ..sig.clone() };
+    // The `Self: Sized` bounds which keep the trait object safe are not needed
+    if let Some(where_clause) = &mut sig.generics.where_clause {
+        where_clause.predicates = Punctuated::from_iter(where_clause.predicates.iter().filter(|predicate| {
+            !matches!(predicate, WherePredicate::Type(PredicateType { bounded_ty: Type::Path(TypePath { qself: None, path }), .. }) if path.is_ident("Self"))
+        }).cloned());
+        if where_clause.predicates.is_empty() {
+            sig.generics.where_clause = None;
+        }
+    }
+    ReplaceSelfType { self_type: trait_.ident.clone() }.visit_signature_mut(&mut sig);
+
+    let mut function = create_function(sig, block.stmts.clone(), trait_.vis.clone());
+    ReplaceSelfType { self_type: self_type.clone() }.visit_block_mut(&mut function.block);
+    function
+}
@@ -1,7 +1,11 @@
 use std::path::PathBuf;
 use std::fs;
+use std::process::Command;
 
-use rfood::transform::transformer::{transform_file, TransformType};
+use rfood::transform::transformer::{transform_crate, transform_file, TransformType};
+use rfood::transform::plan::TransformPlan;
+use rfood::transform::selection::Selection;
+use rfood::context::delta::Pointer;
 
 fn test_run_transform_example(example_path: PathBuf, example_output_path: PathBuf, direction: TransformType) {
     // Remove the existing output file if it exists
@@ -9,7 +13,7 @@ fn test_run_transform_example(example_path: PathBuf, example_output_path: PathBu
     assert!(!example_output_path.exists());
 
     // Transform the file
-    transform_file(&example_path, &example_output_path, &direction);
+    transform_file(&example_path, &example_output_path, &TransformPlan::new(direction, Selection::default())).unwrap();
 
     // Assert that the output file exists and that is is not empty
     assert!(example_output_path.exists());
@@ -70,3 +74,531 @@ fn test_run_transform_example_oop_mutable() {
         TransformType::OOPToFP
     )
 }
+
+#[test]
+fn test_run_transform_example_oop_mutable_tuple() {
+    let example_output_path = PathBuf::from(r"./outputs/src/mutable/oop_tuple.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/mutable/oop_tuple.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The literals added to the `i8` fields are `i8` too, so the fields are not dereferenced
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("field_0 += 1;"));
+    assert!(output.contains("return (field_0 + field_1 + field_2) / 3;"));
+}
+
+#[test]
+fn test_run_transform_example_fp_mutable() {
+    let example_output_path = PathBuf::from(r"./outputs/src/mutable/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/mutable/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The tuple variants become tuple structs, a mutable variable holding one is a trait object
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub struct Dimmer(pub i8);"));
+    assert!(output.contains("Box::new(RGB(self.0 + 1, self.1 + 1, self.2 + 1))"));
+    assert!(output.contains("let mut dimmer: Box<dyn DimmableLight> = Box::new(Dimmer(10));"));
+    assert!(output.contains("dimmer = dimmer.increase_brightness();"));
+}
+
+#[test]
+fn test_run_transform_example_fp_shared_rc() {
+    let example_output_path = PathBuf::from(r"./outputs/src/shared/fp.rs");
+    let _ = fs::remove_file(&example_output_path);
+
+    let plan = TransformPlan::new(TransformType::FPToOOP, Selection::default()).with_pointer(Pointer::Rc);
+    transform_file(&PathBuf::from(r"./examples/src/shared/fp.rs"), &example_output_path, &plan).unwrap();
+
+    // The trait objects created from the enum are shared
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("fn double(self: Rc<Self>) -> Rc<dyn Expr>"));
+    assert!(output.contains("pub struct Add(pub Rc<dyn Expr>, pub Rc<dyn Expr>);"));
+}
+
+#[test]
+fn test_run_transform_example_oop_shared() {
+    let example_output_path = PathBuf::from(r"./outputs/src/shared/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/shared/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+    // The number operands of the shared shapes are not borrowed again
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("side: side * factor,"));
+}
+
+#[test]
+fn test_run_transform_example_oop_aliases() {
+    let example_output_path = PathBuf::from(r"./outputs/src/aliases/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/aliases/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+    // The operands typed by an alias of a number are not borrowed again
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("side: side * factor,"));
+}
+
+#[test]
+fn test_run_transform_example_fp_aliases() {
+    let example_output_path = PathBuf::from(r"./outputs/src/aliases/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/aliases/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The alias of the enum is rewritten with it and the code using the alias is unchanged
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub type ExprRef = Box<dyn Expr>;"));
+    assert!(output.contains("pub struct Add(pub ExprRef, pub ExprRef);"));
+}
+
+#[test]
+fn test_run_transform_example_oop_imports() {
+    let example_output_path = PathBuf::from(r"./outputs/src/imports/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/imports/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+    // The number operands are neither dereferenced nor borrowed again
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("Shape::Circle { radius } => 3.0 * radius * radius,"));
+    assert!(output.contains("radius: radius * factor,"));
+}
+
+#[test]
+fn test_run_transform_example_fp_imports() {
+    let example_output_path = PathBuf::from(r"./outputs/src/imports/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/imports/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The renamed imports are resolved, the consumer is now a method of the imported trait
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("use super::exprs::Expr;"));
+    assert!(!output.contains("evaluate"));
+    assert!(!output.contains("use super::exprs::Expr::Lit;"));
+}
+
+#[test]
+fn test_run_transform_example_fp_library() {
+    let example_output_path = PathBuf::from(r"./outputs/src/library/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/library/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The arguments of the methods of the standard library are boxed where they need to be
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("items.push(Box::new(Book {"));
+    assert!(output.contains("entries.insert(self.title(), self.items.len());"));
+}
+
+#[test]
+fn test_run_transform_example_oop_parsers() {
+    let example_output_path = PathBuf::from(r"./outputs/src/parsers/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/parsers/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The associated type given by each generator becomes a type parameter of the enum, the one
+    // given the same type by every generator is replaced by it
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub enum Parser<Output> {"));
+    assert!(output.contains("-> Result<Output, String>"));
+    assert!(output.contains("parser: &Box<Parser<usize>>"));
+}
+
+#[test]
+fn test_run_transform_example_fp_parsers() {
+    let example_output_path = PathBuf::from(r"./outputs/src/parsers/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/parsers/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The type parameter of the enum which the consumers return becomes an associated type
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("type T: Clone;"));
+    assert!(output.contains("impl<T: Clone> Parser for Literal<T> {"));
+    assert!(output.contains("parser: &dyn Parser<T = usize>"));
+}
+
+#[test]
+fn test_run_transform_example_oop_polygons() {
+    let example_output_path = PathBuf::from(r"./outputs/src/polygons/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/polygons/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The associated consts become consumers, the uses of the consts call them
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub fn sides(polygon: &Polygon) -> usize {"));
+    assert!(output.contains("Polygon::Square { side } => sides(polygon) * side,"));
+    assert!(output.contains("_ => \"polygon\","));
+    assert!(!output.contains("SIDES"));
+}
+
+#[test]
+fn test_run_transform_example_fp_polygons() {
+    let example_output_path = PathBuf::from(r"./outputs/src/polygons/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/polygons/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The consumers which only return a constant become associated consts
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("const SIDES: usize;"));
+    assert!(output.contains("const SIDES: usize = 4;"));
+    assert!(output.contains("Self::NAME"));
+}
+
+#[test]
+fn test_run_transform_example_oop_figures() {
+    let example_output_path = PathBuf::from(r"./outputs/src/figures/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/figures/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The destructors of the supertrait become consumers of the enum, the foreign supertraits
+    // are derived or implemented by it
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("#[derive(Debug, Clone)]\npub enum Figure {"));
+    assert!(output.contains("impl fmt::Display for Figure {"));
+    assert!(output.contains("pub fn name(figure: &Figure) -> String {"));
+    assert!(output.contains("pub fn label(item: &Figure) -> String {"));
+    assert!(!output.contains("Named"));
+}
+
+#[test]
+fn test_run_transform_example_oop_units() {
+    let example_output_path = PathBuf::from(r"./outputs/src/units/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/units/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The static methods become functions for each generator, or a single function when no
+    // generator overrides them
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub fn metres_from_millimetres(millimetres: u32) -> Length {"));
+    assert!(output.contains("pub fn centimetres_unit() -> &'static str {"));
+    assert!(output.contains("format!(\"{}{}\", centimetres, centimetres_unit())"));
+    assert!(output.contains("millimetres(length) / precision()"));
+    assert!(!output.contains("Sized"));
+}
+
+#[test]
+fn test_run_transform_example_oop_bundles() {
+    let example_output_path = PathBuf::from(r"./outputs/src/bundles/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/bundles/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The type parameters of the methods are kept by the consumers, those named differently in an
+    // impl are renamed to those of the trait
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub fn map<F: Fn(i32) -> i32>(bundle: &Bundle, f: F) -> Bundle {"));
+    assert!(output.contains("pub fn fold<A, F>(bundle: &Bundle, init: A, f: F) -> A\nwhere\n    F: Fn(A, i32) -> A,\n{"));
+    assert!(output.contains("let acc: A = f(init, *first);"));
+    assert!(output.contains("fold::<i32, _>(&doubled, 0, |acc, x| acc + x)"));
+}
+
+#[test]
+fn test_run_transform_example_fp_bundles() {
+    let example_output_path = PathBuf::from(r"./outputs/src/bundles/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/bundles/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The generic methods are only required of sized types so the trait is still object safe
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("fn map<F: Fn(i32) -> i32>(&self, f: F) -> Box<dyn Bundle>\n    where\n        Self: Sized;"));
+    assert!(output.contains("two.fold::<i32, _>(0, |acc, x| acc + x * 2)"));
+}
+
+#[test]
+fn test_run_transform_example_oop_trait_types() {
+    let example_output_path = PathBuf::from(r"./outputs/src/trait_types/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/trait_types/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The type parameters bounded by the trait are replaced with the enum in items, impls and
+    // functions, and the matching type arguments are removed where they are used
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub struct Holder {\n    pub shape: Shape,\n}"));
+    assert!(output.contains("impl Holder {"));
+    assert!(output.contains("let holder: Holder = Holder {"));
+    assert!(output.contains("pub fn double(shape: &Shape) -> f64 {"));
+    assert!(output.contains("+ double(&Shape::Square { side: 1.0 })"));
+}
+
+#[test]
+fn test_run_transform_example_fp_inherent() {
+    let example_output_path = PathBuf::from(r"./outputs/src/inherent/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/inherent/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The methods of the inherent impl taking self are consumers, the others stay in an inherent
+    // impl of the trait object
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("fn scale(&self, factor: f64) -> Box<dyn Shape>;"));
+    assert!(output.contains("3.0 * self.radius * self.radius"));
+    assert!(output.contains("impl dyn Shape {\n    pub fn unit() -> Box<dyn Shape> {"));
+    assert!(output.contains("let shape = <dyn Shape>::unit();"));
+}
+
+#[test]
+fn test_run_transform_example_fp_frames() {
+    let example_output_path = PathBuf::from(r"./outputs/src/frames/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/frames/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The methods of another type with the same names as the consumers keep their own signatures,
+    // even though the inherent impl of the enum comes before the enum
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("fn scale(&self, factor: f64) -> Box<dyn Shape>;"));
+    assert!(output.contains("pub fn scale(&self, factor: u32) -> Frame {"));
+    assert!(output.contains("Frame::new(self.area())"));
+    assert!(output.contains("let frame = shape.scale(2.0).framed().scale(2);"));
+    // Functions which are not called by a path are left as they are
+    assert!(output.contains("transforms[0](self.area())"));
+}
+
+#[test]
+fn test_run_transform_example_oop_modules() {
+    let example_output_path = PathBuf::from(r"./outputs/src/modules/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/modules/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The enum and its consumers are created in the module of the trait, the other modules refer
+    // to them through their paths
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub mod shapes {\n    pub enum Shape {"));
+    assert!(output.contains("pub mod rectangle {}"));
+    assert!(output.contains("let circle = Shape::Circle { radius: 1.0 };"));
+    assert!(output.contains("super::shapes::area(&circle) + super::shapes::area(&rectangle)"));
+}
+
+#[test]
+fn test_run_transform_example_fp_modules() {
+    let example_output_path = PathBuf::from(r"./outputs/src/modules/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/modules/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The trait and its generators are created in the module of the enum, the consumer called
+    // through its path in another module is now a method
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub mod exprs {\n    pub trait Expr {"));
+    assert!(output.contains("left: Box::new(super::exprs::Lit { value: 1 }),"));
+    assert!(output.contains("expr.eval()"));
+}
+
+#[test]
+fn test_run_transform_example_oop_containers() {
+    let example_output_path = PathBuf::from(r"./outputs/src/containers/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/containers/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The trait objects nested in containers become the enum
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("shapes: Vec<Shape>,"));
+    assert!(output.contains("border: Option<Box<Shape>>,"));
+    assert!(output.contains("pub fn squares(sides: Vec<f64>) -> Vec<Shape> {"));
+    assert!(output.contains("border: Some(Box::new(Shape::Square { side: 3.0 })),"));
+}
+
+#[test]
+fn test_run_transform_example_fp_containers() {
+    let example_output_path = PathBuf::from(r"./outputs/src/containers/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/containers/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The enum nested in containers becomes a trait object, the generators put in them are boxed
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("pub fn total_area(shapes: &Vec<Box<dyn Shape>>) -> f64 {"));
+    assert!(output.contains("pub fn with_area(side: f64) -> (Box<dyn Shape>, f64) {"));
+    assert!(output.contains("shapes.push(Box::new(Square { side: 1.0 }));"));
+    assert!(output.contains("let spare: Option<Box<dyn Shape>> = Some(Box::new(Circle { radius: 2.0 }));"));
+}
+
+#[test]
+fn test_run_transform_example_oop_pointers() {
+    let example_output_path = PathBuf::from(r"./outputs/src/pointers/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/pointers/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // The enum behind boxes and references is dereferenced to the reference the consumers take
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("describe(&**shape)"));
+    assert!(output.contains("describe(&*shape) + area(&*shape)"));
+    assert!(output.contains("pub fn mut_area(shape: &mut Box<Shape>) -> f64 {"));
+    assert!(output.contains("describe(*shape) + area(*shape)"));
+}
+
+#[test]
+fn test_run_transform_example_fp_pointers() {
+    let example_output_path = PathBuf::from(r"./outputs/src/pointers/fp.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/pointers/fp.rs"),
+        example_output_path.clone(),
+        TransformType::FPToOOP
+    );
+
+    // The consumers taking the enum behind other pointers take self through the matching receiver
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("fn owned_area(self: Box<Self>) -> f64 {"));
+    assert!(output.contains("fn mut_area(&mut self) -> f64 {"));
+    assert!(output.contains("first + mutable.mut_area() + boxed.owned_area()"));
+}
+
+#[test]
+fn test_run_transform_example_oop_literals() {
+    let example_output_path = PathBuf::from(r"./outputs/src/literals/oop.rs");
+    test_run_transform_example(
+        PathBuf::from(r"./examples/src/literals/oop.rs"),
+        example_output_path.clone(),
+        TransformType::OOPToFP
+    );
+
+    // Number literals are typed by their suffix or by the field they are used with, so only the
+    // comparison borrows them, and string literals are already references
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("if level > &0 {"));
+    assert!(output.contains("if by > 1u8 {"));
+    assert!(output.contains("Counter::Level { level: level + 2 }"));
+    assert!(output.contains("format!(\"{}: {}\", label, clicks + 1)"));
+    assert!(output.contains("let clicks = step(&step(&clicks, 2u8), 1);"));
+    assert!(output.contains("describe(&clicks, \"clicks\")"));
+}
+
+#[test]
+fn test_run_transform_example_fp_sql_predicate() {
+    let example_output_path = PathBuf::from(r"./outputs/src/sql/fp_predicate.rs");
+    let _ = fs::remove_file(&example_output_path);
+
+    let selection = Selection::from_strs(&["Predicate"], &[]).unwrap();
+    let plan = TransformPlan::new(TransformType::FPToOOP, selection);
+    transform_file(&PathBuf::from(r"./examples/src/sql/fp.rs"), &example_output_path, &plan).unwrap();
+
+    // Only the selected enum becomes a trait, the other enums are kept
+    let output = fs::read_to_string(&example_output_path).expect("Unable to read file");
+    assert!(output.contains("trait Predicate {}"));
+    assert!(output.contains("impl Predicate for Eq {}"));
+    assert!(output.contains("enum Operator {"));
+    assert!(output.contains("pred: Box<dyn Predicate>,"));
+    assert!(output.contains("enum Ref {"));
+}
+
+#[test]
+fn test_run_transform_crate_oop_multi_file() {
+    let example_output_path = PathBuf::from(r"./outputs/src/multi_file/oop");
+    let _ = fs::remove_dir_all(&example_output_path);
+    assert!(!example_output_path.exists());
+
+    // Transform the crate, each module is written to the file it was loaded from
+    let skipped = transform_crate(
+        &PathBuf::from(r"./examples/src/multi_file/oop/mod.rs"),
+        &example_output_path,
+        &TransformPlan::new(TransformType::OOPToFP, Selection::default()),
+    ).unwrap();
+    assert!(skipped.is_empty());
+    for file in ["mod.rs", "shape.rs", "circle.rs", "square_shape.rs"] {
+        assert!(example_output_path.join(file).exists());
+    }
+
+    // The enum is created in the module of the trait and the modules of the generators no longer
+    // import the trait
+    let shape = fs::read_to_string(example_output_path.join("shape.rs")).expect("Unable to read file");
+    assert!(shape.contains("pub enum Shape {"));
+    assert!(shape.contains("Shape::Circle { radius } => 3.0 * radius * radius,"));
+    let circle = fs::read_to_string(example_output_path.join("circle.rs")).expect("Unable to read file");
+    assert!(!circle.contains("use super::shape::Shape;"));
+    let root = fs::read_to_string(example_output_path.join("mod.rs")).expect("Unable to read file");
+    assert!(root.contains("shape::area(&circle) + shape::area(&square)"));
+}
+
+#[test]
+fn test_run_cli_logs_to_stderr() {
+    let output_path = std::env::temp_dir().join("rfood_test_run_cli_exp.rs");
+    let _ = fs::remove_file(&output_path);
+
+    // The transformed code is written to the output file and the logs to stderr, stdout is clean
+    let output = Command::new(env!("CARGO_BIN_EXE_rfood"))
+        .args(["transform", "./examples/src/exp/oop.rs", "oop-to-fp"])
+        .arg(&output_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    assert!(fs::read_to_string(&output_path).expect("Unable to read file").contains("pub enum Exp {"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rfood"))
+        .args(["transform", "./examples/src/exp/oop.rs", "oop-to-fp", "-vv"])
+        .arg(&output_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Transforming trait"));
+
+    // The report of check is the only thing on stdout
+    let output = Command::new(env!("CARGO_BIN_EXE_rfood"))
+        .args(["check", "./examples/src/exp/oop.rs", "-vv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok: Exp\n1 of 1 traits can be transformed\n");
+}